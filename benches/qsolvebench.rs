@@ -1,9 +1,15 @@
 use std::{hint::black_box, str::FromStr, time::Duration};
 
 use criterion::{Criterion, criterion_group, criterion_main};
+use image::{
+    Rgb, RgbImage,
+    imageops::{FilterType, resize},
+};
 use qsolve::{
     board::Board,
+    datastructure::CoordSet,
     heuristic::all_heuristics,
+    image::{analyze_grid_image, detect_dominant_colors, detect_glyphs, detect_grid},
     solveiter::solve_iter,
     solvestate::{SolveState, SolveStrategy},
 };
@@ -26,9 +32,123 @@ fn benchmark_puzzle(c: &mut Criterion, name: &str, file: &str) {
     g.finish();
 }
 
+/// Benchmarks the [CoordSet] operations that heuristics lean on, such as the
+/// reduce loop in `AllPossibilitiesEliminateSquare`, which intersects the
+/// squares bordering every possible queen in a region.
+fn benchmark_coord_set(c: &mut Criterion, name: &str, file: &str) {
+    let mut g = c.benchmark_group(name);
+    g.measurement_time(Duration::from_secs(5));
+    let content = std::fs::read_to_string(file).unwrap();
+    let board = Board::from_str(&content).unwrap();
+    let regions = board
+        .all_colors()
+        .into_iter()
+        .map(|color| board.coords_for_color(color))
+        .collect::<Vec<_>>();
+    g.bench_function("IntersectionReduce", |b| {
+        b.iter(|| {
+            for region in black_box(&regions) {
+                black_box(
+                    region
+                        .iter()
+                        .map(|coord| board.queen_borders(&coord))
+                        .reduce(|acc, e| acc.intersection(&e))
+                        .unwrap_or_default(),
+                );
+            }
+        })
+    });
+    g.bench_function("Union", |b| {
+        b.iter(|| {
+            black_box(
+                black_box(&regions)
+                    .iter()
+                    .fold(CoordSet::default(), |acc, region| acc.union(region)),
+            )
+        })
+    });
+    g.bench_function("Len", |b| {
+        b.iter(|| black_box(&regions).iter().map(CoordSet::len).sum::<usize>())
+    });
+    g.bench_function("Iter", |b| {
+        b.iter(|| black_box(board.all_coords()).iter().count())
+    });
+    g.finish();
+}
+
+/// Draws the given board as a screenshot would show it: each square filled
+/// with a distinct color, separated (and surrounded) by 2px black lines.
+fn synthetic_image(file: &str, cell_size: u32) -> RgbImage {
+    let content = std::fs::read_to_string(file).unwrap();
+    let board = Board::from_str(&content).unwrap();
+    let line = 2;
+    let pitch = cell_size + line;
+    let side = board.size() as u32 * pitch + line;
+    RgbImage::from_fn(side, side, |x, y| {
+        if x % pitch < line || y % pitch < line {
+            return Rgb([0, 0, 0]);
+        }
+        let coord = ((y / pitch) as usize, (x / pitch) as usize);
+        let color = board.color(&coord) as u8;
+        // Spread the colors out so that no two are mistaken for each other.
+        Rgb([
+            60 + (color % 4) * 60,
+            60 + (color / 4) * 60,
+            60 + ((color + 2) % 3) * 80,
+        ])
+    })
+}
+
+fn benchmark_image_phases(c: &mut Criterion, name: &str, img: &RgbImage) {
+    let mut g = c.benchmark_group(name);
+    g.measurement_time(Duration::from_secs(10));
+    g.sample_size(50);
+    let grid = detect_grid(img).unwrap();
+    let colors = detect_dominant_colors(img, &grid).unwrap();
+    g.bench_function("Analyze", |b| {
+        b.iter(|| black_box(analyze_grid_image(black_box(img)).unwrap()))
+    });
+    g.bench_function("DetectGrid", |b| {
+        b.iter(|| black_box(detect_grid(black_box(img)).unwrap()))
+    });
+    g.bench_function("DominantColors", |b| {
+        b.iter(|| black_box(detect_dominant_colors(black_box(img), &grid).unwrap()))
+    });
+    g.bench_function("Glyphs", |b| {
+        b.iter(|| black_box(detect_glyphs(black_box(img), &grid, &colors)))
+    });
+    g.finish();
+}
+
+fn benchmark_image(c: &mut Criterion, name: &str, file: &str, upscale: u32) {
+    let img = image::open(file).unwrap().to_rgb8();
+    let img = resize(
+        &img,
+        img.width() * upscale,
+        img.height() * upscale,
+        FilterType::Nearest,
+    );
+    benchmark_image_phases(c, &format!("{name}{upscale}x"), &img);
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     benchmark_puzzle(c, "LinkedIn1", "games/linkedin-1-empty.txt");
     benchmark_puzzle(c, "GameOfCrowns1", "games/gameofcrowns-1.txt");
+    benchmark_coord_set(c, "CoordSet", "games/gameofcrowns-1.txt");
+    benchmark_image(c, "LinkedIn1Image", "games/linkedin-1.png", 1);
+    benchmark_image(c, "LinkedIn1Image", "games/linkedin-1.png", 4);
+    benchmark_image(c, "QueensGame1Image", "games/queensgame-1.png", 1);
+    benchmark_image(c, "GameOfCrowns1Image", "games/gameofcrowns-1.png", 1);
+    benchmark_image_phases(
+        c,
+        "SyntheticImageSmall",
+        &synthetic_image("games/gameofcrowns-1.txt", 40),
+    );
+    benchmark_image_phases(
+        c,
+        "SyntheticImageLarge",
+        &synthetic_image("games/gameofcrowns-1.txt", 400),
+    );
 }
 
 criterion_group!(benches, criterion_benchmark);
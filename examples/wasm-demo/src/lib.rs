@@ -0,0 +1,158 @@
+//! The WebAssembly side of the demo page in `www/`, which steps through
+//! solving a Queens puzzle one hint at a time.
+//!
+//! This only uses the parts of qsolve that build without its `cli` feature:
+//! boards are parsed from text rather than read from files, and the page is
+//! drawn from the frames that [qsolve::animate::frames] generates, rather
+//! than by a terminal. It also steps with [qsolve::solveiter::solve_iter]
+//! rather than [qsolve::solution::Solution], since timing a solve needs a
+//! clock that `wasm32-unknown-unknown` doesn't have.
+
+use std::{cell::RefCell, fmt::Write, str::FromStr};
+
+use anyhow::Result;
+use qsolve::{
+    animate::{Frame, FrameOptions, frames},
+    board::Board,
+    file::QueensFile,
+    heuristic::all_heuristics,
+    palette::Palette,
+    solveiter::solve_iter,
+    solvestate::{SolveState, SolveStrategy},
+};
+use wasm_bindgen::prelude::*;
+
+/// The colors the board is drawn with on the page.
+const PALETTE: Palette = Palette::Pastel;
+
+thread_local! {
+    /// The puzzle most recently passed to [load_text], if any.
+    static DEMO: RefCell<Option<Demo>> = const { RefCell::new(None) };
+}
+
+/// A loaded puzzle, with every frame of its solve.
+struct Demo {
+    board: Board,
+    frames: Vec<Frame>,
+    current: usize,
+}
+
+impl Demo {
+    /// Parses the board (and any Queens and Xs) from the text of a board
+    /// file, and solves it.
+    fn new(text: &str) -> Result<Self> {
+        let queens_file = QueensFile::from_str(text)?;
+        let solve_state = SolveState::from(&queens_file);
+        let heuristics = all_heuristics(solve_state.board);
+        let frame_options = FrameOptions {
+            palette: PALETTE,
+            ..FrameOptions::default()
+        };
+        let steps = solve_iter(solve_state, SolveStrategy::Fast, &heuristics);
+        let frames = frames(steps, frame_options).collect();
+        Ok(Demo {
+            board: queens_file.board,
+            frames,
+            current: 0,
+        })
+    }
+
+    /// Moves on to the next frame that has a caption, which applies the hint
+    /// shown now (if any) and highlights the squares of the next one, and
+    /// returns that caption. Returns None once the solve is done.
+    fn next_hint(&mut self) -> Option<String> {
+        let next = (self.current + 1..self.frames.len())
+            .find(|&idx| self.frames[idx].caption.is_some())?;
+        self.current = next;
+        self.frames[next].caption.clone()
+    }
+
+    /// Returns the current frame as an HTML table.
+    fn render(&self) -> String {
+        let frame = &self.frames[self.current];
+        let mut html = String::from("<table class=\"qsolve-board\">");
+        for (r, row) in frame.squares.iter().enumerate() {
+            html.push_str("<tr>");
+            for (c, square) in row.iter().enumerate() {
+                let (red, green, blue) = PALETTE.rgb(self.board.color(&(r, c)));
+                let highlighted = frame
+                    .highlights
+                    .iter()
+                    .any(|(coords, _)| coords.contains(&(r, c)));
+                // Writing to a String can't fail.
+                write!(
+                    html,
+                    "<td class=\"{}\" style=\"background: rgb({red}, {green}, {blue})\">{}</td>",
+                    if highlighted { "highlighted" } else { "" },
+                    without_ansi(square).trim(),
+                )
+                .unwrap();
+            }
+            html.push_str("</tr>");
+        }
+        html.push_str("</table>");
+        html
+    }
+}
+
+/// Strips the ANSI color escapes from a square of a [Frame], leaving what
+/// the square shows.
+fn without_ansi(square: &str) -> String {
+    let mut plain = String::with_capacity(square.len());
+    let mut chars = square.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|&c| c == 'm');
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+/// Loads the puzzle from the text of a board file (as in `games/`), and
+/// returns its size. Later calls to [next_hint] and [render] step through
+/// solving it.
+#[wasm_bindgen(js_name = loadText)]
+pub fn load_text(text: &str) -> Result<usize, JsError> {
+    let demo = Demo::new(text).map_err(|err| JsError::new(&format!("{err:#}")))?;
+    let size = demo.board.size();
+    DEMO.with_borrow_mut(|loaded| *loaded = Some(demo));
+    Ok(size)
+}
+
+/// Applies the hint shown now (if any) and shows the next one, returning
+/// its description, or undefined once the puzzle is solved.
+#[wasm_bindgen(js_name = nextHint)]
+pub fn next_hint() -> Option<String> {
+    DEMO.with_borrow_mut(|loaded| loaded.as_mut()?.next_hint())
+}
+
+/// Returns the board as it is now, as an HTML table whose highlighted
+/// squares have the `highlighted` class, or an empty string if no puzzle
+/// has been loaded.
+#[wasm_bindgen]
+pub fn render() -> String {
+    DEMO.with_borrow(|loaded| loaded.as_ref().map(Demo::render).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_through_the_solve() -> Result<()> {
+        let mut demo = Demo::new(include_str!("../../../games/linkedin-1-empty.txt"))?;
+        assert_eq!(demo.render().matches("<tr>").count(), 8);
+        assert!(!demo.render().contains("highlighted"));
+
+        let mut hints = vec![];
+        while let Some(hint) = demo.next_hint() {
+            hints.push(hint);
+        }
+        assert_eq!(hints.last().map(String::as_str), Some("Done!"));
+        assert_eq!(demo.render().matches('\u{265B}').count(), 8);
+        assert!(!demo.render().contains('\x1b'));
+        Ok(())
+    }
+}
@@ -0,0 +1,281 @@
+use itertools::Itertools;
+
+use crate::{
+    datastructure::{Coord, CoordSet},
+    heuristic::Heuristic,
+    palette::Palette,
+    solveiter::SolveIterItem,
+    solvestate::{Charset, HighlightStyle},
+};
+
+/// Options that control how the frames of an animation are rendered.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FrameOptions {
+    /// What characters to draw the squares with.
+    pub charset: Charset,
+
+    /// What colors to draw the squares with.
+    pub palette: Palette,
+
+    /// How to highlight the squares the next heuristic considers.
+    pub highlight_style: HighlightStyle,
+}
+
+/// A single frame of an animated solve.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    /// The board as a string colored by OwoColorize, with one line per row.
+    pub ansi: String,
+
+    /// The same board as [Frame::ansi], with each square as its own string,
+    /// one row at a time.
+    pub squares: Vec<Vec<String>>,
+
+    /// The squares that are highlighted in the frame, and how.
+    pub highlights: Vec<(CoordSet, HighlightStyle)>,
+
+    /// The text to show below the board, if any. This may span several lines.
+    pub caption: Option<String>,
+
+    /// How far along the solve is in this frame, as returned by
+    /// [SolveState::progress][crate::solvestate::SolveState::progress].
+    pub progress: f32,
+}
+
+impl Frame {
+    /// Returns how many lines the caption takes up.
+    pub fn caption_lines(&self) -> usize {
+        self.caption
+            .as_ref()
+            .map_or(0, |caption| caption.lines().count())
+    }
+
+    /// Returns the squares that are drawn differently in this frame than in
+    /// the given earlier one, along with how to draw them now, so that only
+    /// they need to be redrawn.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::animate::{FrameOptions, frames};
+    /// # use qsolve::board::Board;
+    /// # use qsolve::heuristic::all_heuristics;
+    /// # use qsolve::solveiter::solve_iter;
+    /// # use qsolve::solvestate::{SolveState, SolveStrategy};
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+    /// let heuristics = all_heuristics(&board);
+    /// let steps = solve_iter(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+    /// let frames = frames(steps, FrameOptions::default()).collect::<Vec<_>>();
+    /// assert!(frames[0].changed_squares(&frames[0]).is_empty());
+    /// // The second frame highlights the squares the first heuristic looks at.
+    /// assert!(!frames[1].changed_squares(&frames[0]).is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn changed_squares<'a>(&'a self, previous: &Frame) -> Vec<(Coord, &'a str)> {
+        self.squares
+            .iter()
+            .enumerate()
+            .flat_map(|(r, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(move |(c, square)| ((r, c), square.as_str()))
+            })
+            .filter(|((r, c), square)| {
+                previous
+                    .squares
+                    .get(*r)
+                    .and_then(|row| row.get(*c))
+                    .is_none_or(|previous| previous != square)
+            })
+            .collect()
+    }
+}
+
+/// Returns the frames that animate the given solve.
+///
+/// Each step of the solve becomes two frames: one showing the state on its
+/// own, and one highlighting the squares the next heuristic considers and
+/// describing it. The last step's second frame says that the solve is done.
+///
+/// This consumes the steps lazily, so it can animate a solve as it happens.
+///
+/// # Examples
+/// ```
+/// # use qsolve::animate::{FrameOptions, frames};
+/// # use qsolve::board::Board;
+/// # use qsolve::heuristic::all_heuristics;
+/// # use qsolve::solveiter::solve_iter;
+/// # use qsolve::solvestate::{SolveState, SolveStrategy};
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+/// let heuristics = all_heuristics(&board);
+/// let steps = solve_iter(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+/// let frames = frames(steps, FrameOptions::default()).collect::<Vec<_>>();
+/// assert_eq!(frames.last().unwrap().caption.as_deref(), Some("Done!"));
+/// # Ok(())
+/// # }
+/// ```
+pub fn frames<'h, 'ss>(
+    steps: impl IntoIterator<Item = SolveIterItem<'h, 'ss>>,
+    frame_options: FrameOptions,
+) -> impl Iterator<Item = Frame> {
+    steps.into_iter().flat_map(move |item| {
+        let seen = item
+            .next_heuristic
+            .map(|h| h.seen_coords(&item.solve_state))
+            .unwrap_or_default();
+        let caption = item
+            .next_heuristic
+            .map_or("Done!".to_string(), Heuristic::description);
+        [
+            render(&item, vec![], None, frame_options),
+            render(
+                &item,
+                vec![(seen, frame_options.highlight_style)],
+                Some(caption),
+                frame_options,
+            ),
+        ]
+    })
+}
+
+/// Returns the frames that animate the given solve from the given step on,
+/// like [frames], fast-forwarding past the earlier steps without rendering
+/// them. If the solve has fewer steps, only its last step is animated.
+///
+/// The skipped steps are consumed as soon as this is called, so that the
+/// animation can start from the later step right away.
+///
+/// # Examples
+/// ```
+/// # use qsolve::animate::{FrameOptions, frames, frames_from};
+/// # use qsolve::board::Board;
+/// # use qsolve::heuristic::all_heuristics;
+/// # use qsolve::solveiter::solve_iter;
+/// # use qsolve::solvestate::{SolveState, SolveStrategy};
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+/// let heuristics = all_heuristics(&board);
+/// let steps = || solve_iter(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+/// let all = frames(steps(), FrameOptions::default()).collect::<Vec<_>>();
+/// let endgame = frames_from(steps(), 2, FrameOptions::default()).collect::<Vec<_>>();
+/// assert_eq!(endgame, all[4..]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn frames_from<'h, 'ss>(
+    steps: impl IntoIterator<Item = SolveIterItem<'h, 'ss>>,
+    start: usize,
+    frame_options: FrameOptions,
+) -> impl Iterator<Item = Frame> {
+    let mut steps = steps.into_iter();
+    let mut first = steps.next();
+    for _ in 0..start {
+        match steps.next() {
+            Some(next) => first = Some(next),
+            None => break,
+        }
+    }
+    frames(first.into_iter().chain(steps), frame_options)
+}
+
+/// Renders a frame of the given step.
+fn render(
+    item: &SolveIterItem,
+    highlights: Vec<(CoordSet, HighlightStyle)>,
+    caption: Option<String>,
+    frame_options: FrameOptions,
+) -> Frame {
+    let squares = item
+        .solve_state
+        .ansi_squares_with_highlights(&highlights, frame_options.charset, frame_options.palette)
+        // Writing to a String can't fail.
+        .unwrap();
+    Frame {
+        ansi: squares.iter().map(|row| row.concat()).join("\n"),
+        squares,
+        highlights,
+        caption,
+        progress: item.solve_state.progress(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use anyhow::Result;
+
+    use crate::{
+        board::Board,
+        heuristic::all_heuristics,
+        solution::Solution,
+        solvestate::{SolveState, SolveStrategy},
+    };
+
+    use super::*;
+
+    #[test]
+    fn frames_of_solution() -> Result<()> {
+        let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        let heuristics = all_heuristics(&board);
+        let solution = Solution::solve(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+        let step_count = solution.steps.len();
+        let frames = frames(solution.steps, FrameOptions::default()).collect::<Vec<_>>();
+        assert_eq!(frames.len(), 2 * step_count);
+        for pair in frames.chunks_exact(2) {
+            assert!(pair[0].highlights.is_empty() && pair[0].caption.is_none());
+            assert_eq!(pair[0].ansi.lines().count(), board.size());
+            assert_eq!(pair[1].highlights.len(), 1);
+            assert!(pair[1].caption_lines() >= 1);
+            assert_eq!(pair[0].progress, pair[1].progress);
+        }
+        assert!(frames.is_sorted_by(|a, b| a.progress <= b.progress));
+        let last = frames.last().unwrap();
+        assert_eq!(last.caption.as_deref(), Some("Done!"));
+        assert!(last.highlights[0].0.is_empty());
+        assert_eq!(last.progress, 1.0);
+        Ok(())
+    }
+
+    #[test]
+    fn changed_squares_between_steps() -> Result<()> {
+        let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        let heuristics = all_heuristics(&board);
+        let solution = Solution::solve(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+        let frames = frames(solution.steps, FrameOptions::default()).collect::<Vec<_>>();
+        let first = &frames[0];
+        let blank = Frame {
+            squares: vec![],
+            ..first.clone()
+        };
+        assert_eq!(first.changed_squares(&blank).len(), board.square_count());
+        for (previous, frame) in frames.iter().tuple_windows() {
+            let mut redrawn = previous.squares.clone();
+            for ((r, c), square) in frame.changed_squares(previous) {
+                redrawn[r][c] = square.to_string();
+            }
+            assert_eq!(redrawn, frame.squares);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn frames_from_past_the_end() -> Result<()> {
+        let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        let heuristics = all_heuristics(&board);
+        let steps =
+            || Solution::solve(SolveState::from(&board), SolveStrategy::Fast, &heuristics).steps;
+        let all = frames(steps(), FrameOptions::default()).collect::<Vec<_>>();
+        let skipped = frames_from(steps(), 100, FrameOptions::default()).collect::<Vec<_>>();
+        assert_eq!(skipped, all[all.len() - 2..]);
+        Ok(())
+    }
+}
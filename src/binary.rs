@@ -0,0 +1,243 @@
+use anyhow::{Context, Result, anyhow, ensure};
+
+use crate::{
+    board::{Board, RuleSet},
+    clue::Clues,
+    error::QSolveError,
+    file::{InputSquares, QueensFile},
+    solvestate::{SolveState, SquareVal},
+    squarecolor::ALL_SQUARE_COLORS,
+};
+
+/// The clue byte for a line without a clue.
+const NO_CLUE: u8 = u8::MAX;
+
+/// Encodes the given state and its board as compact bytes, which
+/// [from_bytes] reads back.
+///
+/// The encoding is:
+/// * One byte with the board's size in its low 6 bits and its [RuleSet] in
+///   its high 2 bits.
+/// * The color of each square, row by row, as a nibble indexing
+///   [ALL_SQUARE_COLORS], two to a byte with the first in the high nibble.
+/// * The value of each square, row by row, in 2 bits (0 for blank, 1 for an
+///   X, 2 for a Queen), four to a byte with the first in the high bits.
+/// * Only if the board has [Clues], a byte for each row's clue and then
+///   each column's, with 255 for lines without one.
+///
+/// An 8x8 board with its markings takes 49 bytes, about a third of its text
+/// file.
+pub fn to_bytes(solve_state: &SolveState) -> Vec<u8> {
+    let board = solve_state.board;
+    let size = board.size();
+    let rules = match board.rules() {
+        RuleSet::AdjacencyOnly => 0,
+        RuleSet::FullDiagonals => 1,
+        RuleSet::NoDiagonals => 2,
+    };
+    let mut bytes = vec![size as u8 | rules << 6];
+    let coords = || (0..size).flat_map(|r| (0..size).map(move |c| (r, c)));
+    let nibbles = coords()
+        .map(|coord| {
+            let color = board.color(&coord);
+            ALL_SQUARE_COLORS
+                .iter()
+                .position(|&c| c == color)
+                .expect("Every color is in ALL_SQUARE_COLORS") as u8
+        })
+        .collect::<Vec<_>>();
+    bytes.extend(nibbles.chunks(2).map(|pair| pack(pair, 4)));
+    let cells = coords()
+        .map(|coord| match solve_state.square(&coord) {
+            None => 0,
+            Some(SquareVal::X) => 1,
+            Some(SquareVal::Queen) => 2,
+        })
+        .collect::<Vec<_>>();
+    bytes.extend(cells.chunks(4).map(|quad| pack(quad, 2)));
+    let clues = board.clues();
+    if !clues.is_empty() {
+        let line = |clues: &[Option<usize>], i: usize| {
+            clues
+                .get(i)
+                .copied()
+                .flatten()
+                .map_or(NO_CLUE, |clue| clue as u8)
+        };
+        bytes.extend((0..size).map(|r| line(&clues.rows, r)));
+        bytes.extend((0..size).map(|c| line(&clues.cols, c)));
+    }
+    bytes
+}
+
+/// Packs values of the given number of bits into one byte, the first in the
+/// highest bits.
+fn pack(values: &[u8], bits: usize) -> u8 {
+    values
+        .iter()
+        .enumerate()
+        .fold(0, |byte, (i, &value)| byte | value << (8 - bits * (i + 1)))
+}
+
+/// Returns the value of the given number of bits at the given index of
+/// bytes packed by [pack].
+fn unpack(bytes: &[u8], bits: usize, index: usize) -> u8 {
+    let per_byte = 8 / bits;
+    let shift = 8 - bits * (index % per_byte + 1);
+    (bytes[index / per_byte] >> shift) & ((1 << bits) - 1)
+}
+
+/// Reads a board and its markings from the bytes written by [to_bytes].
+///
+/// Failures carry [QSolveError::Parse] if the bytes aren't an encoded board,
+/// or [QSolveError::InvalidBoard] if they are but it breaks the rules.
+///
+/// # Examples
+/// ```
+/// # use qsolve::binary::{from_bytes, to_bytes};
+/// # use qsolve::file::QueensFile;
+/// # use qsolve::solvestate::SolveState;
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let queens_file = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\nxQxx\n....\n....\n....")?;
+/// let bytes = to_bytes(&SolveState::from(&queens_file));
+/// assert_eq!(bytes.len(), 1 + 8 + 4);
+/// let decoded = from_bytes(&bytes)?;
+/// assert_eq!(decoded.board.fingerprint(), queens_file.board.fingerprint());
+/// assert_eq!(
+///     SolveState::from(&decoded).to_string(),
+///     SolveState::from(&queens_file).to_string()
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn from_bytes(bytes: &[u8]) -> Result<QueensFile> {
+    let (board, squares) = parse(bytes).context(QSolveError::Parse)?;
+    let builder = QueensFile::builder().board(board);
+    let builder = if squares.iter().all(Option::is_none) {
+        builder
+    } else {
+        builder.squares(InputSquares(squares))
+    };
+    builder.build()
+}
+
+/// Reads the board and squares from the bytes, without validating them.
+fn parse(bytes: &[u8]) -> Result<(Board, Vec<Option<SquareVal>>)> {
+    let (&header, rest) = bytes
+        .split_first()
+        .ok_or_else(|| anyhow!("Encoded board is empty"))?;
+    let size = (header & 0x3f) as usize;
+    ensure!(
+        (1..=ALL_SQUARE_COLORS.len()).contains(&size),
+        "Encoded board has invalid size {size}"
+    );
+    let rules = match header >> 6 {
+        0 => RuleSet::AdjacencyOnly,
+        1 => RuleSet::FullDiagonals,
+        2 => RuleSet::NoDiagonals,
+        rules => return Err(anyhow!("Encoded board has unknown rules {rules}")),
+    };
+    let square_count = size * size;
+    let (colors, rest) = rest
+        .split_at_checked(square_count.div_ceil(2))
+        .ok_or_else(|| anyhow!("Encoded board is missing colors"))?;
+    let (cells, rest) = rest
+        .split_at_checked(square_count.div_ceil(4))
+        .ok_or_else(|| anyhow!("Encoded board is missing squares"))?;
+    let colors = (0..square_count)
+        .map(|i| ALL_SQUARE_COLORS[unpack(colors, 4, i) as usize])
+        .collect();
+    let squares = (0..square_count)
+        .map(|i| match unpack(cells, 2, i) {
+            0 => Ok(None),
+            1 => Ok(Some(SquareVal::X)),
+            2 => Ok(Some(SquareVal::Queen)),
+            value => Err(anyhow!("Encoded board has invalid square value {value}")),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut board = Board::new(size, colors).with_rules(rules);
+    match rest.len() {
+        0 => {}
+        len if len == 2 * size => {
+            let line = |bytes: &[u8]| {
+                bytes
+                    .iter()
+                    .map(|&clue| match clue {
+                        NO_CLUE => Ok(None),
+                        clue if (clue as usize) < size => Ok(Some(clue as usize)),
+                        clue => Err(anyhow!("Encoded board has invalid clue {clue}")),
+                    })
+                    .collect::<Result<Vec<_>>>()
+            };
+            let (rows, cols) = rest.split_at(size);
+            board = board.with_clues(Clues {
+                rows: line(rows)?,
+                cols: line(cols)?,
+            });
+        }
+        len => {
+            return Err(anyhow!(
+                "Encoded board has {len} unexpected bytes at the end"
+            ));
+        }
+    }
+    Ok((board, squares))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::error::QSolveError;
+
+    #[test]
+    fn bytes_roundtrip_rules_and_clues() -> Result<()> {
+        for path in [
+            "games/linkedin-1-partial.txt",
+            "games/gameofcrowns-1.txt",
+            "tests/fixtures/sandwich.txt",
+        ] {
+            let queens_file = QueensFile::try_from_text_file(&path.into())?;
+            let queens_file = queens_file
+                .into_builder()
+                .rules(RuleSet::NoDiagonals)
+                .build()?;
+            let solve_state = SolveState::from(&queens_file);
+            let decoded = from_bytes(&solve_state.to_bytes())?;
+            assert_eq!(decoded.board.rules(), RuleSet::NoDiagonals, "{path}");
+            assert_eq!(decoded.board.clues(), queens_file.board.clues(), "{path}");
+            assert_eq!(
+                decoded.board.fingerprint(),
+                queens_file.board.fingerprint(),
+                "{path}"
+            );
+            assert_eq!(
+                SolveState::from(&decoded).to_string(),
+                solve_state.to_string(),
+                "{path}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn bytes_reject_corruption() -> Result<()> {
+        let queens_file = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        let bytes = SolveState::from(&queens_file).to_bytes();
+        let parse_error = |bytes: &[u8]| QSolveError::from_error(&from_bytes(bytes).unwrap_err());
+        assert_eq!(parse_error(&[]), Some(QSolveError::Parse));
+        assert_eq!(parse_error(&bytes[..5]), Some(QSolveError::Parse));
+        assert_eq!(
+            parse_error(&[bytes.as_slice(), &[0]].concat()),
+            Some(QSolveError::Parse)
+        );
+        // Two Queens in the first row.
+        let mut two_queens = bytes.clone();
+        two_queens[9] = 0b1010_0000;
+        assert_eq!(parse_error(&two_queens), Some(QSolveError::InvalidBoard));
+        Ok(())
+    }
+}
@@ -3,12 +3,17 @@ use std::{
     str::FromStr,
 };
 
-use anyhow::{Result, ensure};
+use anyhow::{Context, Result, anyhow, bail, ensure};
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
 use itertools::{Itertools, Position, iproduct};
 
 use crate::{
+    clue::Clues,
     datastructure::{Coord, CoordSet},
-    squarecolor::SquareColor,
+    error::QSolveError,
+    repair::SplitRegion,
+    squarecolor::{ALL_SQUARE_COLORS, SquareColor},
 };
 
 /// A representation of a Queens board.
@@ -64,14 +69,66 @@ use crate::{
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Eq, PartialEq)]
 pub struct Board {
     size: usize,
     colors: Vec<SquareColor>,
     coords: CoordSet,
+    rules: RuleSet,
+    clues: Clues,
     queen_borders: Vec<CoordSet>,
 }
 
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+/// Which squares along the diagonals a Queen rules out, beyond its row,
+/// column and color, since variants of Queens differ on this.
+pub enum RuleSet {
+    /// Queens can't touch, even diagonally, as in LinkedIn's Queens
+    #[default]
+    AdjacencyOnly,
+    /// Queens can't share any diagonal, like chess queens
+    FullDiagonals,
+    /// Queens can touch diagonally; only rows, columns and colors count
+    #[cfg_attr(feature = "cli", value(name = "none"))]
+    NoDiagonals,
+}
+
+/// The direction of a line of squares through the board.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Direction {
+    /// Across the board, along a row.
+    Row,
+    /// Down the board, along a column.
+    Col,
+    /// From the upper left towards the lower right.
+    Diagonal,
+    /// From the upper right towards the lower left.
+    AntiDiagonal,
+}
+
+impl RuleSet {
+    /// Returns whether Queens on the two given squares rule each other out
+    /// along a diagonal under these rules.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::RuleSet;
+    /// assert!(RuleSet::AdjacencyOnly.diagonal_conflict((1, 1), (2, 2)));
+    /// assert!(!RuleSet::AdjacencyOnly.diagonal_conflict((1, 1), (3, 3)));
+    /// assert!(RuleSet::FullDiagonals.diagonal_conflict((1, 1), (3, 3)));
+    /// assert!(!RuleSet::NoDiagonals.diagonal_conflict((1, 1), (2, 2)));
+    /// ```
+    pub fn diagonal_conflict(&self, a: Coord, b: Coord) -> bool {
+        let (dr, dc) = (a.0.abs_diff(b.0), a.1.abs_diff(b.1));
+        match self {
+            RuleSet::AdjacencyOnly => dr == 1 && dc == 1,
+            RuleSet::FullDiagonals => dr == dc && dr > 0,
+            RuleSet::NoDiagonals => false,
+        }
+    }
+}
+
 impl Board {
     /// Creates a new Board with the given size and [SquareColor]s.
     ///
@@ -96,12 +153,79 @@ impl Board {
             size,
             colors,
             coords,
+            rules: RuleSet::default(),
+            clues: Clues::default(),
             queen_borders: vec![],
         };
         board.compute_queen_borders();
         board
     }
 
+    /// Returns a [BoardBuilder] for a board of the given size, whose regions
+    /// are given by labels of any type rather than by [SquareColor]s.
+    pub fn builder<L: Clone + Eq>(size: usize) -> BoardBuilder<L> {
+        BoardBuilder {
+            size,
+            labels: vec![None; size * size],
+            colors: vec![],
+            ragged_row: None,
+        }
+    }
+
+    /// Returns this board played under the given [RuleSet], rather than the
+    /// default of [RuleSet::AdjacencyOnly].
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::{Board, RuleSet};
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("kkkk\nkrrr\nbbbb\nwwww")?.with_rules(RuleSet::FullDiagonals);
+    /// assert_eq!(board.rules(), RuleSet::FullDiagonals);
+    /// assert!(board.queen_borders(&(0, 0)).contains(&(3, 3)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_rules(mut self, rules: RuleSet) -> Self {
+        if rules != self.rules {
+            self.rules = rules;
+            self.compute_queen_borders();
+        }
+        self
+    }
+
+    /// Returns the [RuleSet] the board is played under.
+    pub fn rules(&self) -> RuleSet {
+        self.rules
+    }
+
+    /// Returns this board with the given [Clues] on its rows and columns.
+    ///
+    /// # Panics
+    /// Panics if there are clues for a different number of rows or columns
+    /// than the board has, or a clue that is too large for the board.
+    pub fn with_clues(mut self, clues: Clues) -> Self {
+        for lines in [&clues.rows, &clues.cols] {
+            assert!(
+                lines.is_empty() || lines.len() == self.size,
+                "Clues must be given for every line or none"
+            );
+            assert!(
+                lines.iter().flatten().all(|&clue| clue < self.size),
+                "Clues must be less than the size"
+            );
+        }
+        self.clues = clues;
+        self
+    }
+
+    /// Returns the [Clues] on the board's rows and columns, which are empty
+    /// unless the board is a sandwich variant.
+    pub fn clues(&self) -> &Clues {
+        &self.clues
+    }
+
     /// Returns the length/width of the board.
     ///
     /// # Examples
@@ -115,46 +239,45 @@ impl Board {
         self.size
     }
 
-    /// Returns the total number of squares on the board.
+    /// Returns whether the given coord is one of the board's squares.
+    ///
+    /// Coords that come from outside, such as squares a player typed, should
+    /// be checked with this before going into a [CoordSet], which can't
+    /// tell every coord off the board apart from one on it.
     ///
     /// # Examples
     /// ```
     /// # use qsolve::board::Board;
     /// # use qsolve::squarecolor::SquareColor;
     /// let board = Board::new(4, vec![SquareColor::Black; 16]);
-    /// assert_eq!(board.square_count(), 16);
+    /// assert!(board.contains(&(3, 0)));
+    /// assert!(!board.contains(&(0, 4)));
+    /// assert!(!board.contains(&(98, 0)));
     /// ```
-    pub fn square_count(&self) -> usize {
-        self.size * self.size
+    pub fn contains(&self, (row, col): &Coord) -> bool {
+        *row < self.size && *col < self.size
     }
 
-    /// Converts a [Coord] to its index in a 1D, row-major representation.
+    /// Returns the total number of squares on the board.
     ///
     /// # Examples
     /// ```
     /// # use qsolve::board::Board;
     /// # use qsolve::squarecolor::SquareColor;
     /// let board = Board::new(4, vec![SquareColor::Black; 16]);
-    /// assert_eq!(board.coord_to_idx(&(0,0)), 0);
-    /// assert_eq!(board.coord_to_idx(&(0,1)), 1);
-    /// assert_eq!(board.coord_to_idx(&(1,0)), 4);
+    /// assert_eq!(board.square_count(), 16);
     /// ```
-    pub fn coord_to_idx(&self, coord: &Coord) -> usize {
+    pub fn square_count(&self) -> usize {
+        self.size * self.size
+    }
+
+    /// Converts a [Coord] to its index in a 1D, row-major representation.
+    pub(crate) fn coord_to_idx(&self, coord: &Coord) -> usize {
         coord.0 * self.size + coord.1
     }
 
     /// Converts an index from a 1D, row-major representation to a [Coord].
-    ///
-    /// # Examples
-    /// ```
-    /// # use qsolve::board::Board;
-    /// # use qsolve::squarecolor::SquareColor;
-    /// let board = Board::new(4, vec![SquareColor::Black; 16]);
-    /// assert_eq!(board.idx_to_coord(&0), (0,0));
-    /// assert_eq!(board.idx_to_coord(&1), (0,1));
-    /// assert_eq!(board.idx_to_coord(&4), (1,0));
-    /// ```
-    pub fn idx_to_coord(&self, idx: &usize) -> Coord {
+    pub(crate) fn idx_to_coord(&self, idx: &usize) -> Coord {
         (idx / self.size, idx % self.size)
     }
 
@@ -241,7 +364,7 @@ impl Board {
     /// assert_eq!(board.row_coords(1), CoordSet::from_iter(vec![(1,0),(1,1),(1,2),(1,3)]));
     /// ```
     pub fn row_coords(&self, r: usize) -> CoordSet {
-        (0..self.size).map(|c| (r, c)).collect()
+        CoordSet::from_row_masks([(r, (0..self.size).collect())])
     }
 
     /// Returns a list of all [Coord]s in a given column.
@@ -258,8 +381,87 @@ impl Board {
         (0..self.size).map(|r| (r, c)).collect()
     }
 
-    /// Returns a set of all [Coord]s that are eliminated (by row, col, color or proximity)
-    /// if a queen is placed in the given square.
+    /// Returns the squares directly above, below, left and right of the given
+    /// one, leaving out any that would be off the board.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::datastructure::CoordSet;
+    /// # use qsolve::squarecolor::SquareColor;
+    /// let board = Board::new(4, vec![SquareColor::Black; 16]);
+    /// assert_eq!(board.adjacent_coords((0, 1)), CoordSet::from_iter(vec![(0,0),(0,2),(1,1)]));
+    /// ```
+    pub fn adjacent_coords(&self, (row, col): Coord) -> CoordSet {
+        self.on_board([
+            (row.wrapping_sub(1), col),
+            (row + 1, col),
+            (row, col.wrapping_sub(1)),
+            (row, col + 1),
+        ])
+    }
+
+    /// Returns the squares that touch the corners of the given one, leaving
+    /// out any that would be off the board.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::datastructure::CoordSet;
+    /// # use qsolve::squarecolor::SquareColor;
+    /// let board = Board::new(4, vec![SquareColor::Black; 16]);
+    /// assert_eq!(board.diagonal_neighbors((0, 1)), CoordSet::from_iter(vec![(1,0),(1,2)]));
+    /// ```
+    pub fn diagonal_neighbors(&self, (row, col): Coord) -> CoordSet {
+        self.on_board([
+            (row.wrapping_sub(1), col.wrapping_sub(1)),
+            (row.wrapping_sub(1), col + 1),
+            (row + 1, col.wrapping_sub(1)),
+            (row + 1, col + 1),
+        ])
+    }
+
+    /// Returns every square on the line through the given square in the given
+    /// [Direction], including the square itself.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::{Board, Direction};
+    /// # use qsolve::datastructure::CoordSet;
+    /// # use qsolve::squarecolor::SquareColor;
+    /// let board = Board::new(4, vec![SquareColor::Black; 16]);
+    /// assert_eq!(board.line((1, 2), Direction::Row), board.row_coords(1));
+    /// assert_eq!(board.line((1, 2), Direction::Diagonal), CoordSet::from_iter(vec![(0,1),(1,2),(2,3)]));
+    /// assert_eq!(board.line((1, 2), Direction::AntiDiagonal), CoordSet::from_iter(vec![(0,3),(1,2),(2,1),(3,0)]));
+    /// ```
+    pub fn line(&self, (row, col): Coord, direction: Direction) -> CoordSet {
+        match direction {
+            Direction::Row => self.row_coords(row),
+            Direction::Col => self.col_coords(col),
+            Direction::Diagonal => self
+                .all_coords()
+                .iter()
+                .filter(|&(r, c)| r + col == c + row)
+                .collect(),
+            Direction::AntiDiagonal => self
+                .all_coords()
+                .iter()
+                .filter(|&(r, c)| r + c == row + col)
+                .collect(),
+        }
+    }
+
+    /// Returns the given coords that are on the board.
+    fn on_board(&self, coords: [Coord; 4]) -> CoordSet {
+        coords
+            .into_iter()
+            .filter(|&(r, c)| r < self.size && c < self.size)
+            .collect()
+    }
+
+    /// Returns a set of all [Coord]s that are eliminated (by row, col, color or,
+    /// depending on the board's [RuleSet], diagonals) if a queen is placed in
+    /// the given square.
     ///
     /// # Performance
     ///
@@ -284,41 +486,79 @@ impl Board {
         self.queen_borders[self.coord_to_idx(queen)]
     }
 
+    /// Returns a stable fingerprint identifying the layout of this board.
+    ///
+    /// Two boards have the same fingerprint if they have the same size, the
+    /// same color in every square, the same [RuleSet] and the same [Clues].
+    /// Unlike [std::hash::Hash], this is guaranteed to be stable across
+    /// platforms and compiler versions, so it's safe to persist or share with
+    /// others.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("kkkk\nkrrr\nbbbb\nwwww")?;
+    /// let same_board = Board::from_str("kkkk\nkrrr\nbbbb\nwwww")?;
+    /// let other_board = Board::from_str("kkkk\nkkrr\nbbbb\nwwww")?;
+    /// assert_eq!(board.fingerprint(), same_board.fingerprint());
+    /// assert_ne!(board.fingerprint(), other_board.fingerprint());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fingerprint(&self) -> u64 {
+        // This is 64-bit FNV-1a over the size followed by each color.
+        const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+        const FNV_PRIME: u64 = 0x100000001b3;
+        // The default rules add nothing, so fingerprints from before there
+        // were other rules still match.
+        let rules = match self.rules {
+            RuleSet::AdjacencyOnly => None,
+            RuleSet::FullDiagonals => Some(u8::MAX),
+            RuleSet::NoDiagonals => Some(u8::MAX - 1),
+        };
+        // Likewise, boards without clues add nothing for them.
+        let clues = if self.clues.is_empty() {
+            vec![]
+        } else {
+            self.clues
+                .rows
+                .iter()
+                .chain(&self.clues.cols)
+                .map(|clue| clue.map_or(u8::MAX, |clue| clue as u8))
+                .collect()
+        };
+        std::iter::once(self.size as u8)
+            .chain(self.colors.iter().map(|&c| c as u8))
+            .chain(rules)
+            .chain(clues)
+            .fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+            })
+    }
+
     /// Pre-computes the queen borders to avoid repeating that computation on\
     /// repeated calls to [Board::queen_borders].
     fn compute_queen_borders(&mut self) {
         let mut queen_borders = Vec::with_capacity(self.square_count());
         for idx in 0..self.square_count() {
-            let queen = &self.idx_to_coord(&idx);
-            let mut hs = CoordSet::default();
-            hs.extend(
-                (0..self.size)
-                    .map(|r| (r, queen.1))
-                    .filter(|coord| coord != queen),
-            );
-            hs.extend(
-                (0..self.size)
-                    .map(|c| (queen.0, c))
-                    .filter(|coord| coord != queen),
-            );
-            hs.extend(
-                self.all_coords()
-                    .iter()
-                    .filter(|coord| self.color(coord) == self.color(queen))
-                    .filter(|coord| coord != queen),
-            );
-            if queen.0 > 0 && queen.1 > 0 {
-                hs.add((queen.0 - 1, queen.1 - 1));
-            }
-            if queen.0 > 0 && queen.1 < self.size - 1 {
-                hs.add((queen.0 - 1, queen.1 + 1));
-            }
-            if queen.0 < self.size - 1 && queen.1 > 0 {
-                hs.add((queen.0 + 1, queen.1 - 1));
-            }
-            if queen.0 < self.size - 1 && queen.1 < self.size - 1 {
-                hs.add((queen.0 + 1, queen.1 + 1));
+            let queen = self.idx_to_coord(&idx);
+            let mut hs = self
+                .line(queen, Direction::Row)
+                .union(&self.line(queen, Direction::Col))
+                .union(&self.coords_for_color(&self.color(&queen)));
+            match self.rules {
+                RuleSet::AdjacencyOnly => hs = hs.union(&self.diagonal_neighbors(queen)),
+                RuleSet::FullDiagonals => {
+                    hs = hs
+                        .union(&self.line(queen, Direction::Diagonal))
+                        .union(&self.line(queen, Direction::AntiDiagonal))
+                }
+                RuleSet::NoDiagonals => {}
             }
+            hs.remove(queen);
             queen_borders.push(hs);
         }
         self.queen_borders = queen_borders;
@@ -331,8 +571,9 @@ impl FromStr for Board {
     /// Attempts to parse the given string into a valid board.
     ///
     /// For a string to represent a board, it must have `n` lines, each
-    /// with `n` characters. Each character must be convertable to a
-    /// [SquareColor] using [SquareColor::try_from].
+    /// with `n` characters, where `n` is at most the number of colors. Each
+    /// character must be convertable to a [SquareColor] using
+    /// [SquareColor::try_from].
     ///
     /// # Examples
     /// ```
@@ -348,6 +589,11 @@ impl FromStr for Board {
     fn from_str(s: &str) -> Result<Self> {
         let lines = s.trim().lines().collect::<Vec<_>>();
         let size = lines.len();
+        ensure!(
+            size <= ALL_SQUARE_COLORS.len(),
+            "Invalid board: boards can be at most {} squares wide, but this is {size}.",
+            ALL_SQUARE_COLORS.len()
+        );
         for (line_num, &line) in lines.iter().enumerate() {
             ensure!(
                 line.len() == size,
@@ -381,6 +627,282 @@ impl Display for Board {
     }
 }
 
+/// Assembles a [Board] from a region label for every square, where the
+/// labels can be anything that can be compared, like numbers or names.
+///
+/// This is for importers whose regions aren't already [SquareColor]s. Each
+/// label gets its own color, in the order the labels are first seen reading
+/// row by row, unless one was chosen with [BoardBuilder::color]. Nothing is
+/// checked until [BoardBuilder::build].
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Board;
+/// # use qsolve::squarecolor::SquareColor;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let board = Board::builder(4)
+///     .rows([[1, 1, 1, 1], [1, 2, 2, 2], [3, 3, 3, 3], [4, 4, 4, 4]])
+///     .color(4, SquareColor::Blue)
+///     .build()?;
+/// assert_eq!(board.to_string(), "kkkk\nkrrr\ngggg\nbbbb");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct BoardBuilder<L> {
+    size: usize,
+    labels: Vec<Option<L>>,
+    colors: Vec<(L, SquareColor)>,
+    ragged_row: Option<(usize, usize)>,
+}
+
+impl<L: Clone + Eq> BoardBuilder<L> {
+    /// Gives the square the given region label, replacing any it had.
+    ///
+    /// Squares off the board are ignored.
+    pub fn square(mut self, (row, col): Coord, label: L) -> Self {
+        if row < self.size && col < self.size {
+            self.labels[row * self.size + col] = Some(label);
+        }
+        self
+    }
+
+    /// Gives every square a region label, row by row.
+    ///
+    /// A row of the wrong length is reported by [BoardBuilder::build].
+    pub fn rows<R: IntoIterator<Item = L>>(mut self, rows: impl IntoIterator<Item = R>) -> Self {
+        for (r, row) in rows.into_iter().enumerate() {
+            let row = row.into_iter().collect::<Vec<_>>();
+            if row.len() != self.size && self.ragged_row.is_none() {
+                self.ragged_row = Some((r, row.len()));
+            }
+            for (c, label) in row.into_iter().enumerate() {
+                self = self.square((r, c), label);
+            }
+        }
+        self
+    }
+
+    /// Chooses the color for the region with the given label, rather than
+    /// letting the builder pick one.
+    pub fn color(mut self, label: L, color: SquareColor) -> Self {
+        self.colors.retain(|(l, _)| *l != label);
+        self.colors.push((label, color));
+        self
+    }
+
+    /// Builds the board, checking that it is no wider than there are
+    /// colors, that every square has a label, that there are as many regions
+    /// as rows, and that every region is connected.
+    ///
+    /// Failures carry [QSolveError::InvalidBoard].
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::squarecolor::SquareColor;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::builder(3)
+    ///     .rows([["sky", "sky", "sea"], ["sky", "sun", "sea"], ["sun", "sun", "sea"]])
+    ///     .color("sun", SquareColor::Yellow)
+    ///     .build()?;
+    /// assert_eq!(board.to_string(), "kkr\nkyr\nyyr");
+    ///
+    /// // Region 1 is split in two.
+    /// let split = Board::builder(3).rows([[1, 2, 1], [3, 2, 2], [3, 3, 3]]);
+    /// assert!(split.build().is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn build(self) -> Result<Board> {
+        self.check().context(QSolveError::InvalidBoard)
+    }
+
+    fn check(self) -> Result<Board> {
+        ensure!(
+            (1..=ALL_SQUARE_COLORS.len()).contains(&self.size),
+            "Boards must be between 1 and {} squares wide",
+            ALL_SQUARE_COLORS.len()
+        );
+        if let Some((r, len)) = self.ragged_row {
+            bail!(
+                "Invalid board: row {} has {len} entries but the board is {} rows long.",
+                r + 1,
+                self.size
+            );
+        }
+        let labels = self
+            .labels
+            .into_iter()
+            .enumerate()
+            .map(|(idx, label)| {
+                label.ok_or_else(|| {
+                    anyhow!(
+                        "Square r{}c{} has no region",
+                        idx / self.size + 1,
+                        idx % self.size + 1
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if let Some((_, color)) = self.colors.iter().duplicates_by(|(_, color)| *color).next() {
+            bail!("Two regions were given the '{color:?}' color");
+        }
+
+        let mut assigned = self.colors;
+        let mut unused = ALL_SQUARE_COLORS
+            .into_iter()
+            .filter(|color| assigned.iter().all(|(_, c)| c != color))
+            .collect::<Vec<_>>()
+            .into_iter();
+        let mut colors = Vec::with_capacity(labels.len());
+        for label in labels {
+            let color = match assigned.iter().find(|(l, _)| *l == label) {
+                Some(&(_, color)) => color,
+                None => {
+                    let color = unused.next().ok_or_else(|| {
+                        anyhow!("Board has more than {} regions", ALL_SQUARE_COLORS.len())
+                    })?;
+                    assigned.push((label, color));
+                    color
+                }
+            };
+            colors.push(color);
+        }
+
+        let board = Board::new(self.size, colors);
+        let region_count = board.all_colors().len();
+        ensure!(
+            region_count == self.size,
+            "Board has {region_count} regions but {} rows",
+            self.size
+        );
+        if let Some(split) = SplitRegion::find(&board).first() {
+            bail!("{split}");
+        }
+        Ok(board)
+    }
+}
+
+/// A region of a board: a single color, row or column.
+///
+/// Regions parse from strings like `color=yellow` (or `color=y`, using the
+/// color's character in board files), `row=3` or `col=3`, where rows and
+/// columns are numbered from 1 as in heuristic descriptions.
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::{Board, Region};
+/// # use qsolve::squarecolor::SquareColor;
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// assert_eq!(Region::from_str("color=yellow")?, Region::Color(SquareColor::Yellow));
+/// assert_eq!(Region::from_str("color=Y")?, Region::Color(SquareColor::BrightYellow));
+/// assert_eq!(Region::from_str("row=3")?, Region::Row(2));
+/// assert!(Region::from_str("row=0").is_err());
+///
+/// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+/// assert_eq!(Region::Col(0).coords(&board), board.col_coords(0));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Region {
+    /// Every square of the given color.
+    Color(SquareColor),
+    /// The given row, numbered from 0.
+    Row(usize),
+    /// The given column, numbered from 0.
+    Col(usize),
+}
+
+impl Region {
+    /// Returns whether the region is one of the given board's rows, columns
+    /// or colors.
+    ///
+    /// Regions that come from outside, such as a `--focus` a player typed,
+    /// should be checked with this before calling [Region::coords].
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::{Board, Region};
+    /// # use qsolve::squarecolor::SquareColor;
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+    /// assert!(Region::Row(3).is_on(&board));
+    /// assert!(!Region::Col(4).is_on(&board));
+    /// assert!(!Region::Color(SquareColor::Yellow).is_on(&board));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn is_on(&self, board: &Board) -> bool {
+        match self {
+            Region::Color(color) => board.all_colors().contains(&color),
+            Region::Row(l) | Region::Col(l) => *l < board.size(),
+        }
+    }
+
+    /// Returns the squares of the region on the given board.
+    pub fn coords(&self, board: &Board) -> CoordSet {
+        match self {
+            Region::Color(color) => board.coords_for_color(color),
+            Region::Row(r) => board.row_coords(*r),
+            Region::Col(c) => board.col_coords(*c),
+        }
+    }
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Region::Color(color) => write!(f, "'{color:?}' Color"),
+            Region::Row(r) => write!(f, "Row {}", r + 1),
+            Region::Col(c) => write!(f, "Col {}", c + 1),
+        }
+    }
+}
+
+impl FromStr for Region {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (kind, value) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Expected color=<color>, row=<n> or col=<n>, got {s}"))?;
+        let line = || -> Result<usize> {
+            match value.parse::<usize>() {
+                Ok(n) if n >= 1 => Ok(n - 1),
+                _ => Err(anyhow!("Expected a line number from 1, got {value}")),
+            }
+        };
+        match kind {
+            "color" => {
+                let mut chars = value.chars();
+                let by_char = match (chars.next(), chars.next()) {
+                    (Some(c), None) => SquareColor::try_from(c).ok(),
+                    _ => None,
+                };
+                by_char
+                    .or_else(|| {
+                        ALL_SQUARE_COLORS
+                            .into_iter()
+                            .find(|color| format!("{color:?}").eq_ignore_ascii_case(value))
+                    })
+                    .map(Region::Color)
+                    .ok_or_else(|| anyhow!("Unknown color {value}"))
+            }
+            "row" => Ok(Region::Row(line()?)),
+            "col" => Ok(Region::Col(line()?)),
+            _ => Err(anyhow!("Unknown region {kind}; expected color, row or col")),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -392,6 +914,17 @@ mod tests {
         assert_eq!(board.square_count(), 16);
     }
 
+    #[test]
+    fn board_coord_idx_roundtrip() {
+        let board = Board::new(4, vec![SquareColor::Black; 16]);
+        assert_eq!(board.coord_to_idx(&(0, 0)), 0);
+        assert_eq!(board.coord_to_idx(&(0, 1)), 1);
+        assert_eq!(board.coord_to_idx(&(1, 0)), 4);
+        for idx in 0..board.square_count() {
+            assert_eq!(board.coord_to_idx(&board.idx_to_coord(&idx)), idx);
+        }
+    }
+
     #[test]
     #[should_panic]
     fn board_wrong_size() {
@@ -490,6 +1023,12 @@ mod tests {
         );
     }
 
+    #[test]
+    fn board_fingerprint() {
+        let board = Board::from_str("wwww\nkkkk\nrrrr\nbbbb").unwrap();
+        assert_eq!(board.fingerprint(), 0x2a8437d13e1f2733);
+    }
+
     #[test]
     fn board_queen_borders() {
         let board_str = "wwww\nkkkk\nrrrr\nbbbb";
@@ -501,4 +1040,104 @@ mod tests {
             CoordSet::from_iter(vec![(0, 1), (0, 2), (0, 3), (1, 0), (1, 1), (2, 0), (3, 0)])
         );
     }
+
+    #[test]
+    fn board_queen_borders_follow_rules() {
+        let board = Board::from_str("wwww\nkkkk\nrrrr\nbbbb").unwrap();
+        let fingerprint = board.fingerprint();
+
+        let board = board.with_rules(RuleSet::FullDiagonals);
+        assert_eq!(
+            board.queen_borders(&(1, 1)),
+            CoordSet::from_iter(vec![
+                (1, 0),
+                (1, 2),
+                (1, 3),
+                (0, 1),
+                (2, 1),
+                (3, 1),
+                (0, 0),
+                (0, 2),
+                (2, 0),
+                (2, 2),
+                (3, 3)
+            ])
+        );
+        assert_ne!(board.fingerprint(), fingerprint);
+
+        let board = board.with_rules(RuleSet::NoDiagonals);
+        assert!(!board.queen_borders(&(1, 1)).contains(&(0, 0)));
+        assert_eq!(board.queen_borders(&(1, 1)).len(), 6);
+
+        let board = board.with_rules(RuleSet::AdjacencyOnly);
+        assert_eq!(board.fingerprint(), fingerprint);
+    }
+
+    #[test]
+    fn region_from_str() -> Result<()> {
+        assert_eq!(
+            Region::from_str("color=yellow")?,
+            Region::Color(SquareColor::Yellow)
+        );
+        assert_eq!(Region::from_str("row=3")?, Region::Row(2));
+        assert_eq!(Region::from_str("col=1")?, Region::Col(0));
+        assert!(Region::from_str("row=0").is_err());
+        assert!(Region::from_str("color=mauve").is_err());
+        assert!(Region::from_str("square=1").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn board_builder_rejects_invalid_boards() {
+        let invalid = [
+            Board::builder(2).rows(vec![vec![1, 1], vec![2]]),
+            Board::builder(2).square((0, 0), 1),
+            Board::builder(3).rows([[1, 1, 1], [1, 2, 2], [2, 2, 2]]),
+            Board::builder(2)
+                .rows([[1, 2], [1, 2]])
+                .color(1, SquareColor::Red)
+                .color(2, SquareColor::Red),
+            Board::builder(17).rows((0..17).map(|r| vec![r; 17])),
+            Board::builder(20).rows((0..20).map(|r| vec![r % 4; 20])),
+        ];
+        for builder in invalid {
+            let err = builder.build().unwrap_err();
+            assert_eq!(
+                QSolveError::from_error(&err),
+                Some(QSolveError::InvalidBoard)
+            );
+        }
+    }
+
+    #[test]
+    fn board_builder_skips_chosen_colors() -> Result<()> {
+        let board = Board::builder(2)
+            .rows([['a', 'b'], ['a', 'b']])
+            .color('b', SquareColor::Black)
+            .color('b', SquareColor::Blue)
+            .build()?;
+        assert_eq!(board.to_string(), "kb\nkb");
+        Ok(())
+    }
+
+    #[test]
+    fn board_neighbors_and_lines() {
+        let board = Board::new(5, vec![SquareColor::Black; 25]);
+        assert_eq!(board.adjacent_coords((2, 2)).len(), 4);
+        assert_eq!(
+            board.adjacent_coords((4, 4)),
+            CoordSet::from_iter([(3, 4), (4, 3)])
+        );
+        assert_eq!(board.diagonal_neighbors((2, 2)).len(), 4);
+        assert_eq!(
+            board.diagonal_neighbors((4, 0)),
+            CoordSet::from_iter([(3, 1)])
+        );
+        assert_eq!(board.line((3, 1), Direction::Col), board.col_coords(1));
+        assert_eq!(
+            board.line((3, 1), Direction::Diagonal),
+            CoordSet::from_iter([(2, 0), (3, 1), (4, 2)])
+        );
+        assert_eq!(board.line((4, 4), Direction::AntiDiagonal).len(), 1);
+    }
 }
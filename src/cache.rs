@@ -0,0 +1,421 @@
+use std::{fmt::Write, path::PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use log::debug;
+use serde_json::{Value, json};
+
+use crate::{
+    board::Board,
+    datastructure::Coord,
+    file::QueensFile,
+    release::VERSION,
+    solution::Solution,
+    solvestate::{SolveOptions, SolveState, SolveStrategy},
+    stall::OnStall,
+};
+
+/// A previously computed solution to a board, as stored in a [SolutionCache].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CachedSolution {
+    /// Where the queens are in the solved board.
+    pub queens: Vec<Coord>,
+
+    /// How many steps the solver took to solve the board, starting from a
+    /// blank board, with the options the solution was looked up with.
+    pub steps: usize,
+}
+
+impl CachedSolution {
+    /// Returns the solved state of the given board.
+    ///
+    /// Since a queen is placed in every row, every other square is x'd out.
+    pub fn solve_state<'a>(&self, board: &'a Board) -> SolveState<'a> {
+        SolveState::with_queens(board, &self.queens)
+    }
+}
+
+impl std::fmt::Display for CachedSolution {
+    /// Displays a one-line description of the solve, in the same format as [Solution].
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "solved in {} steps", self.steps)
+    }
+}
+
+/// Returns the directory qsolve keeps its files in, as described on
+/// [SolutionCache::in_default_dir], or None if there isn't one.
+pub(crate) fn default_dir() -> Option<PathBuf> {
+    let env_dir = |var| std::env::var_os(var).filter(|dir| !dir.is_empty());
+    env_dir("QSOLVE_CACHE_DIR")
+        .map(PathBuf::from)
+        .or_else(|| env_dir("XDG_CACHE_HOME").map(|dir| PathBuf::from(dir).join("qsolve")))
+        .or_else(|| env_dir("HOME").map(|dir| PathBuf::from(dir).join(".cache/qsolve")))
+        .or_else(|| env_dir("LOCALAPPDATA").map(|dir| PathBuf::from(dir).join("qsolve")))
+}
+
+/// An on-disk cache of solved boards, keyed by [Board::fingerprint].
+///
+/// Each board is stored as its own JSON file in the cache directory, holding
+/// the version of qsolve that wrote it, its solved state in the compact
+/// [binary][crate::binary] encoding (in base64), and the step count for each
+/// set of [SolveOptions] that has solved it: the strategy, the seed, and
+/// whichever other options change the steps the solver takes. Only solves
+/// that start from a blank board are cached, since the step count depends
+/// on the starting markings, and only those without a
+/// [change filter][SolveOptions::change_filter], which the cache can't tell
+/// apart. The step counts assume the default heuristics of
+/// [all_heuristics][crate::heuristic::all_heuristics].
+///
+/// Reading from the cache never fails: a missing, unreadable or corrupt entry
+/// (including one whose queens don't actually solve the board), or one
+/// written by another version of qsolve, whose solver may take different
+/// steps, is treated as a cache miss.
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Board;
+/// # use qsolve::cache::SolutionCache;
+/// # use qsolve::heuristic::all_heuristics;
+/// # use qsolve::solution::Solution;
+/// # use qsolve::solvestate::{SolveOptions, SolveState};
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let cache = SolutionCache::new(std::env::temp_dir().join("qsolve-cache-doctest"));
+/// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+/// let heuristics = all_heuristics(&board);
+/// let solve_options = SolveOptions::default();
+/// let solution = Solution::solve_with_options(SolveState::from(&board), solve_options, &heuristics);
+/// cache.put(&solution, &solve_options)?;
+///
+/// let cached = cache.get(&SolveState::from(&board), &solve_options).unwrap();
+/// assert_eq!(cached.steps, solution.step_count());
+/// assert!(cached.solve_state(&board).complete());
+///
+/// // A different seed may take different steps.
+/// let reseeded = SolveOptions { seed: 1, ..solve_options };
+/// assert!(cache.get(&SolveState::from(&board), &reseeded).is_none());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct SolutionCache {
+    dir: PathBuf,
+}
+
+impl SolutionCache {
+    /// Creates a cache that stores its entries in the given directory.
+    ///
+    /// The directory is created the first time an entry is stored.
+    pub fn new(dir: PathBuf) -> Self {
+        SolutionCache { dir }
+    }
+
+    /// Creates a cache in the default location, or None if there isn't one.
+    ///
+    /// This is `$QSOLVE_CACHE_DIR` if it is set, and otherwise a `qsolve`
+    /// directory inside `$XDG_CACHE_HOME`, `$HOME/.cache` or `%LOCALAPPDATA%`,
+    /// whichever is found first.
+    pub fn in_default_dir() -> Option<Self> {
+        default_dir().map(SolutionCache::new)
+    }
+
+    /// Looks up the solution to the given state's board with the given options.
+    ///
+    /// This returns None if the state has any markings, if the options have a
+    /// change filter, or if the board hasn't been solved with those options
+    /// by this version of qsolve before.
+    pub fn get(
+        &self,
+        solve_state: &SolveState,
+        solve_options: &SolveOptions,
+    ) -> Option<CachedSolution> {
+        if !is_blank(solve_state) || solve_options.change_filter.is_some() {
+            return None;
+        }
+        let board = solve_state.board;
+        let entry = self
+            .read_entry(board)
+            .inspect_err(|err| debug!("Solution cache miss: {err:#}"))
+            .ok()?;
+        let steps = entry["steps"][options_key(solve_options)].as_u64()? as usize;
+        let solved = URL_SAFE_NO_PAD
+            .decode(entry["state"].as_str()?)
+            .ok()
+            .and_then(|bytes| QueensFile::from_bytes(&bytes).ok())
+            .filter(|solved| solved.board.fingerprint() == board.fingerprint());
+        let Some(solved) = solved else {
+            debug!(
+                "Solution cache entry for {:016x} has no solved state for this board",
+                board.fingerprint()
+            );
+            return None;
+        };
+        let queens = SolveState::from(&solved)
+            .queens()
+            .iter()
+            .collect::<Vec<_>>();
+        if !SolveState::with_queens(board, &queens).is_valid() {
+            debug!(
+                "Solution cache entry for {:016x} is invalid",
+                board.fingerprint()
+            );
+            return None;
+        }
+        let cached = CachedSolution { queens, steps };
+        if !cached.solve_state(board).complete() {
+            debug!(
+                "Solution cache entry for {:016x} is incomplete",
+                board.fingerprint()
+            );
+            return None;
+        }
+        Some(cached)
+    }
+
+    /// Stores the given solution, which was solved with the given options.
+    ///
+    /// Solutions that didn't solve the puzzle, that didn't start from a
+    /// blank board, or whose options have a change filter, are ignored.
+    pub fn put(&self, solution: &Solution, solve_options: &SolveOptions) -> Result<()> {
+        let Some(first) = solution.steps.first() else {
+            return Ok(());
+        };
+        if !solution.is_solved()
+            || !is_blank(&first.solve_state)
+            || solve_options.change_filter.is_some()
+        {
+            return Ok(());
+        }
+        let board = solution.final_state.board;
+        let state = URL_SAFE_NO_PAD.encode(solution.final_state.to_bytes());
+        // Keep the step counts for other options, unless they were for a
+        // different solution or from another version.
+        let mut entry = self
+            .read_entry(board)
+            .ok()
+            .filter(|entry| entry["state"] == state.as_str())
+            .unwrap_or_else(|| json!({ "version": VERSION, "state": state, "steps": {} }));
+        entry["steps"][options_key(solve_options)] = solution.step_count().into();
+
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Could not create cache directory {:?}", self.dir))?;
+        let path = self.entry_path(board);
+        std::fs::write(&path, serde_json::to_string_pretty(&entry)?)
+            .with_context(|| format!("Could not write cache entry {path:?}"))
+    }
+
+    /// Returns the path of the file storing the entry for the given board.
+    fn entry_path(&self, board: &Board) -> PathBuf {
+        self.dir.join(format!("{:016x}.json", board.fingerprint()))
+    }
+
+    /// Reads the raw entry for the given board.
+    fn read_entry(&self, board: &Board) -> Result<Value> {
+        let path = self.entry_path(board);
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Could not read cache entry {path:?}"))?;
+        let entry: Value = serde_json::from_str(&content)?;
+        if entry["version"] != VERSION {
+            Err(anyhow!(
+                "Cache entry {path:?} is from version {} of qsolve, not {VERSION}",
+                entry["version"]
+            ))
+        } else if entry["steps"].is_object() {
+            Ok(entry)
+        } else {
+            Err(anyhow!("Cache entry {path:?} has no step counts"))
+        }
+    }
+}
+
+/// Returns whether no square of the given state has been marked.
+fn is_blank(solve_state: &SolveState) -> bool {
+    solve_state
+        .board
+        .all_coords()
+        .iter()
+        .all(|coord| solve_state.square(&coord).is_none())
+}
+
+/// Returns the key under which step counts for solves with the given options
+/// are stored, which has the strategy and seed, and any other option that
+/// changes the steps that strategy takes.
+fn options_key(solve_options: &SolveOptions) -> String {
+    let strategy = match solve_options.strategy {
+        SolveStrategy::Fast => "fast",
+        SolveStrategy::Short => "short",
+        SolveStrategy::Simple => "simple",
+        SolveStrategy::Human => "human",
+    };
+    let mut key = format!("{strategy}/seed={}", solve_options.seed);
+    match solve_options.strategy {
+        SolveStrategy::Short => write!(key, "/lookahead={}", solve_options.lookahead),
+        SolveStrategy::Human => write!(key, "/miss={}", solve_options.miss_percent),
+        SolveStrategy::Fast | SolveStrategy::Simple => Ok(()),
+    }
+    .expect("Writing to a String can't fail");
+    if solve_options.on_stall == OnStall::Search {
+        key.push_str("/on_stall=search");
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{file::QueensFile, heuristic::all_heuristics};
+
+    use super::*;
+
+    fn options(strategy: SolveStrategy) -> SolveOptions {
+        SolveOptions {
+            strategy,
+            ..SolveOptions::default()
+        }
+    }
+
+    fn test_cache(name: &str) -> SolutionCache {
+        let dir = std::env::temp_dir().join(format!("qsolve-cache-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        SolutionCache::new(dir)
+    }
+
+    #[test]
+    fn cache_roundtrips_per_strategy() -> Result<()> {
+        let cache = test_cache("roundtrip");
+        let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        let heuristics = all_heuristics(&board);
+        assert!(
+            cache
+                .get(&SolveState::from(&board), &options(SolveStrategy::Fast))
+                .is_none()
+        );
+
+        let solution = Solution::solve(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+        cache.put(&solution, &options(SolveStrategy::Fast))?;
+        let cached = cache
+            .get(&SolveState::from(&board), &options(SolveStrategy::Fast))
+            .unwrap();
+        assert_eq!(cached.queens, vec![(0, 1), (1, 3), (2, 0), (3, 2)]);
+        assert_eq!(cached.to_string(), solution.to_string());
+        assert_eq!(
+            format!("{}", cached.solve_state(&board)),
+            format!("{}", solution.final_state)
+        );
+        assert!(
+            cache
+                .get(&SolveState::from(&board), &options(SolveStrategy::Short))
+                .is_none()
+        );
+
+        let solution = Solution::solve(SolveState::from(&board), SolveStrategy::Short, &heuristics);
+        cache.put(&solution, &options(SolveStrategy::Short))?;
+        assert!(
+            cache
+                .get(&SolveState::from(&board), &options(SolveStrategy::Short))
+                .is_some()
+        );
+        assert!(
+            cache
+                .get(&SolveState::from(&board), &options(SolveStrategy::Fast))
+                .is_some()
+        );
+
+        // The Human strategy's steps depend on its seed and how much it misses.
+        let human = options(SolveStrategy::Human);
+        let solution = Solution::solve_with_options(SolveState::from(&board), human, &heuristics);
+        cache.put(&solution, &human)?;
+        assert!(cache.get(&SolveState::from(&board), &human).is_some());
+        for other in [
+            SolveOptions { seed: 7, ..human },
+            SolveOptions {
+                miss_percent: 0,
+                ..human
+            },
+        ] {
+            assert!(cache.get(&SolveState::from(&board), &other).is_none());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn cache_ignores_marked_and_unsolved_states() -> Result<()> {
+        let cache = test_cache("ignores");
+        let queens_file = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\nx...\n....\n....\n....")?;
+        let heuristics = all_heuristics(&queens_file.board);
+        let solution = Solution::solve(
+            SolveState::from(&queens_file),
+            SolveStrategy::Fast,
+            &heuristics,
+        );
+        cache.put(&solution, &options(SolveStrategy::Fast))?;
+        assert!(
+            cache
+                .get(
+                    &SolveState::from(&queens_file.board),
+                    &options(SolveStrategy::Fast)
+                )
+                .is_none()
+        );
+
+        let board = Board::from_str("wwww\nkkkk\nrrrr\nbbbb")?;
+        let heuristics = all_heuristics(&board);
+        let solution = Solution::solve(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+        cache.put(&solution, &options(SolveStrategy::Fast))?;
+        assert!(
+            cache
+                .get(&SolveState::from(&board), &options(SolveStrategy::Fast))
+                .is_none()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cache_rejects_corrupt_entries() -> Result<()> {
+        let cache = test_cache("corrupt");
+        let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        std::fs::create_dir_all(&cache.dir)?;
+        let state = |text: &str| -> Result<String> {
+            let queens_file = QueensFile::from_str(text)?;
+            Ok(URL_SAFE_NO_PAD.encode(SolveState::from(&queens_file).to_bytes()))
+        };
+        let solved = state("wwww\nwkkk\nrrrr\nbbbb\n\nxQxx\nxxxQ\nQxxx\nxxQx")?;
+        let unsolved = state("wwww\nwkkk\nrrrr\nbbbb\n\nxQxx\n....\n....\n....")?;
+        let other_board = state("wwww\nkkkk\nrrrr\nbbbb\n\nxQxx\nxxxQ\nQxxx\nxxQx")?;
+        let key = options_key(&options(SolveStrategy::Fast));
+        for content in [
+            "not json".to_string(),
+            format!(
+                r#"{{"version": "{VERSION}", "state": "not base64!", "steps": {{"{key}": 1}}}}"#
+            ),
+            format!(
+                r#"{{"version": "{VERSION}", "state": "{unsolved}", "steps": {{"{key}": 1}}}}"#
+            ),
+            format!(
+                r#"{{"version": "{VERSION}", "state": "{other_board}", "steps": {{"{key}": 1}}}}"#
+            ),
+            format!(r#"{{"version": "{VERSION}", "state": "{solved}"}}"#),
+            // Another version's solver may have taken different steps.
+            format!(r#"{{"version": "0.0.1", "state": "{solved}", "steps": {{"{key}": 1}}}}"#),
+            format!(r#"{{"state": "{solved}", "steps": {{"{key}": 1}}}}"#),
+        ] {
+            std::fs::write(cache.entry_path(&board), content)?;
+            assert!(
+                cache
+                    .get(&SolveState::from(&board), &options(SolveStrategy::Fast))
+                    .is_none()
+            );
+        }
+        let content =
+            format!(r#"{{"version": "{VERSION}", "state": "{solved}", "steps": {{"{key}": 1}}}}"#);
+        std::fs::write(cache.entry_path(&board), content)?;
+        assert!(
+            cache
+                .get(&SolveState::from(&board), &options(SolveStrategy::Fast))
+                .is_some()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,1376 @@
+use std::{
+    ffi::OsStr,
+    io::{IsTerminal, Read},
+    path::Path,
+    str::FromStr,
+    sync::OnceLock,
+    time::Duration,
+};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use image::{ImageFormat, RgbImage};
+use itertools::Itertools;
+use log::warn;
+
+use crate::{
+    board::{Board, Region, RuleSet},
+    datastructure::Coord,
+    file::QueensFile,
+    fit::fit_to_width,
+    generate::Difficulty,
+    heuristic::{Heuristic, advanced_heuristics, all_heuristics},
+    link::{LINK_SCHEME, from_link, is_link},
+    notation::CoordStyle,
+    palette::Palette,
+    release::{Channel, RELEASES_URL},
+    repair::ColorEdit,
+    session::MarkingMode,
+    share::{ShareStyle, board_from_share_text},
+    solvestate::{Charset, Overlay, SolveOptions, SolveStrategy, SquareVal},
+    spoiler::SpoilerStyle,
+};
+
+/// The command line accepted by the `qsolve` binary.
+#[derive(Parser)]
+#[command(
+    version,
+    about,
+    propagate_version = true,
+    after_help = "Exit codes:
+  0  Success
+  1  Any other failure
+  2  Invalid command line arguments
+  3  The puzzle could not be parsed
+  4  The puzzle is not a valid Queens puzzle
+  5  No Queens grid could be detected in the image
+  6  The puzzle could not be solved
+  7  A step made invalid changes with --checked, or a proof doesn't check"
+)]
+pub struct Cli {
+    /// The seed for any choice made at random, such as the deductions the
+    /// human strategy overlooks, so that runs can be reproduced. Solving
+    /// defaults to 0, and generating to the current time
+    #[clap(long, global = true)]
+    pub seed: Option<u64>,
+
+    /// Write a timed span for each piece of solver work (such as one
+    /// heuristic checking one board state) to this file, as JSON lines
+    #[clap(long, global = true)]
+    pub trace_json: Option<std::path::PathBuf>,
+
+    /// The subcommand to run
+    #[command(subcommand)]
+    pub command: Commands,
+}
+
+/// The subcommands of the `qsolve` binary.
+#[derive(Debug, Subcommand)]
+pub enum Commands {
+    /// Prints out the board
+    Print {
+        /// Which file to read the board from
+        #[command(flatten)]
+        path_args: PathCli,
+
+        /// How to display the board
+        #[command(flatten)]
+        display_args: DisplayCli,
+    },
+
+    /// Display an animation of the solving of the board
+    Animate {
+        /// Which file to read the board from
+        #[command(flatten)]
+        path_args: PathCli,
+
+        /// How to display the board
+        #[command(flatten)]
+        display_args: DisplayCli,
+
+        /// How to solve the board
+        #[command(flatten)]
+        solve_args: SolveCli,
+
+        /// The length of delay between animation steps, in ms
+        #[clap(long, value_parser = |s: &str| s.parse().map(Duration::from_millis), default_value = "500")]
+        delay: Duration,
+
+        /// Fast-forward through this many steps (the hints a player would
+        /// have asked for) without animating them, to show only the endgame
+        #[clap(long, default_value_t = 0)]
+        from_hints: usize,
+    },
+
+    /// Solve the board and display the solution
+    Solve {
+        /// Which file to read the board from
+        #[command(flatten)]
+        path_args: PathCli,
+
+        /// How to display the board
+        #[command(flatten)]
+        display_args: DisplayCli,
+
+        /// How to solve the board
+        #[command(flatten)]
+        solve_args: SolveCli,
+
+        /// How to generate the share text
+        #[command(flatten)]
+        share_args: ShareCli,
+
+        /// How to format the output
+        #[command(flatten)]
+        output_args: OutputCli,
+
+        /// Don't read or write the on-disk cache of solved boards
+        #[clap(long, default_value = "false")]
+        no_cache: bool,
+    },
+
+    /// Solve boards repeatedly for profiling
+    Profile {
+        /// Which file to read the board from
+        #[command(flatten)]
+        path_args: PathCli,
+
+        /// How to solve the board
+        #[command(flatten)]
+        solve_args: SolveCli,
+
+        /// How many iterations to run
+        #[clap(long, default_value_t = 1)]
+        iterations: usize,
+
+        /// Also print counters of the solver's work, such as how many times
+        /// heuristics were checked, summed over every iteration
+        #[clap(long)]
+        metrics: bool,
+    },
+
+    /// Provide a hint about the next move on the board
+    Hint {
+        /// Which file to read the board from
+        #[command(flatten)]
+        path_args: PathCli,
+
+        /// How to display the board
+        #[command(flatten)]
+        display_args: DisplayCli,
+
+        /// How to solve the board
+        #[command(flatten)]
+        solve_args: SolveCli,
+
+        /// The type of hint that should be provided
+        #[clap(long, default_value = "combined")]
+        hint_type: HintType,
+
+        /// Only give a hint about the given region, such as `color=yellow`,
+        /// `row=3` or `col=3` (numbered from 1)
+        #[clap(long)]
+        focus: Option<Region>,
+
+        /// List this many distinct applicable heuristics, best first, with
+        /// the number of squares each would decide, instead of one hint
+        #[clap(long)]
+        alternatives: Option<usize>,
+
+        /// Hide the hint and the squares it changes in this style, and only
+        /// draw it on the board once asked to at a prompt
+        #[clap(long, num_args = 0..=1, require_equals = true, default_missing_value = "rot13")]
+        spoiler_safe: Option<SpoilerStyle>,
+    },
+
+    /// Compare the markings in a file against a reference file for the same board
+    #[command(allow_missing_positional = true)]
+    Compare {
+        /// Which file to read the board from
+        #[command(flatten)]
+        path_args: PathCli,
+
+        /// How to display the board
+        #[command(flatten)]
+        display_args: DisplayCli,
+
+        /// The path to the file containing the reference markings
+        reference: std::path::PathBuf,
+
+        /// How the markings were made: with `assisted`, the squares that a
+        /// Queen rules out count as x'd out even where a file leaves them
+        /// blank, as they would be in the LinkedIn app
+        #[clap(long, default_value = "assisted")]
+        marking_mode: MarkingMode,
+    },
+
+    /// Print a link that carries the whole board, which any command that
+    /// reads a board accepts in place of a file
+    Link {
+        /// Which file to read the board from
+        #[command(flatten)]
+        path_args: PathCli,
+
+        /// Print just the base64, for the fragment of a URL, instead of a
+        /// `qsolve://` link
+        #[arg(long)]
+        plain: bool,
+
+        /// Also draw the link as a QR code in the terminal, to scan with
+        /// another device
+        #[arg(long)]
+        qr: bool,
+
+        /// Also save the link as a QR code in this PNG file
+        #[arg(long)]
+        qr_png: Option<std::path::PathBuf>,
+    },
+
+    /// Solve the board in a screenshot and save a copy of the screenshot
+    /// with a crown on each Queen, to hold up against the game
+    Overlay {
+        /// Which screenshot to read the board from
+        #[command(flatten)]
+        path_args: PathCli,
+
+        /// How to solve the board
+        #[command(flatten)]
+        solve_args: SolveCli,
+
+        /// Where to save the screenshot with the Queens drawn on it
+        #[clap(short, long)]
+        output: std::path::PathBuf,
+    },
+
+    /// Score how hard the puzzle is for a human, from the heuristics its
+    /// simplest solve needs and how many squares they examine
+    Grade {
+        /// Which file to read the board from
+        #[command(flatten)]
+        path_args: PathCli,
+
+        /// How to print the grade
+        #[clap(long, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Search the board for every solution, and report whether it has none,
+    /// exactly one, or more than one; it fails unless there is exactly one
+    #[command(allow_missing_positional = true)]
+    Verify {
+        /// Which file to read the board from
+        #[command(flatten)]
+        path_args: PathCli,
+
+        /// Count every solution rather than stopping at the second, which
+        /// can be slow on boards with few distinct regions
+        #[clap(long)]
+        count: bool,
+
+        /// How to display the board
+        #[command(flatten)]
+        display_args: DisplayCli,
+    },
+
+    /// Verify that a share payload matches solving the board
+    #[command(allow_missing_positional = true)]
+    VerifyShare {
+        /// Which file to read the board from
+        #[command(flatten)]
+        path_args: PathCli,
+
+        /// The payload emitted by `solve --share --share-payload`
+        payload: String,
+    },
+
+    /// Solve the board and print a certificate of the deductions that solve
+    /// it, as JSON, which `check-proof` can check without the solver
+    Prove {
+        /// Which file to read the board from
+        #[command(flatten)]
+        path_args: PathCli,
+
+        /// How to solve the board
+        #[command(flatten)]
+        solve_args: SolveCli,
+    },
+
+    /// Check a certificate from `prove` against the board, replaying each
+    /// deduction from its premises alone
+    #[command(allow_missing_positional = true)]
+    CheckProof {
+        /// Which file to read the board from
+        #[command(flatten)]
+        path_args: PathCli,
+
+        /// The path to the certificate printed by `prove`
+        proof: std::path::PathBuf,
+    },
+
+    /// Show how many solving steps considered and marked each square
+    Heatmap {
+        /// Which file to read the board from
+        #[command(flatten)]
+        path_args: PathCli,
+
+        /// How to solve the board
+        #[command(flatten)]
+        solve_args: SolveCli,
+
+        /// How to render the heatmap
+        #[clap(long, default_value = "terminal")]
+        heatmap_format: HeatmapFormat,
+    },
+
+    /// Find the boards in a directory that the solver stalls on, and suggest
+    /// what new heuristics would solve them
+    Discover {
+        /// The directory of board text files to solve
+        dir: std::path::PathBuf,
+
+        /// How to solve the boards
+        #[command(flatten)]
+        solve_args: SolveCli,
+    },
+
+    /// Manage the community corpus of freely licensed boards
+    Corpus {
+        /// What to do with the corpus
+        #[command(subcommand)]
+        command: CorpusCommand,
+    },
+
+    /// Solve one or more boards and print a line about each, optionally
+    /// drawing them into a single image
+    Report {
+        /// The files to read the boards from
+        #[clap(required = true)]
+        paths: Vec<std::path::PathBuf>,
+
+        /// How to solve the boards
+        #[command(flatten)]
+        solve_args: SolveCli,
+
+        /// Write a montage to the given .svg or .png file: the key steps of
+        /// the solve for a single board, or where each board ended up for
+        /// several (PNG montages have no captions)
+        #[clap(long)]
+        montage: Option<std::path::PathBuf>,
+
+        /// How many boards to put in each row of the montage
+        #[clap(long, default_value = "4")]
+        montage_columns: usize,
+
+        /// What colors to draw the montage with
+        #[clap(long, default_value = "pastel")]
+        palette: Palette,
+    },
+
+    /// Solve the board and explain why the given square ended up marked the
+    /// way it is
+    #[command(allow_missing_positional = true)]
+    WhyFilled {
+        /// Which file to read the board from
+        #[command(flatten)]
+        path_args: PathCli,
+
+        /// The square to explain, like `r3c4` (numbered from 1, unless
+        /// `--coords` says otherwise)
+        square: String,
+
+        /// How to display the board
+        #[command(flatten)]
+        display_args: DisplayCli,
+
+        /// How to solve the board
+        #[command(flatten)]
+        solve_args: SolveCli,
+    },
+
+    /// List every kind of heuristic the solver uses, with how hard each is
+    /// and what its descriptions say
+    Heuristics {
+        /// How to print the list
+        #[clap(long, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Solve boards read from stdin, printing a line of JSON for each, so
+    /// that benchmarks don't pay for starting qsolve on every board
+    ///
+    /// Each request is either a line of JSON, an object whose `board` is the
+    /// text of a board file (as a string, or an array of lines) and whose
+    /// optional `id` is echoed back, or a line holding a number N followed
+    /// by N lines of board text.
+    BenchServer {
+        /// How to solve the boards
+        #[command(flatten)]
+        solve_args: SolveCli,
+    },
+
+    /// Start an interactive session; type `help` at the prompt for a list of commands
+    Repl {
+        /// The path to a file containing a board to load at startup
+        path: Option<std::path::PathBuf>,
+
+        /// How the puzzles are played
+        #[command(flatten)]
+        play_args: PlayCli,
+
+        /// How to display the board
+        #[command(flatten)]
+        display_args: DisplayCli,
+
+        /// How to solve the board
+        #[command(flatten)]
+        solve_args: SolveCli,
+    },
+
+    /// Step through solving the board one heuristic at a time, seeing why
+    /// each heuristic does or doesn't apply; type `help` at the prompt for
+    /// a list of commands
+    Debug {
+        /// Which file to read the board from
+        #[command(flatten)]
+        path_args: PathCli,
+
+        /// How to display the board
+        #[command(flatten)]
+        display_args: DisplayCli,
+
+        /// How to solve the board
+        #[command(flatten)]
+        solve_args: SolveCli,
+    },
+
+    /// Fix the colors of a text board, such as one read from a screenshot,
+    /// by merging colors or splitting squares off into their own color, and
+    /// print the fixed board in the text format
+    FixColors {
+        /// The text file containing the board
+        path: std::path::PathBuf,
+
+        /// Merge a color into another, like `y=Y` to make every yellow
+        /// square bright yellow. Merges are made before splits
+        #[clap(long, value_parser = ColorEdit::parse_merge)]
+        merge: Vec<ColorEdit>,
+
+        /// Give some squares a new color, like `r3c4,r3c5=g` to make those
+        /// two squares green
+        #[clap(long)]
+        split: Vec<String>,
+
+        /// How squares are written in `--split`: `r1c1`, `c1r1`, `r0c0` or
+        /// `c0r0`
+        #[clap(long, default_value = "r1c1")]
+        coords: CoordStyle,
+
+        /// Write the fixed board back to the file instead of printing it
+        #[clap(long)]
+        in_place: bool,
+    },
+
+    /// Paint the colors of a board square by square in the terminal, and
+    /// save it in the text format
+    Edit {
+        /// The text file to save the board to. If it already exists, its
+        /// board is loaded to be edited
+        path: std::path::PathBuf,
+
+        /// How many squares wide a new board should be. Defaults to 8
+        #[clap(long)]
+        size: Option<usize>,
+
+        /// How to display the board
+        #[command(flatten)]
+        display_args: DisplayCli,
+    },
+
+    /// Generate a new board with exactly one solution, which the solver can
+    /// solve without searching, and print it in the text format
+    Generate {
+        /// How many squares wide the board should be
+        #[clap(long, default_value_t = 8)]
+        size: usize,
+
+        /// How hard the board should be
+        #[clap(long, default_value = "medium")]
+        difficulty: Difficulty,
+
+        /// Write the board to this file instead of printing it
+        #[clap(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+
+    /// Play generated puzzles one after another, recording how each went
+    Practice {
+        /// How many squares wide each board should be
+        #[clap(long, default_value_t = 8)]
+        size: usize,
+
+        /// How hard each board should be
+        #[clap(long, default_value = "medium")]
+        difficulty: Difficulty,
+
+        /// How the puzzles are played
+        #[command(flatten)]
+        play_args: PlayCli,
+
+        /// How to display the board
+        #[command(flatten)]
+        display_args: DisplayCli,
+
+        /// How to solve the board
+        #[command(flatten)]
+        solve_args: SolveCli,
+    },
+
+    /// Export or combine the results of the puzzles played in practice mode
+    History {
+        /// What to do with the history
+        #[command(subcommand)]
+        command: HistoryCommand,
+    },
+
+    /// Play a puzzle against someone else, taking turns on a shared duel
+    /// file; whoever resolves the most squares wins
+    Duel {
+        /// What to do with the duel
+        #[command(subcommand)]
+        command: DuelCommand,
+    },
+
+    /// Play every puzzle in a pack in turn, and print a scoreboard of the
+    /// times taken, with penalties for hints and mistakes
+    Tournament {
+        /// The pack of puzzles to play, such as `games/sample.qpk`
+        pack: std::path::PathBuf,
+
+        /// How many seconds to add for each hint or solver step
+        #[clap(long, default_value_t = 30)]
+        hint_penalty: u64,
+
+        /// How many seconds to add for each move that disagrees with the
+        /// solution
+        #[clap(long, default_value_t = 10)]
+        mistake_penalty: u64,
+
+        /// How the puzzles are played
+        #[command(flatten)]
+        play_args: PlayCli,
+
+        /// How to display the board
+        #[command(flatten)]
+        display_args: DisplayCli,
+
+        /// How to solve the board
+        #[command(flatten)]
+        solve_args: SolveCli,
+    },
+
+    /// Manage qsolve itself
+    #[command(name = "self")]
+    SelfCmd {
+        /// What to do
+        #[command(subcommand)]
+        command: SelfCommand,
+    },
+}
+
+/// What the hint subcommand should show
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum HintType {
+    /// Show only the heuristic used, and not the resulting change.
+    Heuristic,
+    /// Show only the resulting change, and not the heuristic used.
+    Result,
+    /// Show both the heuristic used and the resulting change, in a single
+    /// board where the changed squares are drawn in reverse video.
+    #[default]
+    Combined,
+    /// Show both the heuristic used and the resulting change, as the board
+    /// with the squares the heuristic looked at highlighted, beside the
+    /// board with the changed squares highlighted (or as a single combined
+    /// board, on a terminal too narrow for both).
+    Both,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+/// What type of file to read
+pub enum FileType {
+    /// Automatically detect from the file's contents and extension, falling
+    /// back to the other type if that fails
+    #[default]
+    Auto,
+    /// Force text file parsing
+    Text,
+    /// Force image file parsing
+    Image,
+}
+
+impl FileType {
+    /// Guesses whether the file at the given path, with the given contents,
+    /// is text or an image: by the magic bytes that start most image
+    /// formats, then by the extension, and then by whether it is UTF-8.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::cli::FileType;
+    /// # use std::path::Path;
+    /// let png = b"\x89PNG\r\n\x1a\n";
+    /// assert_eq!(FileType::detect(Path::new("board"), png), FileType::Image);
+    /// assert_eq!(FileType::detect(Path::new("board.jpg"), b"wk\nkw"), FileType::Image);
+    /// assert_eq!(FileType::detect(Path::new("board"), b"wk\nkw"), FileType::Text);
+    /// ```
+    pub fn detect(path: &Path, bytes: &[u8]) -> FileType {
+        let image_extension = path
+            .extension()
+            .and_then(OsStr::to_str)
+            .is_some_and(|ext| ImageFormat::from_extension(ext).is_some());
+        if image::guess_format(bytes).is_ok()
+            || image_extension
+            || std::str::from_utf8(bytes).is_err()
+        {
+            FileType::Image
+        } else {
+            FileType::Text
+        }
+    }
+
+    /// Returns what to call this type of file in messages.
+    fn noun(self) -> &'static str {
+        match self {
+            FileType::Auto => "a board",
+            FileType::Text => "text",
+            FileType::Image => "an image",
+        }
+    }
+}
+
+/// Arguments describing which file to read the board from
+#[derive(Args, Debug)]
+pub struct PathCli {
+    /// The path to the file containing the board, a puzzle link from
+    /// `qsolve link`, or `-` to read the board from standard input
+    #[clap(required_unless_present = "from_share")]
+    pub path: Option<std::path::PathBuf>,
+
+    /// Read the board's colors from the emoji grid in text someone shared,
+    /// instead of from a file
+    #[clap(long, conflicts_with = "path")]
+    pub from_share: Option<String>,
+
+    /// What type of file to read
+    #[clap(long, default_value = "auto")]
+    pub file_type: FileType,
+
+    /// Whether we should use the Queens and Xs in the file, or
+    /// clear it to be an empty board
+    #[clap(long, default_value = "false")]
+    pub clear: bool,
+
+    /// The path to a second file (such as a later screenshot of the same
+    /// board) whose Queens and Xs should be used instead of the ones in
+    /// the main file
+    #[clap(long, conflicts_with = "clear")]
+    pub progress: Option<std::path::PathBuf>,
+
+    /// Also require every color to form one connected region, which catches
+    /// squares misread from an image
+    #[clap(long, default_value = "false")]
+    pub strict: bool,
+
+    /// Reassign misread squares that split a color into pieces to the
+    /// nearest matching color around them, instead of failing
+    #[clap(long, default_value = "false")]
+    pub auto_repair: bool,
+
+    /// Which squares along the diagonals a Queen rules out, for variants of
+    /// Queens that differ from LinkedIn's. Defaults to the rules a puzzle
+    /// link carries, and to adjacency-only for every other board
+    #[clap(long)]
+    pub rules: Option<RuleSet>,
+}
+
+/// Arguments controlling how boards are displayed
+#[derive(Args, Debug)]
+pub struct DisplayCli {
+    #[clap(long, default_value = "unicode")]
+    /// What charset to use when displaying the board
+    pub charset: Charset,
+
+    #[clap(long, default_value = "classic")]
+    /// What colors to use when displaying the board
+    pub palette: Palette,
+
+    #[clap(long, default_value = "none")]
+    /// What extra information to draw on blank squares, where the board is
+    /// shown without highlights
+    pub overlay: Overlay,
+
+    #[clap(long)]
+    /// How many columns wide the board can be drawn; wider boards are split
+    /// into bands. Defaults to the width of the terminal
+    pub width: Option<usize>,
+
+    #[clap(long)]
+    /// How to write and read squares, named by the top-left square: `r1c1`,
+    /// `c1r1`, `r0c0` or `c0r0`. Defaults to `r1c1` in text and `r0c0` in
+    /// JSON
+    pub coords: Option<CoordStyle>,
+}
+
+impl DisplayCli {
+    /// Returns how many columns wide the output can be: the width chosen,
+    /// else the terminal's width when printing to one, else unlimited.
+    pub fn width(&self) -> usize {
+        self.width
+            .or_else(|| {
+                std::io::stdout()
+                    .is_terminal()
+                    .then(crossterm::terminal::size)
+                    .and_then(Result::ok)
+                    .map(|(columns, _)| columns.into())
+            })
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Returns the [CoordStyle] for squares in text.
+    pub fn coord_style(&self) -> CoordStyle {
+        self.coords.unwrap_or_default()
+    }
+
+    /// Returns the [CoordStyle] for squares in JSON.
+    pub fn json_coord_style(&self) -> CoordStyle {
+        self.coords.unwrap_or(CoordStyle::JSON)
+    }
+
+    /// Fits a rendered board into [DisplayCli::width].
+    pub fn fit(&self, ansi: &str) -> String {
+        fit_to_width(ansi, self.width())
+    }
+}
+
+/// Arguments controlling how the board is solved
+#[derive(Args, Debug)]
+pub struct SolveCli {
+    #[clap(long, default_value = "fast")]
+    /// What strategy to use for solving the puzzle
+    pub strategy: SolveStrategy,
+
+    /// How many steps ahead the short strategy looks when choosing each
+    /// step; 0 chooses greedily. Each extra step makes solving much slower
+    #[clap(long, default_value_t = 0)]
+    pub lookahead: usize,
+
+    /// How likely, in percent, the human strategy is to overlook each
+    /// deduction it could make. The other strategies never overlook any
+    #[clap(long, default_value_t = 10, value_parser = clap::value_parser!(u8).range(..=100))]
+    pub miss_percent: u8,
+
+    /// Check that every step only marks blank squares and keeps the board
+    /// valid, failing if one doesn't
+    #[clap(long, default_value = "false")]
+    pub checked: bool,
+
+    /// Also use advanced heuristics, such as mirroring Queens and xs across
+    /// a symmetry of the regions, which assume the puzzle has exactly one
+    /// solution
+    #[clap(long, default_value = "false")]
+    pub advanced: bool,
+}
+
+impl SolveCli {
+    /// Returns the heuristics to solve the given board with: those from
+    /// [all_heuristics], and with `--advanced`, those from
+    /// [advanced_heuristics] too.
+    pub fn heuristics(&self, board: &Board) -> Vec<Box<dyn Heuristic>> {
+        let mut heuristics = all_heuristics(board);
+        if self.advanced {
+            heuristics.extend(advanced_heuristics(board));
+        }
+        heuristics
+    }
+
+    /// Returns the [SolveOptions] chosen by these arguments, drawing any
+    /// random choices from the given `--seed`.
+    pub fn solve_options(&self, seed: u64) -> SolveOptions {
+        SolveOptions {
+            strategy: self.strategy,
+            seed,
+            miss_percent: self.miss_percent,
+            lookahead: self.lookahead,
+            checked: self.checked,
+            ..SolveOptions::default()
+        }
+    }
+}
+
+/// Arguments controlling the share text
+#[derive(Args, Debug)]
+pub struct ShareCli {
+    /// Generate a share text, with the provided string as the name
+    #[clap(long, num_args = 0..=1, require_equals = true, default_missing_value = "")]
+    pub share: Option<String>,
+
+    /// How many of the first queens to reveal in the share text
+    #[clap(long, default_value_t = 3)]
+    pub share_queens: usize,
+
+    /// How to represent the colors of queens in the share text
+    #[clap(long, default_value = "emoji")]
+    pub share_style: ShareStyle,
+
+    /// Also emit a compact payload that others can check with `verify-share`
+    #[clap(long, default_value = "false")]
+    pub share_payload: bool,
+
+    /// Add a line to the share text tracing the solve path, with a crown for
+    /// each step that placed a queen and a dot for each step that only x'd
+    /// out squares, showing at most this many steps
+    #[clap(long, num_args = 0..=1, require_equals = true, default_missing_value = "20")]
+    pub share_path: Option<usize>,
+}
+
+/// Arguments controlling how puzzles are played interactively
+#[derive(Args, Debug)]
+pub struct PlayCli {
+    /// Pause the clock while a hint is shown, until the next command
+    #[arg(long)]
+    pub pause_on_hint: bool,
+
+    /// Whether placing a Queen also x's out the squares it rules out
+    #[clap(long, default_value = "assisted")]
+    pub marking_mode: MarkingMode,
+
+    /// Don't point out moves that disagree with the solution; they are
+    /// still counted
+    #[arg(long)]
+    pub hard_mode: bool,
+}
+
+/// Arguments controlling the output of the solve subcommand
+#[derive(Args, Debug)]
+pub struct OutputCli {
+    /// Print only whether the puzzle was solved, and in how many steps
+    #[clap(short, long, default_value = "false", conflicts_with_all = ["summary", "share"])]
+    pub quiet: bool,
+
+    /// After the solution, print the number of steps, the time taken, and how
+    /// often each heuristic was used
+    #[clap(long, default_value = "false")]
+    pub summary: bool,
+
+    /// After the solution, print a table of how many times each heuristic
+    /// was used and how many squares it marked
+    #[clap(long, default_value = "false", conflicts_with = "quiet")]
+    pub heuristic_table: bool,
+
+    /// How to format the output
+    #[clap(long, default_value = "text", conflicts_with_all = ["quiet", "summary", "heuristic_table"])]
+    pub format: OutputFormat,
+
+    /// A TOML file of calibration constants for the estimated human solve
+    /// time shown by `--summary`, `--share` and `--format json`
+    #[clap(long)]
+    pub time_model: Option<std::path::PathBuf>,
+
+    /// When solving from a screenshot, first draw it in the terminal beside
+    /// the board parsed from it (needs the `preview` feature)
+    #[clap(long, num_args = 0..=1, require_equals = true, default_missing_value = "half-blocks", conflicts_with = "quiet")]
+    pub preview: Option<PreviewProtocol>,
+
+    /// Hide the Queens' squares in this style, and only draw the solved
+    /// board once asked to at a prompt
+    #[clap(long, num_args = 0..=1, require_equals = true, default_missing_value = "rot13", conflicts_with = "quiet")]
+    pub spoiler_safe: Option<SpoilerStyle>,
+}
+
+/// How `--preview` draws images in the terminal
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum PreviewProtocol {
+    /// Unicode half blocks, which work in any terminal with truecolor
+    #[default]
+    HalfBlocks,
+    /// The kitty graphics protocol, for a sharper image in terminals that
+    /// support it
+    Kitty,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+/// How to format the output of a command
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// A single JSON object, which includes everything `--summary` and `--share` would print
+    Json,
+}
+
+/// How the heatmap subcommand should render its output
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum HeatmapFormat {
+    /// Colored grids in the terminal
+    #[default]
+    Terminal,
+    /// An SVG image, written to stdout
+    Svg,
+}
+
+/// What the corpus subcommand should do
+#[derive(Debug, Subcommand)]
+pub enum CorpusCommand {
+    /// Download every freely licensed board in a corpus index into a
+    /// directory, along with a manifest of their licenses and step counts.
+    /// Downloading from a URL needs the `net` feature
+    Sync {
+        /// The URL or path of the corpus index, a TOML file with a
+        /// `[[board]]` table for each board
+        index: String,
+
+        /// The directory to save the boards and manifest in
+        #[clap(long, default_value = "games/community")]
+        dir: std::path::PathBuf,
+    },
+}
+
+/// What the self subcommand should do
+#[derive(Debug, Subcommand)]
+pub enum SelfCommand {
+    /// Check whether a newer release of qsolve is out. This is the only
+    /// command that looks for releases, and it needs the `net` feature
+    CheckUpdate {
+        /// Which releases to consider
+        #[clap(long, value_enum, default_value_t)]
+        channel: Channel,
+
+        /// The URL or path of the list of releases, as JSON in the form the
+        /// GitHub API returns it
+        #[clap(long, default_value = RELEASES_URL)]
+        releases: String,
+
+        /// How to print the result
+        #[clap(long, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+}
+
+/// What the history subcommand should do
+#[derive(Debug, Subcommand)]
+pub enum HistoryCommand {
+    /// Print every practice result as JSON
+    Export {
+        /// Leave out the seeds and boards, so that the statistics can be
+        /// shared, and add how many steps of each tier of heuristic the
+        /// solver needs for each board
+        #[clap(long)]
+        anonymized: bool,
+    },
+
+    /// Combine statistics from `history export --anonymized` into one
+    /// dataset, printed as JSON
+    Merge {
+        /// The files of statistics to combine
+        #[clap(required = true)]
+        paths: Vec<std::path::PathBuf>,
+    },
+}
+
+/// What the duel subcommand should do
+#[derive(Debug, Subcommand)]
+pub enum DuelCommand {
+    /// Start a duel on the board, writing it to a new duel file
+    New {
+        /// Where to read the board from
+        #[command(flatten)]
+        path_args: PathCli,
+
+        /// The file to write the duel to, which the players pass back and
+        /// forth
+        #[clap(short, long)]
+        output: std::path::PathBuf,
+
+        /// The names of the two players, the first of whom moves first
+        #[clap(long, num_args = 2, value_names = ["FIRST", "SECOND"], required = true)]
+        players: Vec<String>,
+    },
+
+    /// Make a move in a duel, which must agree with the puzzle's solution,
+    /// such as `r3c4 Q` to place a Queen or `r1c1 r1c2 x` to x out squares
+    Move {
+        /// The duel file, which is updated with the move
+        duel: std::path::PathBuf,
+
+        /// The name of the player making the move
+        player: String,
+
+        /// The squares to mark, followed by `Q` or `x`
+        #[clap(required = true, num_args = 2..)]
+        marks: Vec<String>,
+
+        /// How to display the board
+        #[command(flatten)]
+        display_args: DisplayCli,
+    },
+
+    /// Show the board, each player's score, and whose turn it is or who won
+    Status {
+        /// The duel file
+        duel: std::path::PathBuf,
+
+        /// How to display the board
+        #[command(flatten)]
+        display_args: DisplayCli,
+    },
+}
+
+/// A command typed at the prompt of the repl subcommand.
+#[derive(Debug, Eq, PartialEq)]
+pub enum ReplCommand {
+    /// Load the puzzle at the given path, replacing the current one
+    Load(std::path::PathBuf),
+    /// Print the current board
+    Show,
+    /// Show the next heuristic the solver would use
+    Hint,
+    /// Apply the next heuristic the solver would use
+    Step,
+    /// Mark a square with a Queen or an X
+    Apply(Coord, SquareVal),
+    /// Undo the most recent change
+    Undo,
+    /// Apply heuristics until the puzzle is solved or no heuristic applies
+    Solve,
+    /// Print how long the puzzle has been played for
+    Time,
+    /// Print the share text for the solved puzzle
+    Share,
+    /// Print the list of commands
+    Help,
+    /// Leave the repl
+    Quit,
+}
+
+impl ReplCommand {
+    /// The text printed by the `help` command.
+    pub const HELP: &str = "Commands:
+  load <path>     Load the puzzle at the given path
+  show            Print the current board
+  hint            Show the next heuristic the solver would use
+  step            Apply the next heuristic the solver would use
+  apply r<R>c<C> <Q|x>
+                  Mark the square at row R, column C with a Queen or an X
+  undo            Undo the most recent change
+  solve           Apply heuristics until the puzzle is solved
+  time            Print how long the puzzle has been played for
+  share           Print the share text for the solved puzzle
+  help            Print this list of commands
+  quit            Leave the repl";
+
+    /// Parses a line typed at the prompt, with squares written in the given
+    /// style.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::cli::ReplCommand;
+    /// # use qsolve::notation::CoordStyle;
+    /// # use qsolve::solvestate::SquareVal;
+    /// # use std::str::FromStr;
+    /// let style = CoordStyle::from_str("c0r0").unwrap();
+    /// assert_eq!(
+    ///     ReplCommand::parse("apply c3r2 Q", &style).unwrap(),
+    ///     ReplCommand::Apply((2, 3), SquareVal::Queen)
+    /// );
+    /// ```
+    pub fn parse(s: &str, coord_style: &CoordStyle) -> Result<Self> {
+        let words = s.split_whitespace().collect::<Vec<_>>();
+        match words.as_slice() {
+            ["load", path] => Ok(ReplCommand::Load(path.into())),
+            ["show"] => Ok(ReplCommand::Show),
+            ["hint"] => Ok(ReplCommand::Hint),
+            ["step"] => Ok(ReplCommand::Step),
+            ["apply", coord, val] => {
+                let coord = coord_style.parse(coord)?;
+                let Some(val) = val
+                    .chars()
+                    .exactly_one()
+                    .ok()
+                    .and_then(|c| SquareVal::try_from(c).ok().flatten())
+                else {
+                    bail!("Expected Q or x, but found `{val}`");
+                };
+                Ok(ReplCommand::Apply(coord, val))
+            }
+            ["undo"] => Ok(ReplCommand::Undo),
+            ["solve"] => Ok(ReplCommand::Solve),
+            ["time"] => Ok(ReplCommand::Time),
+            ["share"] => Ok(ReplCommand::Share),
+            ["help"] => Ok(ReplCommand::Help),
+            ["quit"] | ["exit"] => Ok(ReplCommand::Quit),
+            _ => bail!(
+                "Unknown command `{}`; type `help` for a list of commands",
+                s.trim()
+            ),
+        }
+    }
+}
+
+impl FromStr for ReplCommand {
+    type Err = anyhow::Error;
+
+    /// Parses a line typed at the prompt.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::cli::ReplCommand;
+    /// # use qsolve::solvestate::SquareVal;
+    /// # use std::str::FromStr;
+    /// assert_eq!(ReplCommand::from_str("show").unwrap(), ReplCommand::Show);
+    /// assert_eq!(
+    ///     ReplCommand::from_str("apply r3c4 Q").unwrap(),
+    ///     ReplCommand::Apply((2, 3), SquareVal::Queen)
+    /// );
+    /// assert!(ReplCommand::from_str("apply r0c4 Q").is_err());
+    /// assert!(ReplCommand::from_str("dance").is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self> {
+        ReplCommand::parse(s, &CoordStyle::default())
+    }
+}
+
+/// A command typed at the prompt of the debug subcommand.
+#[derive(Debug, Eq, PartialEq)]
+pub enum DebugCommand {
+    /// Print the current board
+    Show,
+    /// Apply the next heuristic the solver would use
+    Step,
+    /// Evaluate every heuristic, listing the ones that apply and counting
+    /// why the rest don't
+    Eval,
+    /// List why each heuristic with the given name doesn't apply
+    Why(String),
+    /// List the blank squares left in each row, column and color
+    Candidates,
+    /// Print the list of commands
+    Help,
+    /// Leave the debugger
+    Quit,
+}
+
+impl DebugCommand {
+    /// The text printed by the `help` command.
+    pub const HELP: &str = "Commands:
+  show            Print the current board
+  step            Apply the next heuristic the solver would use
+  eval            List the heuristics that apply, and count why the rest don't
+  why <name>      List why each heuristic with the given name, such as
+                  LastSquareAvailable, doesn't apply
+  candidates      List the blank squares left in each row, column and color
+  help            Print this list of commands
+  quit            Leave the debugger";
+}
+
+impl FromStr for DebugCommand {
+    type Err = anyhow::Error;
+
+    /// Parses a line typed at the prompt.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::cli::DebugCommand;
+    /// # use std::str::FromStr;
+    /// assert_eq!(DebugCommand::from_str("step").unwrap(), DebugCommand::Step);
+    /// assert_eq!(
+    ///     DebugCommand::from_str("why LastSquareAvailable").unwrap(),
+    ///     DebugCommand::Why("LastSquareAvailable".to_string())
+    /// );
+    /// assert!(DebugCommand::from_str("why").is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self> {
+        let words = s.split_whitespace().collect::<Vec<_>>();
+        match words.as_slice() {
+            ["show"] => Ok(DebugCommand::Show),
+            ["step"] => Ok(DebugCommand::Step),
+            ["eval"] => Ok(DebugCommand::Eval),
+            ["why", name] => Ok(DebugCommand::Why(name.to_string())),
+            ["candidates"] => Ok(DebugCommand::Candidates),
+            ["help"] => Ok(DebugCommand::Help),
+            ["quit"] | ["exit"] => Ok(DebugCommand::Quit),
+            _ => bail!(
+                "Unknown command `{}`; type `help` for a list of commands",
+                s.trim()
+            ),
+        }
+    }
+}
+
+/// Reads the file at the given path as the given type of file.
+///
+/// The path may also be an `http://` or `https://` URL, if the `net`
+/// feature is enabled, or [STDIN_PATH] to read standard input.
+///
+/// With [FileType::Auto], the file is read as the type that
+/// [FileType::detect] guesses, and then as the other type if that fails. If
+/// both fail, the error is the one from the guessed type, with the other
+/// one's messages added on top.
+///
+/// A path that doesn't exist but is a puzzle link (see [is_link]) is read
+/// as the link instead.
+pub fn queens_file_from_file_type(
+    path: &std::path::PathBuf,
+    file_type: FileType,
+) -> Result<QueensFile> {
+    if let Some(link) = link_from_path(path) {
+        return from_link(link);
+    }
+    let bytes = read_source(path)?;
+    let read = |file_type: FileType| match file_type {
+        FileType::Image => QueensFile::try_from_image_bytes(&bytes)
+            .with_context(|| format!("Failed to create board from image at {path:?}")),
+        _ => QueensFile::try_from_text_bytes(&bytes)
+            .with_context(|| format!("Failed to create board from text file at {path:?}")),
+    };
+    match file_type {
+        FileType::Text | FileType::Image => read(file_type),
+        FileType::Auto => {
+            let detected = FileType::detect(path, &bytes);
+            let other = match detected {
+                FileType::Image => FileType::Text,
+                _ => FileType::Image,
+            };
+            read(detected).or_else(|err| {
+                read(other).map_err(|other_err| {
+                    err.context(format!(
+                        "Could not read {path:?} as {}, or as {}: {other_err:#}",
+                        detected.noun(),
+                        other.noun(),
+                    ))
+                })
+            })
+        }
+    }
+}
+
+/// Returns the image at the given path, or None if it isn't an image: if
+/// the file type is [FileType::Text], or if it's [FileType::Auto] and
+/// [FileType::detect] decides it's text.
+pub fn image_from_file_type(path: &Path, file_type: FileType) -> Result<Option<RgbImage>> {
+    if link_from_path(path).is_some() {
+        return Ok(None);
+    }
+    let bytes = read_source(path)?;
+    let is_image = match file_type {
+        FileType::Text => false,
+        FileType::Image => true,
+        FileType::Auto => FileType::detect(path, &bytes) == FileType::Image,
+    };
+    if !is_image {
+        return Ok(None);
+    }
+    let img = image::load_from_memory(&bytes)
+        .with_context(|| format!("Could not read image at {path:?}"))?;
+    Ok(Some(img.to_rgb8()))
+}
+
+/// Returns the given path as a puzzle link, if it is one rather than a file.
+///
+/// Plain base64 is only taken as a link if it decodes, so that a mistyped
+/// file name is still reported as a missing file.
+fn link_from_path(path: &Path) -> Option<&str> {
+    let link = path.to_str()?;
+    if link == STDIN_PATH || path.exists() || !is_link(link) {
+        return None;
+    }
+    let explicit = link.starts_with(LINK_SCHEME) || link.starts_with('#');
+    (explicit || from_link(link).is_ok()).then_some(link)
+}
+
+/// The path that stands for standard input, such as an image piped from a
+/// screenshot tool.
+pub const STDIN_PATH: &str = "-";
+
+/// Returns the contents of the given file, of the given URL if the `net`
+/// feature is enabled, or of standard input if the path is [STDIN_PATH].
+pub fn read_source(path: &Path) -> Result<Vec<u8>> {
+    let source = path.to_string_lossy();
+    if source == STDIN_PATH {
+        return read_stdin();
+    }
+    if source.starts_with("http://") || source.starts_with("https://") {
+        return fetch_url(&source);
+    }
+    std::fs::read(path).with_context(|| format!("Could not read file `{path:?}`"))
+}
+
+/// Returns everything on standard input. It's only read the first time, and
+/// kept for later calls, since some commands read their board more than once
+/// (for example, to preview the image it came from).
+fn read_stdin() -> Result<Vec<u8>> {
+    static STDIN: OnceLock<Vec<u8>> = OnceLock::new();
+    if let Some(bytes) = STDIN.get() {
+        return Ok(bytes.clone());
+    }
+    let mut bytes = vec![];
+    std::io::stdin()
+        .read_to_end(&mut bytes)
+        .context("Could not read standard input")?;
+    Ok(STDIN.get_or_init(|| bytes).clone())
+}
+
+/// Downloads the given URL, following redirects, and fails on an error
+/// status.
+#[cfg(feature = "net")]
+fn fetch_url(url: &str) -> Result<Vec<u8>> {
+    ureq::get(url)
+        .header("User-Agent", format!("qsolve/{}", crate::release::VERSION))
+        .call()
+        .and_then(|mut response| response.body_mut().read_to_vec())
+        .with_context(|| format!("Could not download {url}"))
+}
+
+#[cfg(not(feature = "net"))]
+fn fetch_url(url: &str) -> Result<Vec<u8>> {
+    bail!("Could not read {url}: reading URLs needs the `net` feature")
+}
+
+/// Reads the file described by the path arguments (or the share text from
+/// `--from-share`), applying `--auto-repair`, `--progress` and `--clear`, and
+/// validates the result (strictly, with `--strict`).
+pub fn queens_file_from_path(path_args: &PathCli) -> Result<QueensFile> {
+    let mut qf = match (&path_args.path, &path_args.from_share) {
+        (_, Some(share_text)) => QueensFile::builder()
+            .board(board_from_share_text(share_text)?)
+            .build()?,
+        (Some(path), None) => queens_file_from_file_type(path, path_args.file_type)?,
+        (None, None) => bail!("No board given: pass a path or --from-share"),
+    };
+    if path_args.auto_repair {
+        let (repaired, repair) = qf.repair_regions()?;
+        if let Some(repair) = repair {
+            match &path_args.path {
+                Some(path) => warn!("Repaired split regions in {path:?}: {repair}"),
+                None => warn!("Repaired split regions in the shared board: {repair}"),
+            }
+        }
+        qf = repaired;
+    }
+    let builder = if let Some(progress) = &path_args.progress {
+        let progress_qf = queens_file_from_file_type(progress, path_args.file_type)?;
+        qf.with_progress(progress_qf)?.into_builder()
+    } else if path_args.clear {
+        qf.into_builder().clear_squares()
+    } else {
+        qf.into_builder()
+    };
+    let builder = match path_args.rules {
+        Some(rules) => builder.rules(rules),
+        None => builder,
+    };
+    let qf = builder.build()?;
+    if path_args.strict {
+        qf.validate_strict()
+            .context("Board failed strict validation (pass --auto-repair to apply the likely repair, if there is one)")?;
+    }
+    Ok(qf)
+}
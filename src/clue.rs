@@ -0,0 +1,201 @@
+use std::fmt::{Display, Formatter};
+
+use anyhow::{Result, anyhow, bail, ensure};
+use itertools::Itertools;
+
+use crate::{board::Region, datastructure::Coord};
+
+/// Clue numbers on the rows and columns of a board, for the sandwich variant
+/// of Queens.
+///
+/// A clue of `n` on a line means its Queen is sandwiched `n` squares in from
+/// one end of the line: there are exactly `n` squares between the Queen and
+/// either the first or the last square of the line.
+///
+/// In a text file, clues go in a header block before the board, with a
+/// `rows:` and/or `cols:` line giving one clue per line, or `.` for none, and
+/// a blank line after:
+///
+/// ```text
+/// rows: . 0 . .
+/// cols: 1 . . .
+///
+/// wwww
+/// wkkk
+/// rrrr
+/// bbbb
+/// ```
+///
+/// # Examples
+/// ```
+/// # use qsolve::clue::Clues;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let clues = Clues::parse_header(&["rows: . 0 . .", "cols: 1 . . ."], 4)?;
+/// assert!(clues.allows((1, 0), 4));
+/// assert!(clues.allows((1, 3), 4));
+/// assert!(!clues.allows((1, 1), 4));
+/// // Column 1's clue only allows rows 2 and 3.
+/// assert!(!clues.allows((0, 0), 4));
+/// assert_eq!(clues.to_string(), "rows: . 0 . .\ncols: 1 . . .");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Clues {
+    /// The clue on each row, if any; empty if no row has one.
+    pub rows: Vec<Option<usize>>,
+
+    /// The clue on each column, if any; empty if no column has one.
+    pub cols: Vec<Option<usize>>,
+}
+
+impl Clues {
+    /// Returns whether there are no clues at all.
+    pub fn is_empty(&self) -> bool {
+        self.rows.iter().chain(&self.cols).all(Option::is_none)
+    }
+
+    /// Returns every line with a clue, rows first, along with its clue.
+    pub fn lines(&self) -> impl Iterator<Item = (Region, usize)> + '_ {
+        let rows = self
+            .rows
+            .iter()
+            .enumerate()
+            .filter_map(|(r, clue)| clue.map(|clue| (Region::Row(r), clue)));
+        let cols = self
+            .cols
+            .iter()
+            .enumerate()
+            .filter_map(|(c, clue)| clue.map(|clue| (Region::Col(c), clue)));
+        rows.chain(cols)
+    }
+
+    /// Returns whether a Queen on the given square of a board of the given
+    /// size would agree with the clues on its row and column.
+    pub fn allows(&self, (row, col): Coord, size: usize) -> bool {
+        let agrees = |clue: Option<&Option<usize>>, pos: usize| match clue.copied().flatten() {
+            Some(clue) => position_allowed(clue, pos, size),
+            None => true,
+        };
+        agrees(self.rows.get(row), col) && agrees(self.cols.get(col), row)
+    }
+
+    /// Parses the header lines of a text file for a board of the given size.
+    pub fn parse_header(lines: &[&str], size: usize) -> Result<Clues> {
+        let mut clues = Clues::default();
+        for line in lines {
+            let (kind, values) = line
+                .split_once(':')
+                .ok_or_else(|| anyhow!("Expected `rows:` or `cols:` clues, got {line}"))?;
+            let values = values
+                .split_whitespace()
+                .map(|value| match value {
+                    "." => Ok(None),
+                    n => match n.parse::<usize>() {
+                        Ok(n) if n < size => Ok(Some(n)),
+                        _ => Err(anyhow!(
+                            "Invalid clue {n}: must be a number below {size}, or `.`"
+                        )),
+                    },
+                })
+                .collect::<Result<Vec<_>>>()?;
+            ensure!(
+                values.len() == size,
+                "Found {} {kind} clues for a board of size {size}",
+                values.len()
+            );
+            match kind.trim() {
+                "rows" => clues.rows = values,
+                "cols" => clues.cols = values,
+                kind => bail!("Expected `rows:` or `cols:` clues, got `{kind}:`"),
+            }
+        }
+        Ok(clues)
+    }
+
+    /// Returns whether the given line starts a header block of clues.
+    pub(crate) fn is_header(line: &str) -> bool {
+        line.starts_with("rows:") || line.starts_with("cols:")
+    }
+}
+
+/// Returns whether a Queen at the given position of a line of the given
+/// size has `clue` squares between it and one end of the line.
+pub(crate) fn position_allowed(clue: usize, pos: usize, size: usize) -> bool {
+    pos == clue || pos + clue + 1 == size
+}
+
+impl Display for Clues {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let line = |clues: &[Option<usize>]| {
+            clues
+                .iter()
+                .map(|clue| clue.map_or(".".to_string(), |clue| clue.to_string()))
+                .join(" ")
+        };
+        let mut lines = vec![];
+        if self.rows.iter().any(Option::is_some) {
+            lines.push(format!("rows: {}", line(&self.rows)));
+        }
+        if self.cols.iter().any(Option::is_some) {
+            lines.push(format!("cols: {}", line(&self.cols)));
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::{
+        file::QueensFile,
+        heuristic::all_heuristics,
+        search::all_solutions,
+        solution::Solution,
+        solvestate::{SolveState, SolveStrategy},
+    };
+
+    #[test]
+    fn clues_make_board_unique() -> Result<()> {
+        let queens_file = QueensFile::try_from_text_file(&"tests/fixtures/sandwich.txt".into())?;
+        let board = &queens_file.board;
+        assert_eq!(board.clues().lines().count(), 2);
+        assert_eq!(
+            all_solutions(board, 2),
+            vec![
+                [(0, 0), (1, 2), (2, 4), (3, 1), (4, 3)]
+                    .into_iter()
+                    .collect()
+            ]
+        );
+        let heuristics = all_heuristics(board);
+        let solution = Solution::solve(SolveState::from(board), SolveStrategy::Fast, &heuristics);
+        assert!(solution.is_solved());
+        assert_eq!(
+            solution.steps[0].next_heuristic.unwrap().name(),
+            "ClueRulesOutSquares"
+        );
+
+        // The same board has 8 solutions without its clues.
+        let without = QueensFile::from_str("wwkkk\nwwkkk\nrrrbb\nrrgbb\nrrggg")?;
+        assert_eq!(all_solutions(&without.board, 10).len(), 8);
+        assert_ne!(without.board.fingerprint(), board.fingerprint());
+        Ok(())
+    }
+
+    #[test]
+    fn clues_reject_bad_headers() {
+        assert!(Clues::parse_header(&["rows: 0 . ."], 4).is_err());
+        assert!(Clues::parse_header(&["rows: 0 . . 4"], 4).is_err());
+        assert!(Clues::parse_header(&["diagonals: 0 . . ."], 4).is_err());
+        assert!(QueensFile::from_str("rows: 0 . . .\nwwww\nwkkk\nrrrr\nbbbb").is_err());
+        let invalid = QueensFile::from_str(
+            "rows: 1 . . .\n\nwwww\nwkkk\nrrrr\nbbbb\n\nQxxx\n....\n....\n....",
+        )
+        .unwrap();
+        assert!(!SolveState::from(&invalid).is_valid());
+    }
+}
@@ -0,0 +1,219 @@
+use std::fmt::Write;
+
+use anyhow::{Result, anyhow, ensure};
+use owo_colors::{AnsiColors, DynColors, OwoColorize};
+
+use crate::{
+    board::Board,
+    datastructure::Coord,
+    error::QSolveError,
+    palette::Palette,
+    solvestate::{Charset, SolveState, SquareVal},
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// How a single square in one solve state compares to the same square in a reference.
+pub enum SquareComparison {
+    /// Both states have the same value in this square (which might be blank).
+    Agree(Option<SquareVal>),
+    /// Both states have a value in this square, but the values differ.
+    Conflict {
+        /// The value in the state being checked.
+        mine: SquareVal,
+        /// The value in the reference state.
+        reference: SquareVal,
+    },
+    /// The reference has a value in this square, but the state being checked does not.
+    Missing(SquareVal),
+    /// The state being checked has a value in this square, but the reference does not.
+    Extra(SquareVal),
+}
+
+impl SquareComparison {
+    /// Compares a single square from the state being checked against the reference.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::compare::SquareComparison;
+    /// # use qsolve::solvestate::SquareVal;
+    /// assert_eq!(SquareComparison::new(None, None), SquareComparison::Agree(None));
+    /// assert_eq!(SquareComparison::new(None, Some(SquareVal::Queen)), SquareComparison::Missing(SquareVal::Queen));
+    /// assert_eq!(SquareComparison::new(Some(SquareVal::X), None), SquareComparison::Extra(SquareVal::X));
+    /// ```
+    pub fn new(mine: Option<SquareVal>, reference: Option<SquareVal>) -> Self {
+        match (mine, reference) {
+            (m, r) if m == r => SquareComparison::Agree(m),
+            (Some(mine), Some(reference)) => SquareComparison::Conflict { mine, reference },
+            (None, Some(reference)) => SquareComparison::Missing(reference),
+            (Some(mine), None) => SquareComparison::Extra(mine),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    /// Converts the comparison to a character for display.
+    ///
+    /// Agreements display the shared value; conflicts display as `!`, missing
+    /// values as `?` and extra values as `+`.
+    pub fn as_char(&self, charset: &Charset) -> char {
+        match self {
+            SquareComparison::Agree(sv) => SquareVal::as_char(*sv, false, charset),
+            SquareComparison::Conflict { .. } => '!',
+            SquareComparison::Missing(_) => '?',
+            SquareComparison::Extra(_) => '+',
+        }
+    }
+}
+
+/// The result of comparing two solve states over the same [Board].
+#[derive(Debug)]
+pub struct Comparison<'a> {
+    board: &'a Board,
+    squares: Vec<SquareComparison>,
+}
+
+impl<'a> Comparison<'a> {
+    /// Compares the `mine` state against the `reference` state.
+    ///
+    /// This fails with [QSolveError::InvalidBoard] if the two states are not for the same board.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::compare::Comparison;
+    /// # use qsolve::file::QueensFile;
+    /// # use qsolve::solvestate::SolveState;
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let mine = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\nxQxx\nxxx.\nQxxx\nxx..")?;
+    /// let reference = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\nxQxx\nxxxQ\nQxxx\nxxQx")?;
+    /// let comparison = Comparison::new(&SolveState::from(&mine), &SolveState::from(&reference))?;
+    /// assert_eq!(comparison.conflicts(), 0);
+    /// assert_eq!(comparison.missing(), 3);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new(mine: &SolveState<'a>, reference: &SolveState<'a>) -> Result<Self> {
+        ensure!(
+            mine.board == reference.board,
+            anyhow!("Cannot compare solve states for different boards")
+                .context(QSolveError::InvalidBoard)
+        );
+        let squares = mine
+            .board
+            .all_coords()
+            .iter()
+            .map(|coord| SquareComparison::new(mine.square(&coord), reference.square(&coord)))
+            .collect();
+        Ok(Comparison {
+            board: mine.board,
+            squares,
+        })
+    }
+
+    /// Returns the comparison for the given [Coord].
+    pub fn square(&self, coord: &Coord) -> SquareComparison {
+        self.squares[self.board.coord_to_idx(coord)]
+    }
+
+    /// Returns how many squares have the same (non-blank) value in both states.
+    pub fn agreements(&self) -> usize {
+        self.count(|sc| matches!(sc, SquareComparison::Agree(Some(_))))
+    }
+
+    /// Returns how many squares have different values in the two states.
+    pub fn conflicts(&self) -> usize {
+        self.count(|sc| matches!(sc, SquareComparison::Conflict { .. }))
+    }
+
+    /// Returns how many squares are filled in the reference but not in the state being checked.
+    pub fn missing(&self) -> usize {
+        self.count(|sc| matches!(sc, SquareComparison::Missing(_)))
+    }
+
+    /// Returns how many squares are filled in the state being checked but not in the reference.
+    pub fn extra(&self) -> usize {
+        self.count(|sc| matches!(sc, SquareComparison::Extra(_)))
+    }
+
+    fn count(&self, f: impl Fn(&SquareComparison) -> bool) -> usize {
+        self.squares.iter().filter(|sc| f(sc)).count()
+    }
+
+    /// Returns a string colored by OwoColorize that represents the comparison.
+    ///
+    /// Each square uses the board's color as its background, and any square that
+    /// doesn't agree is bolded so it stands out.
+    pub fn ansi_string(&self, charset: Charset, palette: Palette) -> Result<String> {
+        let mut f = String::new();
+        for row_num in 0..self.board.size() {
+            for col_num in 0..self.board.size() {
+                let coord = (row_num, col_num);
+                let comparison = self.square(&coord);
+                let bg_color = palette.bg(self.board.color(&coord));
+                let fg_color = match comparison {
+                    SquareComparison::Conflict { .. } => DynColors::Ansi(AnsiColors::Red),
+                    _ => palette.fg(self.board.color(&coord)),
+                };
+                let c = comparison.as_char(&charset);
+                if matches!(comparison, SquareComparison::Agree(_)) {
+                    write!(f, "{}", c.color(fg_color).on_color(bg_color))?
+                } else {
+                    write!(f, "{}", c.color(fg_color).on_color(bg_color).bold())?
+                }
+            }
+            if row_num != self.board.size() - 1 {
+                writeln!(f)?
+            };
+        }
+        Ok(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use regex::Regex;
+
+    use crate::file::QueensFile;
+
+    use super::*;
+
+    #[test]
+    fn comparison_counts() -> Result<()> {
+        let mine = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\nxQxx\nxxxx\n....\n....")?;
+        let reference = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\nxQxx\nxxxQ\nQxxx\nxxQx")?;
+        let mine_state = SolveState::from(&mine);
+        let reference_state = SolveState::from(&reference);
+        let comparison = Comparison::new(&mine_state, &reference_state)?;
+        assert_eq!(comparison.conflicts(), 1);
+        assert_eq!(comparison.missing(), 6);
+        assert_eq!(comparison.extra(), 0);
+        assert_eq!(comparison.agreements(), 9);
+        assert_eq!(
+            comparison.square(&(1, 3)),
+            SquareComparison::Conflict {
+                mine: SquareVal::X,
+                reference: SquareVal::Queen
+            }
+        );
+
+        let ansi_re = Regex::new(r"\u{1b}\[[0-9;]*m").unwrap();
+        let ansi_string = comparison.ansi_string(Charset::Ascii, Palette::default())?;
+        assert_eq!(
+            ansi_re.replace_all(&ansi_string, ""),
+            "xQxx\nxxx!\n?x??\n?x??"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn comparison_requires_same_board() -> Result<()> {
+        let mine = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        let reference = QueensFile::from_str("wwww\nkkkk\nrrrr\nbbbb")?;
+        let mine_state = SolveState::from(&mine);
+        let reference_state = SolveState::from(&reference);
+        assert!(Comparison::new(&mine_state, &reference_state).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+use std::fmt::{Display, Formatter};
+
+use itertools::Itertools;
+use log::debug;
+
+use crate::{
+    board::{Board, Region},
+    datastructure::{Coord, CoordSet},
+    file::QueensFile,
+    heuristic::{Changes, all_heuristics},
+    solution::Solution,
+    solvestate::{SolveState, SolveStrategy, SquareVal},
+};
+
+/// An explanation of why a partially solved puzzle can't be finished: the
+/// marks that leave some row, column or color with nowhere for its Queen.
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Region;
+/// # use qsolve::conflict::Conflict;
+/// # use qsolve::file::QueensFile;
+/// # use qsolve::solvestate::SquareVal;
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// // The Queen in the top left corner is wrong; the x's are fine.
+/// let queens_file = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\nQ...\n....\n....\n...x")?;
+/// let conflict = Conflict::find(&queens_file).unwrap();
+/// assert_eq!(conflict.marks, vec![((0, 0), SquareVal::Queen)]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Conflict {
+    /// A region that is left without a square for its Queen.
+    pub region: Region,
+
+    /// The marks from the partial solution that lead to the conflict. Each
+    /// one is needed: without any of them, the solver no longer finds this
+    /// (or any other) conflict.
+    pub marks: Vec<(Coord, SquareVal)>,
+}
+
+impl Conflict {
+    /// Finds the marks in the given file's partial solution that lead to
+    /// some row, column or color being left without a square for its Queen,
+    /// either right away or after solving as far as possible.
+    ///
+    /// This returns None if the marks don't lead to such a conflict.
+    pub fn find(queens_file: &QueensFile) -> Option<Conflict> {
+        let board = &queens_file.board;
+        let squares = queens_file.squares.clone()?.0;
+        let mut marks = squares
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, square)| Some((board.idx_to_coord(&idx), (*square)?)))
+            .collect::<Vec<_>>();
+        find_empty_region(board, &marks)?;
+
+        // Drop each mark that the conflict doesn't need, re-solving each time.
+        let mut idx = 0;
+        while idx < marks.len() {
+            let mut without = marks.clone();
+            let removed = without.remove(idx);
+            if find_empty_region(board, &without).is_some() {
+                debug!("Conflict doesn't need {removed:?}");
+                marks = without;
+            } else {
+                idx += 1;
+            }
+        }
+        let region = find_empty_region(board, &marks)?;
+        Some(Conflict { region, marks })
+    }
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} is left with nowhere for its Queen. Revisit these marks: {}",
+            self.region,
+            self.marks
+                .iter()
+                .map(|((r, c), square)| {
+                    let mark = match square {
+                        SquareVal::Queen => "Queen",
+                        SquareVal::X => "x",
+                    };
+                    format!("{mark} at row {} column {}", r + 1, c + 1)
+                })
+                .join(", ")
+        )
+    }
+}
+
+/// Solves the board from just the given marks, and returns a region that is
+/// left without a square for its Queen along the way, if any.
+fn find_empty_region(board: &Board, marks: &[(Coord, SquareVal)]) -> Option<Region> {
+    let x = marks
+        .iter()
+        .filter(|(_, square)| *square == SquareVal::X)
+        .map(|(coord, _)| *coord)
+        .collect();
+    let changes = std::iter::once(Changes::AddX { x })
+        .chain(
+            marks
+                .iter()
+                .filter(|(_, square)| *square == SquareVal::Queen)
+                .map(|(queen, _)| Changes::AddQueen {
+                    queen: *queen,
+                    x: CoordSet::default(),
+                }),
+        )
+        .collect::<Vec<_>>();
+    let heuristics = all_heuristics(board);
+    let solution = Solution::solve(
+        SolveState::with_changes(board, &changes),
+        SolveStrategy::Fast,
+        &heuristics,
+    );
+    solution
+        .steps
+        .iter()
+        .map(|item| &item.solve_state)
+        .chain([&solution.final_state])
+        .find_map(empty_region)
+}
+
+/// Returns a row, column or color without a Queen or any blank squares.
+fn empty_region(solve_state: &SolveState) -> Option<Region> {
+    let board = solve_state.board;
+    let has_queen = |region: &Region| {
+        region
+            .coords(board)
+            .iter()
+            .any(|coord| solve_state.square(&coord) == Some(SquareVal::Queen))
+    };
+    (0..board.size())
+        .filter(|&r| solve_state.candidates_for_row(r).is_empty())
+        .map(Region::Row)
+        .chain(
+            (0..board.size())
+                .filter(|&c| solve_state.candidates_for_col(c).is_empty())
+                .map(Region::Col),
+        )
+        .chain(
+            board
+                .all_colors()
+                .into_iter()
+                .filter(|&&color| solve_state.candidates_for_color(color).is_empty())
+                .map(|&color| Region::Color(color)),
+        )
+        .find(|region| !has_queen(region))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use anyhow::Result;
+
+    use super::*;
+
+    #[test]
+    fn conflict_keeps_only_needed_marks() -> Result<()> {
+        let queens_file =
+            QueensFile::try_from_text_file(&PathBuf::from("tests/fixtures/conflict.txt"))?;
+        let conflict = Conflict::find(&queens_file).unwrap();
+        assert_eq!(conflict.marks, vec![((6, 1), SquareVal::Queen)]);
+        assert!(conflict.to_string().contains("Queen at row 7 column 2"));
+
+        let consistent =
+            QueensFile::try_from_text_file(&PathBuf::from("games/linkedin-1-partial.txt"))?;
+        assert_eq!(Conflict::find(&consistent), None);
+        let empty = QueensFile::try_from_text_file(&PathBuf::from("games/linkedin-1-empty.txt"))?;
+        assert_eq!(Conflict::find(&empty), None);
+        Ok(())
+    }
+}
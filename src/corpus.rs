@@ -0,0 +1,298 @@
+use std::{path::Path, str::FromStr};
+
+use anyhow::{Context, Result, anyhow, ensure};
+use log::warn;
+use toml::{Table, Value};
+
+use crate::{
+    file::QueensFile,
+    heuristic::all_heuristics,
+    manifest::{GameEntry, Manifest},
+    search::all_solutions,
+    solution::Solution,
+    solvestate::{SolveState, SolveStrategy},
+};
+
+/// The licenses a board can be shared under to be synced into a corpus, as
+/// SPDX identifiers. Each lets the board be copied and changed freely.
+pub const FREE_LICENSES: [&str; 6] = [
+    "CC0-1.0",
+    "CC-BY-4.0",
+    "CC-BY-SA-4.0",
+    "MIT",
+    "Apache-2.0",
+    "Unlicense",
+];
+
+/// The strategies whose step counts are recorded for synced boards.
+const STRATEGIES: [SolveStrategy; 3] = [
+    SolveStrategy::Fast,
+    SolveStrategy::Short,
+    SolveStrategy::Simple,
+];
+
+/// A board listed in a [CorpusIndex].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CorpusBoard {
+    /// The name to save the board's text file under, without its extension.
+    pub name: String,
+
+    /// Where to download the board's text file from: a URL, or a path
+    /// relative to the index.
+    pub url: String,
+
+    /// Where the board came from.
+    pub source: String,
+
+    /// How the source rates the board, if it does.
+    pub difficulty: Option<String>,
+
+    /// The license the board is shared under, as an SPDX identifier.
+    pub license: String,
+}
+
+/// A list of freely licensed boards maintained outside this repository,
+/// which [sync] downloads into a directory with a [Manifest].
+///
+/// The index is a TOML file with a `[[board]]` table for each board.
+///
+/// # Examples
+/// ```
+/// # use qsolve::corpus::CorpusIndex;
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let index = CorpusIndex::from_str(
+///     r#"
+///     [[board]]
+///     name = "community-1"
+///     url = "boards/1.txt"
+///     source = "Community"
+///     license = "CC0-1.0"
+///     "#,
+/// )?;
+/// assert_eq!(index.boards[0].name, "community-1");
+/// assert_eq!(
+///     CorpusIndex::resolve("https://example.com/corpus/index.toml", &index.boards[0].url),
+///     "https://example.com/corpus/boards/1.txt"
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CorpusIndex {
+    /// The boards in the index, in the order they are listed.
+    pub boards: Vec<CorpusBoard>,
+}
+
+impl CorpusIndex {
+    /// Returns where to download a board listed with the given URL in the
+    /// index at the given location, which may be a URL or a path.
+    pub fn resolve(index: &str, url: &str) -> String {
+        if url.starts_with("http://") || url.starts_with("https://") {
+            return url.to_string();
+        }
+        match index.rsplit_once('/') {
+            Some((base, _)) => format!("{base}/{url}"),
+            None => url.to_string(),
+        }
+    }
+}
+
+impl FromStr for CorpusIndex {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let table = s.parse::<Table>()?;
+        let boards = match table.get("board") {
+            None => vec![],
+            Some(boards) => boards
+                .as_array()
+                .ok_or_else(|| anyhow!("board must be an array of tables"))?
+                .iter()
+                .map(corpus_board)
+                .collect::<Result<Vec<_>>>()?,
+        };
+        Ok(CorpusIndex { boards })
+    }
+}
+
+/// Parses a single `[[board]]` table.
+fn corpus_board(value: &Value) -> Result<CorpusBoard> {
+    let string = |key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+    let name = string("name").ok_or_else(|| anyhow!("Every board needs a name"))?;
+    // The name becomes a file name, so it can't be allowed to leave the directory.
+    ensure!(
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+        "Board name {name:?} may only use letters, digits, `-` and `_`"
+    );
+    Ok(CorpusBoard {
+        url: string("url").ok_or_else(|| anyhow!("Board {name} needs a url"))?,
+        source: string("source").ok_or_else(|| anyhow!("Board {name} needs a source"))?,
+        difficulty: string("difficulty"),
+        license: string("license").ok_or_else(|| anyhow!("Board {name} needs a license"))?,
+        name,
+    })
+}
+
+/// What [sync] did with each board in the index.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SyncReport {
+    /// The names of the boards that were saved.
+    pub synced: Vec<String>,
+
+    /// The names of the boards that were left out, and why.
+    pub skipped: Vec<(String, String)>,
+}
+
+/// Downloads every board in the index at the given location into the given
+/// directory, with a `manifest.toml` listing them along with their licenses
+/// and how many steps the solver takes on each.
+///
+/// Boards are fetched with the given function, which is passed a URL or
+/// path as returned by [CorpusIndex::resolve]. Boards that aren't under one
+/// of the [FREE_LICENSES], that can't be fetched or read, or that don't have
+/// exactly one solution are skipped with a warning, so that one bad entry
+/// doesn't stop the rest from syncing.
+pub fn sync(
+    index: &CorpusIndex,
+    index_location: &str,
+    dir: &Path,
+    fetch: impl Fn(&str) -> Result<Vec<u8>>,
+) -> Result<SyncReport> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Could not create {dir:?}"))?;
+    let mut report = SyncReport::default();
+    let mut manifest = Manifest::default();
+    for board in &index.boards {
+        match sync_board(board, index_location, dir, &fetch) {
+            Ok(entry) => {
+                report.synced.push(board.name.clone());
+                manifest.games.push(entry);
+            }
+            Err(err) => {
+                warn!("Skipping {}: {err:#}", board.name);
+                report
+                    .skipped
+                    .push((board.name.clone(), format!("{err:#}")));
+            }
+        }
+    }
+    let manifest_path = dir.join("manifest.toml");
+    std::fs::write(&manifest_path, manifest.to_string())
+        .with_context(|| format!("Could not write {manifest_path:?}"))?;
+    Ok(report)
+}
+
+/// Downloads, checks and saves a single board, returning its manifest entry.
+fn sync_board(
+    board: &CorpusBoard,
+    index_location: &str,
+    dir: &Path,
+    fetch: impl Fn(&str) -> Result<Vec<u8>>,
+) -> Result<GameEntry> {
+    ensure!(
+        FREE_LICENSES.contains(&board.license.as_str()),
+        "{} is not a free license",
+        board.license
+    );
+    let bytes = fetch(&CorpusIndex::resolve(index_location, &board.url))?;
+    let queens_file = QueensFile::try_from_text_bytes(&bytes)?;
+    let solutions = all_solutions(&queens_file.board, 2).len();
+    ensure!(solutions == 1, "Board has {solutions} solutions, not one");
+    let heuristics = all_heuristics(&queens_file.board);
+    let steps = STRATEGIES
+        .into_iter()
+        .map(|strategy| {
+            let solution = Solution::solve(SolveState::from(&queens_file), strategy, &heuristics);
+            (strategy, solution)
+        })
+        .filter(|(_, solution)| solution.is_solved())
+        .map(|(strategy, solution)| (strategy, solution.step_count()))
+        .collect();
+    let path = dir.join(format!("{}.txt", board.name));
+    std::fs::write(&path, &bytes).with_context(|| format!("Could not write {path:?}"))?;
+    Ok(GameEntry {
+        name: board.name.clone(),
+        source: board.source.clone(),
+        difficulty: board.difficulty.clone(),
+        license: Some(board.license.clone()),
+        steps,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_rejects_invalid() {
+        for content in [
+            "board = 3",
+            "[[board]]\nurl = \"a.txt\"\nsource = \"S\"\nlicense = \"MIT\"",
+            "[[board]]\nname = \"a\"\nsource = \"S\"\nlicense = \"MIT\"",
+            "[[board]]\nname = \"a\"\nurl = \"a.txt\"\nsource = \"S\"",
+            "[[board]]\nname = \"../a\"\nurl = \"a.txt\"\nsource = \"S\"\nlicense = \"MIT\"",
+        ] {
+            assert!(CorpusIndex::from_str(content).is_err(), "{content}");
+        }
+    }
+
+    #[test]
+    fn sync_skips_bad_boards() -> Result<()> {
+        let index = CorpusIndex::from_str(
+            r#"
+            [[board]]
+            name = "good"
+            url = "good.txt"
+            source = "Community"
+            difficulty = "easy"
+            license = "CC0-1.0"
+
+            [[board]]
+            name = "unlicensed"
+            url = "good.txt"
+            source = "Community"
+            license = "All rights reserved"
+
+            [[board]]
+            name = "ambiguous"
+            url = "ambiguous.txt"
+            source = "Community"
+            license = "MIT"
+
+            [[board]]
+            name = "missing"
+            url = "missing.txt"
+            source = "Community"
+            license = "MIT"
+            "#,
+        )?;
+        let fetch = |url: &str| match url {
+            "corpus/good.txt" => Ok(b"wwww\nwkkk\nrrrr\nbbbb".to_vec()),
+            "corpus/ambiguous.txt" => Ok(b"wwkk\nwwkk\nrrbb\nrrbb".to_vec()),
+            _ => Err(anyhow!("Not found: {url}")),
+        };
+        let dir = std::env::temp_dir().join(format!("qsolve-corpus-{}", std::process::id()));
+        let report = sync(&index, "corpus/index.toml", &dir, fetch)?;
+        assert_eq!(report.synced, vec!["good"]);
+        let skipped = report
+            .skipped
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(skipped, vec!["unlicensed", "ambiguous", "missing"]);
+
+        let manifest = Manifest::try_from_file(&dir.join("manifest.toml"))?;
+        let good = manifest.game("good").unwrap();
+        assert_eq!(good.license.as_deref(), Some("CC0-1.0"));
+        assert_eq!(good.difficulty.as_deref(), Some("easy"));
+        assert!(good.expected_steps(SolveStrategy::Fast).is_some());
+        assert!(QueensFile::try_from_text_file(&good.path(&dir)).is_ok());
+        assert!(!dir.join("unlicensed.txt").exists());
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+}
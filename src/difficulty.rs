@@ -0,0 +1,185 @@
+use std::fmt::{Display, Formatter};
+
+use serde_json::{Value, json};
+
+use crate::{
+    estimate::heuristic_tier,
+    heuristic::all_heuristics,
+    release::VERSION,
+    solution::Solution,
+    solvestate::{SolveState, SolveStrategy},
+};
+
+/// How much more a step from each [heuristic_tier] adds to a [Grade]'s
+/// score than one from the tier below, for the same number of candidates.
+const TIER_WEIGHT: u64 = 3;
+
+/// How hard a puzzle is for a human, as found by replaying its solve with
+/// the [SolveStrategy::Simple] strategy, which takes the easiest step it can
+/// at each point.
+///
+/// Each step adds the number of candidates its heuristic examined (the
+/// squares it looked at that were still blank) to the score, weighted by
+/// `TIER_WEIGHT` (3) to the power of the heuristic's [heuristic_tier]. So a
+/// puzzle scores higher the more it needs hard heuristics, and the more
+/// squares they have to take in. Scores are only meant for sorting puzzles
+/// against each other; they aren't on any fixed scale.
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Board;
+/// # use qsolve::difficulty::Grade;
+/// # use qsolve::file::QueensFile;
+/// # use qsolve::solvestate::SolveState;
+/// # use std::path::PathBuf;
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let small = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+/// let grade = Grade::of(SolveState::from(&small));
+/// assert!(grade.solved);
+/// assert!(grade.score > 0);
+///
+/// let queens_file = QueensFile::try_from_text_file(&PathBuf::from("games/linkedin-1-empty.txt"))?;
+/// assert!(Grade::of(SolveState::from(&queens_file)).score > grade.score);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Grade {
+    /// The difficulty score: higher is harder.
+    pub score: u64,
+
+    /// The hardest [heuristic_tier] of any step, and the name of the first
+    /// heuristic from it, or None if no steps were needed.
+    pub hardest: Option<(usize, &'static str)>,
+
+    /// How many steps the solve took.
+    pub steps: usize,
+
+    /// How many candidates the heuristics examined over every step.
+    pub candidates: usize,
+
+    /// Whether the solver finished the puzzle. If not, the grade only covers
+    /// the steps it managed.
+    pub solved: bool,
+
+    /// The [fingerprint][crate::board::Board::fingerprint] of the board
+    /// that was graded.
+    pub fingerprint: u64,
+}
+
+impl Grade {
+    /// Grades the puzzle from the given state, replaying its solve with
+    /// every heuristic in [all_heuristics].
+    pub fn of(solve_state: SolveState) -> Self {
+        let heuristics = all_heuristics(solve_state.board);
+        let solution = Solution::solve(solve_state, SolveStrategy::Simple, &heuristics);
+        let mut grade = Grade {
+            score: 0,
+            hardest: None,
+            steps: 0,
+            candidates: 0,
+            solved: solution.is_solved(),
+            fingerprint: solution.final_state.board.fingerprint(),
+        };
+        for item in &solution.steps {
+            let Some(h) = item.next_heuristic else {
+                continue;
+            };
+            let tier = heuristic_tier(h.name());
+            let candidates = h
+                .seen_coords(&item.solve_state)
+                .intersection(&item.solve_state.blank_coords())
+                .len();
+            grade.score += TIER_WEIGHT.pow(tier as u32) * candidates as u64;
+            grade.steps += 1;
+            grade.candidates += candidates;
+            if grade.hardest.is_none_or(|(hardest, _)| tier > hardest) {
+                grade.hardest = Some((tier, h.name()));
+            }
+        }
+        grade
+    }
+
+    /// Returns the grade as a JSON object, along with the version of qsolve
+    /// that graded it, since scores may change between versions.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "qsolve_version": VERSION,
+            "board_fingerprint": format!("{:016x}", self.fingerprint),
+            "score": self.score,
+            "hardest_tier": self.hardest.map(|(tier, _)| tier),
+            "hardest_heuristic": self.hardest.map(|(_, name)| name),
+            "steps": self.steps,
+            "candidates": self.candidates,
+            "solved": self.solved,
+        })
+    }
+}
+
+impl Display for Grade {
+    /// Displays the score, then the hardest step and how much was examined
+    /// on their own lines.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Score: {}", self.score)?;
+        if let Some((tier, name)) = self.hardest {
+            writeln!(f, "Hardest step: {name} (tier {tier})")?;
+        }
+        write!(
+            f,
+            "{} step{}, examining {} candidate square{}",
+            self.steps,
+            if self.steps == 1 { "" } else { "s" },
+            self.candidates,
+            if self.candidates == 1 { "" } else { "s" }
+        )?;
+        if !self.solved {
+            write!(f, "\nThe solver couldn't finish the puzzle")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, str::FromStr};
+
+    use anyhow::Result;
+
+    use crate::{board::Board, file::QueensFile};
+
+    use super::*;
+
+    #[test]
+    fn grade_follows_the_simple_solve() -> Result<()> {
+        let queens_file =
+            QueensFile::try_from_text_file(&PathBuf::from("games/linkedin-1-empty.txt"))?;
+        let grade = Grade::of(SolveState::from(&queens_file));
+        let heuristics = all_heuristics(&queens_file.board);
+        let solution = Solution::solve(
+            SolveState::from(&queens_file),
+            SolveStrategy::Simple,
+            &heuristics,
+        );
+        assert!(grade.solved);
+        assert_eq!(grade.steps, solution.step_count());
+        assert!(grade.score >= grade.candidates as u64);
+        assert_eq!(grade.to_json()["steps"], json!(grade.steps));
+        assert_eq!(
+            grade.to_json()["board_fingerprint"],
+            format!("{:016x}", queens_file.board.fingerprint())
+        );
+
+        // A solved puzzle takes no steps.
+        let solved = Grade::of(solution.final_state.clone());
+        assert_eq!((solved.score, solved.hardest, solved.steps), (0, None, 0));
+
+        // Without a unique solution, the solver stalls right away.
+        let ambiguous = Board::from_str("wwww\nkkkk\nrrrr\nbbbb")?;
+        let grade = Grade::of(SolveState::from(&ambiguous));
+        assert!(!grade.solved);
+        assert!(grade.to_string().ends_with("couldn't finish the puzzle"));
+        Ok(())
+    }
+}
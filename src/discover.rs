@@ -0,0 +1,360 @@
+use std::{
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use itertools::Itertools;
+use log::{debug, warn};
+
+use crate::{
+    datastructure::CoordSet,
+    file::QueensFile,
+    heuristic::all_heuristics,
+    solution::Solution,
+    solvestate::{SolveOptions, SolveState, SquareVal},
+};
+
+/// Whether a [StallPattern] is about rows or columns.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum LineKind {
+    /// Rows of the board.
+    Row,
+    /// Columns of the board.
+    Col,
+}
+
+impl Display for LineKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LineKind::Row => write!(f, "rows"),
+            LineKind::Col => write!(f, "cols"),
+        }
+    }
+}
+
+/// The tightest structure left on a board where the solver stalled.
+///
+/// The existing heuristics exploit groups of colors confined to exactly as
+/// many lines (and vice versa), so a stalled board has none that they can
+/// use. The next best thing is a group with just a little slack: one more
+/// line than colors, say. A new family of heuristics that can reason about
+/// that slack would unlock the board.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum StallPattern {
+    /// The blank squares of some colors are confined to a few more lines
+    /// than there are colors.
+    ColorsInLines {
+        /// How many colors are in the group.
+        colors: usize,
+        /// How many lines their blank squares are on.
+        lines: usize,
+        /// Whether the lines are rows or columns.
+        kind: LineKind,
+    },
+    /// The blank squares of some lines contain only a few more colors than
+    /// there are lines.
+    LinesContainColors {
+        /// How many lines are in the group.
+        lines: usize,
+        /// How many colors their blank squares have.
+        colors: usize,
+        /// Whether the lines are rows or columns.
+        kind: LineKind,
+    },
+    /// No group of colors or lines is confined at all.
+    Unstructured,
+}
+
+impl StallPattern {
+    /// Finds the tightest pattern in the given solve state: the group of
+    /// unplaced colors or open lines with the least slack (of at least
+    /// one), and the smallest such group if there are several.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::discover::{LineKind, StallPattern};
+    /// # use qsolve::solvestate::SolveState;
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// // This board has two solutions, so no heuristic can make progress.
+    /// let board = Board::from_str("wwkk\nwwkk\nrrbb\nrrbb")?;
+    /// let pattern = StallPattern::find(&SolveState::from(&board));
+    /// assert_eq!(
+    ///     pattern,
+    ///     StallPattern::ColorsInLines { colors: 1, lines: 2, kind: LineKind::Row }
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn find(solve_state: &SolveState) -> Self {
+        let board = solve_state.board;
+        let color_coords = solve_state
+            .unplaced_colors()
+            .iter()
+            .map(|color| board.coords_for_color(color))
+            .collect::<Vec<_>>();
+        let line_coords = |kind: LineKind| {
+            (0..board.size())
+                .map(|line| match kind {
+                    LineKind::Row => board.row_coords(line),
+                    LineKind::Col => board.col_coords(line),
+                })
+                .filter(|coords| {
+                    !solve_state.blank_colors(coords).is_empty()
+                        && coords
+                            .iter()
+                            .all(|coord| solve_state.square(&coord) != Some(SquareVal::Queen))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut candidates = vec![];
+        for kind in [LineKind::Row, LineKind::Col] {
+            for group in proper_subsets(&color_coords) {
+                let lines = match kind {
+                    LineKind::Row => solve_state.blank_rows(&group.coords).len(),
+                    LineKind::Col => solve_state.blank_cols(&group.coords).len(),
+                };
+                candidates.push(StallPattern::ColorsInLines {
+                    colors: group.size,
+                    lines,
+                    kind,
+                });
+            }
+            for group in proper_subsets(&line_coords(kind)) {
+                candidates.push(StallPattern::LinesContainColors {
+                    lines: group.size,
+                    colors: solve_state.blank_colors(&group.coords).len(),
+                    kind,
+                });
+            }
+        }
+        candidates
+            .into_iter()
+            .filter(|pattern| pattern.slack() > 0)
+            .min_by_key(|pattern| (pattern.slack(), pattern.group_size()))
+            .unwrap_or(StallPattern::Unstructured)
+    }
+
+    /// How many more lines (or colors) the group spans than it has.
+    fn slack(&self) -> usize {
+        match *self {
+            StallPattern::ColorsInLines { colors, lines, .. } => lines.saturating_sub(colors),
+            StallPattern::LinesContainColors { lines, colors, .. } => colors.saturating_sub(lines),
+            StallPattern::Unstructured => usize::MAX,
+        }
+    }
+
+    /// How many colors (or lines) are in the group.
+    fn group_size(&self) -> usize {
+        match *self {
+            StallPattern::ColorsInLines { colors, .. } => colors,
+            StallPattern::LinesContainColors { lines, .. } => lines,
+            StallPattern::Unstructured => usize::MAX,
+        }
+    }
+
+    /// A suggestion for what new heuristic family would unlock boards with
+    /// this pattern.
+    pub fn suggestion(&self) -> String {
+        match (self, self.slack()) {
+            (StallPattern::Unstructured, _) => {
+                "No colors or lines are confined; the board likely needs search, or doesn't have exactly one solution.".to_string()
+            }
+            (StallPattern::ColorsInLines { kind, .. }, 1) => format!(
+                "A heuristic that tries each of the {kind} these colors span, and x's out squares that every choice eliminates."
+            ),
+            (StallPattern::LinesContainColors { kind, .. }, 1) => format!(
+                "A heuristic that tries each color in these {kind}, and x's out squares that every choice eliminates."
+            ),
+            _ => "A heuristic that places a Queen tentatively and x's it out if that leads to a contradiction.".to_string(),
+        }
+    }
+}
+
+impl Display for StallPattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StallPattern::ColorsInLines {
+                colors,
+                lines,
+                kind,
+            } => write!(f, "{colors} colors confined to {lines} {kind}"),
+            StallPattern::LinesContainColors {
+                lines,
+                colors,
+                kind,
+            } => write!(f, "{lines} {kind} containing only {colors} colors"),
+            StallPattern::Unstructured => write!(f, "No confined colors or lines"),
+        }
+    }
+}
+
+/// A group of colors or lines, with all of their squares.
+struct Group {
+    size: usize,
+    coords: CoordSet,
+}
+
+/// Returns every non-empty group of the given sets that doesn't contain all
+/// of them.
+fn proper_subsets(sets: &[CoordSet]) -> impl Iterator<Item = Group> + '_ {
+    sets.iter()
+        .powerset()
+        .filter(move |subset| !subset.is_empty() && subset.len() < sets.len())
+        .map(|subset| Group {
+            size: subset.len(),
+            coords: subset.into_iter().flat_map(CoordSet::iter).collect(),
+        })
+}
+
+/// A report on which boards in a directory the solver stalls on, grouped by
+/// the [StallPattern] left on each.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DiscoveryReport {
+    /// The boards that the solver solved.
+    pub solved: Vec<PathBuf>,
+
+    /// The boards that the solver stalled on, and the pattern left on each.
+    pub stalled: Vec<(PathBuf, StallPattern)>,
+
+    /// The files that could not be read as boards.
+    pub unreadable: Vec<PathBuf>,
+}
+
+impl DiscoveryReport {
+    /// Solves every `.txt` board in the given directory with the given
+    /// strategy or [SolveOptions], and records where the solver stalls.
+    pub fn from_dir(dir: &Path, solve_options: impl Into<SolveOptions>) -> Result<Self> {
+        let solve_options = solve_options.into();
+        let paths = std::fs::read_dir(dir)
+            .with_context(|| format!("Could not read directory {dir:?}"))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<Vec<_>>>()?
+            .into_iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+            .sorted()
+            .collect::<Vec<_>>();
+
+        let mut report = DiscoveryReport::default();
+        for path in paths {
+            let queens_file = match QueensFile::try_from_text_file(&path) {
+                Ok(queens_file) => queens_file,
+                Err(err) => {
+                    warn!("Skipping {path:?}: {err:#}");
+                    report.unreadable.push(path);
+                    continue;
+                }
+            };
+            let heuristics = all_heuristics(&queens_file.board);
+            let solution = Solution::solve_with_options(
+                SolveState::from(&queens_file),
+                solve_options,
+                &heuristics,
+            );
+            if solution.is_solved() {
+                report.solved.push(path);
+            } else {
+                let pattern = StallPattern::find(&solution.final_state);
+                debug!("{path:?} stalled: {pattern}");
+                report.stalled.push((path, pattern));
+            }
+        }
+        Ok(report)
+    }
+
+    /// Returns the stalled boards grouped by pattern, from the most to the
+    /// least common pattern.
+    pub fn clusters(&self) -> Vec<(StallPattern, Vec<&Path>)> {
+        let mut clusters = self
+            .stalled
+            .iter()
+            .into_group_map_by(|(_, pattern)| *pattern)
+            .into_iter()
+            .map(|(pattern, boards)| {
+                (
+                    pattern,
+                    boards
+                        .into_iter()
+                        .map(|(path, _)| path.as_path())
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        clusters.sort_by(|(a, a_boards), (b, b_boards)| {
+            b_boards.len().cmp(&a_boards.len()).then(a.cmp(b))
+        });
+        clusters
+    }
+}
+
+impl Display for DiscoveryReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let total = self.solved.len() + self.stalled.len();
+        write!(f, "Solved {} of {total} boards", self.solved.len())?;
+        if !self.unreadable.is_empty() {
+            write!(f, " ({} files could not be read)", self.unreadable.len())?;
+        }
+        for (pattern, boards) in self.clusters() {
+            write!(f, "\n\n{pattern} ({} boards)", boards.len())?;
+            for board in boards {
+                write!(f, "\n  {}", board.display())?;
+            }
+            write!(f, "\n  Suggestion: {}", pattern.suggestion())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{board::Board, solvestate::SolveStrategy};
+
+    use super::*;
+
+    #[test]
+    fn stall_pattern_of_confined_colors() -> Result<()> {
+        // This board has two solutions, so the solver stalls immediately,
+        // with each color confined to two rows.
+        let board = Board::from_str("wwkk\nwwkk\nrrbb\nrrbb")?;
+        let solve_state = SolveState::from(&board);
+        let pattern = StallPattern::find(&solve_state);
+        assert_eq!(pattern.slack(), 1);
+        assert!(!pattern.suggestion().is_empty());
+
+        let solved = SolveState::with_queens(&board, &[(0, 1), (1, 3), (2, 0), (3, 2)]);
+        assert_eq!(StallPattern::find(&solved), StallPattern::Unstructured);
+        Ok(())
+    }
+
+    #[test]
+    fn discovery_report_of_folders() -> Result<()> {
+        let games = DiscoveryReport::from_dir(Path::new("games"), SolveStrategy::Fast)?;
+        assert!(games.stalled.is_empty());
+        assert!(!games.solved.is_empty());
+        assert!(games.to_string().starts_with("Solved"));
+
+        let fixtures = DiscoveryReport::from_dir(Path::new("tests/fixtures"), SolveStrategy::Fast)?;
+        assert_eq!(
+            fixtures
+                .stalled
+                .iter()
+                .map(|(path, _)| path.as_path())
+                .collect::<Vec<_>>(),
+            vec![
+                Path::new("tests/fixtures/conflict.txt"),
+                Path::new("tests/fixtures/invalid-colors.txt"),
+                Path::new("tests/fixtures/unsolvable-2.txt")
+            ]
+        );
+        assert!(!fixtures.unreadable.is_empty());
+        assert_eq!(fixtures.clusters().len(), 1);
+        assert!(fixtures.to_string().contains("Suggestion: "));
+        Ok(())
+    }
+}
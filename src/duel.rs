@@ -0,0 +1,384 @@
+use std::path::Path;
+
+use anyhow::{Context, Result, anyhow, bail, ensure};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use serde_json::{Value, json};
+
+use crate::{
+    binary::{from_bytes, to_bytes},
+    board::Board,
+    datastructure::{Coord, CoordSet},
+    file::QueensFile,
+    heuristic::Changes,
+    notation::CoordStyle,
+    search::all_solutions,
+    session::Session,
+    solvestate::SolveState,
+};
+
+/// The version of the format written by [Duel::to_json], which is bumped
+/// whenever a change would confuse older versions of qsolve.
+const DUEL_VERSION: u64 = 1;
+
+/// Where a [Duel] stands, as returned by [Duel::status].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DuelStatus {
+    /// The puzzle isn't solved yet, and it's the turn of the player with
+    /// the given index.
+    ToMove(usize),
+
+    /// The puzzle is solved, and the player with the given index resolved
+    /// more squares.
+    Won(usize),
+
+    /// The puzzle is solved, and both players resolved as many squares.
+    Tied,
+}
+
+/// A puzzle that two players take turns on, such as by mail, where each
+/// turn must mark squares that agree with the puzzle's solution.
+///
+/// Each player scores the squares their moves resolve, including the ones
+/// that a Queen they place x's out, and whoever has resolved the most once
+/// the puzzle is solved wins. The duel only keeps the puzzle and the moves
+/// made, so that it can be saved with [Duel::to_json] and passed back and
+/// forth as a file.
+///
+/// # Examples
+/// ```
+/// # use qsolve::datastructure::CoordSet;
+/// # use qsolve::duel::{Duel, DuelStatus};
+/// # use qsolve::file::QueensFile;
+/// # use qsolve::heuristic::Changes;
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let puzzle = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+/// let mut duel = Duel::new(puzzle, ["ann".to_string(), "bo".to_string()])?;
+/// let resolved = duel.play("ann", Changes::AddQueen { queen: (0, 1), x: CoordSet::default() })?;
+/// assert_eq!(resolved, 9);
+/// assert_eq!(duel.status()?, DuelStatus::ToMove(1));
+/// assert!(duel.play("ann", Changes::AddX { x: CoordSet::from_iter([(1, 3)]) }).is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Duel {
+    /// The puzzle, encoded by [to_bytes].
+    puzzle: Vec<u8>,
+    players: [String; 2],
+    moves: Vec<Changes>,
+}
+
+impl Duel {
+    /// Starts a duel between the given players on the given puzzle, with
+    /// the first player to move first.
+    ///
+    /// This fails unless the puzzle has exactly one solution, which moves
+    /// are checked against, and still has squares left to resolve.
+    pub fn new(puzzle: QueensFile, players: [String; 2]) -> Result<Self> {
+        ensure!(
+            players[0] != players[1],
+            "The two players need different names"
+        );
+        ensure!(
+            all_solutions(&puzzle.board, 2).len() == 1,
+            "A duel needs a puzzle with exactly one solution"
+        );
+        ensure!(
+            !SolveState::from(&puzzle).blank_coords().is_empty(),
+            "The puzzle has no squares left to play"
+        );
+        Ok(Duel {
+            puzzle: to_bytes(&SolveState::from(&puzzle)),
+            players,
+            moves: vec![],
+        })
+    }
+
+    /// Returns the names of the two players, in the order they move.
+    pub fn players(&self) -> &[String; 2] {
+        &self.players
+    }
+
+    /// Returns the moves made so far, oldest first; the first player made
+    /// the even-numbered ones and the second player the odd-numbered ones.
+    pub fn moves(&self) -> &[Changes] {
+        &self.moves
+    }
+
+    /// Returns a session on the puzzle with every move made so far applied.
+    pub fn session(&self) -> Result<Session> {
+        let mut session = Session::new(from_bytes(&self.puzzle)?);
+        for changes in &self.moves {
+            session.apply(changes.clone())?;
+        }
+        Ok(session)
+    }
+
+    /// Returns how many squares each player's moves have resolved.
+    pub fn scores(&self) -> Result<[usize; 2]> {
+        let mut session = Session::new(from_bytes(&self.puzzle)?);
+        let mut scores = [0, 0];
+        for (i, changes) in self.moves.iter().enumerate() {
+            let blank = session.solve_state().blank_coords().len();
+            session.apply(changes.clone())?;
+            scores[i % 2] += blank - session.solve_state().blank_coords().len();
+        }
+        Ok(scores)
+    }
+
+    /// Returns whose turn it is, or who won once the puzzle is solved.
+    pub fn status(&self) -> Result<DuelStatus> {
+        if !self.session()?.is_solved() {
+            return Ok(DuelStatus::ToMove(self.moves.len() % 2));
+        }
+        let [first, second] = self.scores()?;
+        Ok(match first.cmp(&second) {
+            std::cmp::Ordering::Greater => DuelStatus::Won(0),
+            std::cmp::Ordering::Less => DuelStatus::Won(1),
+            std::cmp::Ordering::Equal => DuelStatus::Tied,
+        })
+    }
+
+    /// Makes a move for the named player, returning how many squares it
+    /// resolved.
+    ///
+    /// This fails, leaving the duel untouched, if it isn't that player's
+    /// turn, if the puzzle is already solved, if the move can't be applied
+    /// to the puzzle as [Session::apply] describes, or if it marks any square
+    /// differently from the puzzle's solution.
+    pub fn play(&mut self, player: &str, changes: Changes) -> Result<usize> {
+        ensure!(
+            self.players.iter().any(|name| name == player),
+            "{player} isn't playing in this duel"
+        );
+        let to_move = &self.players[self.moves.len() % 2];
+        ensure!(to_move == player, "It's {to_move}'s turn, not {player}'s");
+        let resolved = check_move(&mut self.session()?, &changes)?;
+        self.moves.push(changes);
+        Ok(resolved)
+    }
+
+    /// Reads a duel from a JSON file written by [Duel::to_json].
+    pub fn try_from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read duel {path:?}"))?;
+        serde_json::from_str(&content)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Duel::from_json(&json))
+            .with_context(|| format!("Invalid duel {path:?}"))
+    }
+
+    /// Returns the duel as a JSON object, with the version of the format,
+    /// the puzzle encoded by [to_bytes], the players and the moves made.
+    pub fn to_json(&self) -> Value {
+        let coord_json = |coord: &Coord| CoordStyle::JSON.json(coord);
+        let moves = self
+            .moves
+            .iter()
+            .map(|changes| match changes {
+                Changes::AddQueen { queen, x } => json!({
+                    "queen": coord_json(queen),
+                    "x": x.iter().map(|coord| coord_json(&coord)).collect::<Vec<_>>(),
+                }),
+                Changes::AddX { x } => json!({
+                    "x": x.iter().map(|coord| coord_json(&coord)).collect::<Vec<_>>(),
+                }),
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "version": DUEL_VERSION,
+            "puzzle": URL_SAFE_NO_PAD.encode(&self.puzzle),
+            "players": self.players,
+            "moves": moves,
+        })
+    }
+
+    /// Reads a duel back from the JSON returned by [Duel::to_json], checking
+    /// every move in it again.
+    pub fn from_json(json: &Value) -> Result<Self> {
+        let version = json["version"]
+            .as_u64()
+            .ok_or_else(|| anyhow!("Duel has no version"))?;
+        ensure!(
+            version == DUEL_VERSION,
+            "Duel is version {version}, but only version {DUEL_VERSION} can be read"
+        );
+        let puzzle = json["puzzle"]
+            .as_str()
+            .and_then(|puzzle| URL_SAFE_NO_PAD.decode(puzzle).ok())
+            .ok_or_else(|| anyhow!("Duel has an invalid puzzle"))?;
+        let players = match json["players"].as_array().map(Vec::as_slice) {
+            Some([Value::String(first), Value::String(second)]) => [first.clone(), second.clone()],
+            _ => bail!("Duel needs two players"),
+        };
+        let mut duel = Duel::new(from_bytes(&puzzle)?, players)?;
+        let mut session = duel.session()?;
+        let moves = json["moves"]
+            .as_array()
+            .ok_or_else(|| anyhow!("Duel has no moves"))?;
+        for (i, json) in moves.iter().enumerate() {
+            let changes = changes_from_json(json, session.board())
+                .with_context(|| format!("Move {} is invalid", i + 1))?;
+            check_move(&mut session, &changes)
+                .with_context(|| format!("Move {} is invalid", i + 1))?;
+            duel.moves.push(changes);
+        }
+        Ok(duel)
+    }
+}
+
+/// Applies the given move to the session, returning how many squares it
+/// resolved, or fails, leaving the session untouched, if the move isn't
+/// allowed in a duel.
+fn check_move(session: &mut Session, changes: &Changes) -> Result<usize> {
+    ensure!(!session.is_solved(), "The puzzle is already solved");
+    let mistakes = session.mistakes_in(changes);
+    ensure!(
+        mistakes.is_empty(),
+        "That move disagrees with the solution at {}",
+        CoordStyle::default().squares_text(&mistakes)
+    );
+    let blank = session.solve_state().blank_coords().len();
+    session.apply(changes.clone())?;
+    Ok(blank - session.solve_state().blank_coords().len())
+}
+
+/// Reads a move on the given board back from the JSON written by
+/// [Duel::to_json].
+fn changes_from_json(json: &Value, board: &Board) -> Result<Changes> {
+    let coord = |json: &Value| -> Result<Coord> {
+        let coord = match json.as_array().map(Vec::as_slice) {
+            Some([r, c]) => r
+                .as_u64()
+                .zip(c.as_u64())
+                .map(|(r, c)| (r as usize, c as usize))
+                .ok_or_else(|| anyhow!("Invalid square {json}"))?,
+            _ => bail!("Invalid square {json}"),
+        };
+        ensure!(board.contains(&coord), "Square {json} is not on the board");
+        Ok(coord)
+    };
+    let x = json["x"]
+        .as_array()
+        .ok_or_else(|| anyhow!("Move has no Xs"))?
+        .iter()
+        .map(coord)
+        .collect::<Result<CoordSet>>()?;
+    Ok(match json.get("queen") {
+        Some(queen) => Changes::AddQueen {
+            queen: coord(queen)?,
+            x,
+        },
+        None => Changes::AddX { x },
+    })
+}
+
+/// Parses a move on the given board as typed by a player: either one square
+/// and `Q`, to place a Queen, or one or more squares and `x`, to x them out.
+/// This fails if any of the squares isn't on the board.
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Board;
+/// # use qsolve::datastructure::CoordSet;
+/// # use qsolve::duel::parse_move;
+/// # use qsolve::heuristic::Changes;
+/// # use qsolve::notation::CoordStyle;
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+/// let style = CoordStyle::default();
+/// assert_eq!(
+///     parse_move(&["r1c2", "Q"], &board, &style)?,
+///     Changes::AddQueen { queen: (0, 1), x: CoordSet::default() }
+/// );
+/// assert_eq!(
+///     parse_move(&["r1c1", "r2c1", "x"], &board, &style)?,
+///     Changes::AddX { x: CoordSet::from_iter([(0, 0), (1, 0)]) }
+/// );
+/// assert!(parse_move(&["r1c1", "r2c1", "Q"], &board, &style).is_err());
+/// assert!(parse_move(&["r1c5", "x"], &board, &style).is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_move(words: &[&str], board: &Board, coord_style: &CoordStyle) -> Result<Changes> {
+    let Some((mark, squares)) = words.split_last() else {
+        bail!("Expected squares followed by Q or x");
+    };
+    let squares = squares
+        .iter()
+        .map(|square| {
+            let coord = coord_style.parse(square)?;
+            ensure!(board.contains(&coord), "{square} is not on the board");
+            Ok(coord)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    match (*mark, squares.as_slice()) {
+        (_, []) => bail!("Expected squares followed by Q or x"),
+        ("Q", [queen]) => Ok(Changes::AddQueen {
+            queen: *queen,
+            x: CoordSet::default(),
+        }),
+        ("Q", _) => bail!("Only one Queen can be placed in a move"),
+        ("x", _) => Ok(Changes::AddX {
+            x: squares.into_iter().collect(),
+        }),
+        _ => bail!("Expected Q or x, but found `{mark}`"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn duel_plays_to_a_winner() -> Result<()> {
+        let puzzle = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        let mut duel = Duel::new(
+            QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb")?,
+            ["ann".to_string(), "bo".to_string()],
+        )?;
+        let queen = |queen| Changes::AddQueen {
+            queen,
+            x: CoordSet::default(),
+        };
+        assert!(duel.play("bo", queen((0, 1))).is_err());
+        assert!(duel.play("cy", queen((0, 1))).is_err());
+        assert!(duel.play("ann", queen((0, 0))).is_err());
+        assert!(duel.moves().is_empty());
+
+        duel.play("ann", queen((0, 1)))?;
+        duel.play("bo", queen((1, 3)))?;
+        duel.play("ann", queen((2, 0)))?;
+        assert_eq!(duel.status()?, DuelStatus::ToMove(1));
+        let json = duel.to_json();
+        duel.play("bo", queen((3, 2)))?;
+        let [ann, bo] = duel.scores()?;
+        assert_eq!(ann + bo, 16);
+        assert_eq!(duel.status()?, DuelStatus::Won(0));
+        assert!(duel.play("ann", queen((3, 2))).is_err());
+
+        let reread = Duel::from_json(&json)?;
+        assert_eq!(reread.moves().len(), 3);
+        assert_eq!(reread.status()?, DuelStatus::ToMove(1));
+
+        // A move off the board in the file is refused, not wrapped around.
+        let mut off_board = json.clone();
+        off_board["moves"][2] = json!({ "x": [[0, 20]] });
+        let err = Duel::from_json(&off_board).unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            "Square [0,20] is not on the board"
+        );
+
+        assert!(Duel::new(puzzle, ["ann".to_string(), "ann".to_string()]).is_err());
+        let ambiguous = QueensFile::from_str("wwww\nkkkk\nrrrr\nbbbb")?;
+        assert!(Duel::new(ambiguous, duel.players().clone()).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,315 @@
+use std::fmt::Write;
+
+use anyhow::{Result, anyhow, ensure};
+use itertools::Itertools;
+use owo_colors::Style;
+
+use crate::{
+    board::Board,
+    datastructure::Coord,
+    error::QSolveError,
+    palette::Palette,
+    solvestate::Charset,
+    squarecolor::{ALL_SQUARE_COLORS, SquareColor},
+};
+
+/// A board whose colors are being painted in one square at a time, as by
+/// `qsolve edit`.
+///
+/// Squares start out unpainted, and the board can only be turned into a
+/// [Board] once every square has a color. A cursor picks the square that
+/// painting changes, and the last color painted is kept as the brush, so
+/// it can be painted again.
+///
+/// # Examples
+/// ```
+/// # use qsolve::editor::Editor;
+/// # use qsolve::squarecolor::SquareColor;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let mut editor = Editor::new(4)?;
+/// assert!(editor.to_board().is_err());
+/// editor.fill(SquareColor::White);
+/// assert_eq!(editor.to_board()?.to_string(), "wwww\nwwww\nwwww\nwwww");
+///
+/// editor.move_cursor(3, 0);
+/// editor.paint(SquareColor::Blue);
+/// editor.move_cursor(0, 1);
+/// editor.paint_brush();
+/// assert_eq!(editor.to_board()?.to_string(), "wwww\nwwww\nwwww\nbbww");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Editor {
+    size: usize,
+    squares: Vec<Option<SquareColor>>,
+    cursor: Coord,
+    brush: Option<SquareColor>,
+    history: Vec<Vec<Option<SquareColor>>>,
+}
+
+impl Editor {
+    /// Starts editing an unpainted board of the given size, with the cursor
+    /// in the top left corner.
+    pub fn new(size: usize) -> Result<Self> {
+        ensure!(
+            (1..=ALL_SQUARE_COLORS.len()).contains(&size),
+            anyhow!(
+                "Boards must be between 1 and {} squares wide",
+                ALL_SQUARE_COLORS.len()
+            )
+            .context(QSolveError::InvalidBoard)
+        );
+        Ok(Editor {
+            size,
+            squares: vec![None; size * size],
+            cursor: (0, 0),
+            brush: None,
+            history: vec![],
+        })
+    }
+
+    /// Starts editing the colors of an existing board.
+    pub fn from_board(board: &Board) -> Self {
+        let size = board.size();
+        Editor {
+            size,
+            squares: (0..size)
+                .cartesian_product(0..size)
+                .map(|coord| Some(board.color(&coord)))
+                .collect(),
+            cursor: (0, 0),
+            brush: None,
+            history: vec![],
+        }
+    }
+
+    /// Returns how many squares wide the board is.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the square that painting changes.
+    pub fn cursor(&self) -> Coord {
+        self.cursor
+    }
+
+    /// Returns the color last painted, if any.
+    pub fn brush(&self) -> Option<SquareColor> {
+        self.brush
+    }
+
+    /// Returns the color of the given square, or None if it isn't painted.
+    pub fn color(&self, (row, col): Coord) -> Option<SquareColor> {
+        self.squares[row * self.size + col]
+    }
+
+    /// Returns the color of every square, in row-major order.
+    pub fn colors(&self) -> &[Option<SquareColor>] {
+        &self.squares
+    }
+
+    /// Returns how many squares aren't painted yet.
+    pub fn unpainted(&self) -> usize {
+        self.squares
+            .iter()
+            .filter(|square| square.is_none())
+            .count()
+    }
+
+    /// Moves the cursor by the given number of rows and columns, stopping at
+    /// the edges of the board.
+    pub fn move_cursor(&mut self, rows: isize, cols: isize) {
+        let last = self.size - 1;
+        let (row, col) = self.cursor;
+        self.cursor = (
+            row.saturating_add_signed(rows).min(last),
+            col.saturating_add_signed(cols).min(last),
+        );
+    }
+
+    /// Paints the square under the cursor with the given color, which
+    /// becomes the brush.
+    pub fn paint(&mut self, color: SquareColor) {
+        self.brush = Some(color);
+        self.set(vec![self.cursor], Some(color));
+    }
+
+    /// Paints the square under the cursor with the brush, if there is one.
+    pub fn paint_brush(&mut self) {
+        if let Some(color) = self.brush {
+            self.paint(color);
+        }
+    }
+
+    /// Removes the color from the square under the cursor.
+    pub fn erase(&mut self) {
+        self.set(vec![self.cursor], None);
+    }
+
+    /// Paints the square under the cursor with the given color, which
+    /// becomes the brush, along with every square connected to it through
+    /// squares of the same color (or through unpainted squares, if it isn't
+    /// painted).
+    pub fn fill(&mut self, color: SquareColor) {
+        self.brush = Some(color);
+        let target = self.color(self.cursor);
+        let mut area = vec![self.cursor];
+        let mut next = 0;
+        while let Some(&(row, col)) = area.get(next) {
+            next += 1;
+            let neighbors = [
+                row.checked_sub(1).map(|row| (row, col)),
+                (row + 1 < self.size).then_some((row + 1, col)),
+                col.checked_sub(1).map(|col| (row, col)),
+                (col + 1 < self.size).then_some((row, col + 1)),
+            ];
+            for neighbor in neighbors.into_iter().flatten() {
+                if self.color(neighbor) == target && !area.contains(&neighbor) {
+                    area.push(neighbor);
+                }
+            }
+        }
+        self.set(area, Some(color));
+    }
+
+    /// Undoes the last change to the colors, returning false if there was
+    /// nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some(squares) => {
+                self.squares = squares;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the painted board, failing with [QSolveError::InvalidBoard]
+    /// if some squares aren't painted yet.
+    ///
+    /// The board isn't otherwise checked: see
+    /// [QueensFile::validate_strict][crate::file::QueensFile::validate_strict].
+    pub fn to_board(&self) -> Result<Board> {
+        let unpainted = self.unpainted();
+        ensure!(
+            unpainted == 0,
+            anyhow!(
+                "{unpainted} square{} not painted yet",
+                if unpainted == 1 { " is" } else { "s are" }
+            )
+            .context(QSolveError::InvalidBoard)
+        );
+        Ok(Board::new(
+            self.size,
+            self.squares.iter().flatten().copied().collect(),
+        ))
+    }
+
+    /// Returns the board as a string colored by OwoColorize, one line per
+    /// row. Painted squares show their color's letter, the key that paints
+    /// it, and the square under the cursor is reversed.
+    pub fn ansi_string(&self, charset: Charset, palette: Palette) -> Result<String> {
+        // Imported here, since its color method would hide Editor::color.
+        use owo_colors::OwoColorize;
+
+        let mut rows = Vec::with_capacity(self.size);
+        for row in 0..self.size {
+            let mut line = String::new();
+            for col in 0..self.size {
+                let (c, mut style) = match self.color((row, col)) {
+                    Some(color) => (
+                        color.to_string().chars().next().unwrap(),
+                        Style::new()
+                            .color(palette.fg(color))
+                            .on_color(palette.bg(color)),
+                    ),
+                    None => (
+                        match charset {
+                            Charset::Ascii => '.',
+                            Charset::Unicode => '\u{00B7}',
+                        },
+                        Style::new(),
+                    ),
+                };
+                if (row, col) == self.cursor {
+                    style = style.reversed().bold();
+                }
+                write!(line, "{}", c.style(style))?;
+            }
+            rows.push(line);
+        }
+        Ok(rows.join("\n"))
+    }
+
+    /// Sets the given squares to the given color, remembering the colors
+    /// before so they can be undone if any changed.
+    fn set(&mut self, coords: Vec<Coord>, color: Option<SquareColor>) {
+        let before = self.squares.clone();
+        for (row, col) in coords {
+            self.squares[row * self.size + col] = color;
+        }
+        if self.squares != before {
+            self.history.push(before);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn editor_paints_fills_and_undoes() -> Result<()> {
+        assert!(Editor::new(0).is_err());
+        assert!(Editor::new(17).is_err());
+
+        let mut editor = Editor::new(3)?;
+        editor.paint_brush();
+        assert_eq!(editor.unpainted(), 9);
+        assert!(!editor.undo());
+
+        // The cursor stops at the edges.
+        editor.move_cursor(-1, 5);
+        assert_eq!(editor.cursor(), (0, 2));
+        editor.paint(SquareColor::Red);
+        editor.move_cursor(1, 0);
+        editor.paint_brush();
+        assert_eq!(editor.color((1, 2)), Some(SquareColor::Red));
+        let err = editor.to_board().unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            "7 squares are not painted yet"
+        );
+
+        // Filling only spreads through the unpainted squares.
+        editor.move_cursor(0, -2);
+        editor.paint(SquareColor::Green);
+        editor.move_cursor(1, 0);
+        editor.fill(SquareColor::Blue);
+        assert_eq!(editor.to_board()?, Board::from_str("bbr\ngbr\nbbb")?);
+
+        // Painting a square its own color can't be undone, since nothing
+        // changed.
+        editor.paint(SquareColor::Blue);
+        assert!(editor.undo());
+        assert_eq!(editor.unpainted(), 6);
+        editor.paint_brush();
+        editor.erase();
+        assert_eq!(editor.color((2, 0)), None);
+        assert!(editor.undo());
+        assert_eq!(editor.color((2, 0)), Some(SquareColor::Blue));
+
+        let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        let mut editor = Editor::from_board(&board);
+        assert_eq!(editor.to_board()?, board);
+        // Filling a painted square recolors its whole region.
+        editor.move_cursor(1, 1);
+        editor.fill(SquareColor::Yellow);
+        assert_eq!(editor.to_board()?.to_string(), "wwww\nwyyy\nrrrr\nbbbb");
+        Ok(())
+    }
+}
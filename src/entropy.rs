@@ -0,0 +1,169 @@
+use std::fmt::{Display, Formatter};
+
+use serde_json::{Value, json};
+
+use crate::{
+    board::Region,
+    solvestate::{SolveState, SquareVal},
+};
+
+/// How many squares could still hold the Queen of one row, column or color.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RegionConstraint {
+    /// The row, column or color.
+    pub region: Region,
+
+    /// How many of its squares are still blank.
+    pub candidates: usize,
+}
+
+/// How constrained a partially solved puzzle is: the candidate count of
+/// every row, column and color that doesn't have its Queen yet.
+///
+/// The aggregate [Entropy::bits] adds up log2 of each candidate count, so it
+/// is 0 once every region is down to one square, and each bit is roughly one
+/// yes-or-no choice still open across the board.
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::{Board, Region};
+/// # use qsolve::entropy::Entropy;
+/// # use qsolve::solvestate::SolveState;
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+/// let entropy = Entropy::of(&SolveState::with_queens(&board, &[(0, 1)]));
+/// // The second row and black both have only one square left; ties go to
+/// // rows, then columns, then colors.
+/// assert_eq!(entropy.most_constrained().unwrap().region, Region::Row(1));
+/// assert_eq!(entropy.branching_factor(), Some(1));
+/// assert!(entropy.bits() > 0.0);
+///
+/// let empty = Entropy::of(&SolveState::from(&board));
+/// assert!(empty.bits() > entropy.bits());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Entropy {
+    /// Every row, then column, then color without a Queen, with how many
+    /// candidates it has left.
+    pub regions: Vec<RegionConstraint>,
+}
+
+impl Entropy {
+    /// Measures how constrained the given solve state is.
+    pub fn of(solve_state: &SolveState) -> Self {
+        let board = solve_state.board;
+        let size = board.size();
+        let regions = (0..size)
+            .map(Region::Row)
+            .chain((0..size).map(Region::Col))
+            .chain(board.all_colors().into_iter().map(|c| Region::Color(*c)))
+            .filter_map(|region| {
+                let coords = region.coords(board);
+                let placed = coords
+                    .iter()
+                    .any(|coord| solve_state.square(&coord) == Some(SquareVal::Queen));
+                (!placed).then(|| RegionConstraint {
+                    region,
+                    candidates: coords.intersection(&solve_state.blank_coords()).len(),
+                })
+            })
+            .collect();
+        Entropy { regions }
+    }
+
+    /// Returns the region with the fewest candidates, which is where a
+    /// search would best branch, or the first of them in the order of
+    /// [Entropy::regions]; None if every region has its Queen.
+    pub fn most_constrained(&self) -> Option<&RegionConstraint> {
+        self.regions.iter().min_by_key(|r| r.candidates)
+    }
+
+    /// Returns how many ways a search would have to branch at its best
+    /// choice: the candidate count of [Entropy::most_constrained].
+    pub fn branching_factor(&self) -> Option<usize> {
+        self.most_constrained().map(|r| r.candidates)
+    }
+
+    /// Returns whether some region without a Queen has no candidates left,
+    /// so the puzzle can't be finished from here.
+    pub fn is_contradiction(&self) -> bool {
+        self.branching_factor() == Some(0)
+    }
+
+    /// Returns the total entropy in bits: the sum of log2 of every candidate
+    /// count. Regions without candidates count as 0 bits; see
+    /// [Entropy::is_contradiction].
+    pub fn bits(&self) -> f64 {
+        self.regions
+            .iter()
+            .filter(|r| r.candidates > 0)
+            .map(|r| (r.candidates as f64).log2())
+            .fold(0.0, |total, bits| total + bits)
+    }
+
+    /// Returns the entropy as a JSON object, with the bits, the branching
+    /// factor and each region's candidate count.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "bits": self.bits(),
+            "branching_factor": self.branching_factor(),
+            "regions": self
+                .regions
+                .iter()
+                .map(|r| json!({"region": r.region.to_string(), "candidates": r.candidates}))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl Display for Entropy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.1} bits", self.bits())?;
+        if let Some(most) = self.most_constrained() {
+            write!(
+                f,
+                ", branching factor {} ({})",
+                most.candidates, most.region
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use anyhow::Result;
+
+    use super::*;
+    use crate::{board::Board, file::QueensFile};
+
+    #[test]
+    fn entropy_counts_candidates() -> Result<()> {
+        let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        let empty = Entropy::of(&SolveState::from(&board));
+        assert_eq!(empty.regions.len(), 12);
+        // Every row and column has 4 candidates, and the colors 5, 3, 4, 4.
+        assert!((empty.bits() - (10.0 * 2.0 + 5f64.log2() + 3f64.log2())).abs() < 1e-9);
+        assert_eq!(empty.branching_factor(), Some(3));
+        assert_eq!(
+            empty.to_string(),
+            "23.9 bits, branching factor 3 ('Black' Color)"
+        );
+
+        let solved = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\nxQxx\nxxxQ\nQxxx\nxxQx")?;
+        let solved = Entropy::of(&SolveState::from(&solved));
+        assert!(solved.regions.is_empty());
+        assert_eq!(solved.bits(), 0.0);
+        assert_eq!(solved.to_string(), "0.0 bits");
+
+        let stuck = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\n....\n....\n....\nxxxx")?;
+        assert!(Entropy::of(&SolveState::from(&stuck)).is_contradiction());
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+use std::{fmt::Display, process::ExitCode};
+
+/// The kinds of failure that callers (such as scripts wrapping the `qsolve`
+/// binary) may want to distinguish between.
+///
+/// Functions in this library return [anyhow::Error]s; where a failure is one
+/// of these kinds, a [QSolveError] is attached to the error as context, so it
+/// can be recovered with [QSolveError::from_error].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum QSolveError {
+    /// The input could not be parsed as a Queens puzzle.
+    Parse,
+    /// The input was parsed, but does not describe a valid Queens puzzle.
+    InvalidBoard,
+    /// No Queens grid could be detected in an image.
+    ImageDetection,
+    /// The solver could not make progress before the puzzle was solved.
+    Unsolvable,
+    /// A heuristic made changes that break the rules of the puzzle, or
+    /// overwrite a square that was already decided.
+    InvalidChanges,
+}
+
+impl QSolveError {
+    /// The process exit code used for errors that are not a [QSolveError].
+    pub const GENERIC_EXIT_CODE: u8 = 1;
+
+    /// Returns the process exit code for this kind of error.
+    ///
+    /// Exit code 1 is used for any other failure, and exit code 2 is used by
+    /// the argument parser for invalid command lines, so these start at 3.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::error::QSolveError;
+    /// assert_eq!(QSolveError::Parse.exit_code(), 3);
+    /// assert_eq!(QSolveError::Unsolvable.exit_code(), 6);
+    /// ```
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            QSolveError::Parse => 3,
+            QSolveError::InvalidBoard => 4,
+            QSolveError::ImageDetection => 5,
+            QSolveError::Unsolvable => 6,
+            QSolveError::InvalidChanges => 7,
+        }
+    }
+
+    /// Finds the [QSolveError] attached to the given error, if any.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::error::QSolveError;
+    /// # use qsolve::file::QueensFile;
+    /// # use std::str::FromStr;
+    /// let err = QueensFile::from_str("wwww\nwzzz\nrrrr\nbbbb").unwrap_err();
+    /// assert_eq!(QSolveError::from_error(&err), Some(QSolveError::Parse));
+    /// ```
+    pub fn from_error(err: &anyhow::Error) -> Option<QSolveError> {
+        err.downcast_ref::<QSolveError>().copied()
+    }
+
+    /// Returns the process exit code for the given error.
+    ///
+    /// This is [QSolveError::exit_code] if a [QSolveError] is attached to the error,
+    /// and [QSolveError::GENERIC_EXIT_CODE] otherwise.
+    ///
+    /// # Examples
+    /// ```
+    /// # use anyhow::anyhow;
+    /// # use qsolve::error::QSolveError;
+    /// assert_eq!(QSolveError::exit_code_for(&anyhow!(QSolveError::InvalidBoard)), 4);
+    /// assert_eq!(QSolveError::exit_code_for(&anyhow!("Something else")), 1);
+    /// ```
+    pub fn exit_code_for(err: &anyhow::Error) -> u8 {
+        QSolveError::from_error(err).map_or(QSolveError::GENERIC_EXIT_CODE, |e| e.exit_code())
+    }
+}
+
+impl Display for QSolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match self {
+            QSolveError::Parse => "Could not parse the puzzle",
+            QSolveError::InvalidBoard => "The puzzle is not a valid Queens puzzle",
+            QSolveError::ImageDetection => "Could not detect a Queens grid in the image",
+            QSolveError::Unsolvable => "Could not solve the puzzle",
+            QSolveError::InvalidChanges => "A step made changes that are not allowed",
+        };
+        write!(f, "{description}")
+    }
+}
+
+impl std::error::Error for QSolveError {}
+
+impl From<QSolveError> for ExitCode {
+    fn from(err: QSolveError) -> Self {
+        ExitCode::from(err.exit_code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, str::FromStr};
+
+    use anyhow::{Context, anyhow};
+
+    use super::*;
+    use crate::file::QueensFile;
+
+    #[test]
+    fn exit_codes_are_distinct() {
+        let errors = [
+            QSolveError::Parse,
+            QSolveError::InvalidBoard,
+            QSolveError::ImageDetection,
+            QSolveError::Unsolvable,
+            QSolveError::InvalidChanges,
+        ];
+        for (i, a) in errors.iter().enumerate() {
+            assert!(a.exit_code() > 2);
+            for b in &errors[i + 1..] {
+                assert_ne!(a.exit_code(), b.exit_code());
+            }
+        }
+    }
+
+    #[test]
+    fn from_error_sees_through_context() {
+        let err = anyhow!(QSolveError::Unsolvable)
+            .context("Outer context")
+            .context("Outermost context");
+        assert_eq!(QSolveError::from_error(&err), Some(QSolveError::Unsolvable));
+        assert_eq!(QSolveError::from_error(&anyhow!("Untyped")), None);
+    }
+
+    #[test]
+    fn from_error_on_files() {
+        let parse_err = QueensFile::from_str("wwww\nwkkk\nrrrr").unwrap_err();
+        assert_eq!(
+            QSolveError::from_error(&parse_err),
+            Some(QSolveError::Parse)
+        );
+
+        let invalid_err = QueensFile::from_str("wwww\nwkkk\nrrrr\nrrrr")
+            .unwrap()
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            QSolveError::from_error(&invalid_err),
+            Some(QSolveError::InvalidBoard)
+        );
+
+        let missing_err = QueensFile::try_from_text_file(&PathBuf::from("games/missing.txt"))
+            .context("Reading a missing file")
+            .unwrap_err();
+        assert_eq!(QSolveError::from_error(&missing_err), None);
+    }
+}
@@ -0,0 +1,227 @@
+use std::{path::Path, str::FromStr, time::Duration};
+
+use anyhow::{Context, Result, anyhow};
+use toml::{Table, Value};
+
+use crate::solution::Solution;
+
+/// Calibration constants for estimating how long a human would take to
+/// solve a puzzle the way the solver did.
+///
+/// Each step of the solve costs a fixed amount of time, plus time to scan
+/// each line of the board (so steps on bigger boards take longer), plus
+/// extra time depending on the tier of the heuristic used (see
+/// [heuristic_tier]).
+///
+/// The constants can be adjusted with a TOML file, whose keys are the names
+/// of the fields; any key that is missing keeps its default value.
+///
+/// # Examples
+/// ```
+/// # use qsolve::estimate::TimeModel;
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let time_model = TimeModel::from_str("step_secs = 2.0\ntier_secs = [0.0, 5.0, 10.0]")?;
+/// assert_eq!(time_model.step_secs, 2.0);
+/// assert_eq!(time_model.line_secs, TimeModel::default().line_secs);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeModel {
+    /// Seconds that every step takes, regardless of the board.
+    pub step_secs: f64,
+
+    /// Seconds that every step takes for each line of the board.
+    pub line_secs: f64,
+
+    /// Extra seconds that a step takes for each tier of heuristic.
+    pub tier_secs: [f64; 3],
+}
+
+impl Default for TimeModel {
+    fn default() -> Self {
+        TimeModel {
+            step_secs: 4.0,
+            line_secs: 0.5,
+            tier_secs: [0.0, 6.0, 15.0],
+        }
+    }
+}
+
+impl TimeModel {
+    /// Reads the constants from the given TOML file.
+    pub fn try_from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read time model {path:?}"))?;
+        TimeModel::from_str(&content).with_context(|| format!("Invalid time model {path:?}"))
+    }
+
+    /// Returns how long a human would be expected to take to make the
+    /// steps of the given solution.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::estimate::TimeModel;
+    /// # use qsolve::heuristic::all_heuristics;
+    /// # use qsolve::solution::Solution;
+    /// # use qsolve::solvestate::{SolveState, SolveStrategy};
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+    /// let heuristics = all_heuristics(&board);
+    /// let solution = Solution::solve(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+    /// assert!(TimeModel::default().estimate(&solution).as_secs() > 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn estimate(&self, solution: &Solution) -> Duration {
+        let lines = solution.final_state.board.size() as f64;
+        let secs = solution
+            .steps
+            .iter()
+            .filter_map(|item| item.next_heuristic)
+            .map(|h| {
+                // Advanced heuristics take as long as the hardest tier.
+                let tier = heuristic_tier(h.name()).min(self.tier_secs.len() - 1);
+                self.step_secs + self.line_secs * lines + self.tier_secs[tier]
+            })
+            .sum::<f64>();
+        Duration::from_secs_f64(secs.max(0.0))
+    }
+}
+
+impl FromStr for TimeModel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let table = s.parse::<Table>()?;
+        let secs = |value: &Value, key: &str| {
+            value
+                .as_float()
+                .or_else(|| value.as_integer().map(|secs| secs as f64))
+                .ok_or_else(|| anyhow!("{key} must be a number of seconds"))
+        };
+        let mut time_model = TimeModel::default();
+        for (key, value) in &table {
+            match key.as_str() {
+                "step_secs" => time_model.step_secs = secs(value, key)?,
+                "line_secs" => time_model.line_secs = secs(value, key)?,
+                "tier_secs" => {
+                    let tiers = value
+                        .as_array()
+                        .filter(|tiers| tiers.len() == time_model.tier_secs.len())
+                        .ok_or_else(|| {
+                            anyhow!(
+                                "tier_secs must be an array of {} numbers",
+                                time_model.tier_secs.len()
+                            )
+                        })?;
+                    for (tier_secs, value) in time_model.tier_secs.iter_mut().zip(tiers) {
+                        *tier_secs = secs(value, key)?;
+                    }
+                }
+                _ => return Err(anyhow!("Unknown time model key {key}")),
+            }
+        }
+        Ok(time_model)
+    }
+}
+
+/// The tier of the [advanced_heuristics][crate::heuristic::advanced_heuristics],
+/// which assume that the puzzle has exactly one solution.
+pub const ADVANCED_TIER: usize = 3;
+
+/// Returns how hard a human finds the heuristic with the given
+/// [name][crate::heuristic::Heuristic::name], from 0 (spotting the last
+/// square left in a line or color, or reading a clue) to 2 (reasoning about several lines or
+/// colors at once), or [ADVANCED_TIER] for heuristics that assume the
+/// puzzle has exactly one solution.
+pub fn heuristic_tier(name: &str) -> usize {
+    match name {
+        "LastSquareAvailable" | "ClueRulesOutSquares" => 0,
+        "AllPossibilitiesEliminateSquare" => 1,
+        "SymmetryFixesQueen" | "SymmetryMirrorsSquares" => ADVANCED_TIER,
+        _ => 2,
+    }
+}
+
+/// Formats the given duration as minutes and seconds, like `2:30`.
+///
+/// # Examples
+/// ```
+/// # use qsolve::estimate::format_minutes;
+/// # use std::time::Duration;
+/// assert_eq!(format_minutes(Duration::from_secs(150)), "2:30");
+/// assert_eq!(format_minutes(Duration::from_secs(5)), "0:05");
+/// ```
+pub fn format_minutes(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        file::QueensFile,
+        heuristic::all_heuristics,
+        solvestate::{SolveState, SolveStrategy},
+    };
+
+    use super::*;
+
+    #[test]
+    fn time_model_parses() -> Result<()> {
+        let time_model = TimeModel::from_str("line_secs = 1\ntier_secs = [1, 2.5, 3]")?;
+        assert_eq!(
+            time_model,
+            TimeModel {
+                line_secs: 1.0,
+                tier_secs: [1.0, 2.5, 3.0],
+                ..TimeModel::default()
+            }
+        );
+        assert_eq!(TimeModel::from_str("")?, TimeModel::default());
+        assert!(TimeModel::from_str("speed = 2").is_err());
+        assert!(TimeModel::from_str("step_secs = \"slow\"").is_err());
+        assert!(TimeModel::from_str("tier_secs = [1, 2]").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn estimate_counts_steps_lines_and_tiers() -> Result<()> {
+        let queens_file = QueensFile::try_from_text_file(&"games/linkedin-1-empty.txt".into())?;
+        let heuristics = all_heuristics(&queens_file.board);
+        let solution = Solution::solve(
+            SolveState::from(&queens_file),
+            SolveStrategy::Fast,
+            &heuristics,
+        );
+        let steps = solution.step_count() as f64;
+        let only_steps = TimeModel {
+            step_secs: 2.0,
+            line_secs: 0.0,
+            tier_secs: [0.0; 3],
+        };
+        assert_eq!(only_steps.estimate(&solution).as_secs_f64(), 2.0 * steps);
+        let only_lines = TimeModel {
+            step_secs: 0.0,
+            line_secs: 1.0,
+            tier_secs: [0.0; 3],
+        };
+        assert_eq!(
+            only_lines.estimate(&solution).as_secs_f64(),
+            steps * queens_file.board.size() as f64
+        );
+        let only_tiers = TimeModel {
+            step_secs: 0.0,
+            line_secs: 0.0,
+            tier_secs: [1.0; 3],
+        };
+        assert_eq!(only_tiers.estimate(&solution).as_secs_f64(), steps);
+        Ok(())
+    }
+}
@@ -1,12 +1,22 @@
-use std::{fmt::Display, str::FromStr};
+use std::{
+    fmt::Display,
+    io::{BufRead, Cursor, Read, Seek},
+    str::FromStr,
+};
 
-use anyhow::{Context, Result, ensure};
-use image::ImageReader;
+use anyhow::{Context, Result, anyhow, bail, ensure};
+use image::{DynamicImage, ImageDecoder, ImageReader, metadata::Orientation};
+use itertools::Itertools;
+use log::info;
 
 use crate::{
-    board::Board,
-    image::analyze_grid_image,
-    solvestate::{Charset, SquareVal},
+    binary,
+    board::{Board, RuleSet},
+    clue::Clues,
+    error::QSolveError,
+    image::analyze_grid_image_any_orientation,
+    repair::{RegionRepair, SplitRegion},
+    solvestate::{Charset, SolveState, SquareVal},
 };
 
 /// This represents a solve state as part of an input file.
@@ -85,9 +95,74 @@ pub struct QueensFile {
     /// But if the input file has a partial solution as well, this will
     /// instead be Some(s) where s is that partial solution.
     pub squares: Option<InputSquares>,
+
+    /// The rotation that had to be applied to the image the file was read
+    /// from to find its grid, or None if the grid was found as given (or
+    /// the file wasn't read from an image).
+    pub rotation: Option<Orientation>,
 }
 
 impl QueensFile {
+    /// Returns a [QueensFileBuilder] for assembling a QueensFile programmatically.
+    pub fn builder() -> QueensFileBuilder {
+        QueensFileBuilder::default()
+    }
+
+    /// Returns a [QueensFileBuilder] initialized with the contents of this file.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::file::QueensFile;
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let file = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\nxQxx\n....\n....\n....")?;
+    /// let cleared = file.into_builder().clear_squares().build()?;
+    /// assert!(cleared.squares.is_none());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_builder(self) -> QueensFileBuilder {
+        QueensFileBuilder {
+            board: Some(self.board),
+            squares: self.squares,
+            rotation: self.rotation,
+        }
+    }
+
+    /// Writes the file in the text format, as read by
+    /// [from_str][QueensFile::from_str]: any clues, the board, and then any
+    /// markings, with `.` for blank squares.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::file::QueensFile;
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let text = "rows: . 0 . .\n\nwwww\nwkkk\nrrrr\nbbbb\n\nxQxx\n....\n....\n....";
+    /// assert_eq!(QueensFile::from_str(text)?.to_text(), text);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        if !self.board.clues().is_empty() {
+            text.push_str(&format!("{}\n\n", self.board.clues()));
+        }
+        text.push_str(&self.board.to_string());
+        if let Some(squares) = &self.squares {
+            text.push_str("\n\n");
+            let rows = squares.0.chunks(self.board.size()).map(|row| {
+                row.iter()
+                    .map(|&square| SquareVal::as_char(square, true, &Charset::Ascii))
+                    .collect::<String>()
+            });
+            text.push_str(&rows.collect::<Vec<_>>().join("\n"));
+        }
+        text
+    }
+
     /// This reads the given path as a text file and attempts to return
     /// a QueensFile from it.
     pub fn try_from_text_file(path: &std::path::PathBuf) -> Result<Self> {
@@ -100,19 +175,352 @@ impl QueensFile {
 
     /// This reads the given path as an image file and attempts to return
     /// a QueensFile from it.
+    ///
+    /// Screenshots are frequently rotated, either via their EXIF metadata or
+    /// by having been rotated outright. The EXIF orientation is always applied,
+    /// and if no grid can be found, rotated versions of the image are tried
+    /// as well; the rotation that found the grid is kept in
+    /// [QueensFile::rotation].
     pub fn try_from_image_file(path: &std::path::PathBuf) -> Result<Self> {
-        let rgb_image = ImageReader::open(path)?.decode()?.to_rgb8();
-
-        analyze_grid_image(&rgb_image)
+        QueensFile::try_from_decoded_image(ImageReader::open(path)?)
             .with_context(|| format!("Failed to create board from image at {path:?}"))
     }
+
+    /// Reads a board and its markings from the compact bytes written by
+    /// [SolveState::to_bytes][crate::solvestate::SolveState::to_bytes], as
+    /// described in [crate::binary].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        binary::from_bytes(bytes)
+    }
+
+    /// This reads the given bytes, such as a downloaded file, as text and
+    /// attempts to return a QueensFile from them.
+    pub fn try_from_text_bytes(bytes: &[u8]) -> Result<Self> {
+        let content = std::str::from_utf8(bytes).context("Contents are not UTF-8 text")?;
+        QueensFile::from_str(content)
+    }
+
+    /// This reads the given bytes, such as a downloaded file, as an image in
+    /// any supported format and attempts to return a QueensFile from them,
+    /// like [QueensFile::try_from_image_file].
+    pub fn try_from_image_bytes(bytes: &[u8]) -> Result<Self> {
+        QueensFile::try_from_image_reader(bytes)
+    }
+
+    /// This reads an image in any supported format from the given reader,
+    /// such as standard input piped from a screenshot tool, and attempts to
+    /// return a QueensFile from it, like [QueensFile::try_from_image_file].
+    ///
+    /// Since there's no file name to go by, the format is guessed from the
+    /// image's first bytes.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::file::QueensFile;
+    /// # use std::fs::File;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let queens_file = QueensFile::try_from_image_reader(File::open("games/linkedin-1.png")?)?;
+    /// assert_eq!(queens_file.board.size(), 8);
+    ///
+    /// let err = QueensFile::try_from_image_reader("wwww\nwkkk".as_bytes()).unwrap_err();
+    /// assert_eq!(err.to_string(), "Could not tell the image's format from its first bytes");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn try_from_image_reader(mut reader: impl Read) -> Result<Self> {
+        let mut bytes = vec![];
+        reader
+            .read_to_end(&mut bytes)
+            .context("Could not read image")?;
+        let format = image::guess_format(&bytes)
+            .context("Could not tell the image's format from its first bytes")?;
+        QueensFile::try_from_decoded_image(ImageReader::with_format(Cursor::new(bytes), format))
+    }
+
+    fn try_from_decoded_image(reader: ImageReader<impl BufRead + Seek>) -> Result<Self> {
+        let mut decoder = reader.into_decoder()?;
+        let exif_orientation = decoder.orientation()?;
+        let mut dynamic_image = DynamicImage::from_decoder(decoder)?;
+        dynamic_image.apply_orientation(exif_orientation);
+        if exif_orientation != Orientation::NoTransforms {
+            info!("Applied EXIF orientation {exif_orientation:?} to image");
+        }
+        let rgb_image = dynamic_image.to_rgb8();
+
+        let (mut queens_file, orientation) = analyze_grid_image_any_orientation(&rgb_image)?;
+        if orientation != Orientation::NoTransforms {
+            info!("Found grid in image after applying {orientation:?}");
+            queens_file.rotation = Some(orientation);
+        }
+        Ok(queens_file)
+    }
+
+    /// Merges the markings from another file into this one.
+    ///
+    /// This is useful when the clean board and the in-progress markings come from
+    /// different sources (for example, two screenshots). The board from `self` is
+    /// kept, and the squares are taken from `progress`. This fails if the two files
+    /// do not have the same board, or if `progress` has no markings.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::file::QueensFile;
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+    /// let progress = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\nxQxx\n....\n....\n....")?;
+    /// let merged = board.with_progress(progress)?;
+    /// assert!(merged.squares.is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_progress(self, progress: QueensFile) -> Result<Self> {
+        self.ensure_same_board(&progress)
+            .context(QSolveError::InvalidBoard)?;
+        let Some(squares) = progress.squares else {
+            bail!("Progress file does not contain any markings");
+        };
+        Ok(QueensFile {
+            board: self.board,
+            squares: Some(squares),
+            rotation: self.rotation,
+        })
+    }
+
+    /// Checks that this file describes a valid Queens puzzle.
+    ///
+    /// Parsing only checks that the board is square and uses known colors; this
+    /// additionally requires that the board has exactly as many colors as rows,
+    /// and that the markings (if any) do not already break the rules. Failures
+    /// carry [QSolveError::InvalidBoard].
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::file::QueensFile;
+    /// # use std::str::FromStr;
+    /// assert!(QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb").unwrap().validate().is_ok());
+    /// assert!(QueensFile::from_str("wwww\nwkkk\nrrrr\nrrrr").unwrap().validate().is_err());
+    /// assert!(QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\nQQ..\n....\n....\n....").unwrap().validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        let color_count = self.board.all_colors().len();
+        ensure!(
+            color_count == self.board.size(),
+            anyhow!(
+                "Board has {color_count} colors but {} rows",
+                self.board.size()
+            )
+            .context(QSolveError::InvalidBoard)
+        );
+        ensure!(
+            SolveState::from(self).is_valid(),
+            anyhow!("Markings break the rules of the puzzle").context(QSolveError::InvalidBoard)
+        );
+        Ok(())
+    }
+
+    /// Checks the file like [QueensFile::validate], and also requires every
+    /// color to form one connected region, which real puzzles always do.
+    ///
+    /// A split color usually means squares were misread from an image, so
+    /// the error includes the [RegionRepair] that would fix it, if there is
+    /// one. Failures carry [QSolveError::InvalidBoard].
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::file::QueensFile;
+    /// # use std::str::FromStr;
+    /// assert!(QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb").unwrap().validate_strict().is_ok());
+    /// let split = QueensFile::from_str("wwww\nwkkk\nrrbr\nbbbb").unwrap();
+    /// assert!(split.validate().is_ok());
+    /// assert!(split.validate_strict().is_err());
+    /// ```
+    pub fn validate_strict(&self) -> Result<()> {
+        self.validate()?;
+        let split = SplitRegion::find(&self.board);
+        if split.is_empty() {
+            return Ok(());
+        }
+        let mut message = split.iter().join("; ");
+        if let Some(repair) = RegionRepair::suggest(&self.board)
+            && !repair.reassignments.is_empty()
+        {
+            message.push_str(&format!("; the likely repair is {repair}"));
+        }
+        Err(anyhow!(message).context(QSolveError::InvalidBoard))
+    }
+
+    /// Repairs any colors that are split into pieces, as suggested by
+    /// [RegionRepair], returning the repaired file and the repair that was
+    /// made (or None if no color was split).
+    ///
+    /// This fails with [QSolveError::InvalidBoard] if some piece can't be
+    /// repaired.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::file::QueensFile;
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let misread = QueensFile::from_str("wwww\nwkkk\nrrRr\nRRRR")?;
+    /// let (repaired, repair) = misread.repair_regions()?;
+    /// assert_eq!(repaired.board.to_string(), "wwww\nwkkk\nrrRR\nRRRR");
+    /// assert_eq!(repair.unwrap().reassignments.len(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn repair_regions(self) -> Result<(Self, Option<RegionRepair>)> {
+        let Some(repair) = RegionRepair::suggest(&self.board) else {
+            return Ok((self, None));
+        };
+        ensure!(
+            repair.is_complete(),
+            anyhow!("Could not repair split regions: {repair}").context(QSolveError::InvalidBoard)
+        );
+        let board = repair.apply(&self.board);
+        Ok((
+            QueensFile {
+                board,
+                squares: self.squares,
+                rotation: self.rotation,
+            },
+            Some(repair),
+        ))
+    }
+
+    /// Ensures that the other file has the same board as this one.
+    fn ensure_same_board(&self, progress: &QueensFile) -> Result<()> {
+        ensure!(
+            self.board.size() == progress.board.size(),
+            "Progress board has size {} but the board has size {}",
+            progress.board.size(),
+            self.board.size()
+        );
+        let mismatched = self
+            .board
+            .all_coords()
+            .iter()
+            .filter(|coord| self.board.color(coord) != progress.board.color(coord))
+            .count();
+        ensure!(
+            mismatched == 0,
+            "Progress board does not match the board: {mismatched} squares have different colors"
+        );
+        Ok(())
+    }
+}
+
+/// A builder for [QueensFile], for callers that assemble a puzzle programmatically
+/// rather than parsing one.
+///
+/// Nothing is checked until [QueensFileBuilder::build], which requires a board,
+/// requires the squares (if any) to match the board's size, and then runs
+/// [QueensFile::validate].
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Board;
+/// # use qsolve::file::{InputSquares, QueensFile};
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let file = QueensFile::builder()
+///     .board(Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?)
+///     .squares(InputSquares::from_str("xQxx\n....\n....\n....")?)
+///     .build()?;
+/// assert!(file.squares.is_some());
+///
+/// assert!(QueensFile::builder().build().is_err());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct QueensFileBuilder {
+    board: Option<Board>,
+    squares: Option<InputSquares>,
+    rotation: Option<Orientation>,
+}
+
+impl QueensFileBuilder {
+    /// Sets the board for the file.
+    pub fn board(mut self, board: Board) -> Self {
+        self.board = Some(board);
+        self
+    }
+
+    /// Sets the partial solution for the file.
+    pub fn squares(mut self, squares: InputSquares) -> Self {
+        self.squares = Some(squares);
+        self
+    }
+
+    /// Sets the [RuleSet] the board is played under.
+    pub fn rules(mut self, rules: RuleSet) -> Self {
+        self.board = self.board.map(|board| board.with_rules(rules));
+        self
+    }
+
+    /// Removes any partial solution, leaving an empty board.
+    pub fn clear_squares(mut self) -> Self {
+        self.squares = None;
+        self
+    }
+
+    /// Builds and validates the [QueensFile].
+    ///
+    /// Failures other than a missing board carry [QSolveError::InvalidBoard].
+    pub fn build(self) -> Result<QueensFile> {
+        let Some(board) = self.board else {
+            bail!("No board was provided");
+        };
+        if let Some(squares) = &self.squares {
+            ensure!(
+                squares.0.len() == board.square_count(),
+                anyhow!(
+                    "Squares have {} entries but the board has {}",
+                    squares.0.len(),
+                    board.square_count()
+                )
+                .context(QSolveError::InvalidBoard)
+            );
+        }
+        let queens_file = QueensFile {
+            board,
+            squares: self.squares,
+            rotation: self.rotation,
+        };
+        queens_file.validate()?;
+        Ok(queens_file)
+    }
 }
 
 impl FromStr for QueensFile {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        let lines = s.trim().lines().collect::<Vec<_>>();
+        QueensFile::parse(s).context(QSolveError::Parse)
+    }
+}
+
+impl QueensFile {
+    /// Parses the contents of a text file, without attaching a [QSolveError].
+    fn parse(s: &str) -> Result<Self> {
+        let mut lines = s.trim().lines().collect::<Vec<_>>();
+        let header = if lines.first().is_some_and(|line| Clues::is_header(line)) {
+            let end = lines
+                .iter()
+                .position(|line| line.is_empty())
+                .ok_or_else(|| {
+                    anyhow!("Invalid solve state: expected a blank line after the clues.")
+                })?;
+            let header = lines.drain(..=end).collect::<Vec<_>>();
+            header[..end].to_vec()
+        } else {
+            vec![]
+        };
         let lines_len = lines.len();
         ensure!(lines_len != 0, "Invalid solve state: no lines found.");
 
@@ -123,7 +531,8 @@ impl FromStr for QueensFile {
             "Invalid solve state: {lines_len} lines for size {size}."
         );
 
-        let board = Board::from_str(&lines[0..size].join("\n"))?;
+        let board = Board::from_str(&lines[0..size].join("\n"))?
+            .with_clues(Clues::parse_header(&header, size)?);
         let squares = if is_squares_formatted {
             Some(InputSquares::from_str(
                 &lines[(size + 1)..(size * 2 + 1)].join("\n"),
@@ -132,7 +541,11 @@ impl FromStr for QueensFile {
             None
         };
 
-        Ok(QueensFile { board, squares })
+        Ok(QueensFile {
+            board,
+            squares,
+            rotation: None,
+        })
     }
 }
 
@@ -236,4 +649,75 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn queens_file_with_progress() -> Result<()> {
+        let board = QueensFile::from_str("wwww\nkkkk\nrrrr\nbbbb")?;
+        let progress = QueensFile::from_str("wwww\nkkkk\nrrrr\nbbbb\n\nQxxx\nxx..\nx...\nx...")?;
+        let merged = board.with_progress(progress)?;
+        assert_eq!(
+            format!("{}", merged.squares.unwrap()),
+            "Qxxx\nxx  \nx   \nx   "
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn queens_file_with_mismatched_progress() -> Result<()> {
+        let board = QueensFile::from_str("wwww\nkkkk\nrrrr\nbbbb")?;
+        let other_colors =
+            QueensFile::from_str("wwww\nkkkk\nrrrr\nbbbw\n\nQxxx\nxx..\nx...\nx...")?;
+        assert!(board.with_progress(other_colors).is_err());
+
+        let board = QueensFile::from_str("wwww\nkkkk\nrrrr\nbbbb")?;
+        let other_size = QueensFile::from_str("www\nkkk\nrrr")?;
+        assert!(board.with_progress(other_size).is_err());
+
+        let board = QueensFile::from_str("wwww\nkkkk\nrrrr\nbbbb")?;
+        let no_squares = QueensFile::from_str("wwww\nkkkk\nrrrr\nbbbb")?;
+        assert!(board.with_progress(no_squares).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn queens_file_builder() -> Result<()> {
+        let file = QueensFile::builder()
+            .board(Board::from_str("wwww\nkkkk\nrrrr\nbbbb")?)
+            .squares(InputSquares::from_str("Qxxx\nxx..\nx...\nx...")?)
+            .build()?;
+        assert_eq!(
+            format!("{}", file.squares.unwrap()),
+            "Qxxx\nxx  \nx   \nx   "
+        );
+
+        let file = QueensFile::from_str("wwww\nkkkk\nrrrr\nbbbb\n\nQxxx\nxx..\nx...\nx...")?;
+        let cleared = file.into_builder().clear_squares().build()?;
+        assert!(cleared.squares.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn queens_file_builder_invalid() -> Result<()> {
+        assert!(QueensFile::builder().build().is_err());
+
+        let wrong_size = QueensFile::builder()
+            .board(Board::from_str("wwww\nkkkk\nrrrr\nbbbb")?)
+            .squares(InputSquares::from_str("Qxx\nxx.\nx..")?)
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            QSolveError::from_error(&wrong_size),
+            Some(QSolveError::InvalidBoard)
+        );
+
+        let invalid = QueensFile::builder()
+            .board(Board::from_str("wwww\nkkkk\nrrrr\nrrrr")?)
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            QSolveError::from_error(&invalid),
+            Some(QSolveError::InvalidBoard)
+        );
+        Ok(())
+    }
 }
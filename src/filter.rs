@@ -0,0 +1,150 @@
+use std::fmt::{Debug, Formatter};
+
+use crate::{heuristic::Changes, solvestate::SolveState};
+
+/// A callback that can veto or trim the changes a heuristic proposes, before
+/// the solver applies them.
+///
+/// The callback is given the state the changes would be applied to, and
+/// returns the changes to apply instead, which should be some of the ones
+/// proposed. Returning None (or changes that mark nothing) vetoes the
+/// heuristic, and the solver looks for another one; if every heuristic is
+/// vetoed, the solver stalls.
+///
+/// It is chosen with [SolveOptions::change_filter][crate::solvestate::SolveOptions::change_filter],
+/// and the changes it returns are the ones recorded in each
+/// [SolveIterItem][crate::solveiter::SolveIterItem].
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Board;
+/// # use qsolve::filter::ChangeFilter;
+/// # use qsolve::heuristic::{all_heuristics, Changes};
+/// # use qsolve::solveiter::solve_iter_with_options;
+/// # use qsolve::solvestate::{SolveOptions, SolveState};
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+/// let heuristics = all_heuristics(&board);
+/// // X out at most two squares at a time.
+/// let two_xs = ChangeFilter(|_solve_state, changes| match changes {
+///     Changes::AddQueen { .. } => Some(changes),
+///     Changes::AddX { x } => Some(Changes::AddX { x: x.iter().take(2).collect() }),
+/// });
+/// let solve_options = SolveOptions {
+///     change_filter: Some(two_xs),
+///     ..SolveOptions::default()
+/// };
+/// let items = solve_iter_with_options(SolveState::from(&board), solve_options, &heuristics)
+///     .collect::<Vec<_>>();
+/// assert!(items.last().unwrap().solve_state.complete());
+/// assert!(items.iter().all(|item| match &item.changes {
+///     Some(Changes::AddX { x }) => x.len() <= 2,
+///     _ => true,
+/// }));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy)]
+pub struct ChangeFilter(pub fn(&SolveState, Changes) -> Option<Changes>);
+
+impl ChangeFilter {
+    /// Trims every step that x's out squares to the first of them, for very
+    /// granular animations. Steps that place a Queen are left alone, since
+    /// the solver relies on the squares a Queen rules out being x'd out.
+    pub const ONE_SQUARE: ChangeFilter = ChangeFilter(|_solve_state, changes| match changes {
+        Changes::AddQueen { .. } => Some(changes),
+        Changes::AddX { x } => Some(Changes::AddX {
+            x: x.iter().take(1).collect(),
+        }),
+    });
+
+    /// Returns the changes to apply in place of the given ones, or None if
+    /// they are vetoed.
+    pub fn apply(&self, solve_state: &SolveState, changes: Changes) -> Option<Changes> {
+        (self.0)(solve_state, changes).filter(|changes| !changes.changed_coords().is_empty())
+    }
+}
+
+impl Debug for ChangeFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ChangeFilter({:p})", self.0)
+    }
+}
+
+impl PartialEq for ChangeFilter {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::fn_addr_eq(self.0, other.0)
+    }
+}
+
+impl Eq for ChangeFilter {}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use anyhow::Result;
+
+    use crate::{
+        board::Board,
+        heuristic::all_heuristics,
+        solveiter::solve_iter_with_options,
+        solvestate::{SolveOptions, SolveStrategy},
+    };
+
+    use super::*;
+
+    #[test]
+    fn one_square_filter_marks_a_square_per_step() -> Result<()> {
+        let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        let heuristics = all_heuristics(&board);
+        for strategy in [
+            SolveStrategy::Fast,
+            SolveStrategy::Short,
+            SolveStrategy::Simple,
+            SolveStrategy::Human,
+        ] {
+            let solve_options = SolveOptions {
+                strategy,
+                change_filter: Some(ChangeFilter::ONE_SQUARE),
+                checked: true,
+                ..SolveOptions::default()
+            };
+            let items =
+                solve_iter_with_options(SolveState::from(&board), solve_options, &heuristics)
+                    .collect::<Vec<_>>();
+            let last = items.last().unwrap();
+            assert!(last.solve_state.complete(), "{strategy:?}");
+            assert!(last.solve_state.is_valid(), "{strategy:?}");
+            for item in &items {
+                if let Some(Changes::AddX { x }) = &item.changes {
+                    assert_eq!(x.len(), 1, "{strategy:?}");
+                }
+            }
+            let unfiltered = solve_iter_with_options(
+                SolveState::from(&board),
+                SolveOptions::from(strategy),
+                &heuristics,
+            );
+            assert!(items.len() > unfiltered.count(), "{strategy:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn vetoing_every_change_stalls() -> Result<()> {
+        let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        let heuristics = all_heuristics(&board);
+        let solve_options = SolveOptions {
+            change_filter: Some(ChangeFilter(|_, _| None)),
+            ..SolveOptions::default()
+        };
+        let mut solve_iter =
+            solve_iter_with_options(SolveState::from(&board), solve_options, &heuristics);
+        assert!(solve_iter.next().is_none());
+        assert!(solve_iter.stalled());
+        Ok(())
+    }
+}
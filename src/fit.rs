@@ -0,0 +1,217 @@
+use itertools::Itertools;
+
+use crate::datastructure::Coord;
+
+/// Returns how many columns the given line takes up on a terminal, skipping
+/// the ANSI escape sequences that color it.
+///
+/// # Examples
+/// ```
+/// # use qsolve::fit::visible_width;
+/// # use owo_colors::OwoColorize;
+/// assert_eq!(visible_width("abc"), 3);
+/// assert_eq!(visible_width(&format!("{}{}", 'Q'.red(), 'x'.on_blue())), 2);
+/// ```
+pub fn visible_width(line: &str) -> usize {
+    visible_chunks(line, usize::MAX)
+        .next()
+        .map_or(0, |(_, w)| w)
+}
+
+/// Splits a line into pieces of at most `width` visible columns, returning
+/// each piece along with its visible width. Escape sequences stay with the
+/// character after them, so every piece keeps its own colors.
+fn visible_chunks(line: &str, width: usize) -> impl Iterator<Item = (&str, usize)> {
+    let width = width.max(1);
+    let mut rest = line;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let mut visible = 0;
+        let mut chars = rest.char_indices();
+        let mut end = rest.len();
+        while let Some((i, c)) = chars.next() {
+            if c == '\x1b' {
+                if visible == width {
+                    end = i;
+                    break;
+                }
+                // Skip to the letter that ends the escape sequence.
+                for (_, c) in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+                continue;
+            }
+            if visible == width {
+                end = i;
+                break;
+            }
+            visible += 1;
+        }
+        // Keep the resets that close off the last character in this piece.
+        while rest[end..].starts_with("\x1b[0m") {
+            end += "\x1b[0m".len();
+        }
+        let (chunk, tail) = rest.split_at(end);
+        rest = tail;
+        Some((chunk, visible))
+    })
+}
+
+/// Fits a rendered board, such as from
+/// [SolveState::ansi_string][crate::solvestate::SolveState::ansi_string],
+/// into a terminal the given number of columns wide.
+///
+/// Boards that already fit are returned unchanged. Wider boards are split
+/// into bands of at most `width` columns, drawn one under another with a
+/// blank line between them, rather than letting the terminal wrap each row
+/// into the next. [square_position] gives where each square ends up.
+///
+/// # Examples
+/// ```
+/// # use qsolve::fit::fit_to_width;
+/// assert_eq!(fit_to_width("abcd\nefgh", 4), "abcd\nefgh");
+/// assert_eq!(fit_to_width("abcd\nefgh", 3), "abc\nefg\n\nd\nh");
+/// ```
+pub fn fit_to_width(ansi: &str, width: usize) -> String {
+    if ansi.lines().all(|line| visible_width(line) <= width) {
+        return ansi.to_string();
+    }
+    let rows = ansi
+        .lines()
+        .map(|line| {
+            visible_chunks(line, width)
+                .map(|(chunk, _)| chunk)
+                .collect_vec()
+        })
+        .collect_vec();
+    let bands = rows.iter().map(Vec::len).max().unwrap_or(0);
+    (0..bands)
+        .map(|band| {
+            rows.iter()
+                .map(|row| row.get(band).copied().unwrap_or(""))
+                .join("\n")
+        })
+        .join("\n\n")
+}
+
+/// Returns the line and column that the given square of a board of the
+/// given size is drawn at, once [fit_to_width] fits it into the given width.
+///
+/// # Examples
+/// ```
+/// # use qsolve::fit::square_position;
+/// assert_eq!(square_position((1, 3), 4, 80), (1, 3));
+/// // With 3 columns, the last column moves to a second band, after the
+/// // 4 rows of the first band and a blank line.
+/// assert_eq!(square_position((1, 3), 4, 3), (6, 0));
+/// ```
+pub fn square_position((row, col): Coord, size: usize, width: usize) -> Coord {
+    let width = width.max(1);
+    if size <= width {
+        return (row, col);
+    }
+    ((col / width) * (size + 1) + row, col % width)
+}
+
+/// Draws two rendered boards next to each other, such as a board before and
+/// after a hint, with the given arrow between them on every line, so that
+/// both can be seen at once.
+///
+/// Returns `None` if the two don't fit side by side in a terminal the given
+/// number of columns wide.
+///
+/// # Examples
+/// ```
+/// # use qsolve::fit::side_by_side;
+/// assert_eq!(
+///     side_by_side("ab\ncd", "ef\ngh", "->", 80).as_deref(),
+///     Some("ab -> ef\ncd -> gh")
+/// );
+/// assert_eq!(side_by_side("ab\ncd", "ef\ngh", "->", 4), None);
+/// ```
+pub fn side_by_side(before: &str, after: &str, arrow: &str, width: usize) -> Option<String> {
+    let before_width = before.lines().map(visible_width).max().unwrap_or(0);
+    let after_width = after.lines().map(visible_width).max().unwrap_or(0);
+    if before_width + visible_width(arrow) + 2 + after_width > width {
+        return None;
+    }
+    let before = before.lines().collect_vec();
+    let after = after.lines().collect_vec();
+    let lines = (0..before.len().max(after.len()))
+        .map(|i| {
+            let left = before.get(i).copied().unwrap_or("");
+            let padding = " ".repeat(before_width - visible_width(left));
+            let right = after.get(i).copied().unwrap_or("");
+            format!("{left}{padding} {arrow} {right}")
+        })
+        .join("\n");
+    Some(lines)
+}
+
+/// Returns how many lines of a terminal the given text takes up once lines
+/// wider than the given width wrap, so that it can be erased again.
+///
+/// # Examples
+/// ```
+/// # use qsolve::fit::wrapped_lines;
+/// assert_eq!(wrapped_lines("short\nlines", 80), 2);
+/// assert_eq!(wrapped_lines("a line of twenty-six chars", 10), 3);
+/// assert_eq!(wrapped_lines("", 10), 0);
+/// ```
+pub fn wrapped_lines(text: &str, width: usize) -> usize {
+    text.lines()
+        .map(|line| visible_width(line).div_ceil(width.max(1)).max(1))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use anyhow::Result;
+    use regex::Regex;
+
+    use super::*;
+    use crate::{
+        board::Board,
+        datastructure::CoordSet,
+        palette::Palette,
+        solvestate::{Charset, SolveState},
+    };
+
+    #[test]
+    fn fitted_board_keeps_every_square() -> Result<()> {
+        let board = Board::from_str("wwwww\nwkkkk\nrrrrr\nbbbbb\nggggg")?;
+        let solve_state = SolveState::from(&board);
+        let squares = solve_state.ansi_squares_with_highlights(
+            &[(CoordSet::from_iter([(2, 3)]), Default::default())],
+            Charset::Ascii,
+            Palette::default(),
+        )?;
+        let ansi = solve_state.ansi_string(
+            CoordSet::from_iter([(2, 3)]),
+            Charset::Ascii,
+            Palette::default(),
+        )?;
+        let ansi_re = Regex::new("\x1b\\[[0-9;]*m")?;
+        for width in 1..=6 {
+            let fitted = fit_to_width(&ansi, width);
+            let lines = fitted.lines().collect_vec();
+            assert!(lines.iter().all(|line| visible_width(line) <= width));
+            for (r, row) in squares.iter().enumerate() {
+                for (c, square) in row.iter().enumerate() {
+                    let (line, col) = square_position((r, c), board.size(), width);
+                    let (chunk, _) = visible_chunks(lines[line], 1).nth(col).unwrap();
+                    assert_eq!(chunk, square, "square {r},{c} at width {width}");
+                }
+            }
+            let plain = ansi_re.replace_all(&fitted, "");
+            assert_eq!(plain.chars().filter(|c| *c == '.').count(), 1);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,332 @@
+use std::fmt::{Display, Formatter};
+
+use anyhow::{Result, anyhow, ensure};
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+
+use crate::{
+    board::Board,
+    datastructure::{Coord, CoordSet},
+    entropy::Entropy,
+    estimate::heuristic_tier,
+    heuristic::all_heuristics,
+    rng::SeededRng,
+    search::all_solutions,
+    solution::Solution,
+    solvestate::{SolveState, SolveStrategy},
+    squarecolor::{ALL_SQUARE_COLORS, SquareColor},
+};
+
+/// How many random boards [generate_board] tries before giving up.
+const MAX_ATTEMPTS: usize = 1_000;
+
+/// How many boards of the right difficulty [generate_board] picks between.
+const CANDIDATES: usize = 3;
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+/// How hard a puzzle is for a human, judged by the hardest kind of
+/// heuristic needed to solve it (see [heuristic_tier]).
+pub enum Difficulty {
+    /// Every step fills in the last square left in a line or color.
+    Easy,
+    /// Some step needs to rule out a square that every placement in a line
+    /// or color would eliminate.
+    #[default]
+    Medium,
+    /// Some step needs to reason about several lines or colors at once.
+    Hard,
+}
+
+impl Difficulty {
+    /// Returns the difficulty of the given board: the lowest tier of
+    /// heuristics that can solve it on their own, or None if even all of
+    /// them together can't.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::generate::Difficulty;
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+    /// assert_eq!(Difficulty::of(&board), Some(Difficulty::Medium));
+    ///
+    /// // This board has two solutions, so no heuristic can solve it.
+    /// let board = Board::from_str("wwkk\nwwkk\nrrbb\nrrbb")?;
+    /// assert_eq!(Difficulty::of(&board), None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn of(board: &Board) -> Option<Self> {
+        let mut heuristics = all_heuristics(board);
+        heuristics.sort_by_key(|h| heuristic_tier(h.name()));
+        [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard]
+            .into_iter()
+            .find(|difficulty| {
+                let tier_heuristics =
+                    heuristics.partition_point(|h| heuristic_tier(h.name()) <= difficulty.tier());
+                Solution::solve(
+                    SolveState::from(board),
+                    SolveStrategy::Fast,
+                    &heuristics[..tier_heuristics],
+                )
+                .is_solved()
+            })
+    }
+
+    /// The highest [heuristic_tier] that boards of this difficulty need.
+    fn tier(self) -> usize {
+        match self {
+            Difficulty::Easy => 0,
+            Difficulty::Medium => 1,
+            Difficulty::Hard => 2,
+        }
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Medium => write!(f, "medium"),
+            Difficulty::Hard => write!(f, "hard"),
+        }
+    }
+}
+
+/// Generates a random board of the given size and difficulty, which has
+/// exactly one solution and which the solver can solve without search.
+///
+/// Each attempt places Queens at random and grows a color region out from
+/// each of them. While the board has another solution, a square is moved
+/// between regions to rule it out; the board is kept if it then has the
+/// right difficulty. Of the first few boards kept, the one with the most
+/// [Entropy] when empty is returned, since its regions leave the player the
+/// most to work out. The same seed always generates the same board.
+///
+/// # Examples
+/// ```
+/// # use qsolve::generate::{Difficulty, generate_board};
+/// # use qsolve::rng::SeededRng;
+/// # use qsolve::search::all_solutions;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let board = generate_board(6, Difficulty::Easy, &mut SeededRng::new(1))?;
+/// assert_eq!(board.size(), 6);
+/// assert_eq!(all_solutions(&board, 2).len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub fn generate_board(size: usize, difficulty: Difficulty, rng: &mut SeededRng) -> Result<Board> {
+    ensure!(
+        (4..=ALL_SQUARE_COLORS.len()).contains(&size),
+        "Boards must be between 4 and {} squares wide",
+        ALL_SQUARE_COLORS.len()
+    );
+    let mut candidates = vec![];
+    for _ in 0..MAX_ATTEMPTS {
+        if let Some(board) = random_unique_board(size, rng)
+            && Difficulty::of(&board) == Some(difficulty)
+        {
+            candidates.push(board);
+            if candidates.len() == CANDIDATES {
+                break;
+            }
+        }
+    }
+    candidates
+        .into_iter()
+        .max_by(|a, b| {
+            let bits = |board| Entropy::of(&SolveState::from(board)).bits();
+            bits(a).total_cmp(&bits(b))
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "Could not generate a {difficulty} {size}x{size} board in {MAX_ATTEMPTS} attempts"
+            )
+        })
+}
+
+/// Makes one attempt at a random board of the given size with exactly one
+/// solution, or returns None if the random choices led nowhere.
+///
+/// Unlike [generate_board], the board may be too hard for the solver, or so
+/// easy it isn't worth playing, so this suits testing the solver against
+/// [all_solutions] rather than making puzzles.
+///
+/// # Panics
+///
+/// Panics if there are more rows than [SquareColor]s to give each region.
+///
+/// # Examples
+/// ```
+/// # use qsolve::generate::random_unique_board;
+/// # use qsolve::rng::SeededRng;
+/// # use qsolve::search::all_solutions;
+/// let mut rng = SeededRng::new(1);
+/// let board = std::iter::repeat_with(|| random_unique_board(6, &mut rng))
+///     .find_map(|board| board)
+///     .unwrap();
+/// assert_eq!(all_solutions(&board, 2).len(), 1);
+/// ```
+pub fn random_unique_board(size: usize, rng: &mut SeededRng) -> Option<Board> {
+    let queens = random_queens(size, rng)?;
+    let mut regions = grow_regions(size, &queens, rng);
+    // Moves can undo each other, so give up on boards that take too many.
+    let mut moves_left = size * size;
+    loop {
+        let board = board_from_regions(size, &regions);
+        let solutions = all_solutions(&board, 2);
+        let Some(other) = solutions
+            .iter()
+            .find(|solution| solution.iter().any(|(row, col)| queens[row] != col))
+        else {
+            return Some(board);
+        };
+        if moves_left == 0 || !separate(size, &mut regions, &queens, other, rng) {
+            return None;
+        }
+        moves_left -= 1;
+    }
+}
+
+/// Returns the column of a Queen in each row, such that no two share a
+/// column or touch diagonally, or None if the random choices led nowhere.
+fn random_queens(size: usize, rng: &mut SeededRng) -> Option<Vec<usize>> {
+    let mut cols: Vec<usize> = Vec::with_capacity(size);
+    for _ in 0..size {
+        let open = (0..size)
+            .filter(|col| !cols.contains(col))
+            .filter(|col| cols.last().is_none_or(|last| last.abs_diff(*col) > 1))
+            .collect::<Vec<_>>();
+        if open.is_empty() {
+            return None;
+        }
+        cols.push(open[rng.next_below(open.len())]);
+    }
+    Some(cols)
+}
+
+/// Returns the squares next to the given one, not counting diagonals.
+fn neighbors((row, col): Coord, size: usize) -> impl Iterator<Item = Coord> {
+    [
+        (row.wrapping_sub(1), col),
+        (row + 1, col),
+        (row, col.wrapping_sub(1)),
+        (row, col + 1),
+    ]
+    .into_iter()
+    .filter(move |&(r, c)| r < size && c < size)
+}
+
+/// Returns the region of each square, where each Queen gets its own region,
+/// which then spreads one random neighboring square at a time until every
+/// square is in one.
+fn grow_regions(size: usize, queens: &[usize], rng: &mut SeededRng) -> Vec<usize> {
+    let mut regions: Vec<Option<usize>> = vec![None; size * size];
+    for (row, col) in queens.iter().enumerate() {
+        regions[row * size + col] = Some(row);
+    }
+    loop {
+        let frontier = (0..size * size)
+            .filter(|&i| regions[i].is_none())
+            .flat_map(|i| {
+                let regions = &regions;
+                neighbors((i / size, i % size), size)
+                    .filter_map(move |(r, c)| regions[r * size + c].map(|region| (i, region)))
+            })
+            .collect::<Vec<_>>();
+        if frontier.is_empty() {
+            break;
+        }
+        let (i, region) = frontier[rng.next_below(frontier.len())];
+        regions[i] = Some(region);
+    }
+    regions.into_iter().map(Option::unwrap_or_default).collect()
+}
+
+/// Rules out the given other solution to the board, by moving one of its
+/// Queens that isn't in the intended solution into a neighboring region.
+///
+/// Moving a square that doesn't hold an intended Queen keeps the intended
+/// solution valid, and moving one that holds another Queen leaves the other
+/// solution with two Queens in one region. Only moves that keep every region
+/// connected are made; this returns false if there are none.
+fn separate(
+    size: usize,
+    regions: &mut [usize],
+    queens: &[usize],
+    other: &CoordSet,
+    rng: &mut SeededRng,
+) -> bool {
+    let moves = other
+        .iter()
+        .filter(|&(row, col)| queens[row] != col)
+        .flat_map(|coord| {
+            let regions = &*regions;
+            neighbors(coord, size)
+                .map(move |(r, c)| regions[r * size + c])
+                .filter(move |&region| region != regions[coord.0 * size + coord.1])
+                .map(move |region| (coord, region))
+        })
+        .filter(|&(coord, _)| stays_connected(size, regions, coord))
+        .collect::<Vec<_>>();
+    if moves.is_empty() {
+        return false;
+    }
+    let ((row, col), region) = moves[rng.next_below(moves.len())];
+    regions[row * size + col] = region;
+    true
+}
+
+/// Returns whether the region of the given square would stay connected
+/// without it.
+fn stays_connected(size: usize, regions: &[usize], (row, col): Coord) -> bool {
+    let region = regions[row * size + col];
+    let mut remaining = (0..size * size)
+        .filter(|&i| regions[i] == region && i != row * size + col)
+        .collect::<Vec<_>>();
+    let Some(start) = remaining.pop() else {
+        return false;
+    };
+    let mut stack = vec![start];
+    while let Some(i) = stack.pop() {
+        for (r, c) in neighbors((i / size, i % size), size) {
+            if let Some(pos) = remaining.iter().position(|&j| j == r * size + c) {
+                stack.push(remaining.swap_remove(pos));
+            }
+        }
+    }
+    remaining.is_empty()
+}
+
+/// Returns the board with the given region for each square.
+fn board_from_regions(size: usize, regions: &[usize]) -> Board {
+    let colors = regions
+        .iter()
+        .map(|&region| ALL_SQUARE_COLORS[region])
+        .collect::<Vec<SquareColor>>();
+    Board::new(size, colors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_boards_match_their_difficulty() -> Result<()> {
+        for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+            let board = generate_board(8, difficulty, &mut SeededRng::new(5))?;
+            assert_eq!(all_solutions(&board, 2).len(), 1);
+            assert_eq!(Difficulty::of(&board), Some(difficulty));
+        }
+        assert_eq!(
+            generate_board(6, Difficulty::Easy, &mut SeededRng::new(9))?.to_string(),
+            generate_board(6, Difficulty::Easy, &mut SeededRng::new(9))?.to_string()
+        );
+        assert!(generate_board(3, Difficulty::Easy, &mut SeededRng::new(1)).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,270 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use owo_colors::OwoColorize;
+
+use crate::{board::Board, datastructure::Coord, solution::Solution};
+
+/// Which kind of heuristic activity a [Heatmap] layer counts.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HeatmapLayer {
+    /// How many steps considered the square (see [Heuristic::seen_coords][crate::heuristic::Heuristic::seen_coords]).
+    Seen,
+    /// How many steps marked the square (see [Changes::changed_coords][crate::heuristic::Changes::changed_coords]).
+    Changed,
+}
+
+impl HeatmapLayer {
+    /// A short human-readable name for the layer.
+    pub fn name(&self) -> &'static str {
+        match self {
+            HeatmapLayer::Seen => "Seen",
+            HeatmapLayer::Changed => "Changed",
+        }
+    }
+}
+
+/// Counts, for each square of a board, how many steps of a solve touched it.
+///
+/// Squares that are rarely or never seen are parts of the board that don't
+/// contribute to the puzzle, which is useful for puzzle authors to know.
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Board;
+/// # use qsolve::heatmap::{Heatmap, HeatmapLayer};
+/// # use qsolve::heuristic::all_heuristics;
+/// # use qsolve::solution::Solution;
+/// # use qsolve::solvestate::{SolveState, SolveStrategy};
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+/// let heuristics = all_heuristics(&board);
+/// let solution = Solution::solve(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+/// let heatmap = Heatmap::new(&solution);
+/// // Every square is marked exactly once on the way to a solution.
+/// assert!(board.all_coords().iter().all(|c| heatmap.count(&c, HeatmapLayer::Changed) == 1));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Heatmap<'a> {
+    board: &'a Board,
+    seen: Vec<usize>,
+    changed: Vec<usize>,
+}
+
+impl<'a> Heatmap<'a> {
+    /// The side length of a square in the SVG rendering, in pixels.
+    const SVG_SQUARE_SIZE: usize = 40;
+
+    /// Builds the heatmap from every step of the given solve.
+    pub fn new(solution: &Solution<'_, 'a>) -> Self {
+        let board = solution.final_state.board;
+        let mut seen = vec![0; board.square_count()];
+        let mut changed = vec![0; board.square_count()];
+        for item in &solution.steps {
+            let Some(h) = item.next_heuristic else {
+                continue;
+            };
+            for coord in &h.seen_coords(&item.solve_state) {
+                seen[board.coord_to_idx(&coord)] += 1;
+            }
+            if let Some(changes) = &item.changes {
+                for coord in &changes.changed_coords() {
+                    changed[board.coord_to_idx(&coord)] += 1;
+                }
+            }
+        }
+        Heatmap {
+            board,
+            seen,
+            changed,
+        }
+    }
+
+    /// Returns how many steps touched the given square in the given layer.
+    pub fn count(&self, coord: &Coord, layer: HeatmapLayer) -> usize {
+        self.layer(layer)[self.board.coord_to_idx(coord)]
+    }
+
+    /// Returns the highest count of any square in the given layer.
+    pub fn max(&self, layer: HeatmapLayer) -> usize {
+        self.layer(layer).iter().copied().max().unwrap_or_default()
+    }
+
+    /// Returns how many squares were never considered by any step.
+    pub fn unseen(&self) -> usize {
+        self.seen.iter().filter(|&&count| count == 0).count()
+    }
+
+    /// Returns a string colored by OwoColorize that renders the given layer.
+    ///
+    /// Each square shows its count (or `+` for counts above 9), on a
+    /// background that gets hotter as the count approaches the layer's maximum.
+    pub fn ansi_string(&self, layer: HeatmapLayer) -> Result<String> {
+        let mut f = String::new();
+        for row_num in 0..self.board.size() {
+            for col_num in 0..self.board.size() {
+                let count = self.count(&(row_num, col_num), layer);
+                let (r, g, b) = self.heat_rgb(count, layer);
+                let c = match count {
+                    0..=9 => char::from_digit(count as u32, 10).unwrap(),
+                    _ => '+',
+                };
+                write!(f, "{}", c.truecolor(255, 255, 255).on_truecolor(r, g, b))?;
+            }
+            if row_num != self.board.size() - 1 {
+                writeln!(f)?
+            };
+        }
+        Ok(f)
+    }
+
+    /// Returns an SVG image showing the seen and changed layers side by side.
+    ///
+    /// Each square shows its count on a background colored by its heat, and
+    /// the borders between regions of the board are drawn in thick lines.
+    pub fn svg(&self) -> String {
+        let square = Self::SVG_SQUARE_SIZE;
+        let grid = square * self.board.size();
+        let title_height = square;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"sans-serif\">\n",
+            grid * 2 + square,
+            grid + title_height
+        );
+        for (grid_num, layer) in [HeatmapLayer::Seen, HeatmapLayer::Changed]
+            .into_iter()
+            .enumerate()
+        {
+            let x0 = grid_num * (grid + square);
+            // Writing to a String can't fail, so the results are ignored below.
+            let _ = writeln!(
+                svg,
+                "  <text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"{}\">{} (max {})</text>",
+                x0 + grid / 2,
+                title_height * 2 / 3,
+                square / 2,
+                layer.name(),
+                self.max(layer)
+            );
+            for coord in self.board.all_coords() {
+                let (x, y) = (x0 + coord.1 * square, title_height + coord.0 * square);
+                let count = self.count(&coord, layer);
+                let (r, g, b) = self.heat_rgb(count, layer);
+                let _ = writeln!(
+                    svg,
+                    "  <rect x=\"{x}\" y=\"{y}\" width=\"{square}\" height=\"{square}\" fill=\"rgb({r},{g},{b})\" stroke=\"gray\"/>"
+                );
+                let _ = writeln!(
+                    svg,
+                    "  <text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"{}\" fill=\"white\">{count}</text>",
+                    x + square / 2,
+                    y + square * 2 / 3,
+                    square / 2
+                );
+                let color = self.board.color(&coord);
+                if coord.1 + 1 < self.board.size()
+                    && self.board.color(&(coord.0, coord.1 + 1)) != color
+                {
+                    let _ = writeln!(
+                        svg,
+                        "  <line x1=\"{0}\" y1=\"{y}\" x2=\"{0}\" y2=\"{1}\" stroke=\"black\" stroke-width=\"3\"/>",
+                        x + square,
+                        y + square
+                    );
+                }
+                if coord.0 + 1 < self.board.size()
+                    && self.board.color(&(coord.0 + 1, coord.1)) != color
+                {
+                    let _ = writeln!(
+                        svg,
+                        "  <line x1=\"{x}\" y1=\"{0}\" x2=\"{1}\" y2=\"{0}\" stroke=\"black\" stroke-width=\"3\"/>",
+                        y + square,
+                        x + square
+                    );
+                }
+            }
+            let _ = writeln!(
+                svg,
+                "  <rect x=\"{x0}\" y=\"{title_height}\" width=\"{grid}\" height=\"{grid}\" fill=\"none\" stroke=\"black\" stroke-width=\"3\"/>"
+            );
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Returns the counts for the given layer.
+    fn layer(&self, layer: HeatmapLayer) -> &[usize] {
+        match layer {
+            HeatmapLayer::Seen => &self.seen,
+            HeatmapLayer::Changed => &self.changed,
+        }
+    }
+
+    /// Returns the color for the given count, running from dark blue for
+    /// squares that were never touched to bright red for the layer's maximum.
+    fn heat_rgb(&self, count: usize, layer: HeatmapLayer) -> (u8, u8, u8) {
+        let max = self.max(layer);
+        if count == 0 || max == 0 {
+            return (20, 20, 60);
+        }
+        let heat = count as f64 / max as f64;
+        let lerp = |from: f64, to: f64| (from + (to - from) * heat).round() as u8;
+        (lerp(60.0, 230.0), lerp(60.0, 30.0), lerp(140.0, 20.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use regex::Regex;
+
+    use crate::{
+        heuristic::all_heuristics,
+        solvestate::{SolveState, SolveStrategy},
+    };
+
+    use super::*;
+
+    #[test]
+    fn heatmap_counts() -> Result<()> {
+        let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        let heuristics = all_heuristics(&board);
+        let solution = Solution::solve(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+        let heatmap = Heatmap::new(&solution);
+
+        let changed_total = board
+            .all_coords()
+            .iter()
+            .map(|coord| heatmap.count(&coord, HeatmapLayer::Changed))
+            .sum::<usize>();
+        assert_eq!(changed_total, board.square_count());
+        assert_eq!(heatmap.max(HeatmapLayer::Changed), 1);
+        assert!(heatmap.max(HeatmapLayer::Seen) >= 1);
+        assert!(heatmap.unseen() < board.square_count());
+
+        let ansi_re = Regex::new(r"\u{1b}\[[0-9;]*m").unwrap();
+        let ansi_string = heatmap.ansi_string(HeatmapLayer::Changed)?;
+        assert_eq!(
+            ansi_re.replace_all(&ansi_string, ""),
+            "1111\n1111\n1111\n1111"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn heatmap_svg() -> Result<()> {
+        let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        let heuristics = all_heuristics(&board);
+        let solution = Solution::solve(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+        let svg = Heatmap::new(&solution).svg();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<rect").count(), 2 * (board.square_count() + 1));
+        Ok(())
+    }
+}
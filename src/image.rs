@@ -1,14 +1,18 @@
-use std::ops::Range;
+use std::{collections::VecDeque, ops::Range};
 
 use crate::board::Board;
+use crate::datastructure::CoordSet;
+use crate::error::QSolveError;
 use crate::file::{InputSquares, QueensFile};
+use crate::repair::{neighbors, pieces};
 use crate::solvestate::SquareVal;
 use crate::squarecolor::{ALL_SQUARE_COLORS, SquareColor};
 
-use anyhow::{Context, Result, anyhow, ensure};
-use image::{GenericImageView, Rgb, RgbImage, SubImage};
+use anyhow::{Result, anyhow, ensure};
+use image::{GenericImageView, Rgb, RgbImage, SubImage, imageops, metadata::Orientation};
 use itertools::{Itertools, iproduct};
-use log::trace;
+use log::debug;
+use tracing::{field::Empty, trace, trace_span};
 
 /// RGB values for ANSI terminal colors
 ///
@@ -43,19 +47,19 @@ const BLACK_LINE_RATIO: f32 = 0.35;
 /// to be considered a valid grid, as a percentage.
 const GRID_LENGTH_VARIANCE: usize = 20;
 
-/// The maximum number of colors to track in a given square.
-/// Lowering this number saves a bit of memory, but risks detecting
-/// "border" colors first and never finding the "true" dominant color.
-const MAX_COLORS_TO_TRACK: usize = 1000;
-
 /// The maximum thickness of a black grid line in the image. If this is too large, then the
 /// algorithm might detect black borders around the image as a grid line.
 const MAX_LINE_THICKNESS: usize = 20;
 
+/// Images whose largest dimension is at least twice this are analyzed at a reduced
+/// resolution (of roughly this size) to keep huge screenshots fast.
+const MAX_ANALYSIS_DIMENSION: u32 = 1000;
+
 /// Maximum number of unique colors allowed in the grid
 const MAX_UNIQUE_COLORS: usize = ALL_SQUARE_COLORS.len();
 
-/// Threshold for determining if two colors are the same.
+/// Threshold for determining if two colors are the same. This is also how far a
+/// pixel can be from the average color of a region for it to join the region.
 const COLOR_DISTANCE_THRESHOLD: u32 = 500;
 
 /// Threshold for determining if a square contains a queen (high percentage of black pixels)
@@ -64,6 +68,163 @@ const QUEEN_OTHER_RATIO: f32 = 0.06;
 /// Threshold for determining if a square contains an X (medium percentage of black pixels)
 const X_OTHER_RATIO: f32 = 0.01;
 
+/// How far (as a fraction of its size) the weight of the Queen glyphs must sit
+/// from the middle of their outlines for [glyph_orientation] to trust it.
+/// Upright crowns have a heavy base, so they sit well below the middle.
+const GLYPH_TILT_THRESHOLD: f32 = 0.08;
+
+/// The orientations to try, in order, if a grid cannot be found in the image as given.
+const FALLBACK_ORIENTATIONS: [Orientation; 3] = [
+    Orientation::Rotate90,
+    Orientation::Rotate270,
+    Orientation::Rotate180,
+];
+
+/// Returns how many degrees clockwise the given [Orientation] rotates an
+/// image, ignoring any flip.
+///
+/// # Example
+/// ```
+/// # use qsolve::image::rotation_degrees;
+/// # use image::metadata::Orientation;
+/// assert_eq!(rotation_degrees(Orientation::Rotate90), 90);
+/// assert_eq!(rotation_degrees(Orientation::NoTransforms), 0);
+/// ```
+pub fn rotation_degrees(orientation: Orientation) -> u32 {
+    match orientation {
+        Orientation::Rotate90 | Orientation::Rotate90FlipH => 90,
+        Orientation::Rotate180 | Orientation::FlipVertical => 180,
+        Orientation::Rotate270 | Orientation::Rotate270FlipH => 270,
+        Orientation::NoTransforms | Orientation::FlipHorizontal => 0,
+    }
+}
+
+/// Analyzes an image like [analyze_grid_image], but also finds the grid in
+/// screenshots that were rotated.
+///
+/// A rotated square grid is usually still found as given, so when the Queens
+/// in it are drawn on their side or upside down (going by the heavy base of
+/// the crowns), the image is turned upright and analyzed again. Images
+/// without Queens give no such hint, so they are only rotated if no grid can
+/// be found in the image as given.
+///
+/// # Arguments
+/// * `img` - Reference to the [RgbImage] to analyze
+///
+/// # Returns
+/// A [QueensFile] representing the grid, along with the [Orientation] that was applied
+/// to the image to find it ([Orientation::NoTransforms] if the image was used as given).
+/// If no orientation works, this returns the error from analyzing the image as given,
+/// with [QSolveError::ImageDetection] attached.
+///
+/// # Example
+/// ```no_run
+/// # use qsolve::image::analyze_grid_image_any_orientation;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let img = image::open("path/to/image.png")?.to_rgb8();
+/// let (queens_file, orientation) = analyze_grid_image_any_orientation(&img)?;
+/// println!("Found grid after applying {orientation:?}");
+/// # Ok(())
+/// # }
+/// ```
+pub fn analyze_grid_image_any_orientation(img: &RgbImage) -> Result<(QueensFile, Orientation)> {
+    let upright_err = match analyze_grid(img) {
+        Ok((queens_file, grid)) => {
+            let orientation = glyph_orientation(img, &grid, &queens_file)
+                .filter(|&orientation| orientation != Orientation::NoTransforms);
+            if let Some(orientation) = orientation
+                && let Ok(queens_file) = analyze_grid_image(&rotate(img, orientation))
+            {
+                trace!(name: "analyze_grid_image_orientation", orientation = ?orientation);
+                return Ok((queens_file, orientation));
+            }
+            return Ok((queens_file, Orientation::NoTransforms));
+        }
+        Err(e) => e,
+    };
+    for orientation in FALLBACK_ORIENTATIONS {
+        if let Ok(queens_file) = analyze_grid_image(&rotate(img, orientation)) {
+            trace!(name: "analyze_grid_image_orientation", orientation = ?orientation);
+            return Ok((queens_file, orientation));
+        }
+    }
+    Err(upright_err.context(QSolveError::ImageDetection))
+}
+
+/// Returns the image turned by the given orientation, which must be one of
+/// the [FALLBACK_ORIENTATIONS].
+fn rotate(img: &RgbImage, orientation: Orientation) -> RgbImage {
+    match orientation {
+        Orientation::Rotate90 => imageops::rotate90(img),
+        Orientation::Rotate270 => imageops::rotate270(img),
+        _ => imageops::rotate180(img),
+    }
+}
+
+/// Returns the orientation that would turn the Queens found in the grid
+/// upright, going by where the weight of each crown sits within its outline,
+/// or None if there are no Queens or they don't clearly lean one way.
+fn glyph_orientation(
+    img: &RgbImage,
+    grid: &ImageGrid,
+    queens_file: &QueensFile,
+) -> Option<Orientation> {
+    let squares = &queens_file.squares.as_ref()?.0;
+    let tilts = grid
+        .views(img)
+        .zip(squares)
+        .filter(|(_, square)| **square == Some(SquareVal::Queen))
+        .filter_map(|(view, _)| glyph_tilt(&view, grid.stride))
+        .collect::<Vec<_>>();
+    if tilts.is_empty() {
+        return None;
+    }
+    let count = tilts.len() as f32;
+    let dx = tilts.iter().map(|(dx, _)| dx).sum::<f32>() / count;
+    let dy = tilts.iter().map(|(_, dy)| dy).sum::<f32>() / count;
+    trace!(name: "glyph_orientation", dx, dy);
+    if dx.abs().max(dy.abs()) < GLYPH_TILT_THRESHOLD {
+        return None;
+    }
+    // The base of an upright crown is at the bottom; wherever it is instead,
+    // turn the image to bring it back down.
+    Some(match (dx.abs() > dy.abs(), dx > 0.0, dy > 0.0) {
+        (false, _, true) => Orientation::NoTransforms,
+        (false, _, false) => Orientation::Rotate180,
+        (true, true, _) => Orientation::Rotate90,
+        (true, false, _) => Orientation::Rotate270,
+    })
+}
+
+/// Returns how far the center of the black pixels in the middle of the
+/// square sits from the middle of their outline, across and down, as a
+/// fraction of the outline's width and height.
+fn glyph_tilt(view: &SubImage<&RgbImage>, stride: u32) -> Option<(f32, f32)> {
+    const BORDER_DENOM: u32 = 10;
+    let (width, height) = view.dimensions();
+    let (left, top) = (width / BORDER_DENOM, height / BORDER_DENOM);
+    let points = iproduct!(
+        (top..height - top).step_by(stride as usize),
+        (left..width - left).step_by(stride as usize)
+    )
+    .filter(|&(y, x)| is_black(&view.get_pixel(x, y)))
+    .map(|(y, x)| (x as f32, y as f32))
+    .collect::<Vec<_>>();
+    let (min_x, max_x) = points.iter().map(|p| p.0).minmax().into_option()?;
+    let (min_y, max_y) = points.iter().map(|p| p.1).minmax().into_option()?;
+    if max_x <= min_x || max_y <= min_y {
+        return None;
+    }
+    let count = points.len() as f32;
+    let center_x = points.iter().map(|p| p.0).sum::<f32>() / count;
+    let center_y = points.iter().map(|p| p.1).sum::<f32>() / count;
+    Some((
+        (center_x - (min_x + max_x) / 2.0) / (max_x - min_x),
+        (center_y - (min_y + max_y) / 2.0) / (max_y - min_y),
+    ))
+}
+
 /// Analyzes an image containing a grid of colored boxes and returns a [QueensFile].
 ///
 /// # Arguments
@@ -84,14 +245,138 @@ const X_OTHER_RATIO: f32 = 0.01;
 /// # }
 /// ```
 pub fn analyze_grid_image(img: &RgbImage) -> Result<QueensFile> {
-    trace!("Analyze grid image start: {img:?}");
-    let width_ranges = find_grid_ranges(img, 0..img.width(), true);
+    analyze_grid(img).map(|(queens_file, _)| queens_file)
+}
+
+/// Analyzes an image like [analyze_grid_image], also returning the grid the
+/// [QueensFile] was read from.
+fn analyze_grid(img: &RgbImage) -> Result<(QueensFile, ImageGrid)> {
+    let span = trace_span!(
+        "analyze_grid_image",
+        width = img.width(),
+        height = img.height(),
+        size = Empty,
+        colors = Empty
+    );
+    let grid = detect_grid(img)?;
+    let all_rgb_colors = detect_dominant_colors(img, &grid)?;
+    let square_values = detect_glyphs(img, &grid, &all_rgb_colors);
+    let board_size = grid.size();
+
+    // The same region can have slightly different dominant colors in different squares
+    // (thanks to anti-aliasing and gradients), so we treat any colors within
+    // COLOR_DISTANCE_THRESHOLD of each other as the same color.
+    let mut unique_rgb_colors = Vec::with_capacity(MAX_UNIQUE_COLORS);
+    let color_indices = all_rgb_colors
+        .iter()
+        .map(|&rgb_color| {
+            unique_rgb_colors
+                .iter()
+                .position(|&c| color_distance(c, rgb_color) <= COLOR_DISTANCE_THRESHOLD)
+                .unwrap_or_else(|| {
+                    unique_rgb_colors.push(rgb_color);
+                    unique_rgb_colors.len() - 1
+                })
+        })
+        .collect::<Vec<_>>();
+
+    let mut color_indices = color_indices;
+    reconcile_color_count(
+        board_size,
+        &all_rgb_colors,
+        &mut unique_rgb_colors,
+        &mut color_indices,
+    )?;
+
+    // Map RGB colors to SquareColors
+    let color_mapping = map_image_to_square_colors(&unique_rgb_colors);
+
+    // Create the board colors
+    let colors = color_indices
+        .iter()
+        .map(|&color_idx| color_mapping[color_idx])
+        .collect::<Vec<_>>();
+
+    let board = Board::new(board_size, colors);
+    let squares = InputSquares::from(square_values);
+    span.record("size", board_size);
+    span.record("colors", unique_rgb_colors.len());
+    let queens_file = QueensFile {
+        board,
+        squares: Some(squares),
+        rotation: None,
+    };
+    Ok((queens_file, grid))
+}
+
+/// The squares of a grid found in an image by [detect_grid].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ImageGrid {
+    /// The pixel range of each row of squares, from top to bottom, excluding grid lines.
+    pub rows: Vec<Range<u32>>,
+
+    /// The pixel range of each column of squares, from left to right, excluding grid lines.
+    pub cols: Vec<Range<u32>>,
+
+    /// How many pixels apart to sample in each dimension when looking inside
+    /// the squares. This is 1 unless the image was large enough to be analyzed
+    /// at a reduced resolution.
+    pub stride: u32,
+}
+
+impl ImageGrid {
+    /// Returns the size of the grid.
+    pub fn size(&self) -> usize {
+        self.cols.len()
+    }
+
+    /// Returns a view of each square in the image, in row-major order.
+    fn views<'a>(&self, img: &'a RgbImage) -> impl Iterator<Item = SubImage<&'a RgbImage>> {
+        iproduct!(self.rows.clone(), self.cols.clone()).map(|(row, col)| {
+            img.view(
+                col.start,
+                row.start,
+                col.end - col.start,
+                row.end - row.start,
+            )
+        })
+    }
+}
+
+/// Finds the grid of squares in an image, which is the first phase of
+/// [analyze_grid_image].
+///
+/// This fails if the grid found isn't square, or has fewer than 4 or more
+/// than 16 squares on a side.
+pub fn detect_grid(img: &RgbImage) -> Result<ImageGrid> {
+    // For huge images, we find the grid on a downscaled copy of the image, and then
+    // sample the original image (at a matching stride) to find colors and glyphs.
+    let scale = (img.width().max(img.height()) / MAX_ANALYSIS_DIMENSION).max(1);
+    let span = trace_span!("detect_grid", scale, size = Empty);
+    let (width_ranges, height_ranges) = if scale > 1 {
+        let small_img = downscale_darkest(img, scale);
+        let upscale = |r: Range<u32>| (r.start * scale)..(r.end * scale);
+        (
+            find_grid_ranges(&small_img, 0..small_img.width(), true)
+                .into_iter()
+                .map(upscale)
+                .collect::<Vec<_>>(),
+            find_grid_ranges(&small_img, 0..small_img.height(), false)
+                .into_iter()
+                .map(upscale)
+                .collect::<Vec<_>>(),
+        )
+    } else {
+        (
+            find_grid_ranges(img, 0..img.width(), true),
+            find_grid_ranges(img, 0..img.height(), false),
+        )
+    };
     ensure!(
         width_ranges.len() >= 4,
         "Found too few columns; must be at least 4, found {}",
         width_ranges.len(),
     );
-    let height_ranges = find_grid_ranges(img, 0..img.height(), false);
     ensure!(
         width_ranges.len() == height_ranges.len(),
         "Grid must be a square; width was {} height was {}",
@@ -104,83 +389,183 @@ pub fn analyze_grid_image(img: &RgbImage) -> Result<QueensFile> {
         MAX_UNIQUE_COLORS,
         width_ranges.len()
     );
-    let board_size = width_ranges.len();
-
-    let mut all_rgb_colors = Vec::with_capacity(board_size * board_size);
-    let mut square_values = Vec::with_capacity(board_size * board_size);
+    span.record("size", width_ranges.len());
+    Ok(ImageGrid {
+        rows: height_ranges,
+        cols: width_ranges,
+        stride: scale,
+    })
+}
 
-    trace!(
-        "Analyze grid image ranges found: {width_ranges:?} {height_ranges:?}"
+/// Finds the dominant color of each square of the grid, in row-major order,
+/// which is the second phase of [analyze_grid_image].
+///
+/// Rather than looking at each square on its own, this grows regions of
+/// similar pixels across the whole grid, stepping over the grid lines between
+/// squares, and gives each square the average color of the region that covers
+/// most of it. Anti-aliased or faded pixels along the edges of a square end up
+/// in regions of their own, so they can't outweigh the middle of the square,
+/// even when the squares are small.
+pub fn detect_dominant_colors(img: &RgbImage, grid: &ImageGrid) -> Result<Vec<Rgb<u8>>> {
+    let span = trace_span!(
+        "detect_dominant_colors",
+        squares = grid.size() * grid.size(),
+        regions = Empty
     );
-    for (height_range, width_range) in iproduct!(height_ranges, width_ranges) {
-        let view = img.view(
-            width_range.start,
-            height_range.start,
-            width_range.end - width_range.start,
-            height_range.end - height_range.start,
-        );
+    let regions = GrownRegions::grow(img, grid);
+    span.record("regions", regions.colors.len());
+    iproduct!(&grid.rows, &grid.cols)
+        .zip(regions.squares)
+        .map(|((row, col), region)| {
+            let offset = (col.start, row.start);
+            let rgb_color = region.map(|r| regions.colors[r]).ok_or_else(|| {
+                anyhow!("Could not find dominant color in square at offset {offset:?}")
+            })?;
+            trace!(name: "dominant_color", offset = ?offset, rgb = ?rgb_color.0);
+            Ok(rgb_color)
+        })
+        .collect()
+}
 
-        let rgb_color = get_dominant_color(&view).with_context(|| {
-            format!(
-                "Count not find dominant color in square at offset {:?}",
-                view.offsets()
-            )
-        })?;
-        trace!(
-            "Analyze grid image color: for height {height_range:?} width {width_range:?} got dominant color {rgb_color:?}"
-        );
-        all_rgb_colors.push(rgb_color);
+/// Regions of similar pixels grown across the squares of a grid.
+///
+/// The pixels sampled from inside the squares are laid out as if the grid
+/// lines between them weren't there, so a region can spread from one square
+/// into the next. Black pixels (from glyphs) never join a region.
+struct GrownRegions {
+    /// The average color of each region.
+    colors: Vec<Rgb<u8>>,
+
+    /// The region covering the most of each square, in row-major order, or
+    /// None if the square has no pixels in any region.
+    squares: Vec<Option<usize>>,
+}
 
-        let other_ratio = get_other_ratio(&view, &rgb_color);
-        let square_val = match other_ratio {
-            r if r >= QUEEN_OTHER_RATIO => Some(SquareVal::Queen),
-            r if r >= X_OTHER_RATIO => Some(SquareVal::X),
-            _ => None,
+impl GrownRegions {
+    fn grow(img: &RgbImage, grid: &ImageGrid) -> Self {
+        let samples = |ranges: &[Range<u32>]| {
+            ranges
+                .iter()
+                .map(|range| range.clone().step_by(grid.stride as usize).collect_vec())
+                .collect_vec()
         };
-        trace!(
-            "Analyze grid image ratio: For height {height_range:?} width {width_range:?} got ratio {other_ratio:?} and value {square_val:?}"
-        );
-        square_values.push(square_val);
-    }
+        let (row_ys, col_xs) = (samples(&grid.rows), samples(&grid.cols));
+        let (ys, xs) = (row_ys.concat(), col_xs.concat());
+        let width = xs.len();
+        let pixel = |i: usize| *img.get_pixel(xs[i % width], ys[i / width]);
 
-    let unique_rgb_colors = all_rgb_colors
-        .clone()
-        .into_iter()
-        .unique()
-        .collect::<Vec<_>>();
+        // The range of sampled rows (or columns) that fall in each row (or
+        // column) of squares.
+        let spans = |samples: &[Vec<u32>]| {
+            samples
+                .iter()
+                .scan(0, |start, samples| {
+                    let span = *start..(*start + samples.len());
+                    *start = span.end;
+                    Some(span)
+                })
+                .collect_vec()
+        };
+        let (row_spans, col_spans) = (spans(&row_ys), spans(&col_xs));
 
-    ensure!(
-        unique_rgb_colors.len() == board_size,
-        "Number of unique colors must be equal to the board size"
-    );
+        // Regions are seeded from the middle of each square first, since
+        // that's where its true color is most likely to be, and then from any
+        // pixels that are left over.
+        let seeds = iproduct!(&row_spans, &col_spans)
+            .flat_map(|(rows, cols)| {
+                [(2, 2), (1, 1), (1, 3), (3, 1), (3, 3)].map(|(row_quarter, col_quarter)| {
+                    let y = rows.start + rows.len() * row_quarter / 4;
+                    let x = cols.start + cols.len() * col_quarter / 4;
+                    y * width + x
+                })
+            })
+            .chain(0..xs.len() * ys.len());
 
-    // Map RGB colors to SquareColors
-    let color_mapping = map_image_to_square_colors(&unique_rgb_colors);
+        let mut labels = vec![None; xs.len() * ys.len()];
+        let mut colors = vec![];
+        let mut queue = VecDeque::new();
+        for seed in seeds {
+            if labels[seed].is_some() || is_black(&pixel(seed)) {
+                continue;
+            }
+            let region = colors.len();
+            let (mut count, mut sum) = (0u64, [0u64; 3]);
+            labels[seed] = Some(region);
+            queue.push_back(seed);
+            while let Some(i) = queue.pop_front() {
+                count += 1;
+                for (sum, channel) in sum.iter_mut().zip(pixel(i).0) {
+                    *sum += channel as u64;
+                }
+                let average = Rgb(sum.map(|channel| (channel / count) as u8));
+                let (y, x) = (i / width, i % width);
+                let neighbors = [
+                    (y.wrapping_sub(1), x),
+                    (y + 1, x),
+                    (y, x.wrapping_sub(1)),
+                    (y, x + 1),
+                ];
+                for (ny, nx) in neighbors {
+                    if ny >= ys.len() || nx >= width {
+                        continue;
+                    }
+                    let n = ny * width + nx;
+                    if labels[n].is_none()
+                        && !is_black(&pixel(n))
+                        && color_distance(pixel(n), average) <= COLOR_DISTANCE_THRESHOLD
+                    {
+                        labels[n] = Some(region);
+                        queue.push_back(n);
+                    }
+                }
+            }
+            colors.push(Rgb(sum.map(|channel| (channel / count) as u8)));
+        }
 
-    // Create the board colors
-    let colors = all_rgb_colors
-        .iter()
-        .map(|&rgb_color| {
-            let color_idx = unique_rgb_colors
-                .iter()
-                .position(|&c| c == rgb_color)
-                .unwrap();
-            color_mapping[color_idx]
-        })
-        .collect::<Vec<_>>();
+        let squares = iproduct!(&row_spans, &col_spans)
+            .map(|(rows, cols)| {
+                iproduct!(rows.clone(), cols.clone())
+                    .filter_map(|(y, x)| labels[y * width + x])
+                    .sorted_unstable()
+                    .dedup_with_count()
+                    .max_by_key(|&(count, _)| count)
+                    .map(|(_, region)| region)
+            })
+            .collect();
+        GrownRegions { colors, squares }
+    }
+}
 
-    let board = Board::new(board_size, colors);
-    let squares = InputSquares::from(square_values);
-    trace!("Analyze grid image done.");
-    trace!("Board:\n{board}");
-    trace!("Squares:\n{squares}");
-    Ok(QueensFile {
-        board,
-        squares: Some(squares),
-    })
+/// Finds the Queen and X glyphs drawn in each square of the grid, in
+/// row-major order, given the dominant color of each square from
+/// [detect_dominant_colors]. This is the last phase of [analyze_grid_image].
+pub fn detect_glyphs(
+    img: &RgbImage,
+    grid: &ImageGrid,
+    dominant_colors: &[Rgb<u8>],
+) -> Vec<Option<SquareVal>> {
+    let _span = trace_span!("detect_glyphs", squares = dominant_colors.len());
+    grid.views(img)
+        .zip(dominant_colors)
+        .map(|(view, rgb_color)| {
+            let other_ratio = get_other_ratio(&view, rgb_color, grid.stride);
+            let square_val = match other_ratio {
+                r if r >= QUEEN_OTHER_RATIO => Some(SquareVal::Queen),
+                r if r >= X_OTHER_RATIO => Some(SquareVal::X),
+                _ => None,
+            };
+            trace!(
+                name: "glyph",
+                offset = ?view.offsets(),
+                ratio = other_ratio,
+                value = ?square_val
+            );
+            square_val
+        })
+        .collect()
 }
 
-fn get_other_ratio(view: &SubImage<&RgbImage>, rgb_color: &Rgb<u8>) -> f32 {
+fn get_other_ratio(view: &SubImage<&RgbImage>, rgb_color: &Rgb<u8>, stride: u32) -> f32 {
     const BORDER_DENOM: u32 = 10;
     let (width, height) = view.dimensions();
     let center_subview = view.view(
@@ -189,11 +574,47 @@ fn get_other_ratio(view: &SubImage<&RgbImage>, rgb_color: &Rgb<u8>) -> f32 {
         width - (2 * width / BORDER_DENOM),
         height - (2 * height / BORDER_DENOM),
     );
-    let other_count = center_subview
-        .pixels()
-        .filter(|(_, _, p)| color_distance(*p, *rgb_color) > COLOR_DISTANCE_THRESHOLD)
+    let other_count = sampled_pixels(&center_subview, stride)
+        .filter(|p| color_distance(*p, *rgb_color) > COLOR_DISTANCE_THRESHOLD)
         .count();
-    (other_count as f32) / ((width * height) as f32)
+    let sampled_count = width.div_ceil(stride) * height.div_ceil(stride);
+    (other_count as f32) / (sampled_count as f32)
+}
+
+/// Returns every `stride`th pixel in each dimension of the view.
+///
+/// A stride of 1 returns every pixel in the view.
+fn sampled_pixels<'a>(
+    view: &'a SubImage<&RgbImage>,
+    stride: u32,
+) -> impl Iterator<Item = Rgb<u8>> + 'a {
+    let (width, height) = view.dimensions();
+    (0..height).step_by(stride as usize).flat_map(move |y| {
+        (0..width)
+            .step_by(stride as usize)
+            .map(move |x| view.get_pixel(x, y))
+    })
+}
+
+/// Downscales the image by the given factor, keeping the darkest pixel in each block.
+///
+/// Keeping the darkest pixel (rather than averaging) ensures that thin black grid
+/// lines survive the downscaling and are still detected by [find_grid_ranges].
+fn downscale_darkest(img: &RgbImage, factor: u32) -> RgbImage {
+    let luma = |p: &Rgb<u8>| p.0.iter().map(|&c| c as u32).sum::<u32>();
+    RgbImage::from_fn(
+        img.width().div_ceil(factor),
+        img.height().div_ceil(factor),
+        |x, y| {
+            iproduct!(
+                (y * factor)..((y + 1) * factor).min(img.height()),
+                (x * factor)..((x + 1) * factor).min(img.width())
+            )
+            .map(|(py, px)| *img.get_pixel(px, py))
+            .min_by_key(luma)
+            .unwrap()
+        },
+    )
 }
 
 fn find_grid_ranges(img: &RgbImage, range: Range<u32>, is_vertical: bool) -> Vec<Range<u32>> {
@@ -209,6 +630,9 @@ fn find_grid_ranges(img: &RgbImage, range: Range<u32>, is_vertical: bool) -> Vec
             (start_idx + start_count as u32)..end_idx
         });
     grid_ranges.extend(grid_ranges_iter);
+    if grid_ranges.is_empty() {
+        return grid_ranges;
+    }
     let grid_ranges_len = grid_ranges.len();
     let median_grid_length = grid_ranges
         .clone()
@@ -250,39 +674,160 @@ fn is_black(pixel: &Rgb<u8>) -> bool {
     pixel[0] < BLACK_THRESHOLD && pixel[1] < BLACK_THRESHOLD && pixel[2] < BLACK_THRESHOLD
 }
 
-/// Helper function to get the dominant color in a box
-fn get_dominant_color(img: &SubImage<&RgbImage>) -> Result<Rgb<u8>> {
-    let mut colors = [Rgb([0, 0, 0]); MAX_COLORS_TO_TRACK];
-    let mut counts = [0u32; MAX_COLORS_TO_TRACK];
-    let mut num_colors = 0;
+/// Calculates the color distance between two RGB values using the Euclidean distance
+fn color_distance(rgb1: Rgb<u8>, rgb2: Rgb<u8>) -> u32 {
+    ((rgb1[0] as u32).abs_diff(rgb2[0] as u32)).pow(2)
+        + ((rgb1[1] as u32).abs_diff(rgb2[1] as u32)).pow(2)
+        + ((rgb1[2] as u32).abs_diff(rgb2[2] as u32)).pow(2)
+}
 
-    for pixel in img.pixels().map(|(_, _, p)| p).filter(|&p| !is_black(&p)) {
-        match (
-            num_colors,
-            colors[..num_colors].iter().position(|&p| p == pixel),
-        ) {
-            (_, Some(idx)) => counts[idx] += 1,
-            (MAX_COLORS_TO_TRACK, None) => (),
-            (_, None) => {
-                colors[num_colors] = pixel;
-                counts[num_colors] = 1;
-                num_colors += 1;
-            }
+/// Merges or splits the colors found in an image until there are as many as
+/// the board is wide, since anti-aliasing often makes a few squares look like
+/// an extra color, or makes two similar colors look like one.
+///
+/// `unique_rgb_colors` holds each color, and `color_indices` holds the index
+/// of the color of each square, in row-major order.
+fn reconcile_color_count(
+    board_size: usize,
+    all_rgb_colors: &[Rgb<u8>],
+    unique_rgb_colors: &mut Vec<Rgb<u8>>,
+    color_indices: &mut [usize],
+) -> Result<()> {
+    let found = unique_rgb_colors.len();
+    while unique_rgb_colors.len() > board_size {
+        if !merge_closest_neighbors(board_size, unique_rgb_colors, color_indices) {
+            break;
         }
     }
-    counts[..num_colors]
+    while unique_rgb_colors.len() < board_size {
+        if !split_disconnected_color(board_size, all_rgb_colors, unique_rgb_colors, color_indices) {
+            break;
+        }
+    }
+    ensure!(
+        unique_rgb_colors.len() == board_size,
+        "Number of unique colors must be equal to the board size"
+    );
+    if found != board_size {
+        debug!("Found {found} colors in a board of size {board_size}; reconciled them");
+    }
+    Ok(())
+}
+
+/// Returns the squares of each color, given the color index of each square.
+fn coords_by_color(board_size: usize, colors: usize, color_indices: &[usize]) -> Vec<CoordSet> {
+    let mut coords = vec![CoordSet::default(); colors];
+    for (idx, &color_idx) in color_indices.iter().enumerate() {
+        coords[color_idx].add((idx / board_size, idx % board_size));
+    }
+    coords
+}
+
+/// Merges the color with the fewest squares into the closest color it
+/// touches, preferring the closest pair of colors overall. Returns false if
+/// no two colors touch.
+fn merge_closest_neighbors(
+    board_size: usize,
+    unique_rgb_colors: &mut Vec<Rgb<u8>>,
+    color_indices: &mut [usize],
+) -> bool {
+    let coords = coords_by_color(board_size, unique_rgb_colors.len(), color_indices);
+    let touching = |from: usize, into: usize| {
+        coords[from].iter().any(|coord| {
+            neighbors(coord, board_size).any(|(r, c)| color_indices[r * board_size + c] == into)
+        })
+    };
+    let Some((from, into)) = iproduct!(0..unique_rgb_colors.len(), 0..unique_rgb_colors.len())
+        .filter(|&(from, into)| from != into && touching(from, into))
+        .min_by_key(|&(from, into)| {
+            (
+                color_distance(unique_rgb_colors[from], unique_rgb_colors[into]),
+                coords[from].len(),
+            )
+        })
+    else {
+        return false;
+    };
+    trace!(
+        name: "analyze_grid_image_merge_color",
+        from = ?unique_rgb_colors[from].0,
+        into = ?unique_rgb_colors[into].0,
+        squares = coords[from].len()
+    );
+    unique_rgb_colors.remove(from);
+    for color_idx in color_indices.iter_mut() {
+        if *color_idx == from {
+            *color_idx = into;
+        }
+        if *color_idx > from {
+            *color_idx -= 1;
+        }
+    }
+    true
+}
+
+/// Splits a piece off a color whose squares aren't connected, making it a
+/// color of its own; of all such pieces, this picks the one whose average
+/// color is furthest from the rest of its color. Returns false if every
+/// color is connected.
+fn split_disconnected_color(
+    board_size: usize,
+    all_rgb_colors: &[Rgb<u8>],
+    unique_rgb_colors: &mut Vec<Rgb<u8>>,
+    color_indices: &mut [usize],
+) -> bool {
+    let coords = coords_by_color(board_size, unique_rgb_colors.len(), color_indices);
+    let average = |piece: &CoordSet| {
+        let sum = piece.iter().fold([0u32; 3], |sum, (r, c)| {
+            let rgb = all_rgb_colors[r * board_size + c].0;
+            [0, 1, 2].map(|i| sum[i] + rgb[i] as u32)
+        });
+        Rgb(sum.map(|channel| (channel / piece.len() as u32) as u8))
+    };
+    let Some((piece, rgb)) = coords
         .iter()
-        .zip(colors[..num_colors].iter())
-        .max_by(|&(a, _), &(b, _)| a.cmp(b))
-        .map(|(_, color)| *color)
-        .ok_or_else(|| anyhow!("Could not find dominant color"))
+        .enumerate()
+        .flat_map(|(color_idx, color_coords)| {
+            let mut pieces = pieces(board_size, *color_coords);
+            pieces.sort_by_key(|piece| std::cmp::Reverse(piece.len()));
+            pieces
+                .into_iter()
+                .skip(1)
+                .map(move |piece| (color_idx, piece))
+        })
+        .map(|(color_idx, piece)| {
+            let rgb = average(&piece);
+            let distance = color_distance(rgb, unique_rgb_colors[color_idx]);
+            (piece, rgb, distance)
+        })
+        .max_by_key(|(piece, _, distance)| (*distance, piece.len()))
+        .map(|(piece, rgb, _)| (piece, rgb))
+    else {
+        return false;
+    };
+    trace!(
+        name: "analyze_grid_image_split_color",
+        rgb = ?rgb.0,
+        squares = piece.len()
+    );
+    unique_rgb_colors.push(rgb);
+    for (r, c) in piece.iter() {
+        color_indices[r * board_size + c] = unique_rgb_colors.len() - 1;
+    }
+    true
 }
 
-/// Calculates the color distance between two RGB values using the Euclidean distance
-fn color_distance(rgb1: Rgb<u8>, rgb2: Rgb<u8>) -> u32 {
-    ((rgb1[0] as u32).abs_diff(rgb2[0] as u32)).pow(2)
-        + ((rgb1[1] as u32).abs_diff(rgb2[1] as u32)).pow(2)
-        + ((rgb1[2] as u32).abs_diff(rgb2[2] as u32)).pow(2)
+/// Returns how far apart two square colors are, as the squared distance
+/// between the reference colors that images are matched against.
+pub(crate) fn square_color_distance(a: SquareColor, b: SquareColor) -> u32 {
+    let rgb = |color| {
+        ANSI_COLORS
+            .iter()
+            .find(|(_, c)| *c == color)
+            .map(|(rgb, _)| *rgb)
+            .unwrap_or(Rgb([0, 0, 0]))
+    };
+    color_distance(rgb(a), rgb(b))
 }
 
 /// Maps image colors to SquareColors by trying all NxM combinations, assigning that color,
@@ -320,3 +865,388 @@ fn map_image_to_square_colors(image_colors: &[Rgb<u8>]) -> [SquareColor; MAX_UNI
 
     image_to_square_color
 }
+
+/// Utilities for programmatically building synthetic screenshots in tests.
+#[cfg(test)]
+pub(crate) mod testutil {
+    use std::str::FromStr;
+
+    use image::{Rgb, RgbImage};
+
+    use crate::{board::Board, file::InputSquares, solvestate::SquareVal};
+
+    use super::ANSI_COLORS;
+
+    /// A builder for a synthetic image of a Queens board.
+    ///
+    /// The board is drawn with the exact ANSI RGB value for each [SquareColor][crate::squarecolor::SquareColor],
+    /// separated by black grid lines (including a border), with optional
+    /// per-pixel noise and queen/X glyphs.
+    pub struct SyntheticGrid {
+        board: Board,
+        squares: Option<InputSquares>,
+        cell_size: u32,
+        line_thickness: u32,
+        noise: u8,
+        faded_edges: Vec<u8>,
+    }
+
+    impl SyntheticGrid {
+        /// Creates a synthetic grid for the given board string, with 40px cells and 2px lines.
+        pub fn new(board_str: &str) -> Self {
+            SyntheticGrid {
+                board: Board::from_str(board_str).unwrap(),
+                squares: None,
+                cell_size: 40,
+                line_thickness: 2,
+                noise: 0,
+                faded_edges: vec![],
+            }
+        }
+
+        /// Draws queen and X glyphs in the squares marked in the given string.
+        pub fn squares(mut self, squares_str: &str) -> Self {
+            self.squares = Some(InputSquares::from_str(squares_str).unwrap());
+            self
+        }
+
+        /// Sets the size of each cell, in pixels (excluding grid lines).
+        pub fn cell_size(mut self, cell_size: u32) -> Self {
+            self.cell_size = cell_size;
+            self
+        }
+
+        /// Sets the thickness of each grid line, in pixels.
+        pub fn line_thickness(mut self, line_thickness: u32) -> Self {
+            self.line_thickness = line_thickness;
+            self
+        }
+
+        /// Sets the maximum amount each channel of each cell pixel may deviate from its color.
+        pub fn noise(mut self, noise: u8) -> Self {
+            self.noise = noise;
+            self
+        }
+
+        /// Fades the pixels along the edges of each cell towards white, by the
+        /// given percentage for each ring of pixels, from the outermost in.
+        pub fn faded_edges(mut self, percentages: &[u8]) -> Self {
+            self.faded_edges = percentages.to_vec();
+            self
+        }
+
+        /// Returns the range of pixels (along either axis) covered by the given cell.
+        pub fn cell_range(&self, idx: u32) -> std::ops::Range<u32> {
+            let start = self.line_thickness + idx * (self.cell_size + self.line_thickness);
+            start..(start + self.cell_size)
+        }
+
+        /// Renders the synthetic grid to an image.
+        pub fn build(&self) -> RgbImage {
+            let size = self.board.size() as u32;
+            let dimension = size * (self.cell_size + self.line_thickness) + self.line_thickness;
+            RgbImage::from_fn(dimension, dimension, |x, y| {
+                let pitch = self.cell_size + self.line_thickness;
+                let (cx, cy) = (x % pitch, y % pitch);
+                if cx < self.line_thickness || cy < self.line_thickness {
+                    return Rgb([0, 0, 0]);
+                }
+                let coord = ((y / pitch) as usize, (x / pitch) as usize);
+                let (gx, gy) = (cx - self.line_thickness, cy - self.line_thickness);
+                let square = self
+                    .squares
+                    .as_ref()
+                    .and_then(|s| s.0[self.board.coord_to_idx(&coord)]);
+                if self.is_glyph_pixel(square, gx, gy) {
+                    return Rgb([0, 0, 0]);
+                }
+                let color = self.board.color(&coord);
+                let base = ANSI_COLORS.iter().find(|(_, c)| *c == color).unwrap().0;
+                let ring = gx.min(gy).min(self.cell_size - 1 - gx.max(gy)) as usize;
+                let fade = self.faded_edges.get(ring).copied().unwrap_or(0) as u32;
+                Rgb(base
+                    .0
+                    .map(|channel| channel + ((255 - channel as u32) * fade / 100) as u8)
+                    .map(|channel| self.jitter(channel, x, y)))
+            })
+        }
+
+        /// Whether the given pixel (relative to the cell's upper left) is part of a glyph.
+        ///
+        /// Queens are drawn as a filled square covering the middle third of the cell, and
+        /// Xs as two single-pixel diagonals across the middle third of the cell.
+        fn is_glyph_pixel(&self, square: Option<SquareVal>, gx: u32, gy: u32) -> bool {
+            let third = self.cell_size / 3;
+            let in_middle = (third..2 * third).contains(&gx) && (third..2 * third).contains(&gy);
+            match square {
+                Some(SquareVal::Queen) => in_middle,
+                Some(SquareVal::X) => in_middle && (gx == gy || gx + gy == self.cell_size - 1),
+                None => false,
+            }
+        }
+
+        /// Deterministically perturbs a channel by up to the configured noise.
+        fn jitter(&self, channel: u8, x: u32, y: u32) -> u8 {
+            if self.noise == 0 {
+                return channel;
+            }
+            let hash = x.wrapping_mul(374_761_393) ^ y.wrapping_mul(668_265_263);
+            let hash = (hash ^ (hash >> 13)).wrapping_mul(1_274_126_177);
+            let offset = (hash % (2 * self.noise as u32 + 1)) as i32 - self.noise as i32;
+            (channel as i32 + offset).clamp(0, 255) as u8
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+
+    use super::testutil::SyntheticGrid;
+    use super::*;
+
+    const SYNTHETIC_BOARD: &str = "rrrgg\nrbbgg\nrbbyy\nmmmyy\nmmmyy";
+
+    #[test]
+    fn find_grid_ranges_synthetic() {
+        for (board_str, line_thickness) in iproduct!(
+            [
+                "rrrr\nrggg\nbbbb\nyyyy",
+                SYNTHETIC_BOARD,
+                "rrrrrrrrr\nggggggggg\nbbbbbbbbb\nyyyyyyyyy\nmmmmmmmmm\nccccccccc\nwwwwwwwww\nRRRRRRRRR\nGGGGGGGGG",
+            ],
+            [1, 2, 4, 8]
+        ) {
+            let grid = SyntheticGrid::new(board_str).line_thickness(line_thickness);
+            let img = grid.build();
+            let size = board_str.lines().count();
+            for is_vertical in [true, false] {
+                let dimension = if is_vertical {
+                    img.width()
+                } else {
+                    img.height()
+                };
+                let ranges = find_grid_ranges(&img, 0..dimension, is_vertical);
+                assert_eq!(
+                    ranges,
+                    (0..size as u32)
+                        .map(|idx| grid.cell_range(idx))
+                        .collect::<Vec<_>>(),
+                    "Grid ranges for size {size} and thickness {line_thickness}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn find_grid_ranges_without_border_lines() {
+        let img = SyntheticGrid::new(SYNTHETIC_BOARD).build();
+        let cropped = img.view(2, 2, img.width() - 4, img.height() - 4).to_image();
+        // Without the outer border, the outermost cells cannot be bounded.
+        assert_eq!(
+            find_grid_ranges(&cropped, 0..cropped.width(), true).len(),
+            3
+        );
+    }
+
+    #[test]
+    fn detect_dominant_colors_synthetic() -> Result<()> {
+        for noise in [0, 4, 8, 12] {
+            let synthetic = SyntheticGrid::new(SYNTHETIC_BOARD)
+                .squares("Q....\n.x...\n.....\n.....\n.....")
+                .noise(noise);
+            let img = synthetic.build();
+            let colors = detect_dominant_colors(&img, &detect_grid(&img)?)?;
+            for (idx, expected) in [
+                (0, Rgb([170, 0, 0])),
+                (6, Rgb([0, 0, 170])),
+                (24, Rgb([170, 85, 0])),
+            ] {
+                assert!(
+                    color_distance(colors[idx], expected) < COLOR_DISTANCE_THRESHOLD,
+                    "Dominant color {:?} for noise {noise} should be near {expected:?}",
+                    colors[idx]
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn detect_dominant_colors_ignores_faded_edges() -> Result<()> {
+        // In squares this small, the two faded rings of pixels along the edges
+        // outnumber the pixels in the middle, but each ring on its own doesn't.
+        let img = SyntheticGrid::new(SYNTHETIC_BOARD)
+            .cell_size(11)
+            .faded_edges(&[30, 20])
+            .build();
+        let queens_file = analyze_grid_image(&img)?;
+        assert_eq!(queens_file.board.to_string(), SYNTHETIC_BOARD);
+        Ok(())
+    }
+
+    #[test]
+    fn detect_dominant_colors_fails_on_black() {
+        let grid = ImageGrid {
+            rows: vec![0..5, 5..10],
+            cols: vec![0..5, 5..10],
+            stride: 1,
+        };
+        let img = RgbImage::from_pixel(10, 10, Rgb([0, 0, 0]));
+        assert!(detect_dominant_colors(&img, &grid).is_err());
+    }
+
+    #[test]
+    fn get_other_ratio_synthetic() {
+        let grid = SyntheticGrid::new(SYNTHETIC_BOARD).squares("Qx...\n.....\n.....\n.....\n.....");
+        let img = grid.build();
+        let ratio = |col: u32| {
+            let view = img.view(grid.cell_range(col).start, grid.cell_range(0).start, 40, 40);
+            get_other_ratio(&view, &Rgb([170, 0, 0]), 1)
+        };
+        assert!(ratio(0) >= QUEEN_OTHER_RATIO);
+        assert!((X_OTHER_RATIO..QUEEN_OTHER_RATIO).contains(&ratio(1)));
+        assert!(ratio(2) < X_OTHER_RATIO);
+    }
+
+    #[test]
+    fn analyze_grid_image_synthetic() -> Result<()> {
+        let squares_str = "Qxxxx\nxx...\n.x.x.\n.....\n....x";
+        for (cell_size, line_thickness, noise) in iproduct!([24, 40, 64], [1, 3, 6], [0, 10]) {
+            let img = SyntheticGrid::new(SYNTHETIC_BOARD)
+                .squares(squares_str)
+                .cell_size(cell_size)
+                .line_thickness(line_thickness)
+                .noise(noise)
+                .build();
+            let queens_file = analyze_grid_image(&img).with_context(|| {
+                format!("Analyzing cell size {cell_size} thickness {line_thickness} noise {noise}")
+            })?;
+            assert_eq!(format!("{}", queens_file.board), SYNTHETIC_BOARD);
+            assert_eq!(
+                format!("{}", queens_file.squares.unwrap()),
+                squares_str.replace('.', " "),
+                "Squares for cell size {cell_size} thickness {line_thickness} noise {noise}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn analyze_grid_image_phases_synthetic() -> Result<()> {
+        let synthetic =
+            SyntheticGrid::new(SYNTHETIC_BOARD).squares("Q....\n.x...\n.....\n.....\n.....");
+        let img = synthetic.build();
+        let grid = detect_grid(&img)?;
+        assert_eq!(grid.size(), 5);
+        assert_eq!(grid.stride, 1);
+        assert_eq!(grid.rows, grid.cols);
+        assert_eq!(grid.cols[1], synthetic.cell_range(1));
+
+        let colors = detect_dominant_colors(&img, &grid)?;
+        assert_eq!(colors.len(), 25);
+        let glyphs = detect_glyphs(&img, &grid, &colors);
+        assert_eq!(glyphs[0], Some(SquareVal::Queen));
+        assert_eq!(glyphs[6], Some(SquareVal::X));
+        assert_eq!(glyphs.iter().flatten().count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn analyze_grid_image_reconciles_color_count() -> Result<()> {
+        // A stray square of an extra color joins the closest color it touches.
+        let img = SyntheticGrid::new("rrrr\nrggg\nbbbb\nyyyR").build();
+        assert_eq!(
+            analyze_grid_image(&img)?.board.to_string(),
+            "rrrr\nrggg\nbbbb\nyyyy"
+        );
+
+        // A color in two separate pieces is split into two colors.
+        let img = SyntheticGrid::new("rrrr\nrggg\nbbbb\nrrrr").build();
+        let board = analyze_grid_image(&img)?.board;
+        assert_eq!(board.all_colors().len(), 4);
+        assert_ne!(board.color(&(0, 0)), board.color(&(3, 0)));
+        assert!((0..4).all(|c| board.color(&(3, c)) == board.color(&(3, 0))));
+
+        // With no separate pieces to split, a missing color can't be found.
+        let img = SyntheticGrid::new("rrrr\nrrrr\ngggg\nbbbb").build();
+        assert!(analyze_grid_image(&img).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn analyze_grid_image_rejects_non_square_grid() {
+        let img = SyntheticGrid::new(SYNTHETIC_BOARD).build();
+        let cropped = img.view(0, 0, img.width(), img.height() - 42).to_image();
+        assert!(analyze_grid_image(&cropped).is_err());
+    }
+
+    #[test]
+    fn analyze_grid_image_any_orientation_upright() -> Result<()> {
+        let img = image::open("games/linkedin-1.png")?.to_rgb8();
+        let (queens_file, orientation) = analyze_grid_image_any_orientation(&img)?;
+        assert_eq!(orientation, Orientation::NoTransforms);
+        assert_eq!(queens_file.board.size(), 8);
+        Ok(())
+    }
+
+    #[test]
+    fn analyze_grid_image_any_orientation_rotated() -> Result<()> {
+        let img = image::open("games/linkedin-1.png")?.to_rgb8();
+        let upright = analyze_grid_image(&img)?;
+        for (rotated, expected) in [
+            (imageops::rotate90(&img), Orientation::Rotate270),
+            (imageops::rotate180(&img), Orientation::Rotate180),
+            (imageops::rotate270(&img), Orientation::Rotate90),
+        ] {
+            let (queens_file, orientation) = analyze_grid_image_any_orientation(&rotated)?;
+            assert_eq!(orientation, expected);
+            assert_eq!(queens_file.board, upright.board, "{expected:?}");
+            assert_eq!(
+                format!("{}", queens_file.squares.unwrap()),
+                format!("{}", upright.squares.as_ref().unwrap())
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn analyze_grid_image_downscales_huge_images() -> Result<()> {
+        let img = image::open("games/linkedin-1.png")?.to_rgb8();
+        let huge_img = imageops::resize(
+            &img,
+            img.width() * 4,
+            img.height() * 4,
+            imageops::FilterType::Nearest,
+        );
+        let queens_file = analyze_grid_image(&img)?;
+        let huge_queens_file = analyze_grid_image(&huge_img)?;
+        assert_eq!(queens_file.board, huge_queens_file.board);
+        assert_eq!(
+            format!("{}", queens_file.squares.unwrap()),
+            format!("{}", huge_queens_file.squares.unwrap())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn downscale_darkest_keeps_thin_lines() {
+        let img = RgbImage::from_fn(9, 9, |x, _| {
+            if x == 4 {
+                Rgb([0, 0, 0])
+            } else {
+                Rgb([255, 255, 255])
+            }
+        });
+        let small_img = downscale_darkest(&img, 3);
+        assert_eq!(small_img.dimensions(), (3, 3));
+        assert!((0..3).all(|y| is_black(small_img.get_pixel(1, y))));
+        assert!((0..3).all(|y| !is_black(small_img.get_pixel(0, y))));
+    }
+
+    #[test]
+    fn analyze_grid_image_any_orientation_fails_without_grid() {
+        let img = RgbImage::from_pixel(100, 50, Rgb([255, 255, 255]));
+        assert!(analyze_grid_image_any_orientation(&img).is_err());
+    }
+}
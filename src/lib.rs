@@ -5,21 +5,19 @@
 //! This library is designed to solve [Queens puzzles](<https://www.linkedin.com/games/queens>),
 //! with a few key characteristics:
 //!
-//! * **Human-understandable**: Humans solve queens by iteratively eliminating and confirming squares. This library does the same process; it doesn't use and do any sort of search-algorithms to try and find the solution from afar.
+//! * **Human-understandable**: Humans solve queens by iteratively eliminating and confirming squares. By default the solver does the same process, one heuristic at a time, and never guesses. Search is opt-in: [search::all_solutions] and the uniqueness checks in [verify] try placements to find solutions from afar (for checking a board, generating one, or spotting mistakes), and [OnStall::Search][stall::OnStall::Search] lets the solver fall back on search when the heuristics stall.
 //! * **Speed**: Within the bounds of the above, it tries to be as fast as possible. This means, for example, it uses bitfields rather than HashSets for efficient operations on small sets.
 //! - **Tested**: While tracing code and error recovery means this library doesn't have 100% code coverage, it aspires to be as well-tested as possible. If `cargo test` passes, then we should be confident things work.
 //! - **Documented**: The `qsolve` binary should have clear documentation available with `--help` for every subcommand. The `qsolve` library should have clear documentation (including doctests) for all public functionality.
 //!
 //! # Example
 //!
-//! Basic usage of the library looks something like this:
+//! Basic usage of the library looks something like this, where the [prelude]
+//! brings in the types and functions most uses need:
 //!
 //! ```
 //! # use std::path::PathBuf;
-//! # use qsolve::file::QueensFile;
-//! # use qsolve::heuristic::all_heuristics;
-//! # use qsolve::solveiter::solve_iter;
-//! # use qsolve::solvestate::{SolveState, SolveStrategy};
+//! use qsolve::prelude::*;
 //! # use anyhow::Result;
 //! # fn main() -> Result<()> {
 //! // Parse a text file containing a Queens puzzle.
@@ -39,33 +37,174 @@
 //! # }
 //! ```
 
+/// Frames for animating the process of solving a game.
+pub mod animate;
+
+/// A compact binary encoding of boards and their markings.
+pub mod binary;
+
 /// Structs to represent Queens boards.
 pub mod board;
 
+/// An on-disk cache of solutions to previously solved boards.
+pub mod cache;
+
+/// Command-line argument parsing for the `qsolve` binary, for other binaries
+/// that want to embed its subcommands.
+#[cfg(feature = "cli")]
+pub mod cli;
+
+/// Clue numbers on rows and columns, for the sandwich variant of Queens.
+pub mod clue;
+
+/// Logic to compare two solve states for the same board.
+pub mod compare;
+
+/// Explanations of why a partially solved puzzle can't be finished.
+pub mod conflict;
+
+/// Downloads of freely licensed boards from a community corpus.
+pub mod corpus;
+
 /// Data structures for efficient manipuations of rows, cols, colors and coords.
 pub mod datastructure;
 
+/// Grading how hard puzzles are, for sorting them.
+pub mod difficulty;
+
+/// Reports on where the solver stalls across a directory of boards.
+pub mod discover;
+
+/// Painting the colors of a new board one square at a time.
+pub mod editor;
+
+/// Duels, where two players take turns resolving squares of one puzzle.
+pub mod duel;
+
+/// Measures of how constrained a partially solved puzzle still is.
+pub mod entropy;
+
+/// Typed errors used to distinguish between kinds of failure.
+pub mod error;
+
+/// Estimates of how long a human would take to solve a puzzle.
+pub mod estimate;
+
 /// Logic to represent an underlying file containing a Queens game.
 pub mod file;
 
+/// Callbacks that veto or trim the changes heuristics propose while solving.
+pub mod filter;
+
+/// Fitting rendered boards and text into the width of a terminal.
+pub mod fit;
+
+/// Random generation of new boards, graded by how hard they are to solve.
+pub mod generate;
+
+/// Heatmaps of how often each square was touched while solving.
+pub mod heatmap;
+
 /// Heuristics used to solve the Queens game.
 pub mod heuristic;
 
 /// Image parsing logic to allow screenshots of Queens games to be used.
 pub mod image;
 
+/// Links that carry a whole puzzle, for sharing without a file.
+pub mod link;
+
+/// Lists of games with the results the solver is expected to get on them.
+pub mod manifest;
+
+/// Counters of the solver's work, for performance analysis.
+pub mod metrics;
+
+/// Several boards tiled into one image, with captions.
+pub mod montage;
+
+/// How squares are written for people, and parsed from what they type.
+pub mod notation;
+
+/// Named lists of puzzles to be played one after another.
+pub mod pack;
+
+/// Solved Queens drawn back onto the screenshot they were read from.
+pub mod overlay;
+
+/// Themes that decide what colors the board is drawn with.
+pub mod palette;
+
+/// Previews of screenshots drawn in the terminal.
+#[cfg(feature = "preview")]
+pub mod preview;
+
+/// An adapter trait for grid puzzles, with a simple solving loop that
+/// puzzles other than Queens can share.
+pub mod puzzle;
+
+/// The types and functions most uses of the library need.
+pub mod prelude;
+
+/// QR codes, for opening puzzle links on another device.
+pub mod qr;
+
+/// A record of puzzles played in practice mode, and statistics about them.
+pub mod practice;
+
+/// Certificates of how a puzzle is solved, which can be checked without
+/// running the solver.
+pub mod proof;
+
+/// The running version of qsolve, and checking for newer releases.
+pub mod release;
+
+/// Suggested fixes for boards whose color regions are split apart.
+pub mod repair;
+
+/// A seeded random number generator, for reproducible solves.
+pub mod rng;
+
+/// Exhaustive search for every solution to a board.
+pub mod search;
+
+/// A puzzle being solved interactively, with undo.
+pub mod session;
+
+/// The result of running the solver to completion.
+pub mod solution;
+
 /// Iterators for moving through the process of solving a game.
 pub mod solveiter;
 
 /// Structs to represent intermediate states of solving a Queens puzzle.
 pub mod solvestate;
 
+/// Hiding hints and answers so they can be passed on without spoiling them.
+pub mod spoiler;
+
+/// Analysis of the rotational and reflective symmetries of a puzzle.
+pub mod symmetry;
+
 /// Representation of different square colors and associated display logic.
 pub mod squarecolor;
 
 /// Logic to generate the share text for a solved puzzle.
 pub mod share;
 
+/// Handlers that decide what to do when the solver stalls.
+pub mod stall;
+
+/// Scoring for tournaments, where every puzzle in a pack is played in turn.
+pub mod tournament;
+
+/// A tracing layer that logs the solver's spans and writes them as JSON lines, for performance analysis.
+#[cfg(feature = "cli")]
+pub mod trace;
+
+/// Checking whether a board has exactly one solution.
+pub mod verify;
+
 // Use doc_comment to ensure code snippets in the readme compile.
 extern crate doc_comment;
 doc_comment::doctest!("../README.md");
@@ -0,0 +1,101 @@
+use anyhow::{Context, Result};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+
+use crate::{binary, error::QSolveError, file::QueensFile, solvestate::SolveState};
+
+/// The scheme that starts every puzzle link.
+pub const LINK_SCHEME: &str = "qsolve://";
+
+/// Returns the given state and its board as URL-safe base64, suitable for
+/// the fragment of a URL or for pasting into a chat.
+pub fn to_fragment(solve_state: &SolveState) -> String {
+    URL_SAFE_NO_PAD.encode(binary::to_bytes(solve_state))
+}
+
+/// Returns a `qsolve://` link to the given state and its board, which
+/// [from_link] reads back.
+///
+/// # Examples
+/// ```
+/// # use qsolve::file::QueensFile;
+/// # use qsolve::link::{from_link, to_link};
+/// # use qsolve::solvestate::SolveState;
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let queens_file = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+/// let link = to_link(&SolveState::from(&queens_file));
+/// assert!(link.starts_with("qsolve://"));
+/// assert_eq!(from_link(&link)?.board.fingerprint(), queens_file.board.fingerprint());
+/// # Ok(())
+/// # }
+/// ```
+pub fn to_link(solve_state: &SolveState) -> String {
+    format!("{LINK_SCHEME}{}", to_fragment(solve_state))
+}
+
+/// Returns whether the given string looks like a puzzle link rather than a
+/// path: a `qsolve://` link, a `#` fragment, or nothing but URL-safe base64.
+///
+/// A string of plain base64 could also be the name of a file, so callers
+/// should check for a file first.
+pub fn is_link(s: &str) -> bool {
+    let fragment = strip_link(s);
+    !fragment.is_empty()
+        && fragment
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Returns the base64 from a link, without its scheme or `#`.
+fn strip_link(s: &str) -> &str {
+    let s = s.trim();
+    s.strip_prefix(LINK_SCHEME)
+        .or_else(|| s.strip_prefix('#'))
+        .unwrap_or(s)
+}
+
+/// Reads a board and its markings from a link returned by [to_link], or a
+/// fragment returned by [to_fragment], with or without a leading `#`.
+///
+/// Failures carry [QSolveError::Parse] if the link isn't a board, or
+/// [QSolveError::InvalidBoard] if it is but it breaks the rules.
+pub fn from_link(link: &str) -> Result<QueensFile> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(strip_link(link))
+        .context("Puzzle link is not valid base64")
+        .context(QSolveError::Parse)?;
+    binary::from_bytes(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_roundtrip_in_every_form() -> Result<()> {
+        let queens_file = QueensFile::try_from_text_file(&"games/linkedin-1-partial.txt".into())?;
+        let solve_state = SolveState::from(&queens_file);
+        let fragment = to_fragment(&solve_state);
+        for link in [
+            to_link(&solve_state),
+            fragment.clone(),
+            format!("#{fragment}"),
+            format!(" {fragment}\n"),
+        ] {
+            assert!(is_link(&link), "{link}");
+            let decoded = from_link(&link)?;
+            assert_eq!(
+                SolveState::from(&decoded).to_string(),
+                solve_state.to_string()
+            );
+        }
+        assert!(!is_link("games/linkedin-1-partial.txt"));
+        assert!(!is_link("qsolve://"));
+        assert_eq!(
+            QSolveError::from_error(&from_link("qsolve://AAAA").unwrap_err()),
+            Some(QSolveError::Parse)
+        );
+        Ok(())
+    }
+}
@@ -1,242 +1,164 @@
 use std::{
+    collections::BTreeMap,
     ffi::OsStr,
-    time::{Duration, Instant},
+    io::{BufRead, IsTerminal, Write},
+    path::Path,
+    process::ExitCode,
+    str::FromStr,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use anyhow::Result;
-use clap::{Args, Parser, Subcommand, ValueEnum};
+use anyhow::{Context, Result, anyhow, bail, ensure};
+use clap::Parser;
 use crossterm::{
-    cursor::{Hide, MoveUp, Show},
-    execute,
+    cursor::{Hide, MoveDown, MoveTo, MoveToColumn, MoveUp, Show},
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute, queue,
     style::Print,
-    terminal::Clear,
-};
-use log::debug;
-use qsolve::heuristic::{Heuristic, all_heuristics};
-use qsolve::share::generate_share_content;
-use qsolve::solvestate::{Charset, SolveState, SolveStrategy};
-use qsolve::{datastructure::CoordSet, solveiter::SolveIterItem};
-use qsolve::{file::QueensFile, solveiter::solve_iter};
-
-#[derive(Parser)]
-#[command(version, about, propagate_version = true)]
-struct Cli {
-    #[command(subcommand)]
-    command: Commands,
-}
-
-#[derive(Debug, Subcommand)]
-enum Commands {
-    /// Prints out the board
-    Print {
-        #[command(flatten)]
-        path_args: PathCli,
-
-        #[command(flatten)]
-        display_args: DisplayCli,
-    },
-
-    /// Display an animation of the solving of the board
-    Animate {
-        #[command(flatten)]
-        path_args: PathCli,
-
-        #[command(flatten)]
-        display_args: DisplayCli,
-
-        #[command(flatten)]
-        solve_args: SolveCli,
-
-        /// The length of delay between animation steps, in ms
-        #[clap(long, value_parser = |s: &str| s.parse().map(Duration::from_millis), default_value = "500")]
-        delay: Duration,
-    },
-
-    /// Solve the board and display the solution
-    Solve {
-        #[command(flatten)]
-        path_args: PathCli,
-
-        #[command(flatten)]
-        display_args: DisplayCli,
-
-        #[command(flatten)]
-        solve_args: SolveCli,
-
-        /// Generate a share text, with the provided string as the name
-        #[clap(long, num_args = 0..=1, require_equals = true, default_missing_value = "")]
-        share: Option<String>,
-    },
-
-    /// Solve boards repeatedly for profiling
-    Profile {
-        #[command(flatten)]
-        path_args: PathCli,
-
-        #[command(flatten)]
-        solve_args: SolveCli,
-
-        /// How many iterations to run
-        #[clap(long, default_value_t = 1)]
-        iterations: usize,
+    terminal::{
+        Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode,
+        enable_raw_mode,
     },
+};
+use itertools::Itertools;
+use log::{debug, info, warn};
+use qsolve::animate::{Frame, FrameOptions, frames_from};
+use qsolve::board::Region;
+use qsolve::cache::SolutionCache;
+use qsolve::cli::{
+    Cli, Commands, CorpusCommand, DebugCommand, DisplayCli, DuelCommand, FileType, HeatmapFormat,
+    HintType, HistoryCommand, OutputCli, OutputFormat, PathCli, PlayCli, PreviewProtocol,
+    ReplCommand, SelfCommand, ShareCli, SolveCli, image_from_file_type, queens_file_from_file_type,
+    queens_file_from_path, read_source,
+};
+use qsolve::compare::Comparison;
+use qsolve::conflict::Conflict;
+use qsolve::corpus::{self, CorpusIndex};
+use qsolve::datastructure::CoordSet;
+use qsolve::difficulty::Grade;
+use qsolve::discover::DiscoveryReport;
+use qsolve::duel::{Duel, DuelStatus, parse_move};
+use qsolve::editor::Editor;
+use qsolve::error::QSolveError;
+use qsolve::estimate::{TimeModel, format_minutes};
+use qsolve::file::QueensFile;
+use qsolve::fit::{fit_to_width, side_by_side, square_position, wrapped_lines};
+use qsolve::generate::{Difficulty, generate_board};
+use qsolve::heatmap::{Heatmap, HeatmapLayer};
+use qsolve::heuristic::{
+    Changes, Evaluation, HEURISTIC_CATALOG, Heuristic, PreviewStep, preview_step_with_rng,
+    top_heuristics,
+};
+use qsolve::image::{detect_grid, rotation_degrees};
+use qsolve::link::{to_fragment, to_link};
+use qsolve::metrics::SolveMetrics;
+use qsolve::montage::Montage;
+use qsolve::notation::CoordStyle;
+use qsolve::overlay::overlay_queens;
+use qsolve::pack::Pack;
+use qsolve::palette::Palette;
+use qsolve::practice::{AnonymizedStats, PracticeHistory, PracticeResult, PracticeStats};
+use qsolve::proof::Proof;
+use qsolve::qr::QrCode;
+use qsolve::release::{Channel, UpdateCheck, current_version};
+use qsolve::repair::ColorEdit;
+use qsolve::rng::SeededRng;
+use qsolve::session::{MarkingMode, Session};
+use qsolve::share::{ShareOptions, SharePayload, generate_share_content};
+use qsolve::solution::{Solution, SolveOutcome};
+use qsolve::solveiter::solve_iter_with_options;
+use qsolve::solvestate::{HighlightStyle, SolveOptions, SolveState, SolveStrategy, SquareVal};
+use qsolve::spoiler::SpoilerStyle;
+use qsolve::squarecolor::SquareColor;
+use qsolve::tournament::{Penalties, PuzzleScore, Scoreboard};
+use qsolve::trace::TraceLayer;
+use qsolve::verify::{Uniqueness, count_solutions};
+use tracing_subscriber::{Registry, layer::SubscriberExt};
 
-    /// Provide a hint about the next move on the board
-    Hint {
-        #[command(flatten)]
-        path_args: PathCli,
-
-        #[command(flatten)]
-        display_args: DisplayCli,
-
-        #[command(flatten)]
-        solve_args: SolveCli,
-
-        /// The type of hint that should be provided
-        #[clap(long, default_value = "both")]
-        hint_type: HintType,
-    },
-}
-
-#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
-enum HintType {
-    /// Show only the heuristic used, and not the resulting change.
-    Heuristic,
-    /// Show only the resulting change, and not the heuristic used.
-    Result,
-    /// Show both the heuristic used and the resulting change.
-    #[default]
-    Both,
-}
-
-#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
-/// What type of file to read
-enum FileType {
-    /// Automatically detect based on file extension
-    #[default]
-    Auto,
-    /// Force text file parsing
-    Text,
-    /// Force image file parsing
-    Image,
-}
-
-#[derive(Args, Debug)]
-struct PathCli {
-    /// The path to the file containing the board
-    path: std::path::PathBuf,
-
-    /// What type of file to read
-    #[clap(long, default_value = "auto")]
-    file_type: FileType,
-
-    /// Whether we should use the Queens and Xs in the file, or
-    /// clear it to be an empty board
-    #[clap(long, default_value = "false")]
-    clear: bool,
-}
-
-#[derive(Args, Debug)]
-struct DisplayCli {
-    #[clap(long, default_value = "unicode")]
-    /// What charset to use when displaying the board
-    charset: Charset,
-}
-
-#[derive(Args, Debug)]
-struct SolveCli {
-    #[clap(long, default_value = "fast")]
-    /// What strategy to use for solving the puzzle
-    strategy: SolveStrategy,
-}
-
-fn queens_file_from_path(path_args: &PathCli) -> Result<QueensFile> {
-    let qf = match path_args.file_type {
-        FileType::Text => QueensFile::try_from_text_file(&path_args.path),
-        FileType::Image => QueensFile::try_from_image_file(&path_args.path),
-        FileType::Auto => QueensFile::try_from_text_file(&path_args.path)
-            .or_else(|_| QueensFile::try_from_image_file(&path_args.path)),
-    }?;
-    if path_args.clear {
-        Ok(QueensFile {
-            board: qf.board,
-            squares: None,
-        })
-    } else {
-        Ok(qf)
-    }
+/// Renders the given state without highlights, drawing the overlay chosen
+/// on the command line and fitting it to the output's width.
+fn render(solve_state: &SolveState, display_args: &DisplayCli) -> Result<String> {
+    Ok(display_args.fit(&solve_state.ansi_string_with_highlights(
+        &solve_state.overlay_highlights(display_args.overlay),
+        display_args.charset,
+        display_args.palette,
+    )?))
 }
 
 /// Top-level entry point for the print subcommand.
 fn print(path_args: &PathCli, display_args: &DisplayCli) -> Result<()> {
     let queens_file = queens_file_from_path(path_args)?;
     let solve_state = SolveState::from(&queens_file);
-    println!(
-        "{}",
-        solve_state.ansi_string(CoordSet::default(), display_args.charset)?
-    );
+    println!("{}", render(&solve_state, display_args)?);
     Ok(())
 }
 
-/// Helper function to print a given [SolveIterItem] as part of the
-/// animate command.
-fn print_animated_iter_item(
-    solve_iter_item: &SolveIterItem,
-    charset: Charset,
+/// Helper function to print a given [Frame] as part of the animate command,
+/// then erase its caption unless it is the last one.
+///
+/// The first frame's board is printed in full, fitted to the given width.
+/// Later frames only redraw the squares that changed since the previous
+/// frame, which keeps large boards from flickering, before reprinting the
+/// lines below the board. The cursor is left at the start of the line below
+/// the board, ready for the next one.
+fn print_animated_frame(
+    frame: &Frame,
+    previous: Option<&Frame>,
+    size: usize,
+    width: usize,
     delay: Duration,
+    last: bool,
 ) -> Result<()> {
     let mut stdout = std::io::stdout();
-    let size: u16 = (solve_iter_item.solve_state.board.size())
-        .try_into()
-        .unwrap();
-
-    execute!(
-        stdout,
-        Print(
-            solve_iter_item
-                .solve_state
-                .ansi_string(CoordSet::default(), charset)
-                .unwrap()
-        ),
-        Print("\n"),
-    )?;
-    std::thread::sleep(delay);
-    execute!(
+    let board_lines = square_position((size - 1, size - 1), size, width).0 + 1;
+    match previous {
+        None => queue!(stdout, Print(fit_to_width(&frame.ansi, width)), Print("\n"))?,
+        Some(previous) => {
+            for (coord, square) in frame.changed_squares(previous) {
+                let (line, c) = square_position(coord, size, width);
+                let (up, col) = (u16::try_from(board_lines - line)?, u16::try_from(c)?);
+                queue!(
+                    stdout,
+                    MoveUp(up),
+                    MoveToColumn(col),
+                    Print(square),
+                    MoveDown(up),
+                    MoveToColumn(0),
+                )?;
+            }
+        }
+    }
+    queue!(
         stdout,
-        MoveUp(size),
-        Print(
-            solve_iter_item
-                .solve_state
-                .ansi_string(
-                    solve_iter_item
-                        .next_heuristic
-                        .map(|h| h.seen_coords(&solve_iter_item.solve_state))
-                        .unwrap_or_default(),
-                    charset
-                )
-                .unwrap()
-        ),
-        Print("\n"),
         Clear(crossterm::terminal::ClearType::CurrentLine),
-        Print(
-            solve_iter_item
-                .next_heuristic
-                .map_or("Done!\n".to_string(), Heuristic::description)
-        ),
-        Print("\n"),
+        Print(format!("{:.0}% decided\n", frame.progress * 100.0)),
     )?;
-    if solve_iter_item.next_heuristic.is_none() {
+    if let Some(caption) = &frame.caption {
+        queue!(
+            stdout,
+            Clear(crossterm::terminal::ClearType::CurrentLine),
+            Print(caption),
+            Print("\n"),
+        )?;
+    }
+    stdout.flush()?;
+    if last {
         return Ok(());
     }
     std::thread::sleep(delay);
-    execute!(
-        stdout,
-        MoveUp(1),
-        Clear(crossterm::terminal::ClearType::CurrentLine),
-        MoveUp(1),
-        Clear(crossterm::terminal::ClearType::CurrentLine),
-        MoveUp(size),
-    )?;
+    let caption_lines = frame
+        .caption
+        .as_deref()
+        .map_or(0, |caption| wrapped_lines(caption, width));
+    for _ in 0..caption_lines {
+        queue!(
+            stdout,
+            MoveUp(1),
+            Clear(crossterm::terminal::ClearType::CurrentLine)
+        )?;
+    }
+    // The progress line.
+    execute!(stdout, MoveUp(1))?;
     Ok(())
 }
 
@@ -246,10 +168,12 @@ fn animate(
     display_args: &DisplayCli,
     solve_args: &SolveCli,
     delay: &Duration,
+    from_hints: usize,
+    seed: u64,
 ) -> Result<()> {
     let queens_file = queens_file_from_path(path_args)?;
     let solve_state = SolveState::from(&queens_file);
-    let heuristics = all_heuristics(solve_state.board);
+    let heuristics = solve_args.heuristics(solve_state.board);
 
     let mut stdout = std::io::stdout();
     ctrlc::set_handler(move || {
@@ -259,11 +183,28 @@ fn animate(
     })?;
     execute!(stdout, Hide)?;
 
-    for solve_iter_item in solve_iter(solve_state, solve_args.strategy, &heuristics) {
-        print_animated_iter_item(&solve_iter_item, display_args.charset, *delay)?;
+    let size = solve_state.board.size();
+    let width = display_args.width();
+    let frame_options = FrameOptions {
+        charset: display_args.charset,
+        palette: display_args.palette,
+        ..FrameOptions::default()
+    };
+    let mut steps =
+        solve_iter_with_options(solve_state, solve_args.solve_options(seed), &heuristics);
+    let mut frames = frames_from(steps.by_ref(), from_hints, frame_options).peekable();
+    let mut previous = None;
+    while let Some(frame) = frames.next() {
+        let last = frames.peek().is_none();
+        print_animated_frame(&frame, previous.as_ref(), size, width, *delay, last)?;
+        previous = Some(frame);
     }
     execute!(stdout, Show)?;
-    Ok(())
+    drop(frames);
+    match steps.take_error() {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
 }
 
 /// Top-level entry point for the solve subcommand.
@@ -271,116 +212,1848 @@ fn solve(
     path_args: &PathCli,
     display_args: &DisplayCli,
     solve_args: &SolveCli,
-    share: &Option<String>,
+    share_args: &ShareCli,
+    output_args: &OutputCli,
+    no_cache: bool,
+    seed: u64,
 ) -> Result<()> {
     let start_time = Instant::now();
     let queens_file = queens_file_from_path(path_args)?;
     let solve_state = SolveState::from(&queens_file);
-    let heuristics = all_heuristics(solve_state.board);
-    let state_iter_items =
-        solve_iter(solve_state, solve_args.strategy, &heuristics).collect::<Vec<_>>();
-    let final_state = &state_iter_items.iter().last().unwrap().solve_state;
-    let elapsed = start_time.elapsed();
-    println!(
-        "{}",
-        final_state.ansi_string(CoordSet::default(), display_args.charset)?
+    if let Some(protocol) = output_args.preview {
+        ensure!(
+            output_args.format == OutputFormat::Text,
+            "--preview only works with text output"
+        );
+        preview(path_args, protocol, &solve_state, display_args)?;
+    }
+    ensure!(
+        output_args.spoiler_safe.is_none() || output_args.format == OutputFormat::Text,
+        "--spoiler-safe only works with text output"
     );
-    debug!("Solve complete.");
-    if let Some(share_text) = share {
-        debug!("Generating share text.");
-        let puzzle_name = if !share_text.is_empty() {
-            share_text.clone()
-        } else {
-            path_args
-                .path
-                .file_stem()
-                .and_then(OsStr::to_str)
-                .unwrap_or("")
-                .to_string()
-        };
+    let time_model = match &output_args.time_model {
+        Some(path) => TimeModel::try_from_file(path)?,
+        None => TimeModel::default(),
+    };
+    // The share payload is verified by solving again with just the strategy,
+    // so it can't describe a solve that looked ahead, used advanced
+    // heuristics, or made its random choices from another seed or with
+    // another chance of overlooking deductions.
+    ensure!(
+        solve_args.lookahead == 0 || !share_args.share_payload,
+        "--share-payload cannot be used with --lookahead"
+    );
+    ensure!(
+        !solve_args.advanced || !share_args.share_payload,
+        "--share-payload cannot be used with --advanced"
+    );
+    ensure!(
+        seed == 0 || solve_args.strategy != SolveStrategy::Human || !share_args.share_payload,
+        "--share-payload cannot be used with --seed for the human strategy"
+    );
+    ensure!(
+        solve_args.miss_percent == SolveOptions::default().miss_percent
+            || solve_args.strategy != SolveStrategy::Human
+            || !share_args.share_payload,
+        "--share-payload cannot be used with --miss-percent for the human strategy"
+    );
+    // The cache only knows how to solve the board from blank, so it can't
+    // tell that a puzzle was already solved, and its step counts are for
+    // the default heuristics.
+    let cache = if no_cache || solve_args.advanced || solve_state.complete() {
+        None
+    } else {
+        SolutionCache::in_default_dir()
+    };
+    let solve_options = solve_args.solve_options(seed);
+
+    if let Some(rotation) = queens_file.rotation
+        && output_args.format == OutputFormat::Text
+        && !output_args.quiet
+    {
         println!(
-            "{}",
-            generate_share_content(&state_iter_items, &puzzle_name, elapsed)
+            "Found the grid after rotating the image {}\u{b0}",
+            rotation_degrees(rotation)
         );
     }
+
+    // The cache only knows the solution and step count, which is all that
+    // the plain and quiet text outputs need.
+    let cache_covers_output = output_args.format == OutputFormat::Text
+        && !output_args.summary
+        && !output_args.heuristic_table
+        && share_args.share.is_none();
+    if let Some(cached) = cache
+        .as_ref()
+        .filter(|_| cache_covers_output)
+        .and_then(|cache| cache.get(&solve_state, &solve_options))
+    {
+        debug!("Using cached solution.");
+        let cached_state = cached.solve_state(solve_state.board);
+        if output_args.quiet {
+            println!("{cached}");
+        } else if confirm_reveal(
+            output_args.spoiler_safe,
+            "Queens",
+            &display_args
+                .coord_style()
+                .squares_text(&cached_state.queens()),
+        )? {
+            println!(
+                "{}",
+                display_args.fit(&cached_state.ansi_string(
+                    CoordSet::default(),
+                    display_args.charset,
+                    display_args.palette
+                )?)
+            );
+        }
+        return Ok(());
+    }
+
+    let heuristics = solve_args.heuristics(solve_state.board);
+    let mut solution =
+        Solution::solve_with_options(solve_state.clone(), solve_options, &heuristics);
+    if let Some(err) = solution.error.take() {
+        return Err(err);
+    }
+    let elapsed = start_time.elapsed();
+    // Estimate how long a person would take from the way a person would
+    // solve it, whichever strategy found the solution shown.
+    let expected = match solve_options.strategy {
+        SolveStrategy::Human => time_model.estimate(&solution),
+        _ => {
+            let human_options = SolveOptions {
+                strategy: SolveStrategy::Human,
+                ..solve_options
+            };
+            time_model.estimate(&Solution::solve_with_options(
+                solve_state,
+                human_options,
+                &heuristics,
+            ))
+        }
+    };
+    if let Some(cache) = &cache
+        && let Err(err) = cache.put(&solution, &solve_options)
+    {
+        warn!("Could not cache solution: {err:#}");
+    }
+    let share_content = share_args
+        .share
+        .as_ref()
+        .filter(|_| solution.is_solved())
+        .map(|share_text| {
+            debug!("Generating share text.");
+            let puzzle_name = if !share_text.is_empty() {
+                share_text.clone()
+            } else {
+                path_args
+                    .path
+                    .as_deref()
+                    .and_then(Path::file_stem)
+                    .and_then(OsStr::to_str)
+                    .unwrap_or("")
+                    .to_string()
+            };
+            let share_options = ShareOptions {
+                revealed_queens: share_args.share_queens,
+                style: share_args.share_style,
+                palette: display_args.palette,
+                expected: Some(expected),
+                path_steps: share_args.share_path,
+            };
+            let payload = share_args
+                .share_payload
+                .then(|| SharePayload::new(&solution.steps, elapsed, solve_args.strategy).encode());
+            (
+                generate_share_content(&solution.steps, &puzzle_name, elapsed, &share_options),
+                payload,
+            )
+        });
+
+    match output_args.format {
+        OutputFormat::Json => {
+            let mut json = solution.to_json_with(&display_args.json_coord_style());
+            json["estimated_human_secs"] = expected.as_secs().into();
+            if let Some(rotation) = queens_file.rotation {
+                json["image_rotation_degrees"] = rotation_degrees(rotation).into();
+            }
+            if let Some((share_text, payload)) = share_content {
+                json["share"] = share_text.into();
+                json["share_payload"] = payload.into();
+            }
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        OutputFormat::Text if output_args.quiet => println!("{solution}"),
+        OutputFormat::Text => {
+            if confirm_reveal(
+                output_args.spoiler_safe,
+                "Queens",
+                &display_args
+                    .coord_style()
+                    .squares_text(&solution.final_state.queens()),
+            )? {
+                println!(
+                    "{}",
+                    display_args.fit(&solution.final_state.ansi_string(
+                        CoordSet::default(),
+                        display_args.charset,
+                        display_args.palette
+                    )?)
+                );
+            }
+            if output_args.summary {
+                println!("{}", solution.summary());
+                println!("Estimated human time: {}", format_minutes(expected));
+            }
+            if output_args.heuristic_table {
+                println!("{}", solution.heuristic_table());
+            }
+            if solution.outcome() == SolveOutcome::AlreadySolved {
+                println!("The puzzle is already solved.");
+            }
+            if let Some((share_text, payload)) = share_content {
+                println!("{share_text}");
+                if let Some(payload) = payload {
+                    println!("Verify: {payload}");
+                }
+            }
+        }
+    }
+    if !solution.is_solved() {
+        // If the puzzle's own marks are to blame, say which ones.
+        if let Some(conflict) = Conflict::find(&queens_file) {
+            return Err(anyhow!(conflict.to_string()).context(QSolveError::Unsolvable));
+        }
+        bail!(QSolveError::Unsolvable);
+    }
+    debug!("Solve complete.");
     Ok(())
 }
 
+/// Prints the secret hidden in the given spoiler style, then asks whether to
+/// go on and reveal it, which is always the case without a style.
+fn confirm_reveal(spoiler_safe: Option<SpoilerStyle>, label: &str, secret: &str) -> Result<bool> {
+    let Some(style) = spoiler_safe else {
+        return Ok(true);
+    };
+    println!("{}", style.wrap(label, secret));
+    print!("Reveal it on the board? [y/N] ");
+    std::io::stdout().flush()?;
+    let mut line = String::new();
+    if std::io::stdin().read_line(&mut line)? == 0 {
+        println!();
+    }
+    Ok(matches!(line.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
 /// Top-level entry point for the hint subcommand.
+#[allow(clippy::too_many_arguments)]
 fn hint(
     path_args: &PathCli,
     display_args: &DisplayCli,
     solve_args: &SolveCli,
     hint_type: &HintType,
+    focus: &Option<Region>,
+    alternatives: &Option<usize>,
+    spoiler_safe: Option<SpoilerStyle>,
+    seed: u64,
 ) -> Result<()> {
     let queens_file = queens_file_from_path(path_args)?;
+    if let Some(region) = focus {
+        ensure!(
+            region.is_on(&queens_file.board),
+            anyhow!("--focus {region} is not on the board").context(QSolveError::Parse)
+        );
+    }
     let solve_state = SolveState::from(&queens_file);
-    let heuristics = all_heuristics(solve_state.board);
-    let mut state_iter_items = solve_iter(solve_state, solve_args.strategy, &heuristics);
-    let next_item = state_iter_items.next();
-    let Some(next_item) = next_item else {
-        println!("No next step found.");
+    if solve_state.complete() {
+        println!("The puzzle is already solved.");
         return Ok(());
-    };
-    let Some(next_heuristic) = next_item.next_heuristic else {
+    }
+    let heuristics = solve_args.heuristics(solve_state.board);
+    let region = focus
+        .map(|region| region.coords(solve_state.board))
+        .unwrap_or_else(|| *solve_state.board.all_coords());
+    let candidates = heuristics
+        .iter()
+        .map(|h| &**h)
+        .filter(|h| !h.seen_coords(&solve_state).intersection(&region).is_empty())
+        .collect::<Vec<_>>();
+    if let Some(n) = alternatives {
+        let top = top_heuristics(&solve_state, solve_args.strategy, &candidates, *n);
+        if top.is_empty() {
+            println!("No next step found.");
+        }
+        let lines = top
+            .into_iter()
+            .enumerate()
+            .map(|(i, (h, score))| {
+                format!(
+                    "{}. {} (decides {score} square{})",
+                    i + 1,
+                    h.description().replace('\n', " "),
+                    if score == 1 { "" } else { "s" }
+                )
+            })
+            .join("\n");
+        match spoiler_safe {
+            Some(style) if !lines.is_empty() => println!("{}", style.wrap("Hints", &lines)),
+            _ if !lines.is_empty() => println!("{lines}"),
+            _ => {}
+        }
+        return Ok(());
+    }
+    let solve_options = solve_args.solve_options(seed);
+    let step = preview_step_with_rng(
+        &solve_state,
+        &solve_options,
+        &candidates,
+        &mut solve_options.rng(),
+    );
+    let Some(PreviewStep {
+        heuristic,
+        changes,
+        next_state,
+    }) = step
+    else {
         println!("No next step found.");
         return Ok(());
     };
-    if hint_type == &HintType::Both || hint_type == &HintType::Heuristic {
+    let mut secret = heuristic.description();
+    if hint_type != &HintType::Heuristic {
+        secret.push_str(&format!(
+            "\nSquares: {}",
+            display_args
+                .coord_style()
+                .squares_text(&changes.changed_coords())
+        ));
+    }
+    if !confirm_reveal(spoiler_safe, "Hint", &secret)? {
+        return Ok(());
+    }
+    if hint_type == &HintType::Heuristic {
         println!(
             "{}",
-            next_item
-                .solve_state
-                .ansi_string(
-                    next_heuristic.seen_coords(&next_item.solve_state),
-                    display_args.charset
-                )
-                .unwrap()
+            display_args.fit(&solve_state.ansi_string(
+                heuristic.seen_coords(&solve_state),
+                display_args.charset,
+                display_args.palette
+            )?)
         );
-        println!("{}", next_heuristic.description());
+        println!("{}", heuristic.description());
+        return Ok(());
     }
-    if hint_type == &HintType::Both || hint_type == &HintType::Result {
-        let changes = next_heuristic.changes(&next_item.solve_state);
-        let Some(changes) = changes else {
-            println!("No next step found.");
-            return Ok(());
-        };
-        let following_item = state_iter_items.next();
-        let Some(following_item) = following_item else {
-            println!("No next step found.");
-            return Ok(());
-        };
+    if hint_type == &HintType::Result {
         println!(
             "{}",
-            following_item
-                .solve_state
-                .ansi_string(changes.changed_coords(), display_args.charset)
-                .unwrap()
+            display_args.fit(&next_state.ansi_string(
+                changes.changed_coords(),
+                display_args.charset,
+                display_args.palette
+            )?)
         );
+        return Ok(());
+    }
+    // Show the squares that were considered and the squares that changed in one frame.
+    let combined = || {
+        next_state
+            .ansi_string_with_highlights(
+                &[
+                    (changes.changed_coords(), HighlightStyle::CHANGED),
+                    (heuristic.seen_coords(&solve_state), HighlightStyle::SEEN),
+                ],
+                display_args.charset,
+                display_args.palette,
+            )
+            .map(|frame| display_args.fit(&frame))
+    };
+    let frame = match hint_type {
+        // Show the squares that were considered beside the squares that
+        // changed, if there is room for both.
+        HintType::Both => {
+            let seen = solve_state.ansi_string_with_highlights(
+                &[(heuristic.seen_coords(&solve_state), HighlightStyle::SEEN)],
+                display_args.charset,
+                display_args.palette,
+            )?;
+            let result = next_state.ansi_string_with_highlights(
+                &[(changes.changed_coords(), HighlightStyle::CHANGED)],
+                display_args.charset,
+                display_args.palette,
+            )?;
+            match side_by_side(
+                &seen,
+                &result,
+                display_args.charset.arrow(),
+                display_args.width(),
+            ) {
+                Some(frame) => frame,
+                None => combined()?,
+            }
+        }
+        _ => combined()?,
+    };
+    println!("{frame}");
+    println!("{}", heuristic.description());
+    Ok(())
+}
+
+/// Draws the screenshot the board was read from beside the board parsed
+/// from it, so the parse can be checked before trusting the solution.
+#[cfg(feature = "preview")]
+fn preview(
+    path_args: &PathCli,
+    protocol: PreviewProtocol,
+    solve_state: &SolveState,
+    display_args: &DisplayCli,
+) -> Result<()> {
+    /// How many terminal cells wide the preview is.
+    const PREVIEW_WIDTH: u32 = 32;
+
+    let Some(path) = &path_args.path else {
+        warn!("Not previewing the shared board, since it isn't an image");
+        return Ok(());
+    };
+    let Some(img) = image_from_file_type(path, path_args.file_type)? else {
+        warn!("Not previewing {path:?}, since it isn't an image");
+        return Ok(());
+    };
+    let board = render(solve_state, display_args)?;
+    match protocol {
+        PreviewProtocol::HalfBlocks => {
+            let lines = qsolve::preview::half_blocks(&img, PREVIEW_WIDTH);
+            println!(
+                "{}",
+                qsolve::preview::beside(&lines, PREVIEW_WIDTH as usize, &board)
+            );
+        }
+        PreviewProtocol::Kitty => {
+            println!("{}", qsolve::preview::kitty(&img, PREVIEW_WIDTH)?);
+            println!("{board}");
+        }
     }
+    println!();
     Ok(())
 }
 
+#[cfg(not(feature = "preview"))]
+fn preview(
+    _path_args: &PathCli,
+    _protocol: PreviewProtocol,
+    _solve_state: &SolveState,
+    _display_args: &DisplayCli,
+) -> Result<()> {
+    bail!("Could not draw a preview: --preview needs the `preview` feature")
+}
+
 /// Top-level entry point for the profile subcommand.
-fn profile(path_args: &PathCli, solve_args: &SolveCli, iterations: &usize) -> Result<()> {
+fn profile(
+    path_args: &PathCli,
+    solve_args: &SolveCli,
+    iterations: &usize,
+    metrics: bool,
+    seed: u64,
+) -> Result<()> {
+    let solve_options = SolveOptions {
+        metrics,
+        ..solve_args.solve_options(seed)
+    };
+    let mut total_metrics = SolveMetrics::default();
     let start_time = Instant::now();
     for _ in 0..*iterations {
         let queens_file = queens_file_from_path(path_args)?;
         let solve_state = SolveState::from(&queens_file);
-        let heuristics = all_heuristics(solve_state.board);
-        solve_iter(solve_state, solve_args.strategy, &heuristics).for_each(drop);
+        let heuristics = solve_args.heuristics(solve_state.board);
+        let mut steps = solve_iter_with_options(solve_state, solve_options, &heuristics);
+        steps.by_ref().for_each(drop);
+        if let Some(err) = steps.take_error() {
+            return Err(err);
+        }
+        if let Some(metrics) = steps.metrics() {
+            total_metrics += *metrics;
+        }
     }
     let elapsed = start_time.elapsed();
     println!("{iterations} iterations completed in {elapsed:?}");
+    if metrics {
+        println!("{total_metrics}");
+    }
+    Ok(())
+}
+
+/// Top-level entry point for the compare subcommand.
+fn compare(
+    path_args: &PathCli,
+    display_args: &DisplayCli,
+    reference: &std::path::Path,
+    marking_mode: MarkingMode,
+) -> Result<()> {
+    let queens_file = queens_file_from_path(path_args)?;
+    let reference_file = queens_file_from_file_type(&reference.to_path_buf(), path_args.file_type)?
+        .into_builder()
+        .rules(queens_file.board.rules())
+        .build()?;
+    let (solve_state, reference_state) = match marking_mode {
+        MarkingMode::Assisted => (
+            SolveState::from(&queens_file),
+            SolveState::from(&reference_file),
+        ),
+        MarkingMode::Strict => (
+            SolveState::as_marked(&queens_file),
+            SolveState::as_marked(&reference_file),
+        ),
+    };
+    let comparison = Comparison::new(&solve_state, &reference_state)?;
+    println!(
+        "{}",
+        display_args.fit(&comparison.ansi_string(display_args.charset, display_args.palette)?)
+    );
+    println!(
+        "{} agreements, {} conflicts (!), {} missing (?), {} extra (+)",
+        comparison.agreements(),
+        comparison.conflicts(),
+        comparison.missing(),
+        comparison.extra()
+    );
+    Ok(())
+}
+
+/// How many pixels wide each module of a QR code saved by `link --qr-png` is.
+const QR_PNG_SCALE: usize = 8;
+
+/// Top-level entry point for the link subcommand.
+fn link(path_args: &PathCli, plain: bool, qr: bool, qr_png: Option<&Path>) -> Result<()> {
+    let queens_file = queens_file_from_path(path_args)?;
+    let solve_state = SolveState::from(&queens_file);
+    let link = if plain {
+        to_fragment(&solve_state)
+    } else {
+        to_link(&solve_state)
+    };
+    println!("{link}");
+    if !qr && qr_png.is_none() {
+        return Ok(());
+    }
+    let qr_code = QrCode::encode(link.as_bytes())?;
+    if qr {
+        println!("{}", qr_code.to_ansi());
+    }
+    if let Some(path) = qr_png {
+        qr_code
+            .to_image(QR_PNG_SCALE)
+            .save_with_format(path, image::ImageFormat::Png)
+            .with_context(|| format!("Could not save QR code to {path:?}"))?;
+    }
+    Ok(())
+}
+
+/// Top-level entry point for the overlay subcommand.
+fn overlay(path_args: &PathCli, solve_args: &SolveCli, output: &Path, seed: u64) -> Result<()> {
+    let img = match &path_args.path {
+        Some(path) => image_from_file_type(path, path_args.file_type)?,
+        None => None,
+    }
+    .ok_or_else(|| anyhow!("Can only draw Queens onto a screenshot, not a text board"))?;
+    let grid = detect_grid(&img).context(QSolveError::ImageDetection)?;
+    let queens_file = queens_file_from_path(path_args)?;
+    let solve_state = SolveState::from(&queens_file);
+    let heuristics = solve_args.heuristics(solve_state.board);
+    let mut solution =
+        Solution::solve_with_options(solve_state, solve_args.solve_options(seed), &heuristics);
+    if let Some(err) = solution.error.take() {
+        return Err(err);
+    }
+    if !solution.is_solved() {
+        bail!(QSolveError::Unsolvable);
+    }
+    overlay_queens(&img, &grid, solution.final_state.queens().iter())
+        .save(output)
+        .with_context(|| format!("Could not save overlay to {output:?}"))?;
+    Ok(())
+}
+
+/// Top-level entry point for the verify subcommand.
+fn verify(path_args: &PathCli, count: bool, display_args: &DisplayCli) -> Result<()> {
+    let queens_file = queens_file_from_path(path_args)?;
+    let board = &queens_file.board;
+    if count {
+        let count = count_solutions(board, None);
+        println!(
+            "The puzzle has {count} solution{}",
+            if count == 1 { "" } else { "s" }
+        );
+    }
+    let uniqueness = Uniqueness::of(board);
+    match uniqueness {
+        Uniqueness::NoSolution => {
+            Err(anyhow!("The puzzle has no solution")).context(QSolveError::InvalidBoard)
+        }
+        Uniqueness::Unique(solution) => {
+            let queens = solution.iter().collect::<Vec<_>>();
+            println!("The puzzle has exactly one solution:");
+            println!(
+                "{}",
+                render(&SolveState::with_queens(board, &queens), display_args)?
+            );
+            Ok(())
+        }
+        Uniqueness::Multiple(_, _) => Err(anyhow!(
+            "The puzzle has more than one solution; two of them differ at {}",
+            display_args
+                .coord_style()
+                .squares_text(&uniqueness.differences())
+        ))
+        .context(QSolveError::InvalidBoard),
+    }
+}
+
+/// Top-level entry point for the grade subcommand.
+fn grade(path_args: &PathCli, format: OutputFormat) -> Result<()> {
+    let queens_file = queens_file_from_path(path_args)?;
+    let grade = Grade::of(SolveState::from(&queens_file));
+    if !grade.solved {
+        return Err(anyhow!(
+            "The solver couldn't finish the puzzle, so it can't be graded"
+        ))
+        .context(QSolveError::Unsolvable);
+    }
+    match format {
+        OutputFormat::Text => println!("{grade}"),
+        OutputFormat::Json => println!("{}", grade.to_json()),
+    }
     Ok(())
 }
 
+/// Top-level entry point for the verify-share subcommand.
+fn verify_share(path_args: &PathCli, payload: &str) -> Result<()> {
+    let queens_file = queens_file_from_path(path_args)?;
+    let solve_state = SolveState::from(&queens_file);
+    let payload = SharePayload::decode(payload)?;
+    payload.verify(&solve_state)?;
+    println!(
+        "Verified: solved in {} steps with the {} strategy, in a claimed {:?}",
+        payload.steps, payload.strategy, payload.elapsed
+    );
+    Ok(())
+}
+
+/// Top-level entry point for the prove subcommand.
+fn prove(path_args: &PathCli, solve_args: &SolveCli, seed: u64) -> Result<()> {
+    let queens_file = queens_file_from_path(path_args)?;
+    let solve_state = SolveState::from(&queens_file);
+    let heuristics = solve_args.heuristics(solve_state.board);
+    let mut solution =
+        Solution::solve_with_options(solve_state, solve_args.solve_options(seed), &heuristics);
+    if let Some(err) = solution.error.take() {
+        return Err(err);
+    }
+    let proof = Proof::from_solution(&solution)?;
+    println!("{}", serde_json::to_string_pretty(&proof.to_json())?);
+    Ok(())
+}
+
+/// Top-level entry point for the check-proof subcommand.
+fn check_proof(path_args: &PathCli, proof_path: &Path) -> Result<()> {
+    let queens_file = queens_file_from_path(path_args)?;
+    let content = std::fs::read_to_string(proof_path)
+        .with_context(|| format!("Could not read proof {proof_path:?}"))?;
+    let json = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid proof {proof_path:?}"))
+        .context(QSolveError::Parse)?;
+    let proof = Proof::from_json(&json, &queens_file.board)?;
+    proof.check(SolveState::from(&queens_file))?;
+    println!("Checked: {} deductions solve the board", proof.steps.len());
+    Ok(())
+}
+
+/// Top-level entry point for the discover subcommand.
+fn discover(dir: &Path, solve_args: &SolveCli, seed: u64) -> Result<()> {
+    let report = DiscoveryReport::from_dir(dir, solve_args.solve_options(seed))?;
+    println!("{report}");
+    Ok(())
+}
+
+/// Top-level entry point for the corpus sync subcommand.
+fn corpus_sync(index: &str, dir: &Path) -> Result<()> {
+    let content = read_source(Path::new(index))?;
+    let corpus_index = CorpusIndex::from_str(&String::from_utf8_lossy(&content))
+        .with_context(|| format!("Invalid corpus index {index}"))?;
+    let report = corpus::sync(&corpus_index, index, dir, |url| read_source(Path::new(url)))?;
+    for (name, why) in &report.skipped {
+        println!("Skipped {name}: {why}");
+    }
+    println!(
+        "Synced {} of {} boards into {}",
+        report.synced.len(),
+        corpus_index.boards.len(),
+        dir.display()
+    );
+    Ok(())
+}
+
+/// Top-level entry point for the history export subcommand.
+fn history_export(anonymized: bool) -> Result<()> {
+    let history = PracticeHistory::in_default_dir()
+        .ok_or_else(|| anyhow!("No directory to read practice history from"))?;
+    let results = history.results()?;
+    let json = if anonymized {
+        AnonymizedStats::from_results(&results).to_json()
+    } else {
+        serde_json::json!({ "results": results.iter().map(PracticeResult::to_json).collect::<Vec<_>>() })
+    };
+    println!("{}", serde_json::to_string_pretty(&json)?);
+    Ok(())
+}
+
+/// Top-level entry point for the history merge subcommand.
+fn history_merge(paths: &[std::path::PathBuf]) -> Result<()> {
+    let mut stats = AnonymizedStats::default();
+    for path in paths {
+        stats.merge(AnonymizedStats::try_from_file(path)?);
+    }
+    println!("{}", serde_json::to_string_pretty(&stats.to_json())?);
+    Ok(())
+}
+
+/// Top-level entry point for the self check-update subcommand.
+fn self_check_update(channel: Channel, releases: &str, format: OutputFormat) -> Result<()> {
+    let content = read_source(Path::new(releases))?;
+    let json = serde_json::from_slice(&content)
+        .with_context(|| format!("Invalid list of releases from {releases}"))
+        .context(QSolveError::Parse)?;
+    let check = UpdateCheck::of(current_version(), &json, channel)?;
+    match format {
+        OutputFormat::Text => println!("{check}"),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&check.to_json())?),
+    }
+    Ok(())
+}
+
+/// Top-level entry point for the duel new subcommand.
+fn duel_new(path_args: &PathCli, output: &Path, players: &[String]) -> Result<()> {
+    let queens_file = queens_file_from_path(path_args)?;
+    let players = [players[0].clone(), players[1].clone()];
+    let duel = Duel::new(queens_file, players)?;
+    save_duel(&duel, output)?;
+    let first = &duel.players()[0];
+    println!(
+        "Started a duel between {first} and {}; {first} moves first, with `qsolve duel move {} {first} <squares> Q|x`",
+        duel.players()[1],
+        output.display()
+    );
+    Ok(())
+}
+
+/// Top-level entry point for the duel move subcommand.
+fn duel_move(path: &Path, player: &str, marks: &[String], display_args: &DisplayCli) -> Result<()> {
+    let mut duel = Duel::try_from_file(path)?;
+    let words = marks.iter().map(String::as_str).collect::<Vec<_>>();
+    let changes = parse_move(&words, duel.session()?.board(), &display_args.coord_style())?;
+    let resolved = duel.play(player, changes)?;
+    save_duel(&duel, path)?;
+    println!(
+        "{player} resolved {resolved} square{}.",
+        if resolved == 1 { "" } else { "s" }
+    );
+    print_duel(&duel, display_args)
+}
+
+/// Top-level entry point for the duel status subcommand.
+fn duel_status(path: &Path, display_args: &DisplayCli) -> Result<()> {
+    print_duel(&Duel::try_from_file(path)?, display_args)
+}
+
+/// Writes the duel to the given file, replacing what was there.
+fn save_duel(duel: &Duel, path: &Path) -> Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(&duel.to_json())?)
+        .with_context(|| format!("Could not write duel {path:?}"))
+}
+
+/// Prints the board of the duel, each player's score, and whose turn it is
+/// or who won.
+fn print_duel(duel: &Duel, display_args: &DisplayCli) -> Result<()> {
+    println!("{}", render(&duel.session()?.solve_state(), display_args)?);
+    for (player, score) in duel.players().iter().zip(duel.scores()?) {
+        println!(
+            "{player}: {score} square{}",
+            if score == 1 { "" } else { "s" }
+        );
+    }
+    match duel.status()? {
+        DuelStatus::ToMove(i) => println!("{} to move.", duel.players()[i]),
+        DuelStatus::Won(i) => println!("{} wins!", duel.players()[i]),
+        DuelStatus::Tied => println!("It's a tie!"),
+    }
+    Ok(())
+}
+
+/// Top-level entry point for the report subcommand.
+fn report(
+    paths: &[std::path::PathBuf],
+    solve_args: &SolveCli,
+    montage_path: Option<&Path>,
+    montage_columns: usize,
+    palette: Palette,
+    seed: u64,
+) -> Result<()> {
+    let queens_files = paths
+        .iter()
+        .map(|path| queens_file_from_file_type(path, FileType::Auto))
+        .collect::<Result<Vec<_>>>()?;
+    let heuristics = queens_files
+        .iter()
+        .map(|queens_file| solve_args.heuristics(&queens_file.board))
+        .collect::<Vec<_>>();
+    let mut solutions = vec![];
+    for ((path, queens_file), heuristics) in paths.iter().zip(&queens_files).zip(&heuristics) {
+        let mut solution = Solution::solve_with_options(
+            SolveState::from(queens_file),
+            solve_args.solve_options(seed),
+            heuristics,
+        );
+        if let Some(err) = solution.error.take() {
+            return Err(err.context(format!("Could not solve {path:?}")));
+        }
+        println!(
+            "{}: {solution}; starting entropy {}",
+            path.display(),
+            solution.entropy()
+        );
+        solutions.push(solution);
+    }
+    let Some(montage_path) = montage_path else {
+        return Ok(());
+    };
+    let montage = if let [solution] = solutions.as_slice() {
+        Montage::key_steps(solution, montage_columns, palette)
+    } else {
+        let mut montage = Montage::new(montage_columns, palette);
+        for (path, solution) in paths.iter().zip(&solutions) {
+            let name = path
+                .file_stem()
+                .unwrap_or(path.as_os_str())
+                .to_string_lossy();
+            montage.push(format!("{name} ({solution})"), solution.final_state.clone());
+        }
+        montage
+    };
+    match montage_path.extension().and_then(OsStr::to_str) {
+        Some("svg") => std::fs::write(montage_path, montage.svg())
+            .with_context(|| format!("Could not write montage to {montage_path:?}"))?,
+        Some("png") => montage
+            .png()
+            .save(montage_path)
+            .with_context(|| format!("Could not write montage to {montage_path:?}"))?,
+        _ => bail!("Montages can only be written to .svg or .png files, not {montage_path:?}"),
+    }
+    println!(
+        "Wrote {} boards to {}",
+        montage.tiles.len(),
+        montage_path.display()
+    );
+    Ok(())
+}
+
+/// Top-level entry point for the why-filled subcommand.
+fn why_filled(
+    path_args: &PathCli,
+    square: &str,
+    display_args: &DisplayCli,
+    solve_args: &SolveCli,
+    seed: u64,
+) -> Result<()> {
+    let coord_style = display_args.coord_style();
+    let square = coord_style.parse(square)?;
+    let square_words = coord_style.words(&square);
+    let mut square_title = square_words.clone();
+    square_title[..1].make_ascii_uppercase();
+    let queens_file = queens_file_from_path(path_args)?;
+    let solve_state = SolveState::from(&queens_file);
+    let size = solve_state.board.size();
+    ensure!(
+        square.0 < size && square.1 < size,
+        "{square_title} is not on the board"
+    );
+    let heuristics = solve_args.heuristics(solve_state.board);
+    let mut solution =
+        Solution::solve_with_options(solve_state, solve_args.solve_options(seed), &heuristics);
+    if let Some(err) = solution.error.take() {
+        return Err(err);
+    }
+    let marking = match solution.final_state.square(&square) {
+        None => {
+            println!("{square_title} was never filled in.");
+            return Ok(());
+        }
+        Some(SquareVal::Queen) => "a Queen",
+        Some(SquareVal::X) => "an x",
+    };
+    let Some(step) = solution.marked_by(&square) else {
+        println!("{square_title} has {marking} from the start.");
+        return Ok(());
+    };
+    let h = step
+        .next_heuristic
+        .expect("A step that marked a square has a heuristic");
+    let seen = h.seen_coords(&step.solve_state);
+    println!(
+        "{}",
+        display_args.fit(&step.solve_state.ansi_string(
+            seen,
+            display_args.charset,
+            display_args.palette
+        )?)
+    );
+    let step_number = step.solve_state.changes_applied() + 1;
+    println!(
+        "Step {step_number} of {} put {marking} in {square_words}:",
+        solution.step_count()
+    );
+    println!("{}", h.description());
+    let earlier_steps = seen
+        .iter()
+        .filter_map(|coord| step.solve_state.provenance(&coord))
+        .sorted()
+        .dedup()
+        .map(|change| (change + 1).to_string())
+        .collect::<Vec<_>>();
+    if !earlier_steps.is_empty() {
+        println!(
+            "It relied on squares marked in steps {}.",
+            earlier_steps.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Top-level entry point for the heuristics subcommand.
+fn heuristics(format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Json => {
+            let json = HEURISTIC_CATALOG
+                .iter()
+                .map(|info| info.to_json())
+                .collect::<Vec<_>>();
+            println!("{}", serde_json::to_string_pretty(&json)?);
+        }
+        OutputFormat::Text => {
+            for info in HEURISTIC_CATALOG {
+                println!("{} (tier {})", info.name, info.tier());
+                for line in info.description.lines() {
+                    println!("  {line}");
+                }
+                for (parameter, meaning) in info.parameters {
+                    println!("    {{{parameter}}}: {meaning}");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads the request for the bench-server subcommand that starts with the
+/// given line, returning its id (or null) and its board text.
+fn read_bench_request(
+    line: &str,
+    lines: &mut impl Iterator<Item = std::io::Result<String>>,
+) -> Result<(serde_json::Value, String)> {
+    if let Ok(line_count) = line.trim().parse::<usize>() {
+        let board_lines = lines
+            .take(line_count)
+            .collect::<std::io::Result<Vec<_>>>()?;
+        ensure!(
+            board_lines.len() == line_count,
+            "Expected {line_count} lines of board, but input ended after {}",
+            board_lines.len()
+        );
+        return Ok((serde_json::Value::Null, board_lines.join("\n")));
+    }
+    let request = serde_json::from_str::<serde_json::Value>(line)
+        .map_err(|err| anyhow!("Request is neither a line count nor JSON: {err}"))?;
+    let board = match &request["board"] {
+        serde_json::Value::String(board) => board.clone(),
+        serde_json::Value::Array(rows) => rows
+            .iter()
+            .map(|row| {
+                row.as_str()
+                    .ok_or_else(|| anyhow!("Board rows must be strings"))
+            })
+            .collect::<Result<Vec<_>>>()?
+            .join("\n"),
+        _ => bail!("Request has no board"),
+    };
+    Ok((request["id"].clone(), board))
+}
+
+/// Top-level entry point for the bench-server subcommand.
+///
+/// Each response is a single line of JSON: the [Solution::to_json] of the
+/// solve, or an `error`, along with the request's `id`. Heuristics are kept
+/// for every board seen, so solving a board again only costs the solve.
+fn bench_server(solve_args: &SolveCli, seed: u64) -> Result<()> {
+    let mut heuristics_by_board: BTreeMap<u64, Vec<Box<dyn Heuristic>>> = BTreeMap::new();
+    let mut lines = std::io::stdin().lock().lines();
+    let mut stdout = std::io::stdout().lock();
+    while let Some(line) = lines.next() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut id = serde_json::Value::Null;
+        let response = read_bench_request(&line, &mut lines).and_then(|(request_id, board)| {
+            id = request_id;
+            let queens_file = QueensFile::from_str(&board)?.into_builder().build()?;
+            let heuristics = heuristics_by_board
+                .entry(queens_file.board.fingerprint())
+                .or_insert_with(|| solve_args.heuristics(&queens_file.board));
+            let mut solution = Solution::solve_with_options(
+                SolveState::from(&queens_file),
+                solve_args.solve_options(seed),
+                heuristics,
+            );
+            if let Some(err) = solution.error.take() {
+                return Err(err);
+            }
+            Ok(solution.to_json())
+        });
+        let mut json =
+            response.unwrap_or_else(|err| serde_json::json!({"error": format!("{err:#}")}));
+        json["id"] = id;
+        writeln!(stdout, "{json}")?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+/// Top-level entry point for the heatmap subcommand.
+fn heatmap(
+    path_args: &PathCli,
+    solve_args: &SolveCli,
+    heatmap_format: &HeatmapFormat,
+    seed: u64,
+) -> Result<()> {
+    let queens_file = queens_file_from_path(path_args)?;
+    let solve_state = SolveState::from(&queens_file);
+    let heuristics = solve_args.heuristics(solve_state.board);
+    let mut solution =
+        Solution::solve_with_options(solve_state, solve_args.solve_options(seed), &heuristics);
+    if let Some(err) = solution.error.take() {
+        return Err(err);
+    }
+    let heatmap = Heatmap::new(&solution);
+    match heatmap_format {
+        HeatmapFormat::Svg => print!("{}", heatmap.svg()),
+        HeatmapFormat::Terminal => {
+            for layer in [HeatmapLayer::Seen, HeatmapLayer::Changed] {
+                println!("{} (max {}):", layer.name(), heatmap.max(layer));
+                println!("{}", heatmap.ansi_string(layer)?);
+            }
+            println!(
+                "{} of {} squares were never considered",
+                heatmap.unseen(),
+                queens_file.board.square_count()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Prints how long the session took, if its puzzle has just been solved.
+fn print_if_solved(session: &Session) {
+    if session.is_solved() {
+        println!(
+            "Solved in {}; type `share` for share text.",
+            format_minutes(session.clock().elapsed())
+        );
+    }
+}
+
+/// Runs a single command typed at the prompt of the repl subcommand,
+/// returning false if the repl should exit.
+fn run_repl_command(
+    command: ReplCommand,
+    session: &mut Option<Session>,
+    play_args: &PlayCli,
+    display_args: &DisplayCli,
+    solve_options: SolveOptions,
+) -> Result<bool> {
+    let (charset, palette) = (display_args.charset, display_args.palette);
+    match (command, session.as_mut()) {
+        (ReplCommand::Quit, _) => return Ok(false),
+        (ReplCommand::Help, _) => println!("{}", ReplCommand::HELP),
+        (ReplCommand::Load(path), _) => {
+            let queens_file = queens_file_from_file_type(&path, FileType::Auto)?
+                .into_builder()
+                .build()?;
+            let name = path.file_stem().and_then(OsStr::to_str).unwrap_or("");
+            let loaded = session.insert(
+                Session::new(queens_file)
+                    .with_name(name)
+                    .with_marking_mode(play_args.marking_mode),
+            );
+            println!("{}", render(&loaded.solve_state(), display_args)?);
+        }
+        (_, None) => bail!("No puzzle loaded; use `load <path>` first"),
+        (ReplCommand::Show, Some(session)) => {
+            println!("{}", render(&session.solve_state(), display_args)?)
+        }
+        (ReplCommand::Hint, Some(session)) => match session.hint(solve_options) {
+            Some(h) => {
+                let solve_state = session.solve_state();
+                println!(
+                    "{}",
+                    display_args.fit(&solve_state.ansi_string(
+                        h.seen_coords(&solve_state),
+                        charset,
+                        palette
+                    )?)
+                );
+                println!("{}", h.description());
+                if play_args.pause_on_hint {
+                    session.pause_clock();
+                }
+            }
+            None => println!("No next step found."),
+        },
+        (ReplCommand::Step, Some(session)) => match session.step(solve_options) {
+            Some(h) => {
+                let description = h.description();
+                let changed = session.history().last().unwrap().changed_coords();
+                println!(
+                    "{}",
+                    display_args.fit(
+                        &session
+                            .solve_state()
+                            .ansi_string(changed, charset, palette)?
+                    )
+                );
+                println!("{description}");
+                print_if_solved(session);
+            }
+            None => println!("No next step found."),
+        },
+        (ReplCommand::Apply(coord, val), Some(session)) => {
+            ensure!(
+                session.board().contains(&coord),
+                "Row {} column {} is not on the board",
+                coord.0 + 1,
+                coord.1 + 1
+            );
+            let changes = match val {
+                SquareVal::Queen => Changes::AddQueen {
+                    queen: coord,
+                    x: CoordSet::default(),
+                },
+                SquareVal::X => Changes::AddX {
+                    x: CoordSet::from_iter([coord]),
+                },
+            };
+            // Apply first, so that only moves that pass its checks are
+            // looked up in the solution.
+            session.apply(changes.clone())?;
+            let mistakes = match play_args.hard_mode {
+                true => CoordSet::default(),
+                false => session.mistakes_in(&changes),
+            };
+            let changed = session.history().last().unwrap().changed_coords();
+            println!(
+                "{}",
+                display_args.fit(&session.solve_state().ansi_string_with_highlights(
+                    &[
+                        (mistakes, HighlightStyle::MISTAKE),
+                        (changed, HighlightStyle::default())
+                    ],
+                    charset,
+                    palette
+                )?)
+            );
+            if !mistakes.is_empty() {
+                println!(
+                    "Mistake: {} disagrees with the solution.",
+                    display_args.coord_style().squares_text(&mistakes)
+                );
+            }
+            print_if_solved(session);
+        }
+        (ReplCommand::Undo, Some(session)) => {
+            if session.undo().is_none() {
+                bail!("Nothing to undo");
+            }
+            println!("{}", render(&session.solve_state(), display_args)?);
+        }
+        (ReplCommand::Solve, Some(session)) => {
+            let steps = session.solve(solve_options);
+            println!("{}", render(&session.solve_state(), display_args)?);
+            if session.is_solved() {
+                println!("Solved in {steps} steps.");
+                print_if_solved(session);
+            } else {
+                println!("Stuck after {steps} steps.");
+            }
+        }
+        (ReplCommand::Time, Some(session)) => {
+            println!("{}", format_minutes(session.clock().elapsed()))
+        }
+        (ReplCommand::Share, Some(session)) => {
+            ensure!(session.is_solved(), "The puzzle isn't solved yet");
+            let share_options = ShareOptions {
+                palette: display_args.palette,
+                ..ShareOptions::default()
+            };
+            println!("{}", session.share_content(session.name(), &share_options));
+        }
+    }
+    Ok(true)
+}
+
+/// Prompts for and reads a line of input, returning None at the end of input.
+fn read_prompt() -> Result<Option<String>> {
+    print!("> ");
+    std::io::stdout().flush()?;
+    let mut line = String::new();
+    if std::io::stdin().read_line(&mut line)? == 0 {
+        println!();
+        return Ok(None);
+    }
+    Ok(Some(line))
+}
+
+/// How a puzzle played by [play] ended.
+struct Played {
+    /// The session the puzzle was played in.
+    session: Session,
+    /// How many hints or solver steps the player asked for.
+    hints: usize,
+    /// Whether the player quit, rather than solving the puzzle.
+    quit: bool,
+}
+
+/// Plays the given session with the repl's commands until its puzzle is
+/// solved or the player quits. The puzzle is chosen for the player, so
+/// `load` isn't available; `activity` says what they're doing instead.
+fn play(
+    session: Session,
+    activity: &str,
+    play_args: &PlayCli,
+    display_args: &DisplayCli,
+    solve_options: SolveOptions,
+) -> Result<Played> {
+    println!("{}", render(&session.solve_state(), display_args)?);
+    let mut session = Some(session);
+    let mut hints = 0;
+    let mut quit = false;
+    while !session.as_ref().is_some_and(Session::is_solved) {
+        let Some(line) = read_prompt()? else {
+            quit = true;
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(session) = session.as_mut() {
+            session.resume_clock();
+        }
+        let result = ReplCommand::parse(&line, &display_args.coord_style()).and_then(|command| {
+            match command {
+                ReplCommand::Load(_) => bail!("`load` isn't available while {activity}"),
+                ReplCommand::Hint | ReplCommand::Step | ReplCommand::Solve => hints += 1,
+                _ => {}
+            }
+            run_repl_command(
+                command,
+                &mut session,
+                play_args,
+                display_args,
+                solve_options,
+            )
+        });
+        match result {
+            Ok(true) => {}
+            Ok(false) => {
+                quit = true;
+                break;
+            }
+            Err(err) => println!("Error: {err:#}"),
+        }
+    }
+    Ok(Played {
+        session: session.expect("Played puzzles are never unloaded"),
+        hints,
+        quit,
+    })
+}
+
+/// Asks whether to go on to the next puzzle, returning false if the player
+/// would rather stop.
+fn prompt_for_next_puzzle() -> Result<bool> {
+    println!("Press enter for the next puzzle, or type `quit` to stop.");
+    Ok(matches!(read_prompt()?, Some(line) if line.trim().is_empty()))
+}
+
+/// Returns a seed from the current time, for when none was chosen.
+fn seed_from_clock() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+}
+
+/// Top-level entry point for the fix-colors subcommand.
+///
+/// The board is read without validating it, since fixing a misread board is
+/// the point; it's validated strictly once the edits are made, so that the
+/// fixed board has one contiguous region per color.
+fn fix_colors(
+    path: &std::path::PathBuf,
+    merges: &[ColorEdit],
+    splits: &[String],
+    coord_style: CoordStyle,
+    in_place: bool,
+) -> Result<()> {
+    let QueensFile {
+        mut board, squares, ..
+    } = QueensFile::try_from_text_file(path)?;
+    let splits = splits
+        .iter()
+        .map(|split| ColorEdit::parse_split(split, &coord_style))
+        .collect::<Result<Vec<_>>>()
+        .context(QSolveError::Parse)?;
+    for edit in merges.iter().chain(&splits) {
+        board = edit.apply(&board).context(QSolveError::InvalidBoard)?;
+    }
+    let mut builder = QueensFile::builder().board(board);
+    if let Some(squares) = squares {
+        builder = builder.squares(squares);
+    }
+    let queens_file = builder.build()?;
+    queens_file.validate_strict()?;
+    if !in_place {
+        println!("{}", queens_file.to_text());
+        return Ok(());
+    }
+    std::fs::write(path, format!("{}\n", queens_file.to_text()))
+        .with_context(|| format!("Could not write board {path:?}"))?;
+    println!("Fixed the colors of {}", path.display());
+    Ok(())
+}
+
+/// How many squares wide `edit` makes new boards, unless told otherwise.
+const DEFAULT_EDIT_SIZE: usize = 8;
+
+/// The keys that `edit` responds to, shown under the board.
+const EDIT_KEYS: &str = "Arrows move (with Shift, painting as they go). A color's letter paints it;\r\n\
+    space paints the last color again; f then a letter fills. x erases, u undoes,\r\n\
+    s saves and q quits.";
+
+/// Puts the terminal in raw mode on an alternate screen, as `edit` draws
+/// it, until dropped.
+struct EditTerminal;
+
+impl EditTerminal {
+    fn enter() -> Result<Self> {
+        enable_raw_mode()?;
+        execute!(std::io::stdout(), EnterAlternateScreen, Hide)?;
+        Ok(EditTerminal)
+    }
+}
+
+impl Drop for EditTerminal {
+    fn drop(&mut self) {
+        // There's nothing more to do if the terminal can't be restored.
+        let _ = execute!(std::io::stdout(), LeaveAlternateScreen, Show);
+        let _ = disable_raw_mode();
+    }
+}
+
+/// Top-level entry point for the edit subcommand.
+///
+/// The board is drawn on an alternate screen and changed a key at a time, so
+/// this needs an interactive terminal.
+fn edit(path: &std::path::PathBuf, size: Option<usize>, display_args: &DisplayCli) -> Result<()> {
+    let (mut editor, squares) = if path.exists() {
+        let QueensFile { board, squares, .. } = QueensFile::try_from_text_file(path)?;
+        if let Some(size) = size {
+            ensure!(
+                size == board.size(),
+                "{} already has a board {} squares wide",
+                path.display(),
+                board.size()
+            );
+        }
+        (Editor::from_board(&board), squares)
+    } else {
+        (Editor::new(size.unwrap_or(DEFAULT_EDIT_SIZE))?, None)
+    };
+    ensure!(
+        std::io::stdin().is_terminal() && std::io::stdout().is_terminal(),
+        "Editing a board needs an interactive terminal"
+    );
+    let mut saved_colors = path.exists().then(|| editor.colors().to_vec());
+    let mut saved = false;
+    let mut status = format!("Editing {}", path.display());
+    let mut filling = false;
+    let mut quitting = false;
+    let terminal = EditTerminal::enter()?;
+    loop {
+        let board = editor
+            .ansi_string(display_args.charset, display_args.palette)?
+            .replace('\n', "\r\n");
+        let mut stdout = std::io::stdout();
+        queue!(
+            stdout,
+            MoveTo(0, 0),
+            Clear(ClearType::All),
+            Print(board),
+            Print("\r\n\r\n"),
+            Print(&status),
+            Print("\r\n\r\n"),
+            Print(EDIT_KEYS),
+        )?;
+        stdout.flush()?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        status.clear();
+        let unsaved = saved_colors.as_deref() != Some(editor.colors());
+        let quit = matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+            || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL));
+        if quit && (quitting || !unsaved) {
+            break;
+        }
+        quitting = quit;
+        if std::mem::take(&mut filling) {
+            match key.code {
+                KeyCode::Char(c) if let Ok(color) = SquareColor::try_from(c) => editor.fill(color),
+                _ => status = "Not filled, since that isn't a color's letter".to_string(),
+            }
+            continue;
+        }
+        let (rows, cols) = match key.code {
+            KeyCode::Up => (-1, 0),
+            KeyCode::Down => (1, 0),
+            KeyCode::Left => (0, -1),
+            KeyCode::Right => (0, 1),
+            _ => (0, 0),
+        };
+        match key.code {
+            _ if quit => {
+                status = "The board has unsaved changes; quit again to lose them".to_string()
+            }
+            KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right => {
+                editor.move_cursor(rows, cols);
+                if key.modifiers.contains(KeyModifiers::SHIFT) {
+                    editor.paint_brush();
+                }
+            }
+            KeyCode::Char(' ') | KeyCode::Enter if editor.brush().is_none() => {
+                status = "Paint a color with its letter first".to_string();
+            }
+            KeyCode::Char(' ') | KeyCode::Enter => editor.paint_brush(),
+            KeyCode::Char('f') => {
+                filling = true;
+                status = "Fill with which color?".to_string();
+            }
+            KeyCode::Char('x') | KeyCode::Backspace | KeyCode::Delete => editor.erase(),
+            KeyCode::Char('u') if !editor.undo() => status = "Nothing to undo".to_string(),
+            KeyCode::Char('u') => {}
+            KeyCode::Char('s') => {
+                let built = editor.to_board().and_then(|board| {
+                    let mut builder = QueensFile::builder().board(board);
+                    if let Some(squares) = squares.clone() {
+                        builder = builder.squares(squares);
+                    }
+                    builder.build()
+                });
+                let queens_file = match built {
+                    Ok(queens_file) => queens_file,
+                    Err(err) => {
+                        status = format!("Not saved, since {}", err.root_cause());
+                        continue;
+                    }
+                };
+                std::fs::write(path, format!("{}\n", queens_file.to_text()))
+                    .with_context(|| format!("Could not write board {path:?}"))?;
+                saved_colors = Some(editor.colors().to_vec());
+                saved = true;
+                status = match queens_file.validate_strict() {
+                    Ok(()) => format!("Saved {}", path.display()),
+                    Err(err) => format!("Saved {}, but {}", path.display(), err.root_cause()),
+                };
+            }
+            KeyCode::Char(c) if let Ok(color) = SquareColor::try_from(c) => editor.paint(color),
+            _ => status = "That key does nothing; see the keys below".to_string(),
+        }
+    }
+    drop(terminal);
+    if saved {
+        println!("Saved {}", path.display());
+    }
+    Ok(())
+}
+
+/// Top-level entry point for the generate subcommand.
+fn generate(
+    size: usize,
+    difficulty: Difficulty,
+    seed: Option<u64>,
+    output: Option<&Path>,
+) -> Result<()> {
+    let seed = seed.unwrap_or_else(seed_from_clock);
+    let board = generate_board(size, difficulty, &mut SeededRng::new(seed))?;
+    let Some(path) = output else {
+        info!("Generated the board from seed {seed}");
+        println!("{board}");
+        return Ok(());
+    };
+    std::fs::write(path, format!("{board}\n"))
+        .with_context(|| format!("Could not write board {path:?}"))?;
+    println!(
+        "Wrote the {size}x{size} {difficulty} board to {}; generate it again with --seed {seed}",
+        path.display()
+    );
+    Ok(())
+}
+
+/// Top-level entry point for the practice subcommand.
+///
+/// Each puzzle is played with the repl's commands until it is solved or the
+/// player quits, and its result is added to the [PracticeHistory].
+fn practice(
+    size: usize,
+    difficulty: Difficulty,
+    seed: Option<u64>,
+    play_args: &PlayCli,
+    display_args: &DisplayCli,
+    solve_options: SolveOptions,
+) -> Result<()> {
+    let history = PracticeHistory::in_default_dir();
+    let mut seed = seed.unwrap_or_else(seed_from_clock);
+    loop {
+        let board = generate_board(size, difficulty, &mut SeededRng::new(seed))?;
+        let queens_file = QueensFile::builder().board(board).build()?;
+        let name = format!("{size}x{size} {difficulty} #{seed}");
+        println!("Practice puzzle {name}");
+        let session = Session::new(queens_file)
+            .with_name(name)
+            .with_marking_mode(play_args.marking_mode);
+        let Played {
+            session,
+            hints,
+            quit,
+        } = play(
+            session,
+            "practicing",
+            play_args,
+            display_args,
+            solve_options,
+        )?;
+
+        let result = PracticeResult {
+            size,
+            difficulty,
+            seed,
+            solved: session.is_solved(),
+            elapsed: session.clock().elapsed(),
+            hints,
+            state: Some(session.solve_state().to_bytes()),
+        };
+        match &history {
+            Some(history) => {
+                if let Err(err) = history.record(&result) {
+                    warn!("Could not record practice result: {err:#}");
+                }
+                let results = history.results().unwrap_or_else(|err| {
+                    warn!("Could not read practice history: {err:#}");
+                    vec![result.clone()]
+                });
+                println!(
+                    "{size}x{size} {difficulty}: {}",
+                    PracticeStats::of(&results, size, difficulty)
+                );
+            }
+            None => warn!("No directory to record practice history in"),
+        }
+        if quit || !prompt_for_next_puzzle()? {
+            return Ok(());
+        }
+        seed = SeededRng::new(seed).next_u64();
+    }
+}
+
+/// Top-level entry point for the tournament subcommand.
+///
+/// Each puzzle in the pack is played with the repl's commands until it is
+/// solved or the player quits; quitting leaves the rest of the pack
+/// unsolved. The scoreboard and share text are printed at the end.
+fn tournament(
+    pack_path: &std::path::Path,
+    penalties: Penalties,
+    play_args: &PlayCli,
+    display_args: &DisplayCli,
+    solve_options: SolveOptions,
+) -> Result<()> {
+    let pack = Pack::try_from_file(pack_path)?;
+    let dir = pack_path.parent().unwrap_or(std::path::Path::new(""));
+    let mut scoreboard = Scoreboard {
+        name: pack.name.clone(),
+        penalties,
+        scores: Vec::with_capacity(pack.puzzles.len()),
+    };
+    let mut playing = true;
+    for (i, puzzle) in pack.puzzles.iter().enumerate() {
+        let mut score = PuzzleScore {
+            name: puzzle.name.clone(),
+            ..PuzzleScore::default()
+        };
+        if playing {
+            let queens_file = queens_file_from_file_type(&puzzle.path(dir), FileType::Auto)
+                .with_context(|| format!("Could not read puzzle {}", puzzle.name))?
+                .into_builder()
+                .build()?;
+            println!(
+                "Puzzle {} of {}: {}",
+                i + 1,
+                pack.puzzles.len(),
+                puzzle.name
+            );
+            let session = Session::new(queens_file)
+                .with_name(&puzzle.name)
+                .with_marking_mode(play_args.marking_mode);
+            let Played {
+                session,
+                hints,
+                quit,
+            } = play(
+                session,
+                "in a tournament",
+                play_args,
+                display_args,
+                solve_options,
+            )?;
+            score = PuzzleScore {
+                solved: session.is_solved(),
+                elapsed: session.clock().elapsed(),
+                hints,
+                mistakes: session.mistake_count(),
+                ..score
+            };
+            playing = !quit && i + 1 < pack.puzzles.len() && prompt_for_next_puzzle()?;
+        }
+        scoreboard.scores.push(score);
+    }
+    println!("{scoreboard}");
+    println!();
+    println!("{}", scoreboard.share_content());
+    Ok(())
+}
+
+/// Top-level entry point for the debug subcommand.
+///
+/// Errors from individual commands are printed, and don't end the debugger.
+fn debug(
+    path_args: &PathCli,
+    display_args: &DisplayCli,
+    solve_args: &SolveCli,
+    seed: u64,
+) -> Result<()> {
+    let queens_file = queens_file_from_path(path_args)?;
+    let mut solve_state = SolveState::from(&queens_file);
+    let board = solve_state.board;
+    let heuristics = solve_args.heuristics(board);
+    let solve_options = solve_args.solve_options(seed);
+    let mut rng = solve_options.rng();
+    let coord_style = display_args.coord_style();
+    let first_line = |h: &dyn Heuristic| {
+        let description = h.description();
+        description.lines().next().unwrap_or_default().to_string()
+    };
+    println!("{}", render(&solve_state, display_args)?);
+    while let Some(line) = read_prompt()? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let command = match DebugCommand::from_str(&line) {
+            Ok(command) => command,
+            Err(err) => {
+                println!("Error: {err:#}");
+                continue;
+            }
+        };
+        match command {
+            DebugCommand::Show => println!("{}", render(&solve_state, display_args)?),
+            DebugCommand::Step => {
+                let Some(step) =
+                    preview_step_with_rng(&solve_state, &solve_options, &heuristics, &mut rng)
+                else {
+                    match solve_state.complete() {
+                        true => println!("The puzzle is solved"),
+                        false => println!("No heuristic applies; try `eval`"),
+                    }
+                    continue;
+                };
+                let h = step.heuristic;
+                println!("{}: {}", h.name(), h.description());
+                println!(
+                    "Changed {}",
+                    coord_style.squares_text(&step.changes.changed_coords())
+                );
+                solve_state = step.next_state;
+                println!("{}", render(&solve_state, display_args)?);
+            }
+            DebugCommand::Eval => {
+                let mut rejected = BTreeMap::<_, usize>::new();
+                for h in &heuristics {
+                    let kind = match h.evaluate(&solve_state) {
+                        Evaluation::Applied(changes) => {
+                            println!(
+                                "{}: {} Changes {}",
+                                h.name(),
+                                first_line(h.as_ref()),
+                                coord_style.squares_text(&changes.changed_coords())
+                            );
+                            continue;
+                        }
+                        Evaluation::NotApplicable(_) => "not applicable",
+                        Evaluation::Invalidated(_) => "invalidated",
+                    };
+                    *rejected.entry((h.name(), kind)).or_default() += 1;
+                }
+                for ((name, kind), count) in rejected {
+                    println!("{name}: {count} {kind}");
+                }
+            }
+            DebugCommand::Why(name) => {
+                let named = heuristics
+                    .iter()
+                    .filter(|h| h.name() == name)
+                    .collect::<Vec<_>>();
+                if named.is_empty() {
+                    println!("Error: No heuristic is called `{name}`; `eval` lists them");
+                }
+                for h in named {
+                    let why = match h.evaluate(&solve_state).reject_reason() {
+                        Some(reason) => reason.to_string(),
+                        None => "Applies".to_string(),
+                    };
+                    println!("{} {why}", first_line(h.as_ref()));
+                }
+            }
+            DebugCommand::Candidates => {
+                let size = board.size();
+                let regions = (0..size)
+                    .map(Region::Row)
+                    .chain((0..size).map(Region::Col))
+                    .chain(board.all_colors().into_iter().map(|c| Region::Color(*c)));
+                for region in regions {
+                    let coords = region.coords(board);
+                    match coords.intersection(&solve_state.queens()).iter().next() {
+                        Some(queen) => println!("{region}: Queen at {}", coord_style.text(&queen)),
+                        None => println!(
+                            "{region}: {}",
+                            coord_style
+                                .squares_text(&coords.intersection(&solve_state.blank_coords()))
+                        ),
+                    }
+                }
+            }
+            DebugCommand::Help => println!("{}", DebugCommand::HELP),
+            DebugCommand::Quit => break,
+        }
+    }
+    Ok(())
+}
+
+/// Top-level entry point for the repl subcommand.
+///
+/// Errors from individual commands are printed, and don't end the repl.
+fn repl(
+    path: &Option<std::path::PathBuf>,
+    play_args: &PlayCli,
+    display_args: &DisplayCli,
+    solve_options: SolveOptions,
+) -> Result<()> {
+    let mut session = None;
+    if let Some(path) = path {
+        run_repl_command(
+            ReplCommand::Load(path.clone()),
+            &mut session,
+            play_args,
+            display_args,
+            solve_options,
+        )?;
+    }
+    loop {
+        let Some(line) = read_prompt()? else {
+            return Ok(());
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        // Any paused hint is over once the next command is typed.
+        if let Some(session) = session.as_mut() {
+            session.resume_clock();
+        }
+        let result = ReplCommand::parse(&line, &display_args.coord_style()).and_then(|command| {
+            run_repl_command(
+                command,
+                &mut session,
+                play_args,
+                display_args,
+                solve_options,
+            )
+        });
+        match result {
+            Ok(true) => {}
+            Ok(false) => return Ok(()),
+            Err(err) => println!("Error: {err:#}"),
+        }
+    }
+}
+
 /// Top-level entry point for the program.
-fn main() -> Result<()> {
+///
+/// Errors are reported the same way as returning them from `main` would, but
+/// the exit code reflects the [QSolveError] attached to the error, if any.
+fn main() -> ExitCode {
     env_logger::init();
     let cli = Cli::parse();
 
-    debug!("Running command {:?}", cli.command);
+    match traced(&cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {err:?}");
+            ExitCode::from(QSolveError::exit_code_for(&err))
+        }
+    }
+}
+
+/// Runs the subcommand specified on the command line, writing its tracing
+/// spans to the `--trace-json` file, if there is one, and logging them if
+/// trace logging is on.
+fn traced(cli: &Cli) -> Result<()> {
+    let layer = match &cli.trace_json {
+        Some(path) => {
+            let file = std::fs::File::create(path)
+                .with_context(|| format!("Could not create trace file {}", path.display()))?;
+            TraceLayer::with_json(std::io::BufWriter::new(file))
+        }
+        None if log::max_level() >= log::LevelFilter::Trace => TraceLayer::new(),
+        None => return run(cli),
+    };
+    tracing::subscriber::set_global_default(Registry::default().with(layer.clone()))
+        .context("Could not start tracing")?;
+    let result = run(cli);
+    layer.flush()?;
+    result
+}
+
+/// Runs the subcommand specified on the command line.
+fn run(cli: &Cli) -> Result<()> {
+    debug!("Running command {:?} with seed {:?}", cli.command, cli.seed);
     match &cli.command {
         Commands::Print {
             path_args,
@@ -391,24 +2064,239 @@ fn main() -> Result<()> {
             display_args,
             solve_args,
             delay,
-        } => animate(path_args, display_args, solve_args, delay),
+            from_hints,
+        } => animate(
+            path_args,
+            display_args,
+            solve_args,
+            delay,
+            *from_hints,
+            cli.seed.unwrap_or_default(),
+        ),
         Commands::Solve {
             path_args,
             display_args,
             solve_args,
-            share,
-        } => solve(path_args, display_args, solve_args, share),
+            share_args,
+            output_args,
+            no_cache,
+        } => solve(
+            path_args,
+            display_args,
+            solve_args,
+            share_args,
+            output_args,
+            *no_cache,
+            cli.seed.unwrap_or_default(),
+        ),
         Commands::Profile {
             path_args,
             solve_args,
             iterations,
-        } => profile(path_args, solve_args, iterations),
+            metrics,
+        } => profile(
+            path_args,
+            solve_args,
+            iterations,
+            *metrics,
+            cli.seed.unwrap_or_default(),
+        ),
         Commands::Hint {
             path_args,
             display_args,
             solve_args,
             hint_type,
-        } => hint(path_args, display_args, solve_args, hint_type),
+            focus,
+            alternatives,
+            spoiler_safe,
+        } => hint(
+            path_args,
+            display_args,
+            solve_args,
+            hint_type,
+            focus,
+            alternatives,
+            *spoiler_safe,
+            cli.seed.unwrap_or_default(),
+        ),
+        Commands::Compare {
+            path_args,
+            display_args,
+            reference,
+            marking_mode,
+        } => compare(path_args, display_args, reference, *marking_mode),
+        Commands::Link {
+            path_args,
+            plain,
+            qr,
+            qr_png,
+        } => link(path_args, *plain, *qr, qr_png.as_deref()),
+        Commands::Overlay {
+            path_args,
+            solve_args,
+            output,
+        } => overlay(path_args, solve_args, output, cli.seed.unwrap_or_default()),
+        Commands::Grade { path_args, format } => grade(path_args, *format),
+        Commands::Verify {
+            path_args,
+            count,
+            display_args,
+        } => verify(path_args, *count, display_args),
+        Commands::VerifyShare { path_args, payload } => verify_share(path_args, payload),
+        Commands::Prove {
+            path_args,
+            solve_args,
+        } => prove(path_args, solve_args, cli.seed.unwrap_or_default()),
+        Commands::CheckProof { path_args, proof } => check_proof(path_args, proof),
+        Commands::Heatmap {
+            path_args,
+            solve_args,
+            heatmap_format,
+        } => heatmap(
+            path_args,
+            solve_args,
+            heatmap_format,
+            cli.seed.unwrap_or_default(),
+        ),
+        Commands::Debug {
+            path_args,
+            display_args,
+            solve_args,
+        } => debug(
+            path_args,
+            display_args,
+            solve_args,
+            cli.seed.unwrap_or_default(),
+        ),
+        Commands::Repl {
+            path,
+            play_args,
+            display_args,
+            solve_args,
+        } => repl(
+            path,
+            play_args,
+            display_args,
+            solve_args.solve_options(cli.seed.unwrap_or_default()),
+        ),
+        Commands::Discover { dir, solve_args } => {
+            discover(dir, solve_args, cli.seed.unwrap_or_default())
+        }
+        Commands::Corpus {
+            command: CorpusCommand::Sync { index, dir },
+        } => corpus_sync(index, dir),
+        Commands::History {
+            command: HistoryCommand::Export { anonymized },
+        } => history_export(*anonymized),
+        Commands::History {
+            command: HistoryCommand::Merge { paths },
+        } => history_merge(paths),
+        Commands::Duel {
+            command:
+                DuelCommand::New {
+                    path_args,
+                    output,
+                    players,
+                },
+        } => duel_new(path_args, output, players),
+        Commands::Duel {
+            command:
+                DuelCommand::Move {
+                    duel,
+                    player,
+                    marks,
+                    display_args,
+                },
+        } => duel_move(duel, player, marks, display_args),
+        Commands::Duel {
+            command: DuelCommand::Status { duel, display_args },
+        } => duel_status(duel, display_args),
+        Commands::BenchServer { solve_args } => {
+            bench_server(solve_args, cli.seed.unwrap_or_default())
+        }
+        Commands::Heuristics { format } => heuristics(*format),
+        Commands::Report {
+            paths,
+            solve_args,
+            montage,
+            montage_columns,
+            palette,
+        } => report(
+            paths,
+            solve_args,
+            montage.as_deref(),
+            *montage_columns,
+            *palette,
+            cli.seed.unwrap_or_default(),
+        ),
+        Commands::WhyFilled {
+            path_args,
+            square,
+            display_args,
+            solve_args,
+        } => why_filled(
+            path_args,
+            square,
+            display_args,
+            solve_args,
+            cli.seed.unwrap_or_default(),
+        ),
+        Commands::FixColors {
+            path,
+            merge,
+            split,
+            coords,
+            in_place,
+        } => fix_colors(path, merge, split, *coords, *in_place),
+        Commands::Edit {
+            path,
+            size,
+            display_args,
+        } => edit(path, *size, display_args),
+        Commands::Generate {
+            size,
+            difficulty,
+            output,
+        } => generate(*size, *difficulty, cli.seed, output.as_deref()),
+        Commands::Practice {
+            size,
+            difficulty,
+            play_args,
+            display_args,
+            solve_args,
+        } => practice(
+            *size,
+            *difficulty,
+            cli.seed,
+            play_args,
+            display_args,
+            solve_args.solve_options(cli.seed.unwrap_or_default()),
+        ),
+        Commands::Tournament {
+            pack,
+            hint_penalty,
+            mistake_penalty,
+            play_args,
+            display_args,
+            solve_args,
+        } => tournament(
+            pack,
+            Penalties {
+                hint: Duration::from_secs(*hint_penalty),
+                mistake: Duration::from_secs(*mistake_penalty),
+            },
+            play_args,
+            display_args,
+            solve_args.solve_options(cli.seed.unwrap_or_default()),
+        ),
+        Commands::SelfCmd {
+            command:
+                SelfCommand::CheckUpdate {
+                    channel,
+                    releases,
+                    format,
+                },
+        } => self_check_update(*channel, releases, *format),
     }?;
 
     Ok(())
@@ -0,0 +1,246 @@
+use std::{
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{Context, Result, anyhow, ensure};
+use itertools::Itertools;
+use toml::{Table, Value};
+
+use crate::solvestate::SolveStrategy;
+
+/// The key for each strategy in a game's `steps` table.
+const STRATEGY_KEYS: [(SolveStrategy, &str); 4] = [
+    (SolveStrategy::Fast, "fast"),
+    (SolveStrategy::Short, "short"),
+    (SolveStrategy::Simple, "simple"),
+    (SolveStrategy::Human, "human"),
+];
+
+/// A game listed in a [Manifest], with the results the solver is expected
+/// to get on it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameEntry {
+    /// The name of the game's text file, without its extension.
+    pub name: String,
+
+    /// Where the game came from.
+    pub source: String,
+
+    /// How the source rates the game, if it does.
+    pub difficulty: Option<String>,
+
+    /// The license the game is shared under, as an SPDX identifier such as
+    /// `CC0-1.0`, for games that aren't ours to share otherwise.
+    pub license: Option<String>,
+
+    /// How many steps each strategy takes to solve the game, for the
+    /// strategies that have an expectation.
+    pub steps: Vec<(SolveStrategy, usize)>,
+}
+
+impl GameEntry {
+    /// Returns how many steps the given strategy is expected to take, if the
+    /// manifest says.
+    pub fn expected_steps(&self, solve_strategy: SolveStrategy) -> Option<usize> {
+        self.steps
+            .iter()
+            .find(|(strategy, _)| *strategy == solve_strategy)
+            .map(|(_, steps)| *steps)
+    }
+
+    /// Returns the path to the game's text file, in the given directory.
+    pub fn path(&self, dir: &Path) -> PathBuf {
+        dir.join(format!("{}.txt", self.name))
+    }
+}
+
+/// A list of games and the results the solver is expected to get on them,
+/// used to catch unintended changes to the solver.
+///
+/// The manifest is a TOML file with a `[[game]]` table for each game, such
+/// as `games/manifest.toml`.
+///
+/// # Examples
+/// ```
+/// # use qsolve::manifest::Manifest;
+/// # use qsolve::solvestate::SolveStrategy;
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let manifest = Manifest::from_str(
+///     r#"
+///     [[game]]
+///     name = "linkedin-1-empty"
+///     source = "LinkedIn"
+///     steps = { fast = 9, simple = 10 }
+///     "#,
+/// )?;
+/// let game = manifest.game("linkedin-1-empty").unwrap();
+/// assert_eq!(game.expected_steps(SolveStrategy::Fast), Some(9));
+/// assert_eq!(game.expected_steps(SolveStrategy::Short), None);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Manifest {
+    /// The games in the manifest, in the order they are listed.
+    pub games: Vec<GameEntry>,
+}
+
+impl Manifest {
+    /// Reads the manifest from the given file.
+    pub fn try_from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read manifest {path:?}"))?;
+        Manifest::from_str(&content).with_context(|| format!("Invalid manifest {path:?}"))
+    }
+
+    /// Returns the game with the given name, if it is listed.
+    pub fn game(&self, name: &str) -> Option<&GameEntry> {
+        self.games.iter().find(|game| game.name == name)
+    }
+}
+
+impl FromStr for Manifest {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let table = s.parse::<Table>()?;
+        let games = match table.get("game") {
+            None => vec![],
+            Some(games) => games
+                .as_array()
+                .ok_or_else(|| anyhow!("game must be an array of tables"))?
+                .iter()
+                .map(game_entry)
+                .collect::<Result<Vec<_>>>()?,
+        };
+        for (i, game) in games.iter().enumerate() {
+            ensure!(
+                games[..i].iter().all(|other| other.name != game.name),
+                "Game {} is listed more than once",
+                game.name
+            );
+        }
+        Ok(Manifest { games })
+    }
+}
+
+/// Writes the manifest as TOML that [Manifest::from_str] reads back.
+impl Display for Manifest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, game) in self.games.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "[[game]]")?;
+            writeln!(f, "name = {}", Value::from(game.name.as_str()))?;
+            writeln!(f, "source = {}", Value::from(game.source.as_str()))?;
+            if let Some(difficulty) = &game.difficulty {
+                writeln!(f, "difficulty = {}", Value::from(difficulty.as_str()))?;
+            }
+            if let Some(license) = &game.license {
+                writeln!(f, "license = {}", Value::from(license.as_str()))?;
+            }
+            if !game.steps.is_empty() {
+                let steps = game
+                    .steps
+                    .iter()
+                    .map(|(strategy, count)| {
+                        let (_, key) = STRATEGY_KEYS
+                            .into_iter()
+                            .find(|(s, _)| s == strategy)
+                            .expect("Every strategy has a key");
+                        format!("{key} = {count}")
+                    })
+                    .join(", ");
+                writeln!(f, "steps = {{ {steps} }}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses a single `[[game]]` table.
+fn game_entry(value: &Value) -> Result<GameEntry> {
+    let string = |key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+    let name = string("name").ok_or_else(|| anyhow!("Every game needs a name"))?;
+    let source = string("source").ok_or_else(|| anyhow!("Game {name} needs a source"))?;
+    let mut steps = vec![];
+    if let Some(table) = value.get("steps") {
+        let table = table
+            .as_table()
+            .ok_or_else(|| anyhow!("Steps for game {name} must be a table"))?;
+        for (key, count) in table {
+            let (strategy, _) = STRATEGY_KEYS
+                .into_iter()
+                .find(|(_, name)| name == key)
+                .ok_or_else(|| anyhow!("Unknown strategy {key} for game {name}"))?;
+            let count = count
+                .as_integer()
+                .and_then(|count| usize::try_from(count).ok())
+                .ok_or_else(|| anyhow!("Steps for {key} in game {name} must be a count"))?;
+            steps.push((strategy, count));
+        }
+    }
+    Ok(GameEntry {
+        difficulty: string("difficulty"),
+        license: string("license"),
+        name,
+        source,
+        steps,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_parses() -> Result<()> {
+        let manifest = Manifest::from_str(
+            r#"
+            [[game]]
+            name = "a"
+            source = "Somewhere"
+            difficulty = "hard"
+            license = "CC0-1.0"
+            steps = { fast = 3, short = 2, simple = 4 }
+
+            [[game]]
+            name = "b"
+            source = "Elsewhere"
+            "#,
+        )?;
+        assert_eq!(manifest.games.len(), 2);
+        let a = manifest.game("a").unwrap();
+        assert_eq!(a.difficulty.as_deref(), Some("hard"));
+        assert_eq!(a.license.as_deref(), Some("CC0-1.0"));
+        assert_eq!(a.expected_steps(SolveStrategy::Short), Some(2));
+        assert_eq!(a.path(Path::new("games")), PathBuf::from("games/a.txt"));
+        let b = manifest.game("b").unwrap();
+        assert_eq!(b.difficulty, None);
+        assert!(b.steps.is_empty());
+        assert!(manifest.game("c").is_none());
+        assert_eq!(Manifest::from_str("")?, Manifest::default());
+        assert_eq!(Manifest::from_str(&manifest.to_string())?, manifest);
+        Ok(())
+    }
+
+    #[test]
+    fn manifest_rejects_invalid() {
+        for content in [
+            "not = [toml",
+            "game = 3",
+            "[[game]]\nsource = \"Somewhere\"",
+            "[[game]]\nname = \"a\"",
+            "[[game]]\nname = \"a\"\nsource = \"S\"\nsteps = { slow = 3 }",
+            "[[game]]\nname = \"a\"\nsource = \"S\"\nsteps = { fast = -3 }",
+            "[[game]]\nname = \"a\"\nsource = \"S\"\n[[game]]\nname = \"a\"\nsource = \"S\"",
+        ] {
+            assert!(Manifest::from_str(content).is_err(), "{content}");
+        }
+    }
+}
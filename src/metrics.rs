@@ -0,0 +1,176 @@
+use std::{
+    cell::RefCell,
+    fmt::{Display, Formatter},
+    ops::AddAssign,
+    time::{Duration, Instant},
+};
+
+use serde_json::{Value, json};
+
+thread_local! {
+    /// The metrics being collected on this thread by [collect], if any.
+    static COLLECTING: RefCell<Option<SolveMetrics>> = const { RefCell::new(None) };
+}
+
+/// Counters of the work the solver did, for performance analysis.
+///
+/// These are only collected when [SolveOptions::metrics][crate::solvestate::SolveOptions::metrics]
+/// is on, and are then returned by [SolveIter::metrics][crate::solveiter::SolveIter::metrics]
+/// and kept in [Solution::metrics][crate::solution::Solution::metrics].
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Board;
+/// # use qsolve::heuristic::all_heuristics;
+/// # use qsolve::solution::Solution;
+/// # use qsolve::solvestate::{SolveOptions, SolveState};
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+/// let heuristics = all_heuristics(&board);
+/// let solve_options = SolveOptions {
+///     metrics: true,
+///     ..SolveOptions::default()
+/// };
+/// let solution = Solution::solve_with_options(SolveState::from(&board), solve_options, &heuristics);
+/// let metrics = solution.metrics.unwrap();
+/// assert!(metrics.heuristic_evaluations >= solution.step_count());
+/// assert!(metrics.coordset_ops > 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SolveMetrics {
+    /// How many times a heuristic checked a solve state for changes.
+    pub heuristic_evaluations: usize,
+
+    /// How many times the solver reused work instead of repeating it, such
+    /// as when looking ahead skips changes that an earlier heuristic already
+    /// made.
+    pub cache_hits: usize,
+
+    /// An estimate of the [CoordSet][crate::datastructure::CoordSet]
+    /// operations the heuristics made, counting one for each square that a
+    /// heuristic looks at each time it checks a state.
+    pub coordset_ops: usize,
+
+    /// The wall time spent choosing each step: checking heuristics, and
+    /// filtering their changes.
+    pub choose_time: Duration,
+
+    /// The wall time spent applying each step's changes.
+    pub apply_time: Duration,
+
+    /// The wall time spent deciding what to do when no heuristic applied.
+    pub stall_time: Duration,
+}
+
+impl SolveMetrics {
+    /// The wall time spent in every phase together.
+    pub fn total_time(&self) -> Duration {
+        self.choose_time + self.apply_time + self.stall_time
+    }
+
+    /// Returns the metrics as a JSON object, with times in microseconds.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "heuristic_evaluations": self.heuristic_evaluations,
+            "cache_hits": self.cache_hits,
+            "coordset_ops": self.coordset_ops,
+            "choose_micros": self.choose_time.as_micros() as u64,
+            "apply_micros": self.apply_time.as_micros() as u64,
+            "stall_micros": self.stall_time.as_micros() as u64,
+        })
+    }
+}
+
+impl AddAssign for SolveMetrics {
+    fn add_assign(&mut self, other: SolveMetrics) {
+        self.heuristic_evaluations += other.heuristic_evaluations;
+        self.cache_hits += other.cache_hits;
+        self.coordset_ops += other.coordset_ops;
+        self.choose_time += other.choose_time;
+        self.apply_time += other.apply_time;
+        self.stall_time += other.stall_time;
+    }
+}
+
+impl Display for SolveMetrics {
+    /// Displays one metric per line.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Heuristic evaluations: {}", self.heuristic_evaluations)?;
+        writeln!(f, "Cache hits: {}", self.cache_hits)?;
+        writeln!(f, "CoordSet operations (estimated): {}", self.coordset_ops)?;
+        writeln!(f, "Choosing steps: {:?}", self.choose_time)?;
+        writeln!(f, "Applying steps: {:?}", self.apply_time)?;
+        write!(f, "Handling stalls: {:?}", self.stall_time)
+    }
+}
+
+/// Runs the given function, adding what it counts with [count] to the given
+/// metrics, and adding the time it takes with the given phase.
+pub(crate) fn collect<T>(
+    metrics: &mut SolveMetrics,
+    phase: fn(&mut SolveMetrics) -> &mut Duration,
+    f: impl FnOnce() -> T,
+) -> T {
+    let outer = COLLECTING.replace(Some(SolveMetrics::default()));
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    if let Some(collected) = COLLECTING.replace(outer) {
+        *metrics += collected;
+    }
+    *phase(metrics) += elapsed;
+    result
+}
+
+/// Whether metrics are being collected on this thread, so that counts that
+/// take work to find can be skipped when they aren't.
+pub(crate) fn collecting() -> bool {
+    COLLECTING.with_borrow(Option::is_some)
+}
+
+/// Updates the metrics being collected on this thread, if any.
+pub(crate) fn count(f: impl FnOnce(&mut SolveMetrics)) {
+    COLLECTING.with_borrow_mut(|metrics| {
+        if let Some(metrics) = metrics {
+            f(metrics)
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collect_only_counts_inside() {
+        count(|m| m.cache_hits += 1);
+        assert!(!collecting());
+
+        let mut metrics = SolveMetrics::default();
+        let answer = collect(
+            &mut metrics,
+            |m| &mut m.apply_time,
+            || {
+                assert!(collecting());
+                count(|m| m.cache_hits += 2);
+                // Nested collection is kept apart from the outer one.
+                let mut inner = SolveMetrics::default();
+                collect(
+                    &mut inner,
+                    |m| &mut m.stall_time,
+                    || count(|m| m.cache_hits += 5),
+                );
+                assert_eq!(inner.cache_hits, 5);
+                42
+            },
+        );
+        assert_eq!(answer, 42);
+        assert_eq!(metrics.cache_hits, 2);
+        assert_eq!(metrics.stall_time, Duration::ZERO);
+        assert!(!collecting());
+    }
+}
@@ -0,0 +1,349 @@
+use std::fmt::Write;
+
+use image::{Rgb, RgbImage};
+
+use crate::{
+    heuristic::Changes,
+    palette::Palette,
+    solution::Solution,
+    solvestate::{SolveState, SquareVal},
+};
+
+/// The side length of a square in a montage, in pixels.
+const SQUARE_SIZE: usize = 24;
+
+/// The height of the caption above each board, in pixels.
+const CAPTION_HEIGHT: usize = 20;
+
+/// The space around and between boards, in pixels.
+const GAP: usize = 12;
+
+/// One board in a [Montage], with the caption drawn above it.
+#[derive(Clone, Debug)]
+pub struct MontageTile<'a> {
+    /// The text shown above the board.
+    pub caption: String,
+
+    /// The board and its Queens and Xs.
+    pub solve_state: SolveState<'a>,
+}
+
+/// Several boards tiled into a grid, such as the key steps of a solve or a
+/// pack of puzzles, which can be drawn as a single SVG or PNG image.
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Board;
+/// # use qsolve::montage::Montage;
+/// # use qsolve::palette::Palette;
+/// # use qsolve::solvestate::SolveState;
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+/// let mut montage = Montage::new(2, Palette::Pastel);
+/// montage.push("Empty", SolveState::from(&board));
+/// montage.push("One Queen", SolveState::with_queens(&board, &[(0, 1)]));
+/// assert!(montage.svg().contains("One Queen"));
+/// assert_eq!(montage.png().dimensions(), montage.dimensions());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Montage<'a> {
+    /// The boards, in the order they are laid out: left to right, then top
+    /// to bottom.
+    pub tiles: Vec<MontageTile<'a>>,
+
+    /// How many boards are drawn in each row of the grid.
+    pub columns: usize,
+
+    /// The colors the boards are drawn with.
+    pub palette: Palette,
+}
+
+impl<'a> Montage<'a> {
+    /// Creates an empty montage with the given number of columns.
+    pub fn new(columns: usize, palette: Palette) -> Self {
+        Montage {
+            tiles: vec![],
+            columns: columns.max(1),
+            palette,
+        }
+    }
+
+    /// Adds a board to the end of the montage.
+    pub fn push(&mut self, caption: impl Into<String>, solve_state: SolveState<'a>) {
+        self.tiles.push(MontageTile {
+            caption: caption.into(),
+            solve_state,
+        });
+    }
+
+    /// Creates a montage of the key steps of a solve: the starting board,
+    /// the board after each step that placed a Queen, and (if the solve got
+    /// stuck) the board it ended on.
+    pub fn key_steps(solution: &Solution<'_, 'a>, columns: usize, palette: Palette) -> Self {
+        let mut montage = Montage::new(columns, palette);
+        let mut step_count = 0;
+        for (i, item) in solution.steps.iter().enumerate() {
+            if i == 0 {
+                montage.push("Start", item.solve_state.clone());
+            }
+            let Some(h) = item.next_heuristic else {
+                continue;
+            };
+            step_count += 1;
+            if let Some(Changes::AddQueen { .. }) = item.changes {
+                let after = solution
+                    .steps
+                    .get(i + 1)
+                    .map_or(&solution.final_state, |next| &next.solve_state);
+                montage.push(format!("Step {step_count}: {}", h.name()), after.clone());
+            }
+        }
+        if !solution.is_solved() {
+            montage.push(
+                format!("Unsolved after {step_count} steps"),
+                solution.final_state.clone(),
+            );
+        }
+        montage
+    }
+
+    /// Returns the width and height of the montage, in pixels.
+    pub fn dimensions(&self) -> (u32, u32) {
+        let (width, height) = self.layout().dimensions(self.tiles.len());
+        (width as u32, height as u32)
+    }
+
+    /// Returns the montage as an SVG image, with each board's caption above
+    /// it and the borders between regions drawn in thick lines.
+    pub fn svg(&self) -> String {
+        let layout = self.layout();
+        let (width, height) = layout.dimensions(self.tiles.len());
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" font-family=\"sans-serif\">\n"
+        );
+        // Writing to a String can't fail, so the results are ignored below.
+        let _ = writeln!(
+            svg,
+            "  <rect width=\"{width}\" height=\"{height}\" fill=\"white\"/>"
+        );
+        for (i, tile) in self.tiles.iter().enumerate() {
+            let (x0, y0) = layout.origin(i);
+            let board = tile.solve_state.board;
+            let grid = board.size() * SQUARE_SIZE;
+            let _ = writeln!(
+                svg,
+                "  <text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"{}\">{}</text>",
+                x0 + grid / 2,
+                y0 + CAPTION_HEIGHT * 3 / 4,
+                CAPTION_HEIGHT * 3 / 5,
+                escape_xml(&tile.caption)
+            );
+            let y0 = y0 + CAPTION_HEIGHT;
+            for coord in board.all_coords() {
+                let (x, y) = (x0 + coord.1 * SQUARE_SIZE, y0 + coord.0 * SQUARE_SIZE);
+                let (r, g, b) = self.palette.rgb(board.color(&coord));
+                let _ = writeln!(
+                    svg,
+                    "  <rect x=\"{x}\" y=\"{y}\" width=\"{SQUARE_SIZE}\" height=\"{SQUARE_SIZE}\" fill=\"rgb({r},{g},{b})\" stroke=\"gray\"/>"
+                );
+                let mark = match tile.solve_state.square(&coord) {
+                    Some(SquareVal::Queen) => "\u{265B}",
+                    Some(SquareVal::X) => "\u{00D7}",
+                    None => continue,
+                };
+                let _ = writeln!(
+                    svg,
+                    "  <text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"{}\">{mark}</text>",
+                    x + SQUARE_SIZE / 2,
+                    y + SQUARE_SIZE * 3 / 4,
+                    SQUARE_SIZE * 2 / 3
+                );
+            }
+            for coord in board.all_coords() {
+                let (x, y) = (x0 + coord.1 * SQUARE_SIZE, y0 + coord.0 * SQUARE_SIZE);
+                let color = board.color(&coord);
+                if coord.1 + 1 < board.size() && board.color(&(coord.0, coord.1 + 1)) != color {
+                    let _ = writeln!(
+                        svg,
+                        "  <line x1=\"{0}\" y1=\"{y}\" x2=\"{0}\" y2=\"{1}\" stroke=\"black\" stroke-width=\"2\"/>",
+                        x + SQUARE_SIZE,
+                        y + SQUARE_SIZE
+                    );
+                }
+                if coord.0 + 1 < board.size() && board.color(&(coord.0 + 1, coord.1)) != color {
+                    let _ = writeln!(
+                        svg,
+                        "  <line x1=\"{x}\" y1=\"{0}\" x2=\"{1}\" y2=\"{0}\" stroke=\"black\" stroke-width=\"2\"/>",
+                        y + SQUARE_SIZE,
+                        x + SQUARE_SIZE
+                    );
+                }
+            }
+            let _ = writeln!(
+                svg,
+                "  <rect x=\"{x0}\" y=\"{y0}\" width=\"{grid}\" height=\"{grid}\" fill=\"none\" stroke=\"black\" stroke-width=\"2\"/>"
+            );
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Returns the montage as an image, laid out like [Montage::svg].
+    ///
+    /// The image crate can't draw text, so the captions are left out, and
+    /// Queens are drawn as dots and Xs as crosses.
+    pub fn png(&self) -> RgbImage {
+        let layout = self.layout();
+        let (width, height) = layout.dimensions(self.tiles.len());
+        let mut img = RgbImage::from_pixel(width as u32, height as u32, Rgb([255, 255, 255]));
+        let mut fill = |x: usize, y: usize, w: usize, h: usize, rgb: Rgb<u8>| {
+            for py in y..y + h {
+                for px in x..x + w {
+                    img.put_pixel(px as u32, py as u32, rgb);
+                }
+            }
+        };
+        let black = Rgb([0, 0, 0]);
+        for (i, tile) in self.tiles.iter().enumerate() {
+            let (x0, y0) = layout.origin(i);
+            let y0 = y0 + CAPTION_HEIGHT;
+            let board = tile.solve_state.board;
+            let grid = board.size() * SQUARE_SIZE;
+            for coord in board.all_coords() {
+                let (x, y) = (x0 + coord.1 * SQUARE_SIZE, y0 + coord.0 * SQUARE_SIZE);
+                let (r, g, b) = self.palette.rgb(board.color(&coord));
+                fill(x, y, SQUARE_SIZE, SQUARE_SIZE, Rgb([128, 128, 128]));
+                fill(
+                    x + 1,
+                    y + 1,
+                    SQUARE_SIZE - 1,
+                    SQUARE_SIZE - 1,
+                    Rgb([r, g, b]),
+                );
+                let third = SQUARE_SIZE / 3;
+                match tile.solve_state.square(&coord) {
+                    Some(SquareVal::Queen) => fill(x + third, y + third, third, third, black),
+                    Some(SquareVal::X) => {
+                        for d in 0..third {
+                            fill(x + third + d, y + third + d, 1, 1, black);
+                            fill(x + 2 * third - 1 - d, y + third + d, 1, 1, black);
+                        }
+                    }
+                    None => {}
+                }
+                let color = board.color(&coord);
+                if coord.1 + 1 < board.size() && board.color(&(coord.0, coord.1 + 1)) != color {
+                    fill(x + SQUARE_SIZE - 1, y, 2, SQUARE_SIZE, black);
+                }
+                if coord.0 + 1 < board.size() && board.color(&(coord.0 + 1, coord.1)) != color {
+                    fill(x, y + SQUARE_SIZE - 1, SQUARE_SIZE, 2, black);
+                }
+            }
+            fill(x0 - 1, y0 - 1, grid + 2, 2, black);
+            fill(x0 - 1, y0 + grid - 1, grid + 2, 2, black);
+            fill(x0 - 1, y0 - 1, 2, grid + 2, black);
+            fill(x0 + grid - 1, y0 - 1, 2, grid + 2, black);
+        }
+        img
+    }
+
+    /// Returns the layout of the grid, where every cell is big enough for
+    /// the largest board.
+    fn layout(&self) -> Layout {
+        let largest = self
+            .tiles
+            .iter()
+            .map(|tile| tile.solve_state.board.size())
+            .max()
+            .unwrap_or_default();
+        Layout {
+            columns: self.columns.min(self.tiles.len()).max(1),
+            cell_width: largest * SQUARE_SIZE,
+            cell_height: CAPTION_HEIGHT + largest * SQUARE_SIZE,
+        }
+    }
+}
+
+/// Where the cells of a [Montage] go.
+struct Layout {
+    columns: usize,
+    cell_width: usize,
+    cell_height: usize,
+}
+
+impl Layout {
+    /// Returns the width and height of a montage with the given number of
+    /// tiles.
+    fn dimensions(&self, tiles: usize) -> (usize, usize) {
+        let rows = tiles.div_ceil(self.columns).max(1);
+        (
+            GAP + self.columns * (self.cell_width + GAP),
+            GAP + rows * (self.cell_height + GAP),
+        )
+    }
+
+    /// Returns the top left corner of the cell for the given tile.
+    fn origin(&self, tile: usize) -> (usize, usize) {
+        (
+            GAP + (tile % self.columns) * (self.cell_width + GAP),
+            GAP + (tile / self.columns) * (self.cell_height + GAP),
+        )
+    }
+}
+
+/// Escapes the characters that can't appear as-is in SVG text.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use anyhow::Result;
+
+    use crate::{
+        board::Board,
+        heuristic::all_heuristics,
+        solvestate::{SolveState, SolveStrategy},
+    };
+
+    use super::*;
+
+    #[test]
+    fn montage_tiles_key_steps() -> Result<()> {
+        let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        let heuristics = all_heuristics(&board);
+        let solution = Solution::solve(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+        let montage = Montage::key_steps(&solution, 3, Palette::Pastel);
+        // The start, then one tile for each of the four Queens.
+        assert_eq!(montage.tiles.len(), 5);
+        assert_eq!(montage.tiles[0].caption, "Start");
+        assert!(montage.tiles[4].solve_state.complete());
+
+        let cell = 4 * SQUARE_SIZE;
+        assert_eq!(
+            montage.dimensions(),
+            (
+                (GAP + 3 * (cell + GAP)) as u32,
+                (GAP + 2 * (CAPTION_HEIGHT + cell + GAP)) as u32
+            )
+        );
+        let svg = montage.svg();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("\u{265B}").count(), 1 + 2 + 3 + 4);
+        assert_eq!(montage.png().dimensions(), montage.dimensions());
+
+        let mut escaped = Montage::new(1, Palette::Classic);
+        escaped.push("a < b & c", SolveState::from(&board));
+        assert!(escaped.svg().contains(">a &lt; b &amp; c<"));
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use anyhow::{Result, bail};
+use itertools::Itertools;
+use serde_json::{Value, json};
+
+use crate::datastructure::{Coord, CoordSet};
+
+/// How squares are written for people to read and type: whether the row or
+/// the column comes first, and whether they are numbered from 0 or from 1.
+///
+/// Internally, squares are always `(row, col)` pairs numbered from 0; a
+/// style only changes how they are shown and parsed. A style is named by
+/// how it writes the top-left square: `r1c1` (the default), `c1r1`, `r0c0`
+/// or `c0r0`.
+///
+/// # Examples
+/// ```
+/// # use qsolve::notation::CoordStyle;
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// assert_eq!(CoordStyle::default().text(&(2, 3)), "r3c4");
+/// let style = CoordStyle::from_str("c0r0")?;
+/// assert_eq!(style.text(&(2, 3)), "c3r2");
+/// assert_eq!(style.parse("c3r2")?, (2, 3));
+/// assert_eq!(style.parse("r2c3")?, (2, 3));
+/// assert_eq!(style.json(&(2, 3)), serde_json::json!([3, 2]));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CoordStyle {
+    /// Whether the column is written before the row.
+    pub col_first: bool,
+
+    /// The number of the first row and column: 0 or 1.
+    pub origin: usize,
+}
+
+impl CoordStyle {
+    /// The style of JSON output: row first, numbered from 0.
+    pub const JSON: CoordStyle = CoordStyle {
+        col_first: false,
+        origin: 0,
+    };
+
+    /// Writes the given square, like `r3c4`.
+    pub fn text(&self, &(r, c): &Coord) -> String {
+        let (r, c) = (r + self.origin, c + self.origin);
+        match self.col_first {
+            false => format!("r{r}c{c}"),
+            true => format!("c{c}r{r}"),
+        }
+    }
+
+    /// Writes the given square in words, like `row 3 column 4`.
+    pub fn words(&self, &(r, c): &Coord) -> String {
+        let (r, c) = (r + self.origin, c + self.origin);
+        match self.col_first {
+            false => format!("row {r} column {c}"),
+            true => format!("column {c} row {r}"),
+        }
+    }
+
+    /// Writes the given squares in reading order, separated by spaces.
+    pub fn squares_text(&self, coords: &CoordSet) -> String {
+        coords.iter().map(|coord| self.text(&coord)).join(" ")
+    }
+
+    /// Writes the given square as a JSON pair of numbers, in this style's
+    /// order.
+    pub fn json(&self, &(r, c): &Coord) -> Value {
+        let (r, c) = (r + self.origin, c + self.origin);
+        match self.col_first {
+            false => json!([r, c]),
+            true => json!([c, r]),
+        }
+    }
+
+    /// Parses a square like `r3c4` or `c4r3`, numbered from this style's
+    /// origin. Either order is accepted, since the letters say which number
+    /// is which.
+    pub fn parse(&self, s: &str) -> Result<Coord> {
+        let numbers = |first: char, second: char| {
+            let (a, b) = s.strip_prefix(first)?.split_once(second)?;
+            Some((a.parse::<usize>().ok()?, b.parse::<usize>().ok()?))
+        };
+        let parsed = numbers('r', 'c').or_else(|| numbers('c', 'r').map(|(c, r)| (r, c)));
+        match parsed {
+            Some((r, c)) if r >= self.origin && c >= self.origin => {
+                Ok((r - self.origin, c - self.origin))
+            }
+            _ => bail!(
+                "Expected a square like {}, but found `{s}`",
+                self.text(&(2, 3))
+            ),
+        }
+    }
+}
+
+impl Default for CoordStyle {
+    fn default() -> Self {
+        CoordStyle {
+            col_first: false,
+            origin: 1,
+        }
+    }
+}
+
+impl Display for CoordStyle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text(&(0, 0)))
+    }
+}
+
+impl FromStr for CoordStyle {
+    type Err = anyhow::Error;
+
+    /// Parses a style named by its top-left square, like `r1c1` or `c0r0`.
+    fn from_str(s: &str) -> Result<Self> {
+        let style = match s {
+            "r1c1" => CoordStyle::default(),
+            "c1r1" => CoordStyle {
+                col_first: true,
+                origin: 1,
+            },
+            "r0c0" => CoordStyle::JSON,
+            "c0r0" => CoordStyle {
+                col_first: true,
+                origin: 0,
+            },
+            _ => bail!("Unknown coordinate style `{s}`; expected r1c1, c1r1, r0c0 or c0r0"),
+        };
+        Ok(style)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coord_styles_roundtrip() -> Result<()> {
+        for name in ["r1c1", "c1r1", "r0c0", "c0r0"] {
+            let style = CoordStyle::from_str(name)?;
+            assert_eq!(style.to_string(), name);
+            for coord in [(0, 0), (2, 9), (10, 1)] {
+                assert_eq!(style.parse(&style.text(&coord))?, coord);
+            }
+        }
+        assert!(CoordStyle::from_str("x1y1").is_err());
+        assert!(CoordStyle::default().parse("r0c4").is_err());
+        assert!(CoordStyle::default().parse("r3").is_err());
+        assert_eq!(
+            CoordStyle::default().squares_text(&CoordSet::from_iter([(7, 0), (0, 9)])),
+            "r1c10 r8c1"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use image::{Rgb, RgbImage};
+
+use crate::{datastructure::Coord, image::ImageGrid};
+
+/// The crown drawn on each Queen, one row of blocks per string, where `#`
+/// is filled in.
+const CROWN: [&str; 5] = ["#..#..#", "#..#..#", "##.#.##", "#######", "#######"];
+
+/// How much of the width of a square the crown takes up, in percent.
+const CROWN_PERCENT: u32 = 60;
+
+/// Returns a copy of the screenshot with a crown drawn in the middle of each
+/// of the given squares, using the squares of the grid that
+/// [detect_grid][crate::image::detect_grid] found in it.
+///
+/// The crowns are black with a white outline, so they show up on any color,
+/// and so that the result can be held up against the game itself. Squares
+/// that aren't in the grid are skipped.
+///
+/// # Examples
+/// ```no_run
+/// # use qsolve::image::detect_grid;
+/// # use qsolve::overlay::overlay_queens;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let img = image::open("path/to/image.png")?.to_rgb8();
+/// let grid = detect_grid(&img)?;
+/// let solved = overlay_queens(&img, &grid, [(0, 1), (1, 3), (2, 0), (3, 2)]);
+/// solved.save("solved.png")?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn overlay_queens(
+    img: &RgbImage,
+    grid: &ImageGrid,
+    queens: impl IntoIterator<Item = Coord>,
+) -> RgbImage {
+    let mut overlaid = img.clone();
+    for (row, col) in queens {
+        let (Some(rows), Some(cols)) = (grid.rows.get(row), grid.cols.get(col)) else {
+            continue;
+        };
+        let side = (rows.end - rows.start).min(cols.end - cols.start);
+        let block = (side * CROWN_PERCENT / 100 / CROWN[0].len() as u32).max(1);
+        let outline = (block / 2).max(1);
+        let width = block * CROWN[0].len() as u32;
+        let height = block * CROWN.len() as u32;
+        let left = cols.start + (cols.end - cols.start).saturating_sub(width) / 2;
+        let top = rows.start + (rows.end - rows.start).saturating_sub(height) / 2;
+        for (grow, rgb) in [(outline, Rgb([255, 255, 255])), (0, Rgb([0, 0, 0]))] {
+            for (y, line) in CROWN.iter().enumerate() {
+                for (x, _) in line.char_indices().filter(|&(_, c)| c == '#') {
+                    let x0 = (left + x as u32 * block).saturating_sub(grow);
+                    let y0 = (top + y as u32 * block).saturating_sub(grow);
+                    let x1 = (left + (x as u32 + 1) * block + grow).min(overlaid.width());
+                    let y1 = (top + (y as u32 + 1) * block + grow).min(overlaid.height());
+                    for py in y0..y1 {
+                        for px in x0..x1 {
+                            overlaid.put_pixel(px, py, rgb);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    overlaid
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::image::{detect_grid, testutil::SyntheticGrid};
+
+    use super::*;
+
+    #[test]
+    fn overlay_queens_draws_in_the_square() -> anyhow::Result<()> {
+        let synthetic = SyntheticGrid::new("wwww\nwyyy\nrrrr\nbbbb");
+        let img = synthetic.build();
+        let grid = detect_grid(&img)?;
+        let overlaid = overlay_queens(&img, &grid, [(1, 2), (9, 9)]);
+        assert_eq!(overlaid.dimensions(), img.dimensions());
+
+        let (rows, cols) = (synthetic.cell_range(1), synthetic.cell_range(2));
+        let changed = |px: u32, py: u32| overlaid.get_pixel(px, py) != img.get_pixel(px, py);
+        let (middle_x, middle_y) = ((cols.start + cols.end) / 2, (rows.start + rows.end) / 2);
+        assert_eq!(overlaid.get_pixel(middle_x, middle_y), &Rgb([0, 0, 0]));
+        assert!((0..img.height()).all(|py| !changed(middle_x, py) || rows.contains(&py)));
+        assert!((0..img.width()).all(|px| !changed(px, middle_y) || cols.contains(&px)));
+        Ok(())
+    }
+}
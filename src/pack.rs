@@ -0,0 +1,163 @@
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{Context, Result, anyhow, ensure};
+use toml::{Table, Value};
+
+/// A puzzle listed in a [Pack].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PackPuzzle {
+    /// The name the puzzle is shown with.
+    pub name: String,
+
+    /// Where the puzzle's board is: a path, relative to the pack, or a
+    /// puzzle link from [to_link][crate::link::to_link].
+    pub board: String,
+}
+
+impl PackPuzzle {
+    /// Returns the path to read the puzzle's board from, for a pack in the
+    /// given directory. A board that isn't a file there is returned as it
+    /// is, so that a puzzle link is read as a link.
+    pub fn path(&self, dir: &Path) -> PathBuf {
+        let path = dir.join(&self.board);
+        match path.exists() {
+            true => path,
+            false => PathBuf::from(&self.board),
+        }
+    }
+}
+
+/// A named list of puzzles to be played one after another, as in a
+/// tournament.
+///
+/// A pack is a TOML file, conventionally with a `.qpk` extension, with a
+/// `name` and a `[[puzzle]]` table for each puzzle, such as
+/// `games/sample.qpk`. A puzzle without a `name` is named after its board's
+/// file.
+///
+/// # Examples
+/// ```
+/// # use qsolve::pack::Pack;
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let pack = Pack::from_str(
+///     r#"
+///     name = "Warm-up"
+///
+///     [[puzzle]]
+///     board = "linkedin-1-empty.txt"
+///
+///     [[puzzle]]
+///     name = "Crowns"
+///     board = "gameofcrowns-1.txt"
+///     "#,
+/// )?;
+/// assert_eq!(pack.name, "Warm-up");
+/// assert_eq!(pack.puzzles[0].name, "linkedin-1-empty");
+/// assert_eq!(pack.puzzles[1].name, "Crowns");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Pack {
+    /// The name of the pack.
+    pub name: String,
+
+    /// The puzzles in the pack, in the order they are played.
+    pub puzzles: Vec<PackPuzzle>,
+}
+
+impl Pack {
+    /// Reads the pack from the given file. A pack without a name is named
+    /// after its file.
+    pub fn try_from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read pack {path:?}"))?;
+        let mut pack =
+            Pack::from_str(&content).with_context(|| format!("Invalid pack {path:?}"))?;
+        if pack.name.is_empty() {
+            pack.name = file_stem(path);
+        }
+        Ok(pack)
+    }
+}
+
+impl FromStr for Pack {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let table = s.parse::<Table>()?;
+        let name = match table.get("name") {
+            None => String::new(),
+            Some(name) => name
+                .as_str()
+                .ok_or_else(|| anyhow!("name must be a string"))?
+                .to_string(),
+        };
+        let puzzles = table
+            .get("puzzle")
+            .ok_or_else(|| anyhow!("A pack needs at least one puzzle"))?
+            .as_array()
+            .ok_or_else(|| anyhow!("puzzle must be an array of tables"))?
+            .iter()
+            .map(pack_puzzle)
+            .collect::<Result<Vec<_>>>()?;
+        ensure!(!puzzles.is_empty(), "A pack needs at least one puzzle");
+        Ok(Pack { name, puzzles })
+    }
+}
+
+/// Parses a single `[[puzzle]]` table.
+fn pack_puzzle(value: &Value) -> Result<PackPuzzle> {
+    let string = |key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+    let board = string("board").ok_or_else(|| anyhow!("Every puzzle needs a board"))?;
+    Ok(PackPuzzle {
+        name: string("name").unwrap_or_else(|| file_stem(Path::new(&board))),
+        board,
+    })
+}
+
+/// Returns the name of the given file without its extension, or the whole
+/// path if it has none.
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{file::QueensFile, link::to_link, solvestate::SolveState};
+
+    use super::*;
+
+    #[test]
+    fn pack_reads_paths_and_links() -> Result<()> {
+        let pack = Pack::try_from_file(Path::new("games/sample.qpk"))?;
+        assert!(!pack.name.is_empty());
+        for puzzle in &pack.puzzles {
+            assert!(puzzle.path(Path::new("games")).exists(), "{puzzle:?}");
+        }
+
+        let queens_file = QueensFile::try_from_text_file(&"games/linkedin-1-empty.txt".into())?;
+        let link = to_link(&SolveState::from(&queens_file));
+        let pack = Pack::from_str(&format!(
+            "[[puzzle]]\nname = \"Linked\"\nboard = \"{link}\""
+        ))?;
+        assert_eq!(pack.name, "");
+        assert_eq!(
+            pack.puzzles[0].path(Path::new("games")),
+            PathBuf::from(&link)
+        );
+
+        assert!(Pack::from_str("name = \"Empty\"").is_err());
+        assert!(Pack::from_str("puzzle = []").is_err());
+        assert!(Pack::from_str("[[puzzle]]\nname = \"No board\"").is_err());
+        Ok(())
+    }
+}
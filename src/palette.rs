@@ -0,0 +1,265 @@
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+use owo_colors::{AnsiColors, DynColors};
+
+use crate::squarecolor::{ALL_SQUARE_COLORS, SquareColor};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+/// A theme that decides how each [SquareColor] is drawn.
+///
+/// The classic palette uses the terminal's own ANSI colors, so it matches the
+/// terminal's theme. The other palettes use truecolor, so they look the same
+/// in every terminal that supports it.
+pub enum Palette {
+    /// The terminal's 16 ANSI colors.
+    #[default]
+    Classic,
+    /// Soft pastel colors, like the ones LinkedIn uses.
+    Pastel,
+    /// Saturated colors with black or white text, whichever contrasts more.
+    HighContrast,
+}
+
+impl Palette {
+    /// Returns the background color for squares of the given color.
+    ///
+    /// # Examples
+    /// ```
+    /// # use owo_colors::{AnsiColors, DynColors};
+    /// # use qsolve::palette::Palette;
+    /// # use qsolve::squarecolor::SquareColor;
+    /// assert_eq!(Palette::Classic.bg(SquareColor::Red), DynColors::Ansi(AnsiColors::Red));
+    /// assert!(matches!(Palette::Pastel.bg(SquareColor::Red), DynColors::Rgb(..)));
+    /// ```
+    pub fn bg(&self, color: SquareColor) -> DynColors {
+        match self {
+            Palette::Classic => DynColors::Ansi(AnsiColors::from(color)),
+            Palette::Pastel => {
+                let (r, g, b) = pastel_rgb(color);
+                DynColors::Rgb(r, g, b)
+            }
+            Palette::HighContrast => {
+                let (r, g, b) = high_contrast_rgb(color);
+                DynColors::Rgb(r, g, b)
+            }
+        }
+    }
+
+    /// Returns the color of text drawn on squares of the given color, such as
+    /// Queens and Xs.
+    pub fn fg(&self, color: SquareColor) -> DynColors {
+        match self {
+            Palette::Classic => DynColors::Ansi(color.fg_color()),
+            Palette::Pastel => DynColors::Rgb(0, 0, 0),
+            Palette::HighContrast => {
+                let (r, g, b) = high_contrast_rgb(color);
+                // Perceived brightness, from ITU-R BT.601.
+                let luma = 299 * r as u32 + 587 * g as u32 + 114 * b as u32;
+                if luma > 128_000 {
+                    DynColors::Rgb(0, 0, 0)
+                } else {
+                    DynColors::Rgb(255, 255, 255)
+                }
+            }
+        }
+    }
+
+    /// Returns the RGB value of squares of the given color, for drawing
+    /// boards as images. The classic palette uses the xterm defaults, since
+    /// there is no way to know the terminal's own colors.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::palette::Palette;
+    /// # use qsolve::squarecolor::SquareColor;
+    /// assert_eq!(Palette::Classic.rgb(SquareColor::Red), (205, 0, 0));
+    /// assert_eq!(Palette::HighContrast.rgb(SquareColor::Red), (200, 0, 0));
+    /// ```
+    pub fn rgb(&self, color: SquareColor) -> (u8, u8, u8) {
+        match self {
+            Palette::Classic => classic_rgb(color),
+            Palette::Pastel => pastel_rgb(color),
+            Palette::HighContrast => high_contrast_rgb(color),
+        }
+    }
+
+    /// Returns the emoji used for the given color in share text.
+    ///
+    /// Emoji only come in a few colors, so normal colors are represented by
+    /// circles and bright colors by squares (with the cyans using diamonds,
+    /// since there is no cyan square or circle). Every color in a palette maps
+    /// to a distinct emoji, and since emoji have fixed colors, every palette
+    /// currently uses the same ones.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::palette::Palette;
+    /// # use qsolve::squarecolor::SquareColor;
+    /// assert_eq!(Palette::Classic.emoji(SquareColor::Red), '\u{1F534}');
+    /// assert_eq!(Palette::Classic.emoji(SquareColor::BrightRed), '\u{1F7E5}');
+    /// ```
+    pub fn emoji(&self, color: SquareColor) -> char {
+        match color {
+            SquareColor::Black => '\u{26AB}',
+            SquareColor::Red => '\u{1F534}',
+            SquareColor::Green => '\u{1F7E2}',
+            SquareColor::Yellow => '\u{1F7E1}',
+            SquareColor::Blue => '\u{1F535}',
+            SquareColor::Magenta => '\u{1F7E3}',
+            SquareColor::Cyan => '\u{1F539}',
+            SquareColor::White => '\u{26AA}',
+            SquareColor::BrightBlack => '\u{2B1B}',
+            SquareColor::BrightRed => '\u{1F7E5}',
+            SquareColor::BrightGreen => '\u{1F7E9}',
+            SquareColor::BrightYellow => '\u{1F7E8}',
+            SquareColor::BrightBlue => '\u{1F7E6}',
+            SquareColor::BrightMagenta => '\u{1F7EA}',
+            SquareColor::BrightCyan => '\u{1F537}',
+            SquareColor::BrightWhite => '\u{2B1C}',
+        }
+    }
+
+    /// Returns the color that the given emoji stands for in share text, the
+    /// reverse of [Palette::emoji], or None if it doesn't stand for one.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::palette::Palette;
+    /// # use qsolve::squarecolor::SquareColor;
+    /// assert_eq!(Palette::Classic.color_for_emoji('\u{1F534}'), Some(SquareColor::Red));
+    /// assert_eq!(Palette::Classic.color_for_emoji('\u{1F451}'), None);
+    /// ```
+    pub fn color_for_emoji(&self, emoji: char) -> Option<SquareColor> {
+        ALL_SQUARE_COLORS
+            .into_iter()
+            .find(|&color| self.emoji(color) == emoji)
+    }
+}
+
+/// Returns the RGB value of the given color in xterm's default ANSI colors.
+fn classic_rgb(color: SquareColor) -> (u8, u8, u8) {
+    match color {
+        SquareColor::Black => (0, 0, 0),
+        SquareColor::Red => (205, 0, 0),
+        SquareColor::Green => (0, 205, 0),
+        SquareColor::Yellow => (205, 205, 0),
+        SquareColor::Blue => (0, 0, 238),
+        SquareColor::Magenta => (205, 0, 205),
+        SquareColor::Cyan => (0, 205, 205),
+        SquareColor::White => (229, 229, 229),
+        SquareColor::BrightBlack => (127, 127, 127),
+        SquareColor::BrightRed => (255, 0, 0),
+        SquareColor::BrightGreen => (0, 255, 0),
+        SquareColor::BrightYellow => (255, 255, 0),
+        SquareColor::BrightBlue => (92, 92, 255),
+        SquareColor::BrightMagenta => (255, 0, 255),
+        SquareColor::BrightCyan => (0, 255, 255),
+        SquareColor::BrightWhite => (255, 255, 255),
+    }
+}
+
+/// Returns the RGB value of the given color in the pastel palette.
+fn pastel_rgb(color: SquareColor) -> (u8, u8, u8) {
+    match color {
+        SquareColor::Black => (185, 178, 158),
+        SquareColor::Red => (255, 123, 96),
+        SquareColor::Green => (179, 223, 160),
+        SquareColor::Yellow => (230, 243, 136),
+        SquareColor::Blue => (150, 190, 255),
+        SquareColor::Magenta => (223, 160, 191),
+        SquareColor::Cyan => (163, 210, 216),
+        SquareColor::White => (223, 223, 223),
+        SquareColor::BrightBlack => (160, 160, 170),
+        SquareColor::BrightRed => (255, 180, 160),
+        SquareColor::BrightGreen => (205, 245, 185),
+        SquareColor::BrightYellow => (255, 201, 146),
+        SquareColor::BrightBlue => (187, 163, 226),
+        SquareColor::BrightMagenta => (250, 205, 235),
+        SquareColor::BrightCyan => (195, 240, 240),
+        SquareColor::BrightWhite => (248, 248, 248),
+    }
+}
+
+/// Returns the RGB value of the given color in the high contrast palette.
+fn high_contrast_rgb(color: SquareColor) -> (u8, u8, u8) {
+    match color {
+        SquareColor::Black => (0, 0, 0),
+        SquareColor::Red => (200, 0, 0),
+        SquareColor::Green => (0, 150, 0),
+        SquareColor::Yellow => (200, 120, 0),
+        SquareColor::Blue => (0, 0, 220),
+        SquareColor::Magenta => (170, 0, 170),
+        SquareColor::Cyan => (0, 160, 160),
+        SquareColor::White => (190, 190, 190),
+        SquareColor::BrightBlack => (90, 90, 90),
+        SquareColor::BrightRed => (255, 90, 90),
+        SquareColor::BrightGreen => (90, 255, 90),
+        SquareColor::BrightYellow => (255, 255, 60),
+        SquareColor::BrightBlue => (100, 100, 255),
+        SquareColor::BrightMagenta => (255, 90, 255),
+        SquareColor::BrightCyan => (90, 255, 255),
+        SquareColor::BrightWhite => (255, 255, 255),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn palettes_have_distinct_colors() {
+        for palette in [Palette::Classic, Palette::Pastel, Palette::HighContrast] {
+            assert!(
+                ALL_SQUARE_COLORS
+                    .iter()
+                    .map(|&color| format!("{:?}", palette.bg(color)))
+                    .all_unique(),
+                "{palette:?}"
+            );
+            assert!(
+                ALL_SQUARE_COLORS
+                    .iter()
+                    .map(|&color| palette.rgb(color))
+                    .all_unique(),
+                "{palette:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn palettes_have_distinct_emoji() {
+        for palette in [Palette::Classic, Palette::Pastel, Palette::HighContrast] {
+            let emoji = ALL_SQUARE_COLORS.map(|color| palette.emoji(color));
+            assert_eq!(
+                emoji.iter().unique().count(),
+                ALL_SQUARE_COLORS.len(),
+                "{palette:?}"
+            );
+            assert!(emoji.iter().all(|c| !c.is_ascii()));
+            for color in ALL_SQUARE_COLORS {
+                assert_eq!(palette.color_for_emoji(palette.emoji(color)), Some(color));
+            }
+        }
+    }
+
+    #[test]
+    fn high_contrast_fg() {
+        let palette = Palette::HighContrast;
+        assert_eq!(
+            palette.fg(SquareColor::Black),
+            DynColors::Rgb(255, 255, 255)
+        );
+        assert_eq!(
+            palette.fg(SquareColor::BrightWhite),
+            DynColors::Rgb(0, 0, 0)
+        );
+        assert_eq!(
+            palette.fg(SquareColor::BrightYellow),
+            DynColors::Rgb(0, 0, 0)
+        );
+        assert_eq!(palette.fg(SquareColor::Blue), DynColors::Rgb(255, 255, 255));
+    }
+}
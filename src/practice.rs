@@ -0,0 +1,566 @@
+use std::{
+    fmt::{Display, Formatter},
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result, anyhow, ensure};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use log::warn;
+use serde_json::{Value, json};
+
+use crate::{
+    binary::from_bytes,
+    cache::default_dir,
+    estimate::{format_minutes, heuristic_tier},
+    generate::Difficulty,
+    heuristic::all_heuristics,
+    solution::Solution,
+    solvestate::{SolveState, SolveStrategy},
+};
+
+/// The version of the format written by [AnonymizedStats::to_json], which
+/// is bumped whenever a change would confuse older versions of qsolve.
+const STATS_VERSION: u64 = 1;
+
+/// How many tiers [heuristic_tier] sorts the heuristics of
+/// [all_heuristics][crate::heuristic::all_heuristics] into.
+const TIERS: usize = 3;
+
+/// Reads a [Difficulty] from its [Display] form in the JSON.
+fn difficulty_from_json(json: &Value) -> Result<Difficulty> {
+    match json.as_str() {
+        Some("easy") => Ok(Difficulty::Easy),
+        Some("medium") => Ok(Difficulty::Medium),
+        Some("hard") => Ok(Difficulty::Hard),
+        _ => Err(anyhow!("Practice result has no valid difficulty")),
+    }
+}
+
+/// The result of playing one generated puzzle in practice mode.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PracticeResult {
+    /// How many squares wide the board was.
+    pub size: usize,
+
+    /// How hard the board was.
+    pub difficulty: Difficulty,
+
+    /// The seed the board was generated from, so it can be played again.
+    pub seed: u64,
+
+    /// Whether the player solved the puzzle, rather than giving up on it.
+    pub solved: bool,
+
+    /// How long the player spent on the puzzle.
+    pub elapsed: Duration,
+
+    /// How many hints or solver steps the player asked for.
+    pub hints: usize,
+
+    /// The board as the player left it, encoded by
+    /// [to_bytes][crate::binary::to_bytes]; None for results recorded
+    /// before boards were kept.
+    pub state: Option<Vec<u8>>,
+}
+
+impl PracticeResult {
+    /// Returns this result as a JSON object, as stored in a [PracticeHistory].
+    pub fn to_json(&self) -> Value {
+        let mut json = json!({
+            "size": self.size,
+            "difficulty": self.difficulty.to_string(),
+            "seed": self.seed,
+            "solved": self.solved,
+            "elapsed_millis": self.elapsed.as_millis() as u64,
+            "hints": self.hints,
+        });
+        if let Some(state) = &self.state {
+            json["state"] = json!(URL_SAFE_NO_PAD.encode(state));
+        }
+        json
+    }
+
+    /// Reads a result back from the JSON returned by [PracticeResult::to_json].
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::generate::Difficulty;
+    /// # use qsolve::practice::PracticeResult;
+    /// # use std::time::Duration;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let result = PracticeResult {
+    ///     size: 8,
+    ///     difficulty: Difficulty::Hard,
+    ///     seed: 42,
+    ///     solved: true,
+    ///     elapsed: Duration::from_secs(95),
+    ///     hints: 1,
+    ///     state: Some(vec![4, 0, 0x12]),
+    /// };
+    /// assert_eq!(PracticeResult::from_json(&result.to_json())?, result);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_json(json: &Value) -> Result<Self> {
+        let number = |key: &str| {
+            json[key]
+                .as_u64()
+                .ok_or_else(|| anyhow!("Practice result is missing {key}"))
+        };
+        Ok(PracticeResult {
+            size: number("size")? as usize,
+            difficulty: difficulty_from_json(&json["difficulty"])?,
+            seed: number("seed")?,
+            solved: json["solved"]
+                .as_bool()
+                .ok_or_else(|| anyhow!("Practice result is missing solved"))?,
+            elapsed: Duration::from_millis(number("elapsed_millis")?),
+            hints: number("hints")? as usize,
+            state: match json.get("state") {
+                None => None,
+                Some(state) => Some(
+                    state
+                        .as_str()
+                        .and_then(|state| URL_SAFE_NO_PAD.decode(state).ok())
+                        .ok_or_else(|| anyhow!("Practice result has an invalid state"))?,
+                ),
+            },
+        })
+    }
+}
+
+/// A record of every puzzle played in practice mode, kept as a file with a
+/// line of JSON for each [PracticeResult].
+#[derive(Clone, Debug)]
+pub struct PracticeHistory {
+    path: PathBuf,
+}
+
+impl PracticeHistory {
+    /// Creates a history stored in the given file.
+    pub fn new(path: PathBuf) -> Self {
+        PracticeHistory { path }
+    }
+
+    /// Creates a history in the default location, or None if there isn't
+    /// one; this is the same directory as
+    /// [SolutionCache::in_default_dir][crate::cache::SolutionCache::in_default_dir].
+    pub fn in_default_dir() -> Option<Self> {
+        default_dir().map(|dir| PracticeHistory::new(dir.join("practice-history.jsonl")))
+    }
+
+    /// Adds the given result to the end of the history.
+    pub fn record(&self, result: &PracticeResult) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Could not create history directory {dir:?}"))?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Could not open history {:?}", self.path))?;
+        writeln!(file, "{}", result.to_json())
+            .with_context(|| format!("Could not write to history {:?}", self.path))
+    }
+
+    /// Returns every result in the history, oldest first.
+    ///
+    /// A missing history has no results, and lines that can't be read are
+    /// skipped with a warning.
+    pub fn results(&self) -> Result<Vec<PracticeResult>> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => {
+                return Err(err).with_context(|| format!("Could not read history {:?}", self.path));
+            }
+        };
+        Ok(content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| {
+                serde_json::from_str(line)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|json| PracticeResult::from_json(&json))
+                    .inspect_err(|err| warn!("Skipping practice result {line:?}: {err:#}"))
+                    .ok()
+            })
+            .collect())
+    }
+}
+
+/// A [PracticeResult] without the seed or the board, so that it can be
+/// shared without giving away which puzzle was played; see
+/// [AnonymizedStats].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AnonymizedResult {
+    /// How many squares wide the board was.
+    pub size: usize,
+
+    /// How hard the board was.
+    pub difficulty: Difficulty,
+
+    /// Whether the player solved the puzzle, rather than giving up on it.
+    pub solved: bool,
+
+    /// How long the player spent on the puzzle.
+    pub elapsed: Duration,
+
+    /// How many hints or solver steps the player asked for.
+    pub hints: usize,
+
+    /// How many of the steps the solver takes to solve the board use
+    /// heuristics of each [heuristic_tier], from the easiest up; None for
+    /// results recorded without their board.
+    pub tier_steps: Option<[usize; TIERS]>,
+}
+
+impl AnonymizedResult {
+    /// Anonymizes the given result, solving its board to count the steps in
+    /// each tier. A board that can't be read is left out, with a warning.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::generate::Difficulty;
+    /// # use qsolve::practice::{AnonymizedResult, PracticeResult};
+    /// # use qsolve::solvestate::SolveState;
+    /// # use std::str::FromStr;
+    /// # use std::time::Duration;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+    /// let result = PracticeResult {
+    ///     size: 4,
+    ///     difficulty: Difficulty::Medium,
+    ///     seed: 42,
+    ///     solved: true,
+    ///     elapsed: Duration::from_secs(30),
+    ///     hints: 0,
+    ///     state: Some(SolveState::from(&board).to_bytes()),
+    /// };
+    /// let anonymized = AnonymizedResult::from_result(&result);
+    /// assert_eq!(anonymized.elapsed, result.elapsed);
+    /// assert!(anonymized.tier_steps.is_some_and(|steps| steps[1] > 0));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_result(result: &PracticeResult) -> Self {
+        let tier_steps = result.state.as_deref().and_then(|state| {
+            let board = from_bytes(state)
+                .inspect_err(|err| warn!("Could not read the board of a practice result: {err:#}"))
+                .ok()?
+                .board;
+            let heuristics = all_heuristics(&board);
+            let solution =
+                Solution::solve(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+            let mut tier_steps = [0; TIERS];
+            for usage in solution.heuristic_usage() {
+                tier_steps[heuristic_tier(usage.name).min(TIERS - 1)] += usage.count;
+            }
+            Some(tier_steps)
+        });
+        AnonymizedResult {
+            size: result.size,
+            difficulty: result.difficulty,
+            solved: result.solved,
+            elapsed: result.elapsed,
+            hints: result.hints,
+            tier_steps,
+        }
+    }
+
+    /// Returns this result as a JSON object, as written by
+    /// [AnonymizedStats::to_json].
+    pub fn to_json(&self) -> Value {
+        let mut json = json!({
+            "size": self.size,
+            "difficulty": self.difficulty.to_string(),
+            "solved": self.solved,
+            "elapsed_millis": self.elapsed.as_millis() as u64,
+            "hints": self.hints,
+        });
+        if let Some(tier_steps) = &self.tier_steps {
+            json["tier_steps"] = json!(tier_steps);
+        }
+        json
+    }
+
+    /// Reads a result back from the JSON returned by
+    /// [AnonymizedResult::to_json].
+    pub fn from_json(json: &Value) -> Result<Self> {
+        let number = |json: &Value, key: &str| {
+            json.as_u64()
+                .ok_or_else(|| anyhow!("Practice result is missing {key}"))
+        };
+        Ok(AnonymizedResult {
+            size: number(&json["size"], "size")? as usize,
+            difficulty: difficulty_from_json(&json["difficulty"])?,
+            solved: json["solved"]
+                .as_bool()
+                .ok_or_else(|| anyhow!("Practice result is missing solved"))?,
+            elapsed: Duration::from_millis(number(&json["elapsed_millis"], "elapsed_millis")?),
+            hints: number(&json["hints"], "hints")? as usize,
+            tier_steps: match json.get("tier_steps") {
+                None => None,
+                Some(tier_steps) => Some(
+                    tier_steps
+                        .as_array()
+                        .filter(|steps| steps.len() == TIERS)
+                        .ok_or_else(|| anyhow!("Practice result has invalid tier steps"))?
+                        .iter()
+                        .map(|steps| Ok(number(steps, "tier steps")? as usize))
+                        .collect::<Result<Vec<_>>>()?
+                        .try_into()
+                        .unwrap_or_default(),
+                ),
+            },
+        })
+    }
+}
+
+/// Statistics about practice puzzles, from one player's [PracticeHistory] or
+/// merged from several, that say nothing about the boards played, so that
+/// they can be shared to calibrate how hard each size and difficulty is.
+///
+/// # Examples
+/// ```
+/// # use qsolve::generate::Difficulty;
+/// # use qsolve::practice::{AnonymizedStats, PracticeResult};
+/// # use std::time::Duration;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let result = PracticeResult {
+///     size: 8,
+///     difficulty: Difficulty::Hard,
+///     seed: 42,
+///     solved: true,
+///     elapsed: Duration::from_secs(95),
+///     hints: 1,
+///     state: None,
+/// };
+/// let mut stats = AnonymizedStats::from_results(&[result]);
+/// let json = stats.to_json();
+/// assert!(json["results"][0].get("seed").is_none());
+///
+/// stats.merge(AnonymizedStats::from_json(&json)?);
+/// assert_eq!(stats.results.len(), 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AnonymizedStats {
+    /// The results, in the order they were played (for each dataset merged
+    /// in).
+    pub results: Vec<AnonymizedResult>,
+}
+
+impl AnonymizedStats {
+    /// Anonymizes the given results, as with [AnonymizedResult::from_result].
+    pub fn from_results(results: &[PracticeResult]) -> Self {
+        AnonymizedStats {
+            results: results.iter().map(AnonymizedResult::from_result).collect(),
+        }
+    }
+
+    /// Reads statistics from a JSON file written by [AnonymizedStats::to_json].
+    pub fn try_from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read statistics {path:?}"))?;
+        serde_json::from_str(&content)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| AnonymizedStats::from_json(&json))
+            .with_context(|| format!("Invalid statistics {path:?}"))
+    }
+
+    /// Adds the results of another dataset to the end of this one.
+    ///
+    /// Nothing identifies a result, so merging a dataset twice counts its
+    /// results twice.
+    pub fn merge(&mut self, other: AnonymizedStats) {
+        self.results.extend(other.results);
+    }
+
+    /// Returns the statistics as a JSON object, with the version of the
+    /// format and a list of results.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "version": STATS_VERSION,
+            "results": self.results.iter().map(AnonymizedResult::to_json).collect::<Vec<_>>(),
+        })
+    }
+
+    /// Reads statistics back from the JSON returned by
+    /// [AnonymizedStats::to_json].
+    pub fn from_json(json: &Value) -> Result<Self> {
+        let version = json["version"]
+            .as_u64()
+            .ok_or_else(|| anyhow!("Statistics have no version"))?;
+        ensure!(
+            version == STATS_VERSION,
+            "Statistics are version {version}, but only version {STATS_VERSION} can be read"
+        );
+        let results = json["results"]
+            .as_array()
+            .ok_or_else(|| anyhow!("Statistics have no results"))?
+            .iter()
+            .map(AnonymizedResult::from_json)
+            .collect::<Result<_>>()?;
+        Ok(AnonymizedStats { results })
+    }
+}
+
+/// Statistics about the practice puzzles played at one size and difficulty.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PracticeStats {
+    /// How many puzzles were played.
+    pub played: usize,
+
+    /// How many of them were solved.
+    pub solved: usize,
+
+    /// How many were solved without any hints.
+    pub flawless: usize,
+
+    /// The fastest time for a solved puzzle.
+    pub best: Option<Duration>,
+
+    /// The average time over the solved puzzles.
+    pub average: Option<Duration>,
+}
+
+impl PracticeStats {
+    /// Computes statistics over the results with the given size and
+    /// difficulty.
+    pub fn of(results: &[PracticeResult], size: usize, difficulty: Difficulty) -> Self {
+        let matching = results
+            .iter()
+            .filter(|result| result.size == size && result.difficulty == difficulty)
+            .collect::<Vec<_>>();
+        let times = matching
+            .iter()
+            .filter(|result| result.solved)
+            .map(|result| result.elapsed)
+            .collect::<Vec<_>>();
+        PracticeStats {
+            played: matching.len(),
+            solved: times.len(),
+            flawless: matching
+                .iter()
+                .filter(|result| result.solved && result.hints == 0)
+                .count(),
+            best: times.iter().min().copied(),
+            average: (!times.is_empty())
+                .then(|| times.iter().sum::<Duration>() / times.len() as u32),
+        }
+    }
+}
+
+impl Display for PracticeStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Solved {} of {} ({} without hints)",
+            self.solved, self.played, self.flawless
+        )?;
+        if let (Some(best), Some(average)) = (self.best, self.average) {
+            write!(
+                f,
+                "; best {}, average {}",
+                format_minutes(best),
+                format_minutes(average)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(difficulty: Difficulty, solved: bool, secs: u64, hints: usize) -> PracticeResult {
+        PracticeResult {
+            size: 8,
+            difficulty,
+            seed: secs,
+            solved,
+            elapsed: Duration::from_secs(secs),
+            hints,
+            state: solved.then(|| vec![secs as u8]),
+        }
+    }
+
+    #[test]
+    fn practice_history_records_and_summarizes() -> Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("qsolve-practice-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let history = PracticeHistory::new(path.clone());
+        assert!(history.results()?.is_empty());
+
+        let results = [
+            result(Difficulty::Medium, true, 90, 0),
+            result(Difficulty::Medium, true, 150, 2),
+            result(Difficulty::Medium, false, 30, 0),
+            result(Difficulty::Hard, true, 10, 0),
+        ];
+        for result in &results {
+            history.record(result)?;
+        }
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)?
+            .write_all(b"not json\n")?;
+        assert_eq!(history.results()?, results);
+
+        let stats = PracticeStats::of(&results, 8, Difficulty::Medium);
+        assert_eq!(
+            stats,
+            PracticeStats {
+                played: 3,
+                solved: 2,
+                flawless: 1,
+                best: Some(Duration::from_secs(90)),
+                average: Some(Duration::from_secs(120)),
+            }
+        );
+        assert_eq!(
+            stats.to_string(),
+            "Solved 2 of 3 (1 without hints); best 1:30, average 2:00"
+        );
+        assert_eq!(
+            PracticeStats::of(&results, 6, Difficulty::Medium).to_string(),
+            "Solved 0 of 0 (0 without hints)"
+        );
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn anonymized_stats_round_trip() -> Result<()> {
+        let results = [
+            result(Difficulty::Easy, true, 90, 0),
+            PracticeResult {
+                state: Some(vec![0xff]),
+                ..result(Difficulty::Hard, false, 30, 2)
+            },
+        ];
+        let stats = AnonymizedStats::from_results(&results);
+        assert_eq!(stats.results[0].elapsed, Duration::from_secs(90));
+        assert_eq!(stats.results[1].tier_steps, None);
+
+        let json = stats.to_json();
+        assert_eq!(json["results"].as_array().map(Vec::len), Some(2));
+        assert!(!json.to_string().contains("seed"));
+        assert_eq!(AnonymizedStats::from_json(&json)?, stats);
+
+        let newer = json!({ "version": STATS_VERSION + 1, "results": [] });
+        assert!(AnonymizedStats::from_json(&newer).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+//! The types and functions most uses of the library need, to be imported with
+//! `use qsolve::prelude::*;`.
+//!
+//! # Examples
+//! ```
+//! use qsolve::prelude::*;
+//! # use anyhow::Result;
+//! # fn main() -> Result<()> {
+//! let queens_file = QueensFile::try_from_text_file(&"games/linkedin-1-empty.txt".into())?;
+//! let solve_state = SolveState::from(&queens_file);
+//! let heuristics = all_heuristics(solve_state.board);
+//! let solution = Solution::solve(solve_state, SolveStrategy::Fast, &heuristics);
+//! assert!(solution.is_solved());
+//! # Ok(())
+//! # }
+//! ```
+
+pub use crate::{
+    board::Board,
+    datastructure::{Coord, CoordSet},
+    file::QueensFile,
+    heuristic::{Heuristic, all_heuristics},
+    solution::Solution,
+    solveiter::solve_iter,
+    solvestate::{Charset, SolveState, SolveStrategy, SquareVal},
+};
@@ -0,0 +1,144 @@
+use std::io::Cursor;
+
+use anyhow::Result;
+use base64::{Engine, prelude::BASE64_STANDARD};
+use image::{ImageFormat, RgbImage, imageops};
+use owo_colors::OwoColorize;
+
+/// How many base64 characters the kitty graphics protocol accepts in each
+/// escape sequence.
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// How many pixels wide the image sent with the kitty graphics protocol is
+/// for each terminal cell it covers.
+const KITTY_PIXELS_PER_CELL: u32 = 10;
+
+/// Returns the number of terminal rows that an image drawn the given number
+/// of cells wide takes up, assuming cells are twice as tall as they are wide.
+fn rows_for(img: &RgbImage, width: u32) -> u32 {
+    let (w, h) = img.dimensions();
+    ((h as f64 * width as f64 / w.max(1) as f64) / 2.0)
+        .round()
+        .max(1.0) as u32
+}
+
+/// Returns the lines of a downscaled copy of the image, drawn with Unicode
+/// upper half blocks whose foreground is one pixel and whose background is
+/// the pixel below it, so that each cell shows two pixels.
+///
+/// # Examples
+/// ```
+/// # use image::{Rgb, RgbImage};
+/// # use qsolve::preview::half_blocks;
+/// let img = RgbImage::from_pixel(100, 50, Rgb([255, 0, 0]));
+/// let lines = half_blocks(&img, 20);
+/// // A 2:1 image that's 20 cells wide is 20 pixels and so 10 cells tall.
+/// assert_eq!(lines.len(), 5);
+/// assert_eq!(lines[0].matches('\u{2580}').count(), 20);
+/// ```
+pub fn half_blocks(img: &RgbImage, width: u32) -> Vec<String> {
+    let rows = rows_for(img, width);
+    let small = imageops::resize(img, width, rows * 2, imageops::FilterType::Triangle);
+    (0..rows)
+        .map(|row| {
+            (0..width)
+                .map(|col| {
+                    let top = small.get_pixel(col, row * 2).0;
+                    let bottom = small.get_pixel(col, row * 2 + 1).0;
+                    '\u{2580}'
+                        .truecolor(top[0], top[1], top[2])
+                        .on_truecolor(bottom[0], bottom[1], bottom[2])
+                        .to_string()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns the escape sequences that draw the image with the kitty graphics
+/// protocol, scaled to the given number of cells wide.
+///
+/// The image is sent as a PNG, split over as many escape sequences as it
+/// needs; terminals that don't support the protocol ignore them.
+pub fn kitty(img: &RgbImage, width: u32) -> Result<String> {
+    let (w, h) = img.dimensions();
+    let pixels = (width * KITTY_PIXELS_PER_CELL).min(w);
+    let small = imageops::resize(
+        img,
+        pixels,
+        (h * pixels / w.max(1)).max(1),
+        imageops::FilterType::Triangle,
+    );
+    let mut png = Cursor::new(vec![]);
+    small.write_to(&mut png, ImageFormat::Png)?;
+    let encoded = BASE64_STANDARD.encode(png.into_inner());
+    let chunks = encoded
+        .as_bytes()
+        .chunks(KITTY_CHUNK_SIZE)
+        .collect::<Vec<_>>();
+    let mut escapes = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        // The chunks are slices of base64, which is ASCII.
+        let chunk = std::str::from_utf8(chunk)?;
+        if i == 0 {
+            escapes.push_str(&format!(
+                "\x1b_Ga=T,f=100,c={width},r={},m={more};{chunk}\x1b\\",
+                rows_for(img, width)
+            ));
+        } else {
+            escapes.push_str(&format!("\x1b_Gm={more};{chunk}\x1b\\"));
+        }
+    }
+    Ok(escapes)
+}
+
+/// Returns the given lines with the text drawn to their right, where each
+/// of the lines takes up the given number of cells.
+///
+/// # Examples
+/// ```
+/// # use qsolve::preview::beside;
+/// let left = vec!["ab".to_string(), "cd".to_string(), "ef".to_string()];
+/// assert_eq!(beside(&left, 2, "1\n2"), "ab  1\ncd  2\nef");
+/// assert_eq!(beside(&left[..1], 2, "1\n2"), "ab  1\n    2");
+/// ```
+pub fn beside(lines: &[String], width: usize, text: &str) -> String {
+    let text = text.lines().collect::<Vec<_>>();
+    (0..lines.len().max(text.len()))
+        .map(|i| match (lines.get(i), text.get(i)) {
+            (Some(line), Some(text)) => format!("{line}  {text}"),
+            (Some(line), None) => line.clone(),
+            (None, Some(text)) => format!("{}  {text}", " ".repeat(width)),
+            (None, None) => String::new(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgb;
+
+    use super::*;
+
+    #[test]
+    fn kitty_splits_into_chunks() -> Result<()> {
+        let small = RgbImage::from_pixel(40, 40, Rgb([0, 0, 255]));
+        let escapes = kitty(&small, 4)?;
+        assert!(escapes.starts_with("\x1b_Ga=T,f=100,c=4,r=2,m=0;"));
+        assert_eq!(escapes.matches("\x1b_G").count(), 1);
+
+        // Noise compresses badly, so its PNG needs several chunks.
+        let noisy = RgbImage::from_fn(200, 200, |x, y| {
+            Rgb([(x * 7 + y * 13) as u8, (x * y) as u8, (x ^ y) as u8])
+        });
+        let escapes = kitty(&noisy, 20)?;
+        let count = escapes.matches("\x1b_G").count();
+        assert!(count > 1);
+        assert!(escapes.contains(",m=1;"));
+        assert_eq!(escapes.matches("m=1;").count(), count - 1);
+        assert!(escapes.ends_with("\x1b\\"));
+        Ok(())
+    }
+}
@@ -0,0 +1,651 @@
+use std::str::FromStr;
+
+use anyhow::{Context, Result, anyhow, bail, ensure};
+use serde_json::{Value, json};
+
+use crate::{
+    board::{Board, Region},
+    clue::position_allowed,
+    datastructure::{Coord, CoordSet, LineSet, SquareColorSet},
+    error::QSolveError,
+    heuristic::Changes,
+    release::VERSION,
+    solution::Solution,
+    solvestate::{SolveState, SquareVal},
+};
+
+/// The version of the certificate format written by [Proof::to_json].
+const PROOF_VERSION: u64 = 1;
+
+/// A rule of deduction that a [Proof] may use, each named after the
+/// heuristic that makes it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Rule {
+    /// The premise is a single row, column or color with no Queen and one
+    /// blank square, where the Queen must go.
+    LastSquareAvailable,
+    /// The premise is a row or column with a clue, which rules out every
+    /// square the clue doesn't allow.
+    ClueRulesOutSquares,
+    /// The premise is a single row, column or color with no Queen, and every
+    /// square that all of its blank squares rule out is x'd out.
+    AllPossibilitiesEliminateSquare,
+    /// The premises are N rows or N columns without a Queen, whose blank
+    /// squares have at most N colors between them, so those colors' Queens
+    /// are all on those lines and the colors are x'd out everywhere else.
+    NLinesContainOnlyNColors,
+    /// The premises are N colors without a Queen, and the rows or columns
+    /// that their blank squares all lie on, at most N of them, so those
+    /// lines' Queens all have those colors and the other colors on the lines
+    /// are x'd out.
+    NColorsOnlyAppearInNLines,
+}
+
+/// Every rule, in the order they are listed in [Rule].
+const ALL_RULES: [Rule; 5] = [
+    Rule::LastSquareAvailable,
+    Rule::ClueRulesOutSquares,
+    Rule::AllPossibilitiesEliminateSquare,
+    Rule::NLinesContainOnlyNColors,
+    Rule::NColorsOnlyAppearInNLines,
+];
+
+impl Rule {
+    /// Returns the name of the rule, which is the [name][crate::heuristic::Heuristic::name]
+    /// of the heuristic that makes it.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::LastSquareAvailable => "LastSquareAvailable",
+            Rule::ClueRulesOutSquares => "ClueRulesOutSquares",
+            Rule::AllPossibilitiesEliminateSquare => "AllPossibilitiesEliminateSquare",
+            Rule::NLinesContainOnlyNColors => "NLinesContainOnlyNColors",
+            Rule::NColorsOnlyAppearInNLines => "NColorsOnlyAppearInNLines",
+        }
+    }
+
+    /// Returns the rule with the given name, if there is one.
+    pub fn from_name(name: &str) -> Option<Rule> {
+        ALL_RULES.into_iter().find(|rule| rule.name() == name)
+    }
+}
+
+/// One step of a [Proof]: a rule, the rows, columns and colors it reasons
+/// from, and the markings it concludes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Deduction {
+    /// The rule of deduction used.
+    pub rule: Rule,
+
+    /// The rows, columns and colors the rule reasons from.
+    pub premises: Vec<Region>,
+
+    /// The markings the rule concludes.
+    pub changes: Changes,
+}
+
+impl Deduction {
+    /// Checks that the deduction follows from the given state by its rule,
+    /// looking only at the premises and the squares it marks.
+    pub fn check(&self, solve_state: &SolveState) -> Result<()> {
+        let board = solve_state.board;
+        let size = board.size();
+        ensure!(!self.premises.is_empty(), "It has no premises");
+        if let Some(coord) = self
+            .changes
+            .changed_coords()
+            .iter()
+            .find(|coord| coord.0 >= size || coord.1 >= size)
+        {
+            bail!("{} is not on the board", coord_text(&coord));
+        }
+        if let Some(coord) = self
+            .changes
+            .changed_coords()
+            .iter()
+            .find(|coord| solve_state.square(coord).is_some())
+        {
+            bail!("{} is already marked", coord_text(&coord));
+        }
+        for premise in &self.premises {
+            ensure!(premise.is_on(board), "{premise} is not on the board");
+        }
+        ensure!(
+            self.premises
+                .iter()
+                .enumerate()
+                .all(|(i, premise)| !self.premises[..i].contains(premise)),
+            "Its premises repeat a region"
+        );
+        let queens = |coords: CoordSet| {
+            coords
+                .iter()
+                .filter(|coord| solve_state.square(coord) == Some(SquareVal::Queen))
+                .count()
+        };
+        let blank = |coords: CoordSet| coords.intersection(&solve_state.blank_coords());
+        let x = match (self.rule, &self.changes) {
+            (Rule::LastSquareAvailable, Changes::AddQueen { queen, x }) => {
+                let [region] = self.premises[..] else {
+                    bail!("It needs exactly one premise");
+                };
+                let coords = region.coords(board);
+                ensure!(queens(coords) == 0, "{region} already has a Queen");
+                ensure!(
+                    blank(coords) == CoordSet::from_iter([*queen]),
+                    "{} is not the only blank square in {region}",
+                    coord_text(queen)
+                );
+                // A Queen rules out everything it borders, by the rules of
+                // the puzzle rather than by this deduction.
+                let allowed = board.queen_borders(queen);
+                return match x.iter().find(|coord| !allowed.contains(coord)) {
+                    Some(coord) => Err(anyhow!(
+                        "A Queen on {} doesn't rule out {}",
+                        coord_text(queen),
+                        coord_text(&coord)
+                    )),
+                    None => Ok(()),
+                };
+            }
+            (_, Changes::AddQueen { .. }) => bail!("{} only x's out squares", self.rule.name()),
+            (_, Changes::AddX { x }) => *x,
+        };
+        let ruled_out: CoordSet = match self.rule {
+            Rule::LastSquareAvailable => unreachable!("Handled with its Queen above"),
+            Rule::ClueRulesOutSquares => {
+                let [line] = self.premises[..] else {
+                    bail!("It needs exactly one premise");
+                };
+                let clue = board
+                    .clues()
+                    .lines()
+                    .find(|(region, _)| *region == line)
+                    .map(|(_, clue)| clue)
+                    .ok_or_else(|| anyhow!("{line} has no clue"))?;
+                line.coords(board)
+                    .iter()
+                    .filter(|&(r, c)| {
+                        let pos = match line {
+                            Region::Row(_) => c,
+                            _ => r,
+                        };
+                        !position_allowed(clue, pos, size)
+                    })
+                    .collect()
+            }
+            Rule::AllPossibilitiesEliminateSquare => {
+                let [region] = self.premises[..] else {
+                    bail!("It needs exactly one premise");
+                };
+                let coords = region.coords(board);
+                ensure!(queens(coords) == 0, "{region} already has a Queen");
+                blank(coords)
+                    .iter()
+                    .map(|coord| board.queen_borders(&coord))
+                    .reduce(|acc, borders| acc.intersection(&borders))
+                    .ok_or_else(|| anyhow!("{region} has no blank squares"))?
+            }
+            Rule::NLinesContainOnlyNColors => {
+                let (lines, line_of) = lines_of(&self.premises)?;
+                let line_coords = lines_coords(board, &self.premises);
+                ensure!(queens(line_coords) == 0, "One of its lines has a Queen");
+                let colors =
+                    SquareColorSet::from_iter(blank(line_coords).iter().map(|c| board.color(&c)));
+                ensure!(
+                    colors.len() <= lines.len(),
+                    "Its lines have {} colors between them, more than there are lines",
+                    colors.len()
+                );
+                board
+                    .all_coords()
+                    .iter()
+                    .filter(|coord| colors.contains(&board.color(coord)))
+                    .filter(|&coord| !lines.contains(&line_of(coord)))
+                    .collect()
+            }
+            Rule::NColorsOnlyAppearInNLines => {
+                let (colors, lines): (Vec<_>, Vec<_>) = self
+                    .premises
+                    .iter()
+                    .copied()
+                    .partition(|region| matches!(region, Region::Color(_)));
+                let (line_set, line_of) = lines_of(&lines)?;
+                let color_set = SquareColorSet::from_iter(colors.iter().map(|region| {
+                    let Region::Color(color) = region else {
+                        unreachable!("Partitioned by color above")
+                    };
+                    *color
+                }));
+                ensure!(!color_set.is_empty(), "It needs at least one color");
+                let color_coords = colors.iter().map(|region| region.coords(board)).fold(
+                    CoordSet::default(),
+                    |mut acc, coords| {
+                        acc.extend(coords.iter());
+                        acc
+                    },
+                );
+                ensure!(queens(color_coords) == 0, "One of its colors has a Queen");
+                if let Some(coord) = blank(color_coords)
+                    .iter()
+                    .find(|&coord| !line_set.contains(&line_of(coord)))
+                {
+                    bail!("{} is blank but not on its lines", coord_text(&coord));
+                }
+                ensure!(
+                    line_set.len() <= color_set.len(),
+                    "It has more lines than colors"
+                );
+                board
+                    .all_coords()
+                    .iter()
+                    .filter(|&coord| line_set.contains(&line_of(coord)))
+                    .filter(|coord| !color_set.contains(&board.color(coord)))
+                    .collect()
+            }
+        };
+        match x.iter().find(|coord| !ruled_out.contains(coord)) {
+            Some(coord) => bail!("{} is not ruled out", coord_text(&coord)),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns this deduction as a JSON object, as stored in a [Proof].
+    fn to_json(&self) -> Value {
+        let (queen, x) = match &self.changes {
+            Changes::AddQueen { queen, x } => (Some(queen), x),
+            Changes::AddX { x } => (None, x),
+        };
+        let mut json = json!({
+            "rule": self.rule.name(),
+            "premises": self.premises.iter().map(region_arg).collect::<Vec<_>>(),
+        });
+        if let Some(queen) = queen {
+            json["queen"] = coord_json(queen);
+        }
+        json["x"] = x.iter().map(|coord| coord_json(&coord)).collect();
+        json
+    }
+
+    /// Reads a deduction on the given board back from the JSON returned by
+    /// [Deduction::to_json].
+    fn from_json(json: &Value, board: &Board) -> Result<Self> {
+        let rule = json["rule"]
+            .as_str()
+            .ok_or_else(|| anyhow!("Every step needs a rule"))?;
+        let rule = Rule::from_name(rule).ok_or_else(|| anyhow!("Unknown rule {rule}"))?;
+        let premises = json["premises"]
+            .as_array()
+            .ok_or_else(|| anyhow!("Every step needs a list of premises"))?
+            .iter()
+            .map(|premise| {
+                premise
+                    .as_str()
+                    .ok_or_else(|| anyhow!("Premises must be strings"))
+                    .and_then(Region::from_str)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let x = json["x"]
+            .as_array()
+            .ok_or_else(|| anyhow!("Every step needs a list of x's"))?
+            .iter()
+            .map(|coord| coord_from_json(coord, board))
+            .collect::<Result<CoordSet>>()?;
+        let changes = match json.get("queen") {
+            Some(queen) => Changes::AddQueen {
+                queen: coord_from_json(queen, board)?,
+                x,
+            },
+            None => Changes::AddX { x },
+        };
+        Ok(Deduction {
+            rule,
+            premises,
+            changes,
+        })
+    }
+}
+
+/// A certificate that a board has the solution it does: the deductions
+/// that solve it, in order, each of which [Deduction::check] can confirm
+/// from the premises it lists, without searching or running a heuristic.
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Board;
+/// # use qsolve::heuristic::all_heuristics;
+/// # use qsolve::proof::Proof;
+/// # use qsolve::solution::Solution;
+/// # use qsolve::solvestate::{SolveState, SolveStrategy};
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+/// let heuristics = all_heuristics(&board);
+/// let solution = Solution::solve(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+/// let proof = Proof::from_solution(&solution)?;
+/// let solved = proof.check(SolveState::from(&board))?;
+/// assert_eq!(solved.to_string(), solution.final_state.to_string());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Proof {
+    /// The [fingerprint][Board::fingerprint] of the board the proof is for.
+    pub fingerprint: u64,
+
+    /// The deductions that solve the board, in order.
+    pub steps: Vec<Deduction>,
+}
+
+impl Proof {
+    /// Builds a proof from the steps of the given solve, which must have
+    /// solved its puzzle.
+    ///
+    /// Fails if a step used a heuristic that doesn't list its premises, such
+    /// as a search.
+    pub fn from_solution(solution: &Solution) -> Result<Self> {
+        ensure!(
+            solution.is_solved(),
+            anyhow!("Only a solved puzzle can be proven").context(QSolveError::Unsolvable)
+        );
+        let steps = solution
+            .steps
+            .iter()
+            .filter_map(|item| Some((item.next_heuristic?, &item.solve_state, &item.changes)))
+            .enumerate()
+            .map(|(i, (h, solve_state, changes))| {
+                let rule = Rule::from_name(h.name()).ok_or_else(|| {
+                    anyhow!("Step {} used {}, which can't be proven", i + 1, h.name())
+                })?;
+                let changes = changes
+                    .clone()
+                    .ok_or_else(|| anyhow!("Step {} made no changes", i + 1))?;
+                let premises = h.premises(solve_state);
+                ensure!(
+                    !premises.is_empty(),
+                    "Step {} used {}, which listed no premises",
+                    i + 1,
+                    h.name()
+                );
+                Ok(Deduction {
+                    rule,
+                    premises,
+                    changes,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Proof {
+            fingerprint: solution.final_state.board.fingerprint(),
+            steps,
+        })
+    }
+
+    /// Replays the proof from the given state, checking each deduction
+    /// before applying it, and returns the solved state it ends in.
+    ///
+    /// Fails with [QSolveError::InvalidBoard] if the proof is for another
+    /// board, or with [QSolveError::InvalidChanges] if a deduction doesn't
+    /// follow or the proof stops before the puzzle is solved.
+    pub fn check<'a>(&self, mut solve_state: SolveState<'a>) -> Result<SolveState<'a>> {
+        ensure!(
+            self.fingerprint == solve_state.board.fingerprint(),
+            anyhow!("The proof is for a different board").context(QSolveError::InvalidBoard)
+        );
+        for (i, step) in self.steps.iter().enumerate() {
+            step.check(&solve_state)
+                .and_then(|()| solve_state.try_apply_changes(&step.changes))
+                .with_context(|| format!("Step {} ({}) doesn't follow", i + 1, step.rule.name()))
+                .context(QSolveError::InvalidChanges)?;
+        }
+        ensure!(
+            solve_state.complete() && solve_state.is_valid(),
+            anyhow!("The proof ends before the puzzle is solved")
+                .context(QSolveError::InvalidChanges)
+        );
+        Ok(solve_state)
+    }
+
+    /// Returns the proof as JSON, which [Proof::from_json] reads back.
+    ///
+    /// Squares are `[row, col]` pairs numbered from 0, as in
+    /// [Solution::to_json], and premises are written as for
+    /// [Region::from_str], like `row=3` or `color=yellow`.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "version": PROOF_VERSION,
+            "qsolve_version": VERSION,
+            "fingerprint": format!("{:016x}", self.fingerprint),
+            "steps": self.steps.iter().map(Deduction::to_json).collect::<Vec<_>>(),
+        })
+    }
+
+    /// Reads a proof for the given board back from the JSON returned by
+    /// [Proof::to_json], failing with [QSolveError::Parse] if it isn't one,
+    /// with [QSolveError::InvalidBoard] if it is for another board, or with
+    /// [QSolveError::InvalidChanges] if a step marks a square that isn't on
+    /// the board.
+    pub fn from_json(json: &Value, board: &Board) -> Result<Self> {
+        let parse = || -> Result<Self> {
+            ensure!(
+                json["version"].as_u64() == Some(PROOF_VERSION),
+                "Unsupported proof version {}",
+                json["version"]
+            );
+            let fingerprint = json["fingerprint"]
+                .as_str()
+                .and_then(|fingerprint| u64::from_str_radix(fingerprint, 16).ok())
+                .ok_or_else(|| anyhow!("The proof needs a board fingerprint"))?;
+            ensure!(
+                fingerprint == board.fingerprint(),
+                anyhow!("The proof is for a different board").context(QSolveError::InvalidBoard)
+            );
+            let steps = json["steps"]
+                .as_array()
+                .ok_or_else(|| anyhow!("The proof needs a list of steps"))?
+                .iter()
+                .enumerate()
+                .map(|(i, step)| {
+                    Deduction::from_json(step, board)
+                        .with_context(|| format!("Invalid step {}", i + 1))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Proof { fingerprint, steps })
+        };
+        parse().map_err(|err| match QSolveError::from_error(&err) {
+            Some(_) => err,
+            None => err.context(QSolveError::Parse),
+        })
+    }
+}
+
+/// Picks out the row or the column of a square.
+type LineOf = fn(Coord) -> usize;
+
+/// Returns the lines among the given premises, which must all be rows or
+/// all be columns, and a function giving the line of a square.
+fn lines_of(premises: &[Region]) -> Result<(LineSet, LineOf)> {
+    let rows = premises
+        .iter()
+        .filter_map(|region| match region {
+            Region::Row(r) => Some(*r),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let cols = premises
+        .iter()
+        .filter_map(|region| match region {
+            Region::Col(c) => Some(*c),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    match (rows.is_empty(), cols.is_empty()) {
+        (false, true) if rows.len() == premises.len() => {
+            Ok((LineSet::from_iter(rows), |coord| coord.0))
+        }
+        (true, false) if cols.len() == premises.len() => {
+            Ok((LineSet::from_iter(cols), |coord| coord.1))
+        }
+        _ => bail!("Its lines must be all rows or all columns"),
+    }
+}
+
+/// Returns every square of the given regions.
+fn lines_coords(board: &Board, regions: &[Region]) -> CoordSet {
+    let mut coords = CoordSet::default();
+    for region in regions {
+        coords.extend(region.coords(board).iter());
+    }
+    coords
+}
+
+/// Writes a region as [Region::from_str] reads it.
+fn region_arg(region: &Region) -> String {
+    match region {
+        Region::Color(color) => format!("color={color:?}"),
+        Region::Row(r) => format!("row={}", r + 1),
+        Region::Col(c) => format!("col={}", c + 1),
+    }
+}
+
+/// Writes a square as a `[row, col]` pair.
+fn coord_json(&(r, c): &Coord) -> Value {
+    json!([r, c])
+}
+
+/// Reads a square on the given board written by [coord_json].
+fn coord_from_json(json: &Value, board: &Board) -> Result<Coord> {
+    let coord = match json.as_array().map(Vec::as_slice) {
+        Some([r, c]) => match (r.as_u64(), c.as_u64()) {
+            (Some(r), Some(c)) => (r as usize, c as usize),
+            _ => bail!("Squares must be pairs of numbers, got {json}"),
+        },
+        _ => bail!("Squares must be pairs of numbers, got {json}"),
+    };
+    ensure!(
+        board.contains(&coord),
+        anyhow!("{} is not on the board", coord_text(&coord)).context(QSolveError::InvalidChanges)
+    );
+    Ok(coord)
+}
+
+/// Writes a square as `r1c2`, numbered from 1.
+fn coord_text(&(r, c): &Coord) -> String {
+    format!("r{}c{}", r + 1, c + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::{
+        file::QueensFile,
+        heuristic::all_heuristics,
+        solvestate::{SolveOptions, SolveStrategy},
+    };
+
+    use super::*;
+
+    #[test]
+    fn proofs_check_for_every_game_and_strategy() -> Result<()> {
+        for name in [
+            "linkedin-1-empty",
+            "linkedin-1-partial",
+            "queensgame-1",
+            "gameofcrowns-1",
+        ] {
+            let queens_file =
+                QueensFile::try_from_text_file(&PathBuf::from(format!("games/{name}.txt")))?;
+            let heuristics = all_heuristics(&queens_file.board);
+            for strategy in [
+                SolveStrategy::Fast,
+                SolveStrategy::Short,
+                SolveStrategy::Simple,
+                SolveStrategy::Human,
+            ] {
+                let solution = Solution::solve_with_options(
+                    SolveState::from(&queens_file),
+                    SolveOptions::from(strategy),
+                    &heuristics,
+                );
+                let proof = Proof::from_solution(&solution)?;
+                let proof = Proof::from_json(
+                    &serde_json::from_str(&proof.to_json().to_string())?,
+                    &queens_file.board,
+                )?;
+                let solved = proof
+                    .check(SolveState::from(&queens_file))
+                    .with_context(|| format!("{name} with {strategy:?}"))?;
+                assert_eq!(solved.to_string(), solution.final_state.to_string());
+            }
+        }
+
+        let clued = QueensFile::from_str("rows: . 0 . .\ncols: 1 . . .\n\nwwww\nwkkk\nrrrr\nbbbb")?;
+        let heuristics = all_heuristics(&clued.board);
+        let solution = Solution::solve(SolveState::from(&clued), SolveStrategy::Fast, &heuristics);
+        let proof = Proof::from_solution(&solution)?;
+        assert_eq!(proof.steps[0].rule, Rule::ClueRulesOutSquares);
+        proof.check(SolveState::from(&clued))?;
+        Ok(())
+    }
+
+    #[test]
+    fn proofs_reject_bad_deductions() -> Result<()> {
+        let queens_file = QueensFile::try_from_text_file(&"games/linkedin-1-empty.txt".into())?;
+        let heuristics = all_heuristics(&queens_file.board);
+        let solution = Solution::solve(
+            SolveState::from(&queens_file),
+            SolveStrategy::Fast,
+            &heuristics,
+        );
+        let proof = Proof::from_solution(&solution)?;
+        let error = |proof: &Proof| {
+            QSolveError::from_error(&proof.check(SolveState::from(&queens_file)).unwrap_err())
+        };
+
+        let mut short = proof.clone();
+        short.steps.pop();
+        assert_eq!(error(&short), Some(QSolveError::InvalidChanges));
+
+        let mut skipped = proof.clone();
+        skipped.steps.remove(0);
+        assert_eq!(error(&skipped), Some(QSolveError::InvalidChanges));
+
+        let mut wrong_premise = proof.clone();
+        wrong_premise.steps[0].premises = vec![Region::Row(0)];
+        assert_eq!(error(&wrong_premise), Some(QSolveError::InvalidChanges));
+
+        let mut overreaching = proof.clone();
+        let (Changes::AddQueen { x, .. } | Changes::AddX { x }) =
+            &mut overreaching.steps[0].changes;
+        x.extend(solution.final_state.queens().iter());
+        assert_eq!(error(&overreaching), Some(QSolveError::InvalidChanges));
+
+        let other = QueensFile::try_from_text_file(&"games/queensgame-1.txt".into())?;
+        assert_eq!(
+            QSolveError::from_error(&proof.check(SolveState::from(&other)).unwrap_err()),
+            Some(QSolveError::InvalidBoard)
+        );
+        assert_eq!(
+            QSolveError::from_error(
+                &Proof::from_json(&json!({"version": 1}), &queens_file.board).unwrap_err()
+            ),
+            Some(QSolveError::Parse)
+        );
+
+        for tampered in [json!([40, 3]), json!([2, 20])] {
+            let mut json = proof.to_json();
+            json["steps"][0]["queen"] = tampered.clone();
+            json["steps"][0]["x"] = json!([]);
+            assert_eq!(
+                QSolveError::from_error(&Proof::from_json(&json, &queens_file.board).unwrap_err()),
+                Some(QSolveError::InvalidChanges)
+            );
+            json["steps"][0]["x"] = json!([tampered]);
+            assert_eq!(
+                QSolveError::from_error(&Proof::from_json(&json, &queens_file.board).unwrap_err()),
+                Some(QSolveError::InvalidChanges)
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,217 @@
+use std::fmt::Display;
+
+use crate::{
+    board::{Board, Region},
+    datastructure::Coord,
+    heuristic::{Heuristic, all_heuristics, preview_step},
+    solvestate::{SolveState, SolveStrategy, SquareVal},
+};
+
+/// A logic puzzle played on a square grid of cells, such as Queens.
+///
+/// This is an adapter for sharing a solving loop, not the core of the
+/// solver. It describes a puzzle's cells, the constraints a solution must
+/// meet, the heuristics that make progress on it, and how to draw it, which
+/// is all [solve] needs, so puzzles other than Queens can be solved by
+/// implementing it.
+///
+/// [Board] implements it for Queens on top of [SolveState] and the
+/// [Heuristic]s. The Queens solver itself,
+/// [solve_iter][crate::solveiter::solve_iter] and
+/// [Solution][crate::solution::Solution] with their options, metrics and
+/// stall handling, is not built on this trait.
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Board;
+/// # use qsolve::puzzle::{Puzzle, solve};
+/// # use qsolve::solvestate::SolveStrategy;
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+/// assert_eq!(board.cells().len(), 16);
+/// assert_eq!(board.constraints().len(), 12);
+/// let heuristics = board.heuristics();
+/// let solved = solve(&board, &heuristics, SolveStrategy::Fast);
+/// assert!(board.is_complete(&solved) && board.is_valid(&solved));
+/// # Ok(())
+/// # }
+/// ```
+pub trait Puzzle {
+    /// A cell of the puzzle, such as a square of a Queens board.
+    type Cell;
+
+    /// A partially solved puzzle, which may borrow from the puzzle.
+    type State<'p>: Clone
+    where
+        Self: 'p;
+
+    /// A rule that a solution must follow, such as a row of Queens needing
+    /// one Queen.
+    type Constraint: Display;
+
+    /// A step a solver can look for, such as a [Heuristic] for Queens.
+    type Heuristic: ?Sized;
+
+    /// A choice of how [Puzzle::next_state] picks between heuristics.
+    type Strategy: Copy;
+
+    /// Returns every cell of the puzzle.
+    fn cells(&self) -> Vec<Self::Cell>;
+
+    /// Returns the state to start solving from.
+    fn initial_state(&self) -> Self::State<'_>;
+
+    /// Returns every constraint of the puzzle.
+    fn constraints(&self) -> Vec<Self::Constraint>;
+
+    /// Returns whether the given state doesn't break the constraint yet,
+    /// though it may not meet it until the puzzle is complete.
+    fn allows(&self, state: &Self::State<'_>, constraint: &Self::Constraint) -> bool;
+
+    /// Returns whether the given state breaks no constraint.
+    fn is_valid(&self, state: &Self::State<'_>) -> bool {
+        self.constraints()
+            .iter()
+            .all(|constraint| self.allows(state, constraint))
+    }
+
+    /// Returns whether every cell of the given state is decided.
+    fn is_complete(&self, state: &Self::State<'_>) -> bool;
+
+    /// Returns every heuristic that can make progress on the puzzle.
+    fn heuristics(&self) -> Vec<Box<Self::Heuristic>>;
+
+    /// Returns the state after the heuristic chosen by the strategy makes
+    /// its changes, or None if no heuristic applies.
+    fn next_state<'p>(
+        &'p self,
+        state: &Self::State<'p>,
+        heuristics: &[Box<Self::Heuristic>],
+        strategy: Self::Strategy,
+    ) -> Option<Self::State<'p>>;
+
+    /// Draws the given state as text.
+    fn render(&self, state: &Self::State<'_>) -> String;
+}
+
+/// Solves the puzzle from its initial state with the given heuristics,
+/// returning the last state reached: complete, unless no heuristic applied.
+pub fn solve<'p, P: Puzzle>(
+    puzzle: &'p P,
+    heuristics: &[Box<P::Heuristic>],
+    strategy: P::Strategy,
+) -> P::State<'p> {
+    let mut state = puzzle.initial_state();
+    while !puzzle.is_complete(&state) {
+        match puzzle.next_state(&state, heuristics, strategy) {
+            Some(next) => state = next,
+            None => break,
+        }
+    }
+    state
+}
+
+/// Queens, where each row, column and color needs exactly one Queen, and
+/// Queens can't share a diagonal as the board's
+/// [RuleSet][crate::board::RuleSet] decides.
+impl Puzzle for Board {
+    type Cell = Coord;
+    type State<'p> = SolveState<'p>;
+    type Constraint = Region;
+    type Heuristic = dyn Heuristic;
+    type Strategy = SolveStrategy;
+
+    fn cells(&self) -> Vec<Coord> {
+        self.all_coords().iter().collect()
+    }
+
+    fn initial_state(&self) -> SolveState<'_> {
+        SolveState::from(self)
+    }
+
+    /// Every row, then column, then color, each of which needs one Queen.
+    fn constraints(&self) -> Vec<Region> {
+        (0..self.size())
+            .map(Region::Row)
+            .chain((0..self.size()).map(Region::Col))
+            .chain(self.all_colors().into_iter().map(|&c| Region::Color(c)))
+            .collect()
+    }
+
+    fn allows(&self, state: &SolveState<'_>, region: &Region) -> bool {
+        let coords = region.coords(self);
+        coords
+            .iter()
+            .filter(|coord| state.square(coord) == Some(SquareVal::Queen))
+            .count()
+            <= 1
+            && coords
+                .iter()
+                .any(|coord| state.square(&coord) != Some(SquareVal::X))
+    }
+
+    /// Also checks that no two Queens share a diagonal that the rules rule
+    /// out, which the regions alone don't cover.
+    fn is_valid(&self, state: &SolveState<'_>) -> bool {
+        state.is_valid()
+            && self
+                .constraints()
+                .iter()
+                .all(|region| self.allows(state, region))
+    }
+
+    fn is_complete(&self, state: &SolveState<'_>) -> bool {
+        state.complete()
+    }
+
+    fn heuristics(&self) -> Vec<Box<dyn Heuristic>> {
+        all_heuristics(self)
+    }
+
+    fn next_state<'p>(
+        &'p self,
+        state: &SolveState<'p>,
+        heuristics: &[Box<dyn Heuristic>],
+        strategy: SolveStrategy,
+    ) -> Option<SolveState<'p>> {
+        Some(preview_step(state, strategy, heuristics)?.next_state)
+    }
+
+    fn render(&self, state: &SolveState<'_>) -> String {
+        state.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use anyhow::Result;
+
+    use super::*;
+    use crate::{file::QueensFile, solveiter::solve_iter};
+
+    #[test]
+    fn board_puzzle_matches_solve_iter() -> Result<()> {
+        let queens_file = QueensFile::try_from_text_file(&"games/linkedin-1-empty.txt".into())?;
+        let board = &queens_file.board;
+        let heuristics = board.heuristics();
+        let solved = solve(board, &heuristics, SolveStrategy::Fast);
+        let expected = solve_iter(SolveState::from(board), SolveStrategy::Fast, &heuristics)
+            .last()
+            .unwrap()
+            .solve_state;
+        assert_eq!(board.render(&solved), expected.to_string());
+        assert!(board.is_valid(&solved));
+
+        let stuck = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\n....\n....\n....\nxxxx")?;
+        let stuck_state = SolveState::from(&stuck);
+        // No Queen can go in the last row, which is fine by SolveState::is_valid.
+        assert!(stuck_state.is_valid());
+        assert!(!stuck.board.is_valid(&stuck_state));
+        assert!(!stuck.board.is_complete(&stuck_state));
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use anyhow::{Result, anyhow};
+use image::{GrayImage, Luma};
+use owo_colors::{OwoColorize, Rgb};
+use qrcode::{Color, EcLevel, Version};
+
+/// How many blank modules to leave around the code, as the standard requires
+/// for scanners to find it.
+pub const QUIET_ZONE: usize = 4;
+
+/// A QR code holding some bytes, for opening a puzzle link on another device
+/// by scanning it.
+///
+/// Codes are encoded by the [qrcode] crate at the medium error correction
+/// level, which survives some glare on a photographed screen, and are as
+/// small as the data allows. This only draws them.
+///
+/// # Examples
+/// ```
+/// # use qsolve::qr::QrCode;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let qr = QrCode::encode(b"qsolve://BAARAiIiMzM")?;
+/// assert_eq!(qr.version(), 2);
+/// assert_eq!(qr.size(), 25);
+/// // The top left finder pattern.
+/// assert!(qr.is_dark(0, 0) && !qr.is_dark(1, 1) && qr.is_dark(3, 3));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QrCode {
+    version: usize,
+    modules: Vec<Vec<bool>>,
+}
+
+impl QrCode {
+    /// Encodes the given bytes in the smallest code that holds them, failing
+    /// if they are too long for any code.
+    pub fn encode(data: &[u8]) -> Result<QrCode> {
+        let code = qrcode::QrCode::with_error_correction_level(data, EcLevel::M)
+            .map_err(|err| anyhow!("{} bytes don't fit in a QR code: {err}", data.len()))?;
+        let Version::Normal(version) = code.version() else {
+            unreachable!("Codes are only made in the normal sizes, not the micro ones");
+        };
+        let modules = code
+            .to_colors()
+            .chunks(code.width())
+            .map(|row| row.iter().map(|&color| color == Color::Dark).collect())
+            .collect();
+        Ok(QrCode {
+            version: version as usize,
+            modules,
+        })
+    }
+
+    /// Returns the version of the code, from 1 to 40, which sets its size.
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    /// Returns how many modules wide and high the code is, without its
+    /// quiet zone.
+    pub fn size(&self) -> usize {
+        self.modules.len()
+    }
+
+    /// Returns whether the module at the given column and row is dark.
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.modules[y][x]
+    }
+
+    /// Returns whether the module at the given column and row, counted from
+    /// the outside of the quiet zone, is dark.
+    fn is_dark_with_quiet_zone(&self, x: usize, y: usize) -> bool {
+        let size = self.size();
+        (QUIET_ZONE..QUIET_ZONE + size).contains(&x)
+            && (QUIET_ZONE..QUIET_ZONE + size).contains(&y)
+            && self.is_dark(x - QUIET_ZONE, y - QUIET_ZONE)
+    }
+
+    /// Draws the code for a terminal with half block characters, two rows
+    /// of modules to a line, along with its quiet zone.
+    ///
+    /// The colors are set explicitly, so the code scans the same on light
+    /// and dark terminals.
+    pub fn to_ansi(&self) -> String {
+        let full = self.size() + 2 * QUIET_ZONE;
+        let color = |dark: bool| {
+            if dark {
+                Rgb(0, 0, 0)
+            } else {
+                Rgb(255, 255, 255)
+            }
+        };
+        (0..full)
+            .step_by(2)
+            .map(|y| {
+                (0..full)
+                    .map(|x| {
+                        let top = self.is_dark_with_quiet_zone(x, y);
+                        let bottom = self.is_dark_with_quiet_zone(x, y + 1);
+                        '\u{2580}'
+                            .color(color(top))
+                            .on_color(color(bottom))
+                            .to_string()
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Draws the code as an image with the given number of pixels per
+    /// module, along with its quiet zone.
+    pub fn to_image(&self, scale: usize) -> GrayImage {
+        let scale = scale.max(1);
+        let full = ((self.size() + 2 * QUIET_ZONE) * scale) as u32;
+        GrayImage::from_fn(full, full, |x, y| {
+            let dark = self.is_dark_with_quiet_zone(x as usize / scale, y as usize / scale);
+            Luma([if dark { 0 } else { 255 }])
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn qr_grows_with_its_data() -> Result<()> {
+        assert_eq!(QrCode::encode(&[b'a'; 14])?.version(), 1);
+        assert_eq!(QrCode::encode(&[b'a'; 15])?.version(), 2);
+        let largest = QrCode::encode(&[b'a'; 2331])?;
+        assert_eq!((largest.version(), largest.size()), (40, 177));
+        assert!(QrCode::encode(&[b'a'; 2332]).is_err());
+
+        let qr = QrCode::encode(b"qsolve://BAARAiIiMzM")?;
+        let image = qr.to_image(3);
+        assert_eq!(image.width() as usize, (qr.size() + 2 * QUIET_ZONE) * 3);
+        assert_eq!(image.get_pixel(0, 0), &Luma([255]));
+        assert_eq!(image.get_pixel(12, 12), &Luma([0]));
+        assert_eq!(
+            qr.to_ansi().lines().count(),
+            (qr.size() + 2 * QUIET_ZONE).div_ceil(2)
+        );
+        Ok(())
+    }
+}
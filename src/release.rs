@@ -0,0 +1,240 @@
+use std::{
+    cmp::Ordering,
+    fmt::{Display, Formatter},
+};
+
+use anyhow::{Context, Result, anyhow};
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+pub use semver::Version;
+use serde_json::{Value, json};
+
+use crate::error::QSolveError;
+
+/// The version of qsolve this was built as, which JSON outputs include so
+/// that bug reports say what produced them.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Where `qsolve self check-update` looks for releases unless told
+/// otherwise: the GitHub API's list of the repository's releases.
+pub const RELEASES_URL: &str = "https://api.github.com/repos/dschafer/qsolve/releases";
+
+/// Returns the version of qsolve this was built as.
+pub fn current_version() -> Version {
+    Version::parse(VERSION).expect("The crate version is a semantic version")
+}
+
+/// Reads a semantic version like `1.2.3` or `1.3.0-beta.1`, optionally with a
+/// leading `v` as in a release tag.
+///
+/// Versions are compared with [Version::cmp_precedence], so a prerelease
+/// comes before the release it leads up to, and build metadata after a `+`
+/// is ignored.
+///
+/// # Examples
+/// ```
+/// # use qsolve::release::parse_version;
+/// # use std::cmp::Ordering;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let release = parse_version("v1.2.0")?;
+/// assert_eq!(release.to_string(), "1.2.0");
+/// assert!(parse_version("1.2.0-beta.2")? < release);
+/// assert!(parse_version("1.2.0-beta.2")? > parse_version("1.2.0-beta.1")?);
+/// assert!(parse_version("1.10.0")? > release);
+/// assert_eq!(parse_version("1.2.0+build.5")?.cmp_precedence(&release), Ordering::Equal);
+/// assert!(parse_version("1.2").is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_version(s: &str) -> Result<Version> {
+    let trimmed = s.trim();
+    Version::parse(trimmed.strip_prefix('v').unwrap_or(trimmed))
+        .with_context(|| format!("Invalid version `{s}`"))
+        .context(QSolveError::Parse)
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+/// Which releases to consider when checking for updates.
+pub enum Channel {
+    /// Only full releases.
+    #[default]
+    Stable,
+    /// Prereleases as well as full releases.
+    Prerelease,
+}
+
+impl Channel {
+    /// Returns the name of the channel, as written on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Channel::Stable => "stable",
+            Channel::Prerelease => "prerelease",
+        }
+    }
+
+    /// Returns whether a release of the given version, marked as a
+    /// prerelease or not, is on this channel.
+    fn includes(&self, version: &Version, prerelease: bool) -> bool {
+        match self {
+            Channel::Stable => !prerelease && version.pre.is_empty(),
+            Channel::Prerelease => true,
+        }
+    }
+}
+
+/// A published release of qsolve.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Release {
+    /// The version the release is tagged with.
+    pub version: Version,
+
+    /// The page to download the release from.
+    pub url: String,
+}
+
+/// The newest release on a [Channel], compared against the running version.
+///
+/// # Examples
+/// ```
+/// # use qsolve::release::{Channel, UpdateCheck, parse_version};
+/// # use serde_json::json;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let releases = json!([
+///     { "tag_name": "v1.2.0-beta.1", "prerelease": true, "html_url": "https://example.com/beta" },
+///     { "tag_name": "v1.1.0", "html_url": "https://example.com/1.1.0" },
+/// ]);
+/// let current = parse_version("1.0.1")?;
+/// let stable = UpdateCheck::of(current.clone(), &releases, Channel::Stable)?;
+/// assert!(stable.update_available());
+/// assert_eq!(stable.latest.unwrap().url, "https://example.com/1.1.0");
+///
+/// let newest = UpdateCheck::of(current, &releases, Channel::Prerelease)?;
+/// assert_eq!(newest.latest.unwrap().version.to_string(), "1.2.0-beta.1");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UpdateCheck {
+    /// The version that is running.
+    pub current: Version,
+
+    /// The channel that was checked.
+    pub channel: Channel,
+
+    /// The newest release on the channel, or None if it has none.
+    pub latest: Option<Release>,
+}
+
+impl UpdateCheck {
+    /// Finds the newest release on the channel from a list of releases in
+    /// the form the GitHub API returns them: an array of objects with a
+    /// `tag_name`, an `html_url`, and `prerelease` and `draft` flags. Drafts,
+    /// and releases whose tags aren't versions, are skipped.
+    ///
+    /// This fails with [QSolveError::Parse] if the list isn't an array.
+    pub fn of(current: Version, releases: &Value, channel: Channel) -> Result<Self> {
+        let releases = releases
+            .as_array()
+            .ok_or_else(|| anyhow!("The list of releases isn't a JSON array"))
+            .context(QSolveError::Parse)?;
+        let latest = releases
+            .iter()
+            .filter(|release| !release["draft"].as_bool().unwrap_or(false))
+            .filter_map(|release| {
+                let version = parse_version(release["tag_name"].as_str()?).ok()?;
+                let prerelease = release["prerelease"].as_bool().unwrap_or(false);
+                channel.includes(&version, prerelease).then(|| Release {
+                    version,
+                    url: release["html_url"].as_str().unwrap_or_default().to_string(),
+                })
+            })
+            .max_by(|a, b| a.version.cmp_precedence(&b.version));
+        Ok(UpdateCheck {
+            current,
+            channel,
+            latest,
+        })
+    }
+
+    /// Returns whether the newest release is newer than the running version.
+    pub fn update_available(&self) -> bool {
+        self.latest
+            .as_ref()
+            .is_some_and(|latest| latest.version.cmp_precedence(&self.current) == Ordering::Greater)
+    }
+
+    /// Returns the check as a JSON object.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "qsolve_version": self.current.to_string(),
+            "channel": self.channel.name(),
+            "latest_version": self.latest.as_ref().map(|latest| latest.version.to_string()),
+            "latest_url": self.latest.as_ref().map(|latest| latest.url.clone()),
+            "update_available": self.update_available(),
+        })
+    }
+}
+
+impl Display for UpdateCheck {
+    /// Displays whether there is a newer release, and where to get it.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let channel = self.channel.name();
+        match &self.latest {
+            Some(latest) if self.update_available() => write!(
+                f,
+                "qsolve {} is available (this is {}): {}",
+                latest.version, self.current, latest.url
+            ),
+            Some(latest) => write!(
+                f,
+                "qsolve {} is up to date (the latest {channel} release is {})",
+                self.current, latest.version
+            ),
+            None => write!(f, "No {channel} releases of qsolve were found"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_check_follows_the_channel() -> Result<()> {
+        assert!(current_version() >= parse_version("1.0.0")?);
+        assert!(parse_version("1.0.0-alpha")? < parse_version("1.0.0-alpha.1")?);
+        assert!(parse_version("1.0.0-2")? < parse_version("1.0.0-alpha")?);
+        assert!(parse_version("1.0.0-rc.1")? < parse_version("1.0.0")?);
+        assert_eq!(
+            parse_version("1.0.0+build.5")?.cmp_precedence(&parse_version("1.0.0")?),
+            Ordering::Equal
+        );
+        assert!(parse_version("1.0.0-").is_err());
+
+        let releases = json!([
+            { "tag_name": "v2.0.0", "draft": true, "html_url": "draft" },
+            { "tag_name": "nightly", "html_url": "nightly" },
+            // A prerelease flag is enough to keep a release off stable.
+            { "tag_name": "v1.1.0", "prerelease": true, "html_url": "flagged" },
+            { "tag_name": "v1.0.1", "html_url": "1.0.1" },
+            // Only build metadata differs, so this is the same release.
+            { "tag_name": "v1.0.1+rebuild.2", "html_url": "rebuild" },
+        ]);
+        let current = parse_version("1.0.1")?;
+        let stable = UpdateCheck::of(current.clone(), &releases, Channel::Stable)?;
+        assert!(!stable.update_available());
+        assert!(stable.to_string().contains("is up to date"));
+
+        let prerelease = UpdateCheck::of(current.clone(), &releases, Channel::Prerelease)?;
+        assert!(prerelease.update_available());
+        assert_eq!(prerelease.to_json()["latest_url"], "flagged");
+
+        let none = UpdateCheck::of(current, &json!([]), Channel::Stable)?;
+        assert_eq!(none.to_string(), "No stable releases of qsolve were found");
+        assert!(UpdateCheck::of(current_version(), &json!({}), Channel::Stable).is_err());
+        Ok(())
+    }
+}
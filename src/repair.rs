@@ -0,0 +1,399 @@
+use std::fmt::{Display, Formatter};
+
+use anyhow::{Result, anyhow, bail, ensure};
+use itertools::Itertools;
+
+use crate::{
+    board::Board,
+    datastructure::{Coord, CoordSet},
+    image::square_color_distance,
+    notation::CoordStyle,
+    squarecolor::SquareColor,
+};
+
+/// How far apart (as squared RGB distance) two colors can be for a stray
+/// piece of one to be repaired into the other. This allows shades of the
+/// same hue, like red and bright red, but not different hues.
+const REPAIR_DISTANCE_THRESHOLD: u32 = 25_000;
+
+/// A color whose squares don't form one connected region, which usually
+/// means some of its squares were misread from an image.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SplitRegion {
+    /// The color that is split.
+    pub color: SquareColor,
+
+    /// The connected pieces of the color, largest first.
+    pub pieces: Vec<CoordSet>,
+}
+
+impl SplitRegion {
+    /// Returns every color of the board that is split into more than one
+    /// piece, where squares only connect to the squares beside, above and
+    /// below them.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::repair::SplitRegion;
+    /// # use qsolve::squarecolor::SquareColor;
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// assert!(SplitRegion::find(&Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?).is_empty());
+    ///
+    /// let split = SplitRegion::find(&Board::from_str("wwww\nwkkk\nrrRr\nbbbb")?);
+    /// assert_eq!(split.len(), 1);
+    /// assert_eq!(split[0].color, SquareColor::Red);
+    /// assert_eq!(split[0].pieces.iter().map(|p| p.len()).collect::<Vec<_>>(), vec![2, 1]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn find(board: &Board) -> Vec<SplitRegion> {
+        board
+            .all_colors()
+            .into_iter()
+            .filter_map(|&color| {
+                let mut pieces = pieces(board.size(), board.coords_for_color(&color));
+                (pieces.len() > 1).then(|| {
+                    pieces.sort_by_key(|piece| std::cmp::Reverse(piece.len()));
+                    SplitRegion { color, pieces }
+                })
+            })
+            .collect()
+    }
+}
+
+impl Display for SplitRegion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "'{:?}' Color is split into {} separate pieces",
+            self.color,
+            self.pieces.len()
+        )
+    }
+}
+
+/// A square whose color should change to reconnect the regions of a board.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Reassignment {
+    /// The square to change.
+    pub coord: Coord,
+
+    /// The color the square has now.
+    pub from: SquareColor,
+
+    /// The color the square should have.
+    pub to: SquareColor,
+}
+
+impl Display for Reassignment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "r{}c{} from '{:?}' to '{:?}'",
+            self.coord.0 + 1,
+            self.coord.1 + 1,
+            self.from,
+            self.to
+        )
+    }
+}
+
+/// A proposed repair for a board with [SplitRegion]s.
+///
+/// The largest piece of each split color is assumed to be right. Every other
+/// piece joins the neighboring region whose color is nearest to its own, as
+/// long as the colors are close enough that one could have been misread as
+/// the other; pieces without such a neighbor are left alone.
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Board;
+/// # use qsolve::repair::RegionRepair;
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// // One square of the bright red region was read as red instead.
+/// let board = Board::from_str("wwww\nwkkk\nrrRr\nRRRR")?;
+/// let repair = RegionRepair::suggest(&board).unwrap();
+/// assert!(repair.is_complete());
+/// assert_eq!(repair.reassignments.len(), 1);
+/// assert_eq!(repair.apply(&board).to_string(), "wwww\nwkkk\nrrRR\nRRRR");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegionRepair {
+    /// The squares to change.
+    pub reassignments: Vec<Reassignment>,
+
+    /// The pieces that couldn't be repaired, since none of their neighbors
+    /// have a close enough color.
+    pub unrepaired: Vec<CoordSet>,
+}
+
+impl RegionRepair {
+    /// Proposes a repair for the board, or returns None if no color is split.
+    pub fn suggest(board: &Board) -> Option<RegionRepair> {
+        let split = SplitRegion::find(board);
+        if split.is_empty() {
+            return None;
+        }
+        let mut repair = RegionRepair {
+            reassignments: vec![],
+            unrepaired: vec![],
+        };
+        for region in split {
+            for piece in region.pieces.into_iter().skip(1) {
+                let neighbor_colors = piece
+                    .iter()
+                    .flat_map(|coord| neighbors(coord, board.size()))
+                    .map(|coord| board.color(&coord))
+                    .filter(|&color| color != region.color)
+                    .collect::<Vec<_>>();
+                // Prefer the nearest color, then the one the piece touches most.
+                let target = neighbor_colors
+                    .iter()
+                    .unique()
+                    .map(|&color| {
+                        let touching = neighbor_colors.iter().filter(|&&c| c == color).count();
+                        (color, square_color_distance(region.color, color), touching)
+                    })
+                    .filter(|&(_, distance, _)| distance <= REPAIR_DISTANCE_THRESHOLD)
+                    .min_by_key(|&(color, distance, touching)| {
+                        (distance, std::cmp::Reverse(touching), color as usize)
+                    });
+                match target {
+                    Some((to, _, _)) => {
+                        repair
+                            .reassignments
+                            .extend(piece.iter().map(|coord| Reassignment {
+                                coord,
+                                from: region.color,
+                                to,
+                            }))
+                    }
+                    None => repair.unrepaired.push(piece),
+                }
+            }
+        }
+        Some(repair)
+    }
+
+    /// Returns whether the repair reconnects every split color.
+    pub fn is_complete(&self) -> bool {
+        self.unrepaired.is_empty()
+    }
+
+    /// Returns a copy of the board with the reassignments made.
+    pub fn apply(&self, board: &Board) -> Board {
+        recolor(board, self.reassignments.iter().map(|r| (r.coord, r.to)))
+    }
+}
+
+impl Display for RegionRepair {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.reassignments.iter().join(", "))?;
+        if !self.is_complete() {
+            if !self.reassignments.is_empty() {
+                write!(f, "; ")?;
+            }
+            write!(
+                f,
+                "no close color for {}",
+                self.unrepaired.iter().map(CoordSet::to_string).join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A change to the colors of a board, made by hand to fix colors that were
+/// misread from an image.
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Board;
+/// # use qsolve::notation::CoordStyle;
+/// # use qsolve::repair::ColorEdit;
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let board = Board::from_str("wwww\nwkkk\nrrRr\nbbbb")?;
+/// let merged = ColorEdit::parse_merge("R=r")?.apply(&board)?;
+/// assert_eq!(merged.to_string(), "wwww\nwkkk\nrrrr\nbbbb");
+/// let split = ColorEdit::parse_split("r4c3,r4c4=g", &CoordStyle::default())?;
+/// assert_eq!(split.apply(&merged)?.to_string(), "wwww\nwkkk\nrrrr\nbbgg");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ColorEdit {
+    /// Gives every square of the first color the second color.
+    Merge(SquareColor, SquareColor),
+
+    /// Gives the squares the color, splitting them off from their regions.
+    Split(Vec<Coord>, SquareColor),
+}
+
+impl ColorEdit {
+    /// Parses a merge like `y=Y`, which makes every yellow square bright
+    /// yellow.
+    pub fn parse_merge(s: &str) -> Result<ColorEdit> {
+        let (from, into) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Expected a merge like y=Y, but found `{s}`"))?;
+        Ok(ColorEdit::Merge(parse_color(from)?, parse_color(into)?))
+    }
+
+    /// Parses a split like `r3c4,r3c5=g`, which makes those squares green,
+    /// with the squares written in the given style.
+    pub fn parse_split(s: &str, coord_style: &CoordStyle) -> Result<ColorEdit> {
+        let (squares, color) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Expected a split like r3c4,r3c5=g, but found `{s}`"))?;
+        let coords = squares
+            .split(',')
+            .map(|square| coord_style.parse(square.trim()))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(ColorEdit::Split(coords, parse_color(color)?))
+    }
+
+    /// Returns a copy of the board with the edit made.
+    ///
+    /// This fails if a merged color isn't on the board, or a split square
+    /// is off it; it doesn't check that the result is a valid board.
+    pub fn apply(&self, board: &Board) -> Result<Board> {
+        match self {
+            ColorEdit::Merge(from, into) => {
+                let coords = board.coords_for_color(from);
+                ensure!(!coords.is_empty(), "No squares are colored '{from}'");
+                Ok(recolor(board, coords.iter().map(|coord| (coord, *into))))
+            }
+            ColorEdit::Split(coords, color) => {
+                let size = board.size();
+                if let Some(coord) = coords.iter().find(|&&(r, c)| r >= size || c >= size) {
+                    bail!(
+                        "{} is off the {size}x{size} board",
+                        CoordStyle::default().text(coord)
+                    );
+                }
+                Ok(recolor(board, coords.iter().map(|&coord| (coord, *color))))
+            }
+        }
+    }
+}
+
+/// Parses a single color char, like `y`.
+fn parse_color(s: &str) -> Result<SquareColor> {
+    match s.trim().chars().exactly_one() {
+        Ok(c) => SquareColor::try_from(c),
+        Err(_) => Err(anyhow!("Expected a single color char, but found `{s}`")),
+    }
+}
+
+/// Returns a copy of the board with the given squares recolored.
+fn recolor(board: &Board, changes: impl IntoIterator<Item = (Coord, SquareColor)>) -> Board {
+    let size = board.size();
+    let mut colors = (0..size)
+        .flat_map(|r| (0..size).map(move |c| (r, c)))
+        .map(|coord| board.color(&coord))
+        .collect::<Vec<_>>();
+    for ((r, c), color) in changes {
+        colors[r * size + c] = color;
+    }
+    Board::new(size, colors)
+        .with_rules(board.rules())
+        .with_clues(board.clues().clone())
+}
+
+/// Returns the squares beside, above and below the given one.
+pub(crate) fn neighbors((row, col): Coord, size: usize) -> impl Iterator<Item = Coord> {
+    [
+        (row.wrapping_sub(1), col),
+        (row + 1, col),
+        (row, col.wrapping_sub(1)),
+        (row, col + 1),
+    ]
+    .into_iter()
+    .filter(move |&(r, c)| r < size && c < size)
+}
+
+/// Splits the given squares of a board of the given size into connected
+/// pieces.
+pub(crate) fn pieces(size: usize, mut remaining: CoordSet) -> Vec<CoordSet> {
+    let mut pieces = vec![];
+    while let Some(start) = remaining.iter().next() {
+        remaining.remove(start);
+        let mut piece = CoordSet::from_iter([start]);
+        let mut stack = vec![start];
+        while let Some(coord) = stack.pop() {
+            for neighbor in neighbors(coord, size) {
+                if remaining.contains(&neighbor) {
+                    remaining.remove(neighbor);
+                    piece.add(neighbor);
+                    stack.push(neighbor);
+                }
+            }
+        }
+        pieces.push(piece);
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use anyhow::Result;
+
+    use super::*;
+
+    #[test]
+    fn repair_reconnects_split_regions() -> Result<()> {
+        assert_eq!(
+            RegionRepair::suggest(&Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?),
+            None
+        );
+
+        // The stray blue square borders red and bright blue, and bright
+        // blue is the nearer color.
+        let board = Board::from_str("rrbb\nrbBB\nBBBB\nkkkk")?;
+        let repair = RegionRepair::suggest(&board).unwrap();
+        assert_eq!(repair.to_string(), "r2c2 from 'Blue' to 'BrightBlue'");
+        assert!(SplitRegion::find(&repair.apply(&board)).is_empty());
+
+        // Green is too far from every color around it to be repaired.
+        let board = Board::from_str("wwww\nwkkg\nrrrr\nbbbg")?;
+        let repair = RegionRepair::suggest(&board).unwrap();
+        assert!(!repair.is_complete());
+        assert!(repair.reassignments.is_empty());
+        assert_eq!(repair.unrepaired.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn color_edits_parse_and_check_the_board() -> Result<()> {
+        let style = CoordStyle::default();
+        assert_eq!(
+            ColorEdit::parse_merge(" y = Y")?,
+            ColorEdit::Merge(SquareColor::Yellow, SquareColor::BrightYellow)
+        );
+        assert!(ColorEdit::parse_merge("y").is_err());
+        assert!(ColorEdit::parse_merge("yy=Y").is_err());
+        assert!(ColorEdit::parse_split("r1c1,r9=g", &style).is_err());
+
+        let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        assert!(ColorEdit::parse_merge("g=w")?.apply(&board).is_err());
+        let split = ColorEdit::parse_split("c0r0,r1c1=g", &CoordStyle::from_str("r0c0")?)?;
+        assert_eq!(split.apply(&board)?.to_string(), "gwww\nwgkk\nrrrr\nbbbb");
+        assert!(
+            ColorEdit::parse_split("r5c1,r20c1=g", &style)?
+                .apply(&board)
+                .is_err()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{board::Board, datastructure::CoordSet};
+
+/// An iterator over every solution to a board, found by exhaustive search.
+///
+/// Unlike the heuristics, this doesn't try to solve the puzzle the way a human
+/// would: it places one queen per row, backtracking whenever a queen would share
+/// a column or color with an earlier queen, or share a diagonal with one that the
+/// board's [RuleSet][crate::board::RuleSet] rules out, or disagree with the
+/// board's [Clues][crate::clue::Clues].
+///
+/// # Complexity
+///
+/// For a board of size `n`, at most `n` columns are tried in each of `n` rows,
+/// so the search visits at most `n!` placements, each in `O(1)` time, and uses
+/// `O(n)` memory. In practice the column, color and adjacency constraints prune
+/// almost all of those, but adversarial boards (such as boards with very few
+/// distinct regions) can approach the bound, which is why callers that only
+/// care about uniqueness should stop after two solutions with [all_solutions].
+#[derive(Clone, Debug)]
+pub struct Solutions<'a> {
+    board: &'a Board,
+    /// The column of the queen in each row, for the rows filled so far.
+    queens: Vec<usize>,
+    /// The first column to try in the next row.
+    next_col: usize,
+    done: bool,
+}
+
+impl Solutions<'_> {
+    /// Returns whether a queen can go in the given column of the next row.
+    fn is_allowed(&self, col: usize) -> bool {
+        let row = self.queens.len();
+        let color = self.board.color(&(row, col));
+        let rules = self.board.rules();
+        if !self.board.clues().allows((row, col), self.board.size()) {
+            return false;
+        }
+        self.queens.iter().enumerate().all(|(r, &c)| {
+            c != col
+                && self.board.color(&(r, c)) != color
+                && !rules.diagonal_conflict((r, c), (row, col))
+        })
+    }
+
+    /// Removes the last queen, so the search resumes after its column.
+    fn backtrack(&mut self) {
+        match self.queens.pop() {
+            Some(col) => self.next_col = col + 1,
+            None => self.done = true,
+        }
+    }
+}
+
+impl Iterator for Solutions<'_> {
+    type Item = CoordSet;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let size = self.board.size();
+        while !self.done {
+            if self.queens.len() == size {
+                let solution = self
+                    .queens
+                    .iter()
+                    .enumerate()
+                    .map(|(r, &c)| (r, c))
+                    .collect::<CoordSet>();
+                self.backtrack();
+                return Some(solution);
+            }
+            match (self.next_col..size).find(|&col| self.is_allowed(col)) {
+                Some(col) => {
+                    self.queens.push(col);
+                    self.next_col = 0;
+                }
+                None => self.backtrack(),
+            }
+        }
+        None
+    }
+}
+
+/// Returns an iterator over every solution to the given board, each as the set
+/// of queens, in lexicographic order of their columns from the top row down.
+///
+/// See [Solutions] for the cost of the search.
+pub fn solutions(board: &Board) -> Solutions<'_> {
+    Solutions {
+        board,
+        queens: Vec::with_capacity(board.size()),
+        next_col: 0,
+        done: board.size() == 0,
+    }
+}
+
+/// Returns up to `limit` solutions to the given board, stopping the search as
+/// soon as that many have been found.
+///
+/// A well-formed puzzle has exactly one solution, so `all_solutions(board, 2)`
+/// is the cheapest way to confirm that.
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Board;
+/// # use qsolve::datastructure::CoordSet;
+/// # use qsolve::search::all_solutions;
+/// # use std::str::FromStr;
+/// let unique = Board::from_str("wwww\nwkkk\nrrrr\nbbbb").unwrap();
+/// assert_eq!(
+///     all_solutions(&unique, 2),
+///     vec![CoordSet::from_iter([(0, 1), (1, 3), (2, 0), (3, 2)])]
+/// );
+///
+/// let ambiguous = Board::from_str("wwkk\nwwkk\nrrbb\nrrbb").unwrap();
+/// assert_eq!(all_solutions(&ambiguous, 2).len(), 2);
+/// ```
+pub fn all_solutions(board: &Board, limit: usize) -> Vec<CoordSet> {
+    solutions(board).take(limit).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use anyhow::Result;
+
+    use crate::{board::RuleSet, solvestate::SolveState};
+
+    use super::*;
+
+    #[test]
+    fn solutions_are_valid_and_distinct() -> Result<()> {
+        let board = Board::from_str("wwkk\nwwkk\nrrbb\nrrbb")?;
+        let found = solutions(&board).collect::<Vec<_>>();
+        assert_eq!(found.len(), 2);
+        for (i, solution) in found.iter().enumerate() {
+            assert_eq!(solution.len(), board.size());
+            let queens = solution.iter().collect::<Vec<_>>();
+            let state = SolveState::with_queens(&board, &queens);
+            assert!(state.complete() && state.is_valid());
+            assert!(!found[..i].contains(solution));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn solutions_follow_rules() -> Result<()> {
+        // With one color per column, only the diagonals rule anything out.
+        let with_rules = |rules| -> Result<usize> {
+            let board = Board::from_str(&["wkrbg"; 5].join("\n"))?.with_rules(rules);
+            Ok(solutions(&board).count())
+        };
+        assert_eq!(with_rules(RuleSet::NoDiagonals)?, 120);
+        assert_eq!(with_rules(RuleSet::AdjacencyOnly)?, 14);
+        // The 10 solutions to the 5 queens problem.
+        assert_eq!(with_rules(RuleSet::FullDiagonals)?, 10);
+        Ok(())
+    }
+
+    #[test]
+    fn solutions_limit_stops_early() -> Result<()> {
+        let board = Board::from_str("wwkk\nwwkk\nrrbb\nrrbb")?;
+        assert_eq!(all_solutions(&board, 1).len(), 1);
+        assert!(all_solutions(&board, 0).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn solutions_of_unsolvable_board() -> Result<()> {
+        let board = Board::from_str("wwww\nwwww\nkkrr\nkkbb")?;
+        assert!(all_solutions(&board, 2).is_empty());
+        let board = Board::from_str("rb\nrb")?;
+        assert!(solutions(&board).next().is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,658 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{Result, bail, ensure};
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+
+use crate::{
+    board::Board,
+    datastructure::CoordSet,
+    file::QueensFile,
+    heuristic::{Changes, Heuristic, all_heuristics, next_heuristic_with_rng},
+    search::all_solutions,
+    share::{ShareOptions, generate_share_content},
+    solveiter::{SolveIterItem, solve_iter_with_options},
+    solvestate::{SolveOptions, SolveState, SquareVal},
+};
+
+/// A clock that measures how long a puzzle has been played for, and that
+/// can be paused.
+///
+/// # Examples
+/// ```
+/// # use qsolve::session::Clock;
+/// let mut clock = Clock::started();
+/// assert!(clock.is_running());
+/// clock.pause();
+/// let elapsed = clock.elapsed();
+/// assert_eq!(clock.elapsed(), elapsed);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Clock {
+    elapsed: Duration,
+    running_since: Option<Instant>,
+}
+
+impl Clock {
+    /// Returns a clock that starts running now.
+    pub fn started() -> Self {
+        Clock {
+            elapsed: Duration::ZERO,
+            running_since: Some(Instant::now()),
+        }
+    }
+
+    /// Stops the clock, keeping the time elapsed so far. Does nothing if
+    /// the clock is already paused.
+    pub fn pause(&mut self) {
+        if let Some(since) = self.running_since.take() {
+            self.elapsed += since.elapsed();
+        }
+    }
+
+    /// Starts the clock again. Does nothing if the clock is already running.
+    pub fn resume(&mut self) {
+        self.running_since.get_or_insert_with(Instant::now);
+    }
+
+    /// Returns whether the clock is running.
+    pub fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    /// Returns how long the clock has run for, not counting any time it was
+    /// paused.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+            + self
+                .running_since
+                .map_or(Duration::ZERO, |since| since.elapsed())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+/// Whether placing a Queen by hand also x's out the squares it rules out.
+pub enum MarkingMode {
+    /// Placing a Queen x's out every blank square it rules out, as the
+    /// LinkedIn app does.
+    #[default]
+    Assisted,
+    /// Placing a Queen only places the Queen, and every X must be placed by
+    /// hand.
+    Strict,
+}
+
+/// A puzzle being worked on interactively, one change at a time.
+///
+/// The session owns the puzzle and the heuristics for its board, so that
+/// they are only generated once no matter how many hints or steps are
+/// requested. Every change made during the session is kept, so that it
+/// can be undone; the markings the puzzle started with cannot be.
+///
+/// The session also keeps a [Clock], which starts when the session does and
+/// stops when the puzzle is solved, so that [Session::share_content] can
+/// report how long the puzzle took to play.
+///
+/// Whether the Queens the player places x out the squares they rule out is
+/// up to the session's [MarkingMode]. Either way, hints and steps from the
+/// solver are worked out as if they had, and steps x out everything they
+/// rule out.
+///
+/// If the puzzle has exactly one solution, the session finds it when it
+/// starts, so that [Session::mistakes_in] can tell whether a move disagrees
+/// with it. Every change applied with a mistake in it is counted, even if it
+/// is undone later.
+#[derive(Debug)]
+pub struct Session {
+    queens_file: QueensFile,
+    heuristics: Vec<Box<dyn Heuristic>>,
+    history: Vec<Changes>,
+    clock: Clock,
+    name: String,
+    marking_mode: MarkingMode,
+    solution: Option<CoordSet>,
+    mistake_count: usize,
+}
+
+impl Session {
+    /// Starts a new session on the given puzzle, and starts its clock.
+    pub fn new(queens_file: QueensFile) -> Self {
+        let heuristics = all_heuristics(&queens_file.board);
+        let solution = match all_solutions(&queens_file.board, 2)[..] {
+            [solution] => Some(solution),
+            _ => None,
+        };
+        Session {
+            queens_file,
+            heuristics,
+            history: vec![],
+            clock: Clock::started(),
+            name: String::new(),
+            marking_mode: MarkingMode::default(),
+            solution,
+            mistake_count: 0,
+        }
+    }
+
+    /// Sets whether placing a Queen also x's out the squares it rules out.
+    pub fn with_marking_mode(mut self, marking_mode: MarkingMode) -> Self {
+        self.marking_mode = marking_mode;
+        self
+    }
+
+    /// Returns whether placing a Queen also x's out the squares it rules out.
+    pub fn marking_mode(&self) -> MarkingMode {
+        self.marking_mode
+    }
+
+    /// Names the puzzle, for the share text.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Returns the name of the puzzle, which is empty unless one was given
+    /// with [Session::with_name].
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the clock measuring how long this session has been played.
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// Pauses the clock, such as while a hint is shown.
+    pub fn pause_clock(&mut self) {
+        self.clock.pause();
+    }
+
+    /// Resumes the clock, unless the puzzle has been solved.
+    pub fn resume_clock(&mut self) {
+        if !self.is_solved() {
+            self.clock.resume();
+        }
+    }
+
+    /// Stops the clock if the puzzle has just been solved.
+    fn stop_clock_if_solved(&mut self) {
+        if self.is_solved() {
+            self.clock.pause();
+        }
+    }
+
+    /// Returns the board being solved.
+    pub fn board(&self) -> &Board {
+        &self.queens_file.board
+    }
+
+    /// Returns the changes made during this session, oldest first.
+    pub fn history(&self) -> &[Changes] {
+        &self.history
+    }
+
+    /// Returns the current state of the puzzle: its starting markings with
+    /// every change made during the session applied.
+    pub fn solve_state(&self) -> SolveState<'_> {
+        match self.marking_mode {
+            MarkingMode::Assisted => self.assisted_state(),
+            MarkingMode::Strict => {
+                let mut solve_state = SolveState::from(&self.queens_file);
+                for changes in &self.history {
+                    solve_state.apply_changes_unchecked(changes);
+                }
+                solve_state
+            }
+        }
+    }
+
+    /// Returns the current state of the puzzle with every square that a
+    /// Queen rules out x'd out, which is what the solver works from.
+    fn assisted_state(&self) -> SolveState<'_> {
+        let mut solve_state = SolveState::from(&self.queens_file);
+        solve_state.apply_changes_maintaining_invariant(&self.history);
+        solve_state
+    }
+
+    /// Returns the given changes with every blank square that their Queen
+    /// rules out x'd out as well, so that they can be recorded in a strict
+    /// session.
+    fn with_queen_borders(&self, changes: Changes) -> Changes {
+        match (self.marking_mode, changes) {
+            (MarkingMode::Strict, Changes::AddQueen { queen, x }) => {
+                let solve_state = self.solve_state();
+                let x = self
+                    .board()
+                    .queen_borders(&queen)
+                    .iter()
+                    .chain(x.iter())
+                    .filter(|coord| solve_state.square(coord).is_none())
+                    .collect();
+                Changes::AddQueen { queen, x }
+            }
+            (_, changes) => changes,
+        }
+    }
+
+    /// Returns the Queens of the puzzle's solution, or None if the puzzle
+    /// doesn't have exactly one.
+    pub fn solution(&self) -> Option<CoordSet> {
+        self.solution
+    }
+
+    /// Returns the squares that the given changes mark wrongly: Queens that
+    /// aren't in the solution, and Xs on squares that are. This is always
+    /// empty if the solution isn't known, and never includes a Queen off the
+    /// board, which [Session::apply] refuses anyway.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::datastructure::CoordSet;
+    /// # use qsolve::file::QueensFile;
+    /// # use qsolve::heuristic::Changes;
+    /// # use qsolve::session::Session;
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let session = Session::new(QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb")?);
+    /// let x = CoordSet::from_iter([(0, 0), (0, 1)]);
+    /// assert_eq!(session.mistakes_in(&Changes::AddX { x }), CoordSet::from_iter([(0, 1)]));
+    /// let right = Changes::AddQueen { queen: (0, 1), x: CoordSet::default() };
+    /// assert!(session.mistakes_in(&right).is_empty());
+    /// let off_board = Changes::AddQueen { queen: (98, 0), x: CoordSet::default() };
+    /// assert!(session.mistakes_in(&off_board).is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn mistakes_in(&self, changes: &Changes) -> CoordSet {
+        let Some(solution) = self.solution else {
+            return CoordSet::default();
+        };
+        match changes {
+            Changes::AddQueen { queen, x } => {
+                let mut mistakes = x.intersection(&solution);
+                if self.board().contains(queen) && !solution.contains(queen) {
+                    mistakes.add(*queen);
+                }
+                mistakes
+            }
+            Changes::AddX { x } => x.intersection(&solution),
+        }
+    }
+
+    /// Returns how many of the changes applied with [Session::apply] had a
+    /// mistake in them, including any that were undone.
+    pub fn mistake_count(&self) -> usize {
+        self.mistake_count
+    }
+
+    /// Returns the heuristic the solver would use next, if any.
+    ///
+    /// Any random choices are drawn afresh from the options' seed, so this
+    /// is the heuristic [Session::step] would use with the same options.
+    pub fn hint(&self, solve_options: impl Into<SolveOptions>) -> Option<&dyn Heuristic> {
+        let solve_options = solve_options.into();
+        next_heuristic_with_rng(
+            &self.assisted_state(),
+            &solve_options,
+            &self.heuristics,
+            &mut solve_options.rng(),
+        )
+    }
+
+    /// Applies the given changes to the puzzle.
+    ///
+    /// Every square the changes mark must currently be blank, and the result
+    /// must not break the rules of the puzzle; otherwise this fails and the
+    /// puzzle is left untouched. Unless the session is [MarkingMode::Strict],
+    /// any squares eliminated by an added Queen are x'd out as well.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::datastructure::CoordSet;
+    /// # use qsolve::file::QueensFile;
+    /// # use qsolve::heuristic::Changes;
+    /// # use qsolve::session::Session;
+    /// # use qsolve::solvestate::SquareVal;
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let mut session = Session::new(QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb")?);
+    /// session.apply(Changes::AddQueen { queen: (0, 1), x: CoordSet::default() })?;
+    /// assert_eq!(session.solve_state().square(&(0, 0)), Some(SquareVal::X));
+    /// assert!(session.apply(Changes::AddQueen { queen: (1, 2), x: CoordSet::default() }).is_err());
+    /// assert!(session.apply(Changes::AddQueen { queen: (98, 0), x: CoordSet::default() }).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn apply(&mut self, changes: Changes) -> Result<()> {
+        // Check the coords as given before building any CoordSet from them,
+        // since a CoordSet can't hold every coord off the board.
+        let (queen, x) = match &changes {
+            Changes::AddQueen { queen, x } => (Some(queen), x),
+            Changes::AddX { x } => (None, x),
+        };
+        if let Some(coord) = queen
+            .copied()
+            .into_iter()
+            .chain(x.iter())
+            .find(|coord| !self.board().contains(coord))
+        {
+            bail!(
+                "Row {} column {} is not on the board",
+                coord.0 + 1,
+                coord.1 + 1
+            );
+        }
+        let mut solve_state = self.solve_state();
+        if let Some(coord) = changes
+            .changed_coords()
+            .iter()
+            .find(|coord| solve_state.square(coord).is_some())
+        {
+            bail!(
+                "Row {} column {} is already marked",
+                coord.0 + 1,
+                coord.1 + 1
+            );
+        }
+        match self.marking_mode {
+            MarkingMode::Assisted => {
+                solve_state.apply_changes_maintaining_invariant(std::slice::from_ref(&changes))
+            }
+            MarkingMode::Strict => solve_state.apply_changes_unchecked(&changes),
+        }
+        ensure!(
+            solve_state.is_valid(),
+            "That change would break the rules of the puzzle"
+        );
+        if !self.mistakes_in(&changes).is_empty() {
+            self.mistake_count += 1;
+        }
+        self.history.push(changes);
+        self.stop_clock_if_solved();
+        Ok(())
+    }
+
+    /// Applies the changes from the next heuristic, returning that heuristic,
+    /// or None if no heuristic could be found.
+    pub fn step(&mut self, solve_options: impl Into<SolveOptions>) -> Option<&dyn Heuristic> {
+        let solve_state = self.assisted_state();
+        let solve_options = solve_options.into();
+        let h = next_heuristic_with_rng(
+            &solve_state,
+            &solve_options,
+            &self.heuristics,
+            &mut solve_options.rng(),
+        )?;
+        let changes = self.with_queen_borders(h.changes(&solve_state)?);
+        // The heuristic borrows self, so check for a solve without calling
+        // stop_clock_if_solved.
+        let mut next_state = solve_state.clone();
+        next_state.apply_changes_maintaining_invariant(std::slice::from_ref(&changes));
+        if next_state.complete() && next_state.is_valid() {
+            self.clock.pause();
+        }
+        self.history.push(changes);
+        Some(h)
+    }
+
+    /// Applies heuristics until the puzzle is solved or no heuristic can be
+    /// found, returning how many steps were taken.
+    ///
+    /// Each step is undone separately.
+    pub fn solve(&mut self, solve_options: impl Into<SolveOptions>) -> usize {
+        let changes = solve_iter_with_options(
+            self.assisted_state(),
+            solve_options.into(),
+            &self.heuristics,
+        )
+        .filter_map(|item| item.changes)
+        .collect::<Vec<_>>();
+        let step_count = changes.len();
+        for changes in changes {
+            let changes = self.with_queen_borders(changes);
+            self.history.push(changes);
+        }
+        self.stop_clock_if_solved();
+        step_count
+    }
+
+    /// Undoes the most recent change made during this session, returning it,
+    /// or None if there is nothing to undo.
+    ///
+    /// If that unsolves the puzzle, its clock starts running again.
+    pub fn undo(&mut self) -> Option<Changes> {
+        let was_solved = self.is_solved();
+        let changes = self.history.pop()?;
+        if was_solved {
+            self.clock.resume();
+        }
+        Some(changes)
+    }
+
+    /// Returns whether the puzzle has been solved.
+    /// In a strict session, the squares the Queens rule out don't need to be
+    /// x'd out by hand first.
+    pub fn is_solved(&self) -> bool {
+        let solve_state = self.assisted_state();
+        solve_state.complete() && solve_state.is_valid()
+    }
+
+    /// Returns the number of Queens currently on the board.
+    pub fn queen_count(&self) -> usize {
+        let solve_state = self.solve_state();
+        self.board()
+            .all_coords()
+            .iter()
+            .filter(|coord| solve_state.square(coord) == Some(SquareVal::Queen))
+            .count()
+    }
+
+    /// Generates the share text for this session, as
+    /// [generate_share_content] does for a solve, with the time on the
+    /// session's clock and the Queens in the order they were placed.
+    pub fn share_content(&self, puzzle_name: &str, share_options: &ShareOptions) -> String {
+        let played = self
+            .history
+            .iter()
+            .map(|changes| PlayedChanges(changes.clone()))
+            .collect::<Vec<_>>();
+        let mut solve_state = SolveState::from(&self.queens_file);
+        let mut items = vec![];
+        for played_changes in &played {
+            items.push(SolveIterItem {
+                solve_state: solve_state.clone(),
+                next_heuristic: Some(played_changes as &dyn Heuristic),
+                changes: Some(played_changes.0.clone()),
+            });
+            solve_state
+                .apply_changes_maintaining_invariant(std::slice::from_ref(&played_changes.0));
+        }
+        generate_share_content(&items, puzzle_name, self.clock.elapsed(), share_options)
+    }
+}
+
+/// A change made during a session, replayed as a [Heuristic] so that the
+/// session can be shared like a solve.
+#[derive(Debug)]
+struct PlayedChanges(Changes);
+
+impl Heuristic for PlayedChanges {
+    fn changes(&self, _solve_state: &SolveState) -> Option<Changes> {
+        Some(self.0.clone())
+    }
+
+    fn seen_coords(&self, _solve_state: &SolveState) -> CoordSet {
+        self.0.changed_coords()
+    }
+
+    fn description(&self) -> String {
+        "A change made during play.\nApply it.".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{datastructure::CoordSet, solvestate::SolveStrategy};
+
+    use super::*;
+
+    fn session() -> Result<Session> {
+        Ok(Session::new(QueensFile::from_str(
+            "wwww\nwkkk\nrrrr\nbbbb\n\nx...\n....\n....\n....",
+        )?))
+    }
+
+    #[test]
+    fn session_apply_and_undo() -> Result<()> {
+        let mut session = session()?;
+        session.apply(Changes::AddQueen {
+            queen: (0, 1),
+            x: CoordSet::default(),
+        })?;
+        assert_eq!(session.queen_count(), 1);
+        assert_eq!(session.solve_state().square(&(1, 0)), Some(SquareVal::X));
+
+        assert!(session.undo().is_some());
+        assert_eq!(session.queen_count(), 0);
+        assert_eq!(session.solve_state().square(&(1, 0)), None);
+
+        // The starting markings can't be undone.
+        assert!(session.undo().is_none());
+        assert_eq!(session.solve_state().square(&(0, 0)), Some(SquareVal::X));
+        Ok(())
+    }
+
+    #[test]
+    fn session_apply_rejects_bad_changes() -> Result<()> {
+        let mut session = session()?;
+        assert!(
+            session
+                .apply(Changes::AddQueen {
+                    queen: (0, 0),
+                    x: CoordSet::default(),
+                })
+                .is_err()
+        );
+        session.apply(Changes::AddQueen {
+            queen: (0, 1),
+            x: CoordSet::default(),
+        })?;
+        assert!(
+            session
+                .apply(Changes::AddX {
+                    x: CoordSet::from_iter([(0, 1)]),
+                })
+                .is_err()
+        );
+        assert!(
+            session
+                .apply(Changes::AddQueen {
+                    queen: (3, 4),
+                    x: CoordSet::default(),
+                })
+                .is_err()
+        );
+        assert!(session.history().len() == 1);
+        Ok(())
+    }
+
+    #[test]
+    fn session_step_and_solve() -> Result<()> {
+        let mut session = session()?;
+        assert!(session.hint(SolveStrategy::Fast).is_some());
+        assert!(session.step(SolveStrategy::Fast).is_some());
+        assert_eq!(session.history().len(), 1);
+
+        let steps = session.solve(SolveStrategy::Fast);
+        assert!(session.is_solved());
+        assert_eq!(session.history().len(), steps + 1);
+        assert!(session.hint(SolveStrategy::Fast).is_none());
+        assert!(session.step(SolveStrategy::Fast).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn session_clock_stops_when_solved() -> Result<()> {
+        let mut session = session()?;
+        assert!(session.clock().is_running());
+        session.pause_clock();
+        assert!(!session.clock().is_running());
+        session.resume_clock();
+        assert!(session.clock().is_running());
+
+        session.solve(SolveStrategy::Fast);
+        assert!(!session.clock().is_running());
+        let elapsed = session.clock().elapsed();
+        session.resume_clock();
+        assert_eq!(session.clock().elapsed(), elapsed);
+
+        let share = session.share_content("1", &ShareOptions::default());
+        assert!(share.starts_with(&format!("QSolve #1 | {elapsed:?}")));
+
+        session.undo();
+        assert!(session.clock().is_running());
+        Ok(())
+    }
+
+    #[test]
+    fn session_counts_mistakes() -> Result<()> {
+        let mut session = session()?;
+        assert_eq!(
+            session.solution(),
+            Some(CoordSet::from_iter([(0, 1), (1, 3), (2, 0), (3, 2)]))
+        );
+        session.apply(Changes::AddQueen {
+            queen: (0, 2),
+            x: CoordSet::default(),
+        })?;
+        assert_eq!(session.mistake_count(), 1);
+        session.undo();
+        session.apply(Changes::AddX {
+            x: CoordSet::from_iter([(0, 3)]),
+        })?;
+        assert_eq!(session.mistake_count(), 1);
+
+        // Without a unique solution there is nothing to check against.
+        let mut ambiguous = Session::new(QueensFile::from_str("wwkk\nwwkk\nrrbb\nrrbb")?);
+        assert_eq!(ambiguous.solution(), None);
+        ambiguous.apply(Changes::AddQueen {
+            queen: (0, 0),
+            x: CoordSet::default(),
+        })?;
+        assert_eq!(ambiguous.mistake_count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn session_strict_marking_leaves_xs_to_the_player() -> Result<()> {
+        let mut session = session()?.with_marking_mode(MarkingMode::Strict);
+        session.apply(Changes::AddQueen {
+            queen: (0, 1),
+            x: CoordSet::default(),
+        })?;
+        assert_eq!(session.solve_state().square(&(1, 0)), None);
+        assert!(
+            session
+                .apply(Changes::AddQueen {
+                    queen: (1, 0),
+                    x: CoordSet::default(),
+                })
+                .is_err()
+        );
+        session.apply(Changes::AddX {
+            x: CoordSet::from_iter([(1, 0)]),
+        })?;
+
+        // Hints and steps still see every square the Queen rules out.
+        assert!(session.hint(SolveStrategy::Fast).is_some());
+        session.solve(SolveStrategy::Fast);
+        assert!(session.is_solved());
+        assert!(session.solve_state().complete());
+        Ok(())
+    }
+}
@@ -1,6 +1,85 @@
 use std::time::Duration;
 
-use crate::{heuristic::Changes, solveiter::SolveIterItem};
+use anyhow::{Result, anyhow, bail, ensure};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+use itertools::Itertools;
+
+use crate::{
+    board::Board,
+    error::QSolveError,
+    estimate::format_minutes,
+    heuristic::{Changes, all_heuristics},
+    palette::Palette,
+    solveiter::{SolveIterItem, solve_iter},
+    solvestate::{SolveState, SolveStrategy},
+    squarecolor::SquareColor,
+};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+/// How to represent the color of each revealed queen in the share text.
+pub enum ShareStyle {
+    /// Uses colored emoji, as returned by [Palette::emoji].
+    #[default]
+    Emoji,
+    /// Uses circled letters, as returned by [SquareColor::to_circled_letter].
+    Letters,
+    /// Uses the plain letter for the color, as used in board files (see [SquareColor]).
+    Plain,
+}
+
+impl ShareStyle {
+    /// Returns the character used to represent the given color in this style,
+    /// taking emoji from the given palette.
+    pub fn color_char(&self, color: &SquareColor, palette: Palette) -> char {
+        match self {
+            ShareStyle::Emoji => palette.emoji(*color),
+            ShareStyle::Letters => color.to_circled_letter(),
+            ShareStyle::Plain => color.to_string().chars().next().unwrap(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// Options that control what the share text reveals about the solution.
+pub struct ShareOptions {
+    /// How many of the first placed queens should have their colors revealed.
+    ///
+    /// If this is zero, the line listing the first queens is omitted entirely.
+    pub revealed_queens: usize,
+
+    /// How the color of each revealed queen should be represented.
+    pub style: ShareStyle,
+
+    /// The palette to take emoji from, if the style uses them.
+    pub palette: Palette,
+
+    /// How long a human would be expected to take, to compare the solve
+    /// time against. If this is None, no comparison is made.
+    pub expected: Option<Duration>,
+
+    /// How many steps of the solve to show at most in a line tracing its
+    /// path, with a crown for each step that placed a queen and a dot for
+    /// each step that only x'd out squares. Longer solves end the line with
+    /// how many steps were left out.
+    ///
+    /// If this is None, the line is omitted entirely.
+    pub path_steps: Option<usize>,
+}
+
+impl Default for ShareOptions {
+    fn default() -> Self {
+        ShareOptions {
+            revealed_queens: 3,
+            style: ShareStyle::default(),
+            palette: Palette::default(),
+            expected: None,
+            path_steps: None,
+        }
+    }
+}
 
 /// Generates the share text for a solved puzzle.
 ///
@@ -8,9 +87,12 @@ use crate::{heuristic::Changes, solveiter::SolveIterItem};
 /// * `state_iter_items` - A slice of [SolveIterItem]s that show that path to solve the puzzle.
 /// * `puzzle_name` - The name of the puzzle to include in the share text.
 /// * `elapsed` - A [Duration] that represents how long the puzzle took to solve.
+/// * `share_options` - The [ShareOptions] controlling how many queens are revealed, and how.
 ///
 /// # Returns
-/// A three-line string that serves as the "share text" for the solved puzzle.
+/// A three-line string that serves as the "share text" for the solved puzzle (or a two-line
+/// string, if no queens are revealed), with one more line tracing the solve path if
+/// [ShareOptions::path_steps] is set.
 ///
 /// # Examples
 /// ```
@@ -18,7 +100,7 @@ use crate::{heuristic::Changes, solveiter::SolveIterItem};
 /// # use std::time::Instant;
 /// # use qsolve::heuristic::all_heuristics;
 /// # use qsolve::file::QueensFile;
-/// # use qsolve::share::generate_share_content;
+/// # use qsolve::share::{generate_share_content, ShareOptions};
 /// # use qsolve::solveiter::solve_iter;
 /// # use qsolve::solvestate::{SolveState, SolveStrategy};
 /// # fn solve() -> Result<(), Box<dyn std::error::Error>> {
@@ -31,7 +113,7 @@ use crate::{heuristic::Changes, solveiter::SolveIterItem};
 ///     let elapsed = start_time.elapsed();
 ///
 ///     // Generate and print the share content.
-///     let share_content = generate_share_content(&solve_vec, "Linked In #1", elapsed);
+///     let share_content = generate_share_content(&solve_vec, "Linked In #1", elapsed, &ShareOptions::default());
 ///     println!("{}", share_content);
 /// #   Ok(())
 /// # }
@@ -40,19 +122,18 @@ pub fn generate_share_content(
     state_iter_items: &[SolveIterItem],
     puzzle_name: &str,
     elapsed: Duration,
+    share_options: &ShareOptions,
 ) -> String {
     let queens_order = state_iter_items
         .iter()
         .filter_map(
             |SolveIterItem {
                  solve_state,
-                 next_heuristic,
-             }| {
-                let h = (*next_heuristic)?;
-                if let Some(Changes::AddQueen { queen, x: _ }) = h.changes(solve_state) {
-                    return Some(solve_state.board.color(&queen));
-                }
-                None
+                 changes,
+                 ..
+             }| match changes {
+                Some(Changes::AddQueen { queen, x: _ }) => Some(solve_state.board.color(queen)),
+                _ => None,
             },
         )
         .collect::<Vec<_>>();
@@ -64,25 +145,321 @@ pub fn generate_share_content(
     };
 
     let mut output = String::new();
-    output.push_str(&format!(
-        "QSolve {puzzle_name} | {elapsed:?} and flawless\n"
-    ));
-    output.push_str(&format!(
-        "First \u{1f451}s: {}\n",
-        &queens_order[0..3]
-            .iter()
-            .map(|x| (*x).to_unicode_block().to_string())
-            .collect::<Vec<String>>()
-            .join(" ")
-    ));
+    output.push_str(&format!("QSolve {puzzle_name} | {elapsed:?} and flawless"));
+    if let Some(expected) = share_options.expected {
+        output.push_str(&format!(" (expected {})", format_minutes(expected)));
+    }
+    output.push('\n');
+    // A puzzle that was already solved has no Queens placed by the solver,
+    // and no path, to show.
+    if share_options.revealed_queens > 0 && !queens_order.is_empty() {
+        output.push_str(&format!(
+            "First \u{1f451}s: {}\n",
+            queens_order
+                .iter()
+                .take(share_options.revealed_queens)
+                .map(|x| share_options
+                    .style
+                    .color_char(x, share_options.palette)
+                    .to_string())
+                .collect::<Vec<String>>()
+                .join(" ")
+        ));
+    }
+    if let Some(path_steps) = share_options
+        .path_steps
+        .filter(|_| state_iter_items.iter().any(|item| item.changes.is_some()))
+    {
+        output.push_str(&format!(
+            "Path: {}\n",
+            solve_path_line(state_iter_items, path_steps)
+        ));
+    }
     output.push_str("github.com/dschafer/qsolve");
 
     output
 }
 
+/// Reads the layout of a board from the emoji grid in share text that
+/// someone posted, one line of emoji per row and one emoji per square.
+///
+/// Only the colors are read, not any solution. Emoji that [Palette::emoji]
+/// uses become that color, and any others (such as the orange and brown
+/// squares other games use) are given colors that aren't taken, as
+/// [BoardBuilder][crate::board::BoardBuilder] does. Lines with any ASCII in
+/// them, like the title or a link, are skipped, as are spaces and the
+/// variation selectors that some apps add after emoji.
+///
+/// This fails with [QSolveError::Parse] if there is no emoji grid, and with
+/// [QSolveError::InvalidBoard] if the grid isn't a valid board.
+///
+/// # Examples
+/// ```
+/// # use qsolve::share::board_from_share_text;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let share_text = "Queens #123 | 1:02\n\
+///     \u{1F7E7}\u{1F7E7}\u{1F7E7}\u{1F7E7}\n\
+///     \u{1F7E7}\u{1F534}\u{1F534}\u{1F534}\n\
+///     \u{1F535}\u{1F535}\u{1F535}\u{1F535}\n\
+///     \u{2B1C}\u{2B1C}\u{2B1C}\u{2B1C}\n\
+///     lnkd.in/queens";
+/// let board = board_from_share_text(share_text)?;
+/// assert_eq!(board.to_string(), "kkkk\nkrrr\nbbbb\nWWWW");
+/// # Ok(())
+/// # }
+/// ```
+pub fn board_from_share_text(text: &str) -> Result<Board> {
+    let lines = text
+        .lines()
+        .map(|line| {
+            line.chars()
+                .filter(|c| !c.is_whitespace() && *c != '\u{FE0F}')
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let is_grid_line = |line: &Vec<char>| !line.is_empty() && line.iter().all(|c| !c.is_ascii());
+    let grid = lines
+        .split(|line| !is_grid_line(line))
+        .max_by_key(|run| run.len())
+        .filter(|run| !run.is_empty())
+        .ok_or_else(|| {
+            anyhow!("No emoji grid found in the share text").context(QSolveError::Parse)
+        })?;
+    let palette = Palette::default();
+    let builder = grid
+        .iter()
+        .flatten()
+        .unique()
+        .filter_map(|&emoji| Some((emoji, palette.color_for_emoji(emoji)?)))
+        .fold(
+            Board::builder(grid.len()).rows(grid.iter().cloned()),
+            |builder, (emoji, color)| builder.color(emoji, color),
+        );
+    builder.build()
+}
+
+/// Returns the path of the solve as one symbol per step, as described by
+/// [ShareOptions::path_steps], showing at most `max_steps` steps.
+fn solve_path_line(state_iter_items: &[SolveIterItem], max_steps: usize) -> String {
+    let symbols = state_iter_items
+        .iter()
+        .filter_map(|item| {
+            Some(match item.changes.as_ref()? {
+                Changes::AddQueen { .. } => '\u{1f451}',
+                Changes::AddX { .. } => '\u{b7}',
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut line = symbols.iter().take(max_steps).collect::<String>();
+    if symbols.len() > max_steps {
+        line.push_str(&format!(" +{}", symbols.len() - max_steps));
+    }
+    line
+}
+
+/// The version byte at the start of every encoded [SharePayload]. Version 1
+/// payloads, which had no starting state, can still be decoded.
+const SHARE_PAYLOAD_VERSION: u8 = 2;
+
+/// The length of an encoded [SharePayload] without a starting state, in
+/// bytes, before base64 encoding.
+const SHARE_PAYLOAD_LEN: usize = 26;
+
+/// A compact, machine-readable summary of a solve that can accompany the share text.
+///
+/// The payload records which board was solved (via [Board::fingerprint][crate::board::Board::fingerprint]), how long it
+/// took, how many steps the solve required with which strategy, and the state the solve
+/// started from, in the [binary][crate::binary] encoding. Anyone with the same
+/// puzzle can then call [SharePayload::verify] to confirm that the payload matches the
+/// board and that the step count is the one the solver actually produces.
+///
+/// Note that this is a consistency check, not a cryptographic signature: the elapsed time
+/// cannot be verified, and a determined forger can always construct a matching payload.
+///
+/// # Examples
+/// ```
+/// # use std::path::PathBuf;
+/// # use std::time::Duration;
+/// # use qsolve::heuristic::all_heuristics;
+/// # use qsolve::file::QueensFile;
+/// # use qsolve::share::SharePayload;
+/// # use qsolve::solveiter::solve_iter;
+/// # use qsolve::solvestate::{SolveState, SolveStrategy};
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let queens_file = QueensFile::try_from_text_file(&PathBuf::from("games/linkedin-1-empty.txt"))?;
+/// let solve_state = SolveState::from(&queens_file);
+/// let heuristics = all_heuristics(solve_state.board);
+/// let solve_vec = solve_iter(solve_state, SolveStrategy::Fast, &heuristics).collect::<Vec<_>>();
+///
+/// let payload = SharePayload::new(&solve_vec, Duration::from_secs(1), SolveStrategy::Fast);
+/// let encoded = payload.encode();
+/// let decoded = SharePayload::decode(&encoded)?;
+/// assert_eq!(payload, decoded);
+/// decoded.verify(&SolveState::from(&queens_file))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SharePayload {
+    /// The [Board::fingerprint][crate::board::Board::fingerprint] of the solved board.
+    pub fingerprint: u64,
+
+    /// How long the puzzle took to solve.
+    pub elapsed: Duration,
+
+    /// How many steps were needed to solve the puzzle.
+    pub steps: u32,
+
+    /// Which [SolveStrategy] was used to solve the puzzle.
+    pub strategy: SolveStrategy,
+
+    /// The state the solve started from, encoded by
+    /// [to_bytes][crate::binary::to_bytes]; None for version 1 payloads.
+    pub start: Option<Vec<u8>>,
+}
+
+impl SharePayload {
+    /// Creates a new payload from the path taken to solve a puzzle.
+    pub fn new(
+        state_iter_items: &[SolveIterItem],
+        elapsed: Duration,
+        strategy: SolveStrategy,
+    ) -> Self {
+        let first = state_iter_items.first().map(|item| &item.solve_state);
+        let fingerprint = first
+            .map(|solve_state| solve_state.board.fingerprint())
+            .unwrap_or_default();
+        let steps = state_iter_items
+            .iter()
+            .filter(|item| item.next_heuristic.is_some())
+            .count() as u32;
+        SharePayload {
+            fingerprint,
+            elapsed,
+            steps,
+            strategy,
+            start: first.map(SolveState::to_bytes),
+        }
+    }
+
+    /// Encodes the payload as a URL-safe base64 string.
+    pub fn encode(&self) -> String {
+        let mut bytes = Vec::with_capacity(SHARE_PAYLOAD_LEN);
+        bytes.push(SHARE_PAYLOAD_VERSION);
+        bytes.extend(self.fingerprint.to_be_bytes());
+        bytes.extend((self.elapsed.as_micros() as u64).to_be_bytes());
+        bytes.extend(self.steps.to_be_bytes());
+        bytes.push(strategy_to_byte(self.strategy));
+        bytes.extend(self.start.iter().flatten());
+        bytes.extend(payload_checksum(&bytes).to_be_bytes());
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    /// Decodes a payload previously produced by [SharePayload::encode].
+    ///
+    /// This fails if the string is not valid base64, is the wrong length, or if
+    /// the embedded checksum doesn't match (for example, because it was edited).
+    /// Payloads from before the starting state was included decode with no
+    /// [SharePayload::start].
+    pub fn decode(encoded: &str) -> Result<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(encoded.trim())?;
+        ensure!(
+            bytes.len() >= SHARE_PAYLOAD_LEN,
+            "Invalid share payload: expected at least {SHARE_PAYLOAD_LEN} bytes, found {}",
+            bytes.len()
+        );
+        match bytes[0] {
+            1 => ensure!(
+                bytes.len() == SHARE_PAYLOAD_LEN,
+                "Invalid share payload: expected {SHARE_PAYLOAD_LEN} bytes, found {}",
+                bytes.len()
+            ),
+            SHARE_PAYLOAD_VERSION => {}
+            version => bail!("Invalid share payload: unknown version {version}"),
+        }
+        let (body, checksum) = bytes.split_at(bytes.len() - 4);
+        ensure!(
+            payload_checksum(body).to_be_bytes() == checksum,
+            "Invalid share payload: checksum mismatch"
+        );
+        Ok(SharePayload {
+            fingerprint: u64::from_be_bytes(body[1..9].try_into()?),
+            elapsed: Duration::from_micros(u64::from_be_bytes(body[9..17].try_into()?)),
+            steps: u32::from_be_bytes(body[17..21].try_into()?),
+            strategy: strategy_from_byte(body[21])?,
+            start: (body[0] == SHARE_PAYLOAD_VERSION).then(|| body[22..].to_vec()),
+        })
+    }
+
+    /// Verifies that this payload is consistent with solving the given [SolveState].
+    ///
+    /// This checks that the fingerprint matches the state's board, that the payload
+    /// started from the same markings (if it records them), and that solving the
+    /// state with the payload's strategy completes in exactly the claimed number of
+    /// steps.
+    pub fn verify(&self, solve_state: &SolveState) -> Result<()> {
+        ensure!(
+            self.fingerprint == solve_state.board.fingerprint(),
+            "Share payload is for a different board"
+        );
+        if let Some(start) = &self.start {
+            ensure!(
+                *start == solve_state.to_bytes(),
+                "Share payload starts from different markings than the board"
+            );
+        }
+        let heuristics = all_heuristics(solve_state.board);
+        let state_iter_items =
+            solve_iter(solve_state.clone(), self.strategy, &heuristics).collect::<Vec<_>>();
+        let expected = SharePayload::new(&state_iter_items, self.elapsed, self.strategy);
+        ensure!(
+            state_iter_items
+                .last()
+                .is_some_and(|item| item.solve_state.complete()),
+            "Board could not be solved, so the share payload cannot be verified"
+        );
+        ensure!(
+            self.steps == expected.steps,
+            "Share payload claims {} steps, but the {} strategy takes {} steps",
+            self.steps,
+            self.strategy,
+            expected.steps
+        );
+        Ok(())
+    }
+}
+
+fn strategy_to_byte(strategy: SolveStrategy) -> u8 {
+    match strategy {
+        SolveStrategy::Fast => 0,
+        SolveStrategy::Short => 1,
+        SolveStrategy::Simple => 2,
+        SolveStrategy::Human => 3,
+    }
+}
+
+fn strategy_from_byte(byte: u8) -> Result<SolveStrategy> {
+    match byte {
+        0 => Ok(SolveStrategy::Fast),
+        1 => Ok(SolveStrategy::Short),
+        2 => Ok(SolveStrategy::Simple),
+        3 => Ok(SolveStrategy::Human),
+        _ => bail!("Invalid share payload: unknown strategy {byte}"),
+    }
+}
+
+/// A 32-bit FNV-1a checksum, used to detect accidental edits to a payload.
+fn payload_checksum(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c9dc5, |hash: u32, &byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use std::path::PathBuf;
+    use std::{path::PathBuf, str::FromStr};
 
     use anyhow::Result;
 
@@ -104,20 +481,49 @@ mod tests {
         let state_iter_items =
             solve_iter(solve_state, SolveStrategy::Fast, &heuristics).collect::<Vec<_>>();
 
-        let share_text =
-            generate_share_content(&state_iter_items, "LinkedIn #1", Duration::from_secs(1));
+        let share_text = generate_share_content(
+            &state_iter_items,
+            "LinkedIn #1",
+            Duration::from_secs(1),
+            &ShareOptions::default(),
+        );
         let share_lines = share_text.lines().collect::<Vec<_>>();
         assert_eq!(share_lines.len(), 3);
         assert_eq!(share_lines[0], "QSolve LinkedIn #1 | 1s and flawless");
         assert_eq!(
             share_lines[1],
-            "First \u{1f451}s: \u{1F7E8} \u{2B1C} \u{1F7EA}"
+            "First \u{1f451}s: \u{1F7E8} \u{26AA} \u{1F7EA}"
         );
         assert_eq!(share_lines[2], "github.com/dschafer/qsolve");
 
         Ok(())
     }
 
+    #[test]
+    fn generate_share_content_already_solved() -> Result<()> {
+        let queens_file = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\nxQxx\nxxxQ\nQxxx\nxxQx")?;
+        let solve_state = SolveState::from(&queens_file);
+        let heuristics = all_heuristics(solve_state.board);
+        let state_iter_items =
+            solve_iter(solve_state, SolveStrategy::Fast, &heuristics).collect::<Vec<_>>();
+
+        let share_options = ShareOptions {
+            path_steps: Some(20),
+            ..ShareOptions::default()
+        };
+        let share_text = generate_share_content(
+            &state_iter_items,
+            "LinkedIn #1",
+            Duration::from_secs(1),
+            &share_options,
+        );
+        assert_eq!(
+            share_text,
+            "QSolve LinkedIn #1 | 1s and flawless\ngithub.com/dschafer/qsolve"
+        );
+        Ok(())
+    }
+
     #[test]
     fn generate_share_content_puzzle_number() -> Result<()> {
         let queens_file =
@@ -127,11 +533,221 @@ mod tests {
         let state_iter_items =
             solve_iter(solve_state, SolveStrategy::Fast, &heuristics).collect::<Vec<_>>();
 
-        let share_text = generate_share_content(&state_iter_items, "1234", Duration::from_secs(1));
+        let share_text = generate_share_content(
+            &state_iter_items,
+            "1234",
+            Duration::from_secs(1),
+            &ShareOptions::default(),
+        );
         let share_lines = share_text.lines().collect::<Vec<_>>();
         assert_eq!(share_lines.len(), 3);
         assert_eq!(share_lines[0], "QSolve #1234 | 1s and flawless");
 
         Ok(())
     }
+
+    #[test]
+    fn generate_share_content_revealed_queens() -> Result<()> {
+        let queens_file =
+            QueensFile::try_from_text_file(&PathBuf::from("games/linkedin-1-empty.txt"))?;
+        let solve_state = SolveState::from(&queens_file);
+        let heuristics = all_heuristics(solve_state.board);
+        let state_iter_items =
+            solve_iter(solve_state, SolveStrategy::Fast, &heuristics).collect::<Vec<_>>();
+
+        let share_options = ShareOptions {
+            revealed_queens: 5,
+            style: ShareStyle::Letters,
+            ..ShareOptions::default()
+        };
+        let share_text = generate_share_content(
+            &state_iter_items,
+            "LinkedIn #1",
+            Duration::from_secs(1),
+            &share_options,
+        );
+        let share_lines = share_text.lines().collect::<Vec<_>>();
+        assert_eq!(share_lines.len(), 3);
+        assert_eq!(
+            share_lines[1],
+            "First \u{1f451}s: \u{24CE} \u{24E6} \u{24C2} \u{24BC} \u{24B7}"
+        );
+
+        let hidden_options = ShareOptions {
+            revealed_queens: 0,
+            ..ShareOptions::default()
+        };
+        let share_text = generate_share_content(
+            &state_iter_items,
+            "LinkedIn #1",
+            Duration::from_secs(1),
+            &hidden_options,
+        );
+        assert_eq!(share_text.lines().count(), 2);
+
+        let path_options = ShareOptions {
+            revealed_queens: 0,
+            path_steps: Some(20),
+            ..ShareOptions::default()
+        };
+        let share_text = generate_share_content(
+            &state_iter_items,
+            "LinkedIn #1",
+            Duration::from_secs(1),
+            &path_options,
+        );
+        let share_lines = share_text.lines().collect::<Vec<_>>();
+        assert_eq!(share_lines.len(), 3);
+        let path = share_lines[1].strip_prefix("Path: ").unwrap();
+        assert_eq!(path.chars().count(), state_iter_items.len() - 1);
+        assert_eq!(path.matches('\u{1f451}').count(), 8);
+
+        let capped_options = ShareOptions {
+            path_steps: Some(3),
+            ..path_options
+        };
+        let share_text = generate_share_content(
+            &state_iter_items,
+            "LinkedIn #1",
+            Duration::from_secs(1),
+            &capped_options,
+        );
+        let capped = share_text.lines().nth(1).unwrap();
+        assert!(capped.starts_with(&format!(
+            "Path: {}",
+            &path[..path.char_indices().nth(3).unwrap().0]
+        )));
+        assert!(capped.ends_with(&format!(" +{}", state_iter_items.len() - 4)));
+
+        let expected_options = ShareOptions {
+            expected: Some(Duration::from_secs(150)),
+            ..ShareOptions::default()
+        };
+        let share_text = generate_share_content(
+            &state_iter_items,
+            "LinkedIn #1",
+            Duration::from_secs(1),
+            &expected_options,
+        );
+        assert_eq!(
+            share_text.lines().next(),
+            Some("QSolve LinkedIn #1 | 1s and flawless (expected 2:30)")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn share_payload_roundtrip() -> Result<()> {
+        let queens_file =
+            QueensFile::try_from_text_file(&PathBuf::from("games/linkedin-1-empty.txt"))?;
+        let solve_state = SolveState::from(&queens_file);
+        let heuristics = all_heuristics(solve_state.board);
+        let state_iter_items =
+            solve_iter(solve_state.clone(), SolveStrategy::Short, &heuristics).collect::<Vec<_>>();
+
+        let payload = SharePayload::new(
+            &state_iter_items,
+            Duration::from_millis(1234),
+            SolveStrategy::Short,
+        );
+        assert_eq!(payload.fingerprint, queens_file.board.fingerprint());
+        assert_eq!(payload.steps as usize, state_iter_items.len() - 1);
+
+        let decoded = SharePayload::decode(&payload.encode())?;
+        assert_eq!(decoded, payload);
+        assert_eq!(decoded.start, Some(solve_state.to_bytes()));
+        decoded.verify(&solve_state)?;
+
+        // Version 1 payloads had no starting state.
+        let mut v1 = vec![1];
+        v1.extend(payload.fingerprint.to_be_bytes());
+        v1.extend((payload.elapsed.as_micros() as u64).to_be_bytes());
+        v1.extend(payload.steps.to_be_bytes());
+        v1.push(strategy_to_byte(payload.strategy));
+        v1.extend(payload_checksum(&v1).to_be_bytes());
+        let decoded = SharePayload::decode(&URL_SAFE_NO_PAD.encode(v1))?;
+        assert_eq!(decoded.start, None);
+        assert_eq!(decoded.steps, payload.steps);
+        decoded.verify(&solve_state)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn share_payload_rejects_tampering() -> Result<()> {
+        let queens_file =
+            QueensFile::try_from_text_file(&PathBuf::from("games/linkedin-1-empty.txt"))?;
+        let solve_state = SolveState::from(&queens_file);
+        let heuristics = all_heuristics(solve_state.board);
+        let state_iter_items =
+            solve_iter(solve_state.clone(), SolveStrategy::Fast, &heuristics).collect::<Vec<_>>();
+        let payload = SharePayload::new(
+            &state_iter_items,
+            Duration::from_secs(1),
+            SolveStrategy::Fast,
+        );
+
+        let mut encoded = payload.encode().into_bytes();
+        encoded[5] = if encoded[5] == b'A' { b'B' } else { b'A' };
+        assert!(SharePayload::decode(&String::from_utf8(encoded)?).is_err());
+        assert!(SharePayload::decode("not a payload").is_err());
+
+        let fewer_steps = SharePayload {
+            steps: payload.steps - 1,
+            ..payload.clone()
+        };
+        assert!(fewer_steps.verify(&solve_state).is_err());
+
+        let partial_queens_file =
+            QueensFile::try_from_text_file(&PathBuf::from("games/linkedin-1-partial.txt"))?;
+        assert!(
+            payload
+                .verify(&SolveState::from(&partial_queens_file))
+                .is_err()
+        );
+
+        let other_queens_file =
+            QueensFile::try_from_text_file(&PathBuf::from("games/gameofcrowns-1.txt"))?;
+        assert!(
+            payload
+                .verify(&SolveState::from(&other_queens_file))
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn board_from_share_text_round_trips() -> Result<()> {
+        let queens_file =
+            QueensFile::try_from_text_file(&PathBuf::from("games/linkedin-1-empty.txt"))?;
+        let board = &queens_file.board;
+        let grid = (0..board.size())
+            .map(|r| {
+                (0..board.size())
+                    .map(|c| format!("{}\u{FE0F}", Palette::Classic.emoji(board.color(&(r, c)))))
+                    .join(" ")
+            })
+            .join("\n");
+        let share_text = format!("Queens #1 | 0:42 \u{1F451}\n\n{grid}\n\nlnkd.in/queens");
+
+        assert_eq!(board_from_share_text(&share_text)?, *board);
+        Ok(())
+    }
+
+    #[test]
+    fn board_from_share_text_errors() {
+        let no_grid = board_from_share_text("Queens #1 | 0:42\nlnkd.in/queens").unwrap_err();
+        assert_eq!(QSolveError::from_error(&no_grid), Some(QSolveError::Parse));
+
+        let ragged = board_from_share_text(
+            "\u{1F534}\u{1F534}\u{1F534}\n\u{1F535}\u{1F535}\n\u{2B1C}\u{2B1C}\u{2B1C}",
+        )
+        .unwrap_err();
+        assert_eq!(
+            QSolveError::from_error(&ragged),
+            Some(QSolveError::InvalidBoard)
+        );
+    }
 }
@@ -0,0 +1,641 @@
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use serde_json::{Value, json};
+
+use crate::{
+    datastructure::Coord,
+    entropy::Entropy,
+    heuristic::{AsHeuristic, Changes, Heuristic},
+    metrics::SolveMetrics,
+    notation::CoordStyle,
+    release::VERSION,
+    solveiter::{SolveIterItem, solve_iter_with_options},
+    solvestate::{Charset, SolveOptions, SolveState, SolveStrategy, SquareVal},
+    symmetry::{Symmetry, SymmetryReport},
+};
+
+/// How much one kind of heuristic was used during a solve, as returned by
+/// [Solution::heuristic_usage].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HeuristicUsage {
+    /// The heuristic's [name][Heuristic::name].
+    pub name: &'static str,
+
+    /// How many times the heuristic was applied.
+    pub count: usize,
+
+    /// How many squares those applications marked, with a Queen or an X.
+    pub squares: usize,
+}
+
+/// How a solve ended, as returned by [Solution::outcome].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SolveOutcome {
+    /// The puzzle was already solved before the solver made any changes.
+    AlreadySolved,
+
+    /// The solver solved the puzzle.
+    Solved,
+
+    /// The solver stopped without solving the puzzle.
+    Unsolved,
+}
+
+impl SolveOutcome {
+    /// A short, stable name for this outcome, as used in JSON output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SolveOutcome::AlreadySolved => "already_solved",
+            SolveOutcome::Solved => "solved",
+            SolveOutcome::Unsolved => "unsolved",
+        }
+    }
+}
+
+/// The result of running the solver to completion on a puzzle.
+///
+/// This keeps every step of the solve, along with the state the solver
+/// ended in (which is only a solved board if the heuristics were enough
+/// to solve the puzzle).
+pub struct Solution<'h, 'ss> {
+    /// Each step of the solve, in order, as returned by [crate::solveiter::solve_iter].
+    pub steps: Vec<SolveIterItem<'h, 'ss>>,
+
+    /// The state the solver ended in.
+    pub final_state: SolveState<'ss>,
+
+    /// How long the solve took.
+    pub elapsed: Duration,
+
+    /// Why the solve stopped early, if a heuristic made changes that aren't
+    /// allowed while solving with [SolveOptions::checked].
+    pub error: Option<anyhow::Error>,
+
+    /// The work the solver did, if solving with [SolveOptions::metrics].
+    pub metrics: Option<SolveMetrics>,
+}
+
+impl<'h, 'ss> Solution<'h, 'ss> {
+    /// Solves the given state with the given strategy and heuristics, timing the solve.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::heuristic::all_heuristics;
+    /// # use qsolve::solution::Solution;
+    /// # use qsolve::solvestate::{SolveState, SolveStrategy};
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+    /// let heuristics = all_heuristics(&board);
+    /// let solution = Solution::solve(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+    /// assert!(solution.is_solved());
+    /// assert!(solution.step_count() > 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn solve(
+        solve_state: SolveState<'ss>,
+        solve_strategy: SolveStrategy,
+        heuristics: &'h [Box<dyn Heuristic>],
+    ) -> Self {
+        Self::solve_with(solve_state, solve_strategy, heuristics)
+    }
+
+    /// Solves the given state like [Solution::solve], but with any slice of
+    /// heuristics, such as a slice of a single concrete [Heuristic] type,
+    /// so that they don't need to be boxed.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::datastructure::CoordSet;
+    /// # use qsolve::file::QueensFile;
+    /// # use qsolve::heuristic::{Changes, Heuristic};
+    /// # use qsolve::solution::Solution;
+    /// # use qsolve::solvestate::{SolveState, SolveStrategy};
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// /// Places a Queen in the last blank square of a row.
+    /// #[derive(Debug)]
+    /// struct LastBlankInRow(usize);
+    ///
+    /// impl Heuristic for LastBlankInRow {
+    ///     fn changes(&self, solve_state: &SolveState) -> Option<Changes> {
+    ///         let blank = self
+    ///             .seen_coords(solve_state)
+    ///             .iter()
+    ///             .filter(|coord| solve_state.square(coord).is_none())
+    ///             .collect::<Vec<_>>();
+    ///         match blank[..] {
+    ///             [queen] => Some(Changes::AddQueen { queen, x: CoordSet::default() }),
+    ///             _ => None,
+    ///         }
+    ///     }
+    ///
+    ///     fn seen_coords(&self, solve_state: &SolveState) -> CoordSet {
+    ///         solve_state.board.row_coords(self.0)
+    ///     }
+    ///
+    ///     fn description(&self) -> String {
+    ///         format!("Row {} has one square left.\nPut a Queen there.", self.0 + 1)
+    ///     }
+    /// }
+    ///
+    /// # fn main() -> Result<()> {
+    /// let queens_file = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\nxQxx\nxxxQ\nQxxx\nxx.x")?;
+    /// let heuristics = (0..4).map(LastBlankInRow).collect::<Vec<_>>();
+    /// let solution = Solution::solve_with(SolveState::from(&queens_file), SolveStrategy::Fast, &heuristics);
+    /// assert!(solution.is_solved());
+    /// assert_eq!(solution.step_count(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn solve_with<H: AsHeuristic>(
+        solve_state: SolveState<'ss>,
+        solve_strategy: SolveStrategy,
+        heuristics: &'h [H],
+    ) -> Self {
+        Self::solve_with_options(solve_state, SolveOptions::from(solve_strategy), heuristics)
+    }
+
+    /// Solves the given state like [Solution::solve_with], but with the
+    /// given [SolveOptions].
+    pub fn solve_with_options<H: AsHeuristic>(
+        solve_state: SolveState<'ss>,
+        solve_options: SolveOptions,
+        heuristics: &'h [H],
+    ) -> Self {
+        let start_time = Instant::now();
+        let initial_state = solve_state.clone();
+        let mut solve_iter = solve_iter_with_options(solve_state, solve_options, heuristics);
+        let steps = solve_iter.by_ref().collect::<Vec<_>>();
+        let error = solve_iter.take_error();
+        let metrics = solve_iter.metrics().copied();
+        let elapsed = start_time.elapsed();
+        let final_state = match steps.last() {
+            None => initial_state,
+            Some(SolveIterItem {
+                solve_state,
+                changes: None,
+                ..
+            }) => solve_state.clone(),
+            // The iterator stopped without finding another heuristic, so the
+            // last step's changes have not been applied to any returned state.
+            Some(SolveIterItem {
+                solve_state,
+                changes: Some(changes),
+                ..
+            }) => {
+                let mut final_state = solve_state.clone();
+                final_state.apply_changes(changes);
+                final_state
+            }
+        };
+        Solution {
+            steps,
+            final_state,
+            elapsed,
+            error,
+            metrics,
+        }
+    }
+
+    /// Whether the solver solved the puzzle.
+    pub fn is_solved(&self) -> bool {
+        self.final_state.complete()
+    }
+
+    /// How the solve ended, which tells a puzzle that was solved apart from
+    /// one that was complete before the solver started.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::file::QueensFile;
+    /// # use qsolve::heuristic::all_heuristics;
+    /// # use qsolve::solution::{Solution, SolveOutcome};
+    /// # use qsolve::solvestate::{SolveState, SolveStrategy};
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let queens_file = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\nxQxx\nxxxQ\nQxxx\nxxQx")?;
+    /// let heuristics = all_heuristics(&queens_file.board);
+    /// let solution = Solution::solve(SolveState::from(&queens_file), SolveStrategy::Fast, &heuristics);
+    /// assert_eq!(solution.outcome(), SolveOutcome::AlreadySolved);
+    /// assert_eq!(solution.step_count(), 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn outcome(&self) -> SolveOutcome {
+        match self.is_solved() {
+            true if self.step_count() == 0 => SolveOutcome::AlreadySolved,
+            true => SolveOutcome::Solved,
+            false => SolveOutcome::Unsolved,
+        }
+    }
+
+    /// Returns the step of the solve that marked the given square, or None
+    /// if it is still blank or was marked from the start.
+    ///
+    /// The step's heuristic explains why the square was marked, and the
+    /// [provenance][SolveState::provenance] of the squares that heuristic saw
+    /// explains those in turn.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::heuristic::all_heuristics;
+    /// # use qsolve::solution::Solution;
+    /// # use qsolve::solvestate::{SolveState, SolveStrategy};
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+    /// let heuristics = all_heuristics(&board);
+    /// let solution = Solution::solve(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+    /// let step = solution.marked_by(&(0, 1)).unwrap();
+    /// assert_eq!(step.solve_state.square(&(0, 1)), None);
+    /// assert!(step.next_heuristic.is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn marked_by(&self, coord: &Coord) -> Option<&SolveIterItem<'h, 'ss>> {
+        let first_change = self.steps.first()?.solve_state.changes_applied();
+        let change = self.final_state.provenance(coord)?;
+        self.steps.get(change.checked_sub(first_change)?)
+    }
+
+    /// The number of heuristics that were applied during the solve.
+    pub fn step_count(&self) -> usize {
+        self.steps
+            .iter()
+            .filter(|item| item.next_heuristic.is_some())
+            .count()
+    }
+
+    /// How many times each heuristic was applied during the solve.
+    ///
+    /// This is keyed by [Heuristic::name], and sorted from most to least
+    /// frequently applied (with ties in the order they were first applied).
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::heuristic::all_heuristics;
+    /// # use qsolve::solution::Solution;
+    /// # use qsolve::solvestate::{SolveState, SolveStrategy};
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+    /// let heuristics = all_heuristics(&board);
+    /// let solution = Solution::solve(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+    /// let histogram = solution.heuristic_histogram();
+    /// assert_eq!(histogram.iter().map(|(_, count)| count).sum::<usize>(), solution.step_count());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn heuristic_histogram(&self) -> Vec<(&'static str, usize)> {
+        self.heuristic_usage()
+            .into_iter()
+            .map(|usage| (usage.name, usage.count))
+            .collect()
+    }
+
+    /// How many times each heuristic was applied during the solve, and how
+    /// many squares it marked, in the same order as
+    /// [Solution::heuristic_histogram].
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::heuristic::all_heuristics;
+    /// # use qsolve::solution::Solution;
+    /// # use qsolve::solvestate::{SolveState, SolveStrategy};
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+    /// let heuristics = all_heuristics(&board);
+    /// let solution = Solution::solve(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+    /// let usage = solution.heuristic_usage();
+    /// assert_eq!(usage.iter().map(|usage| usage.squares).sum::<usize>(), 16);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn heuristic_usage(&self) -> Vec<HeuristicUsage> {
+        let mut usage: Vec<HeuristicUsage> = vec![];
+        for item in &self.steps {
+            let Some(h) = item.next_heuristic else {
+                continue;
+            };
+            let squares = item
+                .changes
+                .as_ref()
+                .map_or(0, |changes| changes.changed_coords().len());
+            match usage.iter_mut().find(|usage| usage.name == h.name()) {
+                Some(usage) => {
+                    usage.count += 1;
+                    usage.squares += squares;
+                }
+                None => usage.push(HeuristicUsage {
+                    name: h.name(),
+                    count: 1,
+                    squares,
+                }),
+            }
+        }
+        usage.sort_by_key(|usage| std::cmp::Reverse(usage.count));
+        usage
+    }
+
+    /// A table of [Solution::heuristic_usage], with a row for each heuristic
+    /// and a row for the totals, so that it is clear which heuristics did
+    /// the most to solve the puzzle.
+    pub fn heuristic_table(&self) -> String {
+        let usage = self.heuristic_usage();
+        let width = usage
+            .iter()
+            .map(|usage| usage.name.len())
+            .chain(["Heuristic".len()])
+            .max()
+            .unwrap_or_default();
+        let mut table = format!("{:width$}  {:>4}  {:>7}", "Heuristic", "Uses", "Squares");
+        for usage in &usage {
+            table.push_str(&format!(
+                "\n{:width$}  {:>4}  {:>7}",
+                usage.name, usage.count, usage.squares
+            ));
+        }
+        table.push_str(&format!(
+            "\n{:width$}  {:>4}  {:>7}",
+            "Total",
+            usage.iter().map(|usage| usage.count).sum::<usize>(),
+            usage.iter().map(|usage| usage.squares).sum::<usize>()
+        ));
+        table
+    }
+
+    /// Returns how constrained the puzzle was before the first step; see
+    /// [Entropy].
+    pub fn entropy(&self) -> Entropy {
+        Entropy::of(
+            self.steps
+                .first()
+                .map_or(&self.final_state, |item| &item.solve_state),
+        )
+    }
+
+    /// A multi-line summary of the solve: its outcome, step count, time, how
+    /// often each heuristic was used, its starting [Entropy] (and, if
+    /// unsolved, what's left of it), and (if solved) its [SymmetryReport].
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{} in {} steps ({:?})",
+            if self.is_solved() {
+                "Solved"
+            } else {
+                "Unsolved"
+            },
+            self.step_count(),
+            self.elapsed
+        );
+        for (name, count) in self.heuristic_histogram() {
+            summary.push_str(&format!("\n  {count:>3} {name}"));
+        }
+        summary.push_str(&format!("\nStarting entropy: {}", self.entropy()));
+        if !self.is_solved() {
+            summary.push_str(&format!(
+                "; {:.1} bits left",
+                Entropy::of(&self.final_state).bits()
+            ));
+        }
+        if self.is_solved() {
+            summary.push_str(&format!(
+                "\nSymmetry: {}",
+                SymmetryReport::new(&self.final_state)
+            ));
+        }
+        summary
+    }
+
+    /// A JSON representation of the solve, for consumption by scripts.
+    ///
+    /// This contains the [version][VERSION] of qsolve and the board's
+    /// [fingerprint][crate::board::Board::fingerprint], so that output
+    /// attached to a bug report says how to reproduce it, then the outcome,
+    /// the time taken (in microseconds), the board and final squares (one
+    /// string per row, in the same format as text files), the heuristic
+    /// usage, each step that was taken, the starting [Entropy], and (if
+    /// solved) the names of the symmetries of the queens and regions.
+    ///
+    /// Each step's squares are `[row, col]` pairs numbered from 0, as in
+    /// [CoordStyle::JSON]; see [Solution::to_json_with] for other styles.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::heuristic::all_heuristics;
+    /// # use qsolve::solution::Solution;
+    /// # use qsolve::solvestate::{SolveState, SolveStrategy};
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+    /// let heuristics = all_heuristics(&board);
+    /// let solution = Solution::solve(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+    /// let json = solution.to_json();
+    /// assert_eq!(json["solved"], true);
+    /// assert_eq!(json["squares"][0], "xQxx");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_json(&self) -> Value {
+        self.to_json_with(&CoordStyle::JSON)
+    }
+
+    /// A JSON representation of the solve like [Solution::to_json], with
+    /// each step's squares written in the given style.
+    pub fn to_json_with(&self, coord_style: &CoordStyle) -> Value {
+        let board = self.final_state.board;
+        let coord_json = |coord: &Coord| coord_style.json(coord);
+        let steps = self
+            .steps
+            .iter()
+            .filter_map(|item| {
+                let h = item.next_heuristic?;
+                let (queen, x) = match item.changes.as_ref()? {
+                    Changes::AddQueen { queen, x } => (Some(*queen), x),
+                    Changes::AddX { x } => (None, x),
+                };
+                Some(json!({
+                    "heuristic": h.name(),
+                    "description": h.description(),
+                    "queen": queen.as_ref().map(coord_json),
+                    "x": x.iter().map(|coord| coord_json(&coord)).collect::<Vec<_>>(),
+                }))
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "qsolve_version": VERSION,
+            "board_fingerprint": format!("{:016x}", board.fingerprint()),
+            "solved": self.is_solved(),
+            "outcome": self.outcome().name(),
+            "step_count": self.step_count(),
+            "elapsed_micros": self.elapsed.as_micros() as u64,
+            "board": board.to_string().lines().collect::<Vec<_>>(),
+            "squares": (0..board.size())
+                .map(|r| {
+                    board
+                        .row_coords(r)
+                        .iter()
+                        .map(|coord| SquareVal::as_char(self.final_state.square(&coord), true, &Charset::Ascii))
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>(),
+            "heuristics": self
+                .heuristic_usage()
+                .into_iter()
+                .map(|usage| json!({ "name": usage.name, "count": usage.count, "squares": usage.squares }))
+                .collect::<Vec<_>>(),
+            "steps": steps,
+            "entropy": self.entropy().to_json(),
+            "symmetry": self.is_solved().then(|| {
+                let report = SymmetryReport::new(&self.final_state);
+                let names = |symmetries: &[Symmetry]| {
+                    symmetries.iter().map(Symmetry::name).collect::<Vec<_>>()
+                };
+                json!({ "queens": names(&report.queens), "regions": names(&report.regions) })
+            }),
+        })
+    }
+}
+
+impl Display for Solution<'_, '_> {
+    /// Displays a one-line description of the outcome of the solve.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.outcome() {
+            SolveOutcome::AlreadySolved => write!(f, "already solved"),
+            SolveOutcome::Solved => write!(f, "solved in {} steps", self.step_count()),
+            SolveOutcome::Unsolved => write!(f, "unsolved after {} steps", self.step_count()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use anyhow::Result;
+
+    use crate::{board::Board, file::QueensFile, heuristic::all_heuristics};
+
+    use super::*;
+
+    #[test]
+    fn solution_solves() -> Result<()> {
+        let queens_file = QueensFile::try_from_text_file(&"games/linkedin-1-empty.txt".into())?;
+        let solve_state = SolveState::from(&queens_file);
+        let heuristics = all_heuristics(solve_state.board);
+        let solution = Solution::solve(solve_state, SolveStrategy::Fast, &heuristics);
+        assert!(solution.is_solved());
+        assert_eq!(solution.step_count(), solution.steps.len() - 1);
+        assert_eq!(
+            solution.to_string(),
+            format!("solved in {} steps", solution.step_count())
+        );
+        assert!(solution.summary().starts_with("Solved in "));
+        assert_eq!(
+            solution.summary().lines().count(),
+            solution.heuristic_histogram().len() + 3
+        );
+        assert!(solution.summary().contains("\nStarting entropy: "));
+        let table = solution.heuristic_table();
+        assert_eq!(table.lines().count(), solution.heuristic_usage().len() + 2);
+        let blank = solution.steps[0].solve_state.board.square_count()
+            - solution.steps[0]
+                .solve_state
+                .board
+                .all_coords()
+                .iter()
+                .filter(|coord| solution.steps[0].solve_state.square(coord).is_some())
+                .count();
+        assert!(
+            table
+                .lines()
+                .last()
+                .unwrap()
+                .ends_with(&format!(" {blank}"))
+        );
+        assert_eq!(
+            solution.to_json()["entropy"]["bits"].as_f64(),
+            Some(solution.entropy().bits())
+        );
+        assert!(
+            solution
+                .summary()
+                .lines()
+                .last()
+                .unwrap()
+                .starts_with("Symmetry: ")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn solution_without_steps() -> Result<()> {
+        let board = Board::from_str("wwww\nkkkk\nrrrr\nbbbb")?;
+        let heuristics = all_heuristics(&board);
+        let solution = Solution::solve(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+        assert!(!solution.is_solved());
+        assert_eq!(solution.step_count(), 0);
+        assert!(solution.heuristic_histogram().is_empty());
+        assert_eq!(solution.to_string(), "unsolved after 0 steps");
+        assert!(solution.summary().ends_with(" bits left"));
+        Ok(())
+    }
+
+    #[test]
+    fn solution_outcome_near_solved() -> Result<()> {
+        let solved = "wwww\nwkkk\nrrrr\nbbbb\n\nxQxx\nxxxQ\nQxxx\nxxQx";
+        let queens_file = QueensFile::from_str(solved)?;
+        let heuristics = all_heuristics(&queens_file.board);
+        let solution = Solution::solve(
+            SolveState::from(&queens_file),
+            SolveStrategy::Fast,
+            &heuristics,
+        );
+        assert_eq!(solution.outcome(), SolveOutcome::AlreadySolved);
+        assert_eq!(solution.to_string(), "already solved");
+        assert_eq!(solution.to_json()["outcome"], "already_solved");
+
+        let queens_file = QueensFile::from_str(&solved.replace("xxQx", "...."))?;
+        let solution = Solution::solve(
+            SolveState::from(&queens_file),
+            SolveStrategy::Fast,
+            &heuristics,
+        );
+        assert_eq!(solution.outcome(), SolveOutcome::Solved);
+        assert_eq!(solution.step_count(), 1);
+        assert_eq!(solution.to_json()["outcome"], "solved");
+        Ok(())
+    }
+
+    #[test]
+    fn solution_applies_last_step_when_unsolved() -> Result<()> {
+        let board = Board::from_str("rb\nrb")?;
+        let heuristics = all_heuristics(&board);
+        let solution = Solution::solve(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+        assert!(!solution.is_solved());
+        assert!(solution.step_count() > 0);
+        let last = solution.steps.last().unwrap();
+        assert_ne!(
+            format!("{}", last.solve_state),
+            format!("{}", solution.final_state)
+        );
+        Ok(())
+    }
+}
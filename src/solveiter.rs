@@ -1,6 +1,13 @@
+use std::time::Duration;
+
+use log::debug;
+
 use crate::{
-    heuristic::{Heuristic, next_heuristic},
-    solvestate::{SolveState, SolveStrategy},
+    heuristic::{AsHeuristic, Changes, Heuristic, next_heuristic_with_rng},
+    metrics::{SolveMetrics, collect},
+    rng::SeededRng,
+    solvestate::{SolveOptions, SolveState, SolveStrategy},
+    stall::{StallHandler, StallResolution},
 };
 
 /// This represents a stage in the process of solving a Queens board.
@@ -16,17 +23,126 @@ pub struct SolveIterItem<'h, 'ss> {
     /// The next [Heuristic] that we will apply to the given [SolveState],
     /// or None if no heuristic could be found.
     pub next_heuristic: Option<&'h dyn Heuristic>,
+
+    /// The changes that will be applied to the given [SolveState]: those of
+    /// the next heuristic, as trimmed by any
+    /// [SolveOptions::change_filter], or None if there is no next heuristic.
+    pub changes: Option<Changes>,
 }
 
 /// An Iterator that returns a series of StateIterItem's representing
 /// the solving process for a given board.
-pub struct SolveIter<'h, 'ss> {
+///
+/// The heuristics default to the boxed ones returned by
+/// [all_heuristics][crate::heuristic::all_heuristics], but can be anything
+/// that implements [AsHeuristic].
+pub struct SolveIter<'h, 'ss, H = Box<dyn Heuristic>> {
     solve_state: SolveState<'ss>,
-    solve_strategy: SolveStrategy,
-    heuristics: &'h [Box<dyn Heuristic>],
+    solve_options: SolveOptions,
+    rng: SeededRng,
+    heuristics: &'h [H],
+    stall_handler: &'h dyn StallHandler,
+    stalled: bool,
+    error: Option<anyhow::Error>,
+    metrics: Option<SolveMetrics>,
     done: bool,
 }
-impl<'h, 'ss> Iterator for SolveIter<'h, 'ss> {
+
+impl<'h, 'ss, H: AsHeuristic> SolveIter<'h, 'ss, H> {
+    /// Returns the next heuristic to use, and the changes it makes once
+    /// they have been through the [SolveOptions::change_filter].
+    ///
+    /// Whenever the filter vetoes a heuristic's changes, the remaining
+    /// heuristics are queried again without it.
+    fn next_step(&mut self) -> Option<(&'h dyn Heuristic, Changes)> {
+        let Some(change_filter) = self.solve_options.change_filter else {
+            let h = next_heuristic_with_rng(
+                &self.solve_state,
+                &self.solve_options,
+                self.heuristics,
+                &mut self.rng,
+            )?;
+            return Some((h, h.changes(&self.solve_state)?));
+        };
+        let mut candidates = self
+            .heuristics
+            .iter()
+            .map(AsHeuristic::as_heuristic)
+            .collect::<Vec<_>>();
+        loop {
+            let picked = next_heuristic_with_rng(
+                &self.solve_state,
+                &self.solve_options,
+                &candidates,
+                &mut self.rng,
+            )?;
+            let idx = candidates
+                .iter()
+                .position(|&h| std::ptr::addr_eq(h, picked))?;
+            let h = candidates.remove(idx);
+            let changes = h.changes(&self.solve_state)?;
+            match change_filter.apply(&self.solve_state, changes) {
+                Some(changes) => return Some((h, changes)),
+                None => debug!("Change filter vetoed {}", h.name()),
+            }
+        }
+    }
+
+    /// Returns the changes the given heuristic, offered by the
+    /// [StallHandler], makes once they have been through the
+    /// [SolveOptions::change_filter].
+    fn stall_changes(&self, h: &dyn Heuristic) -> Option<Changes> {
+        let changes = h.changes(&self.solve_state)?;
+        match self.solve_options.change_filter {
+            Some(change_filter) => change_filter.apply(&self.solve_state, changes),
+            None => Some(changes),
+        }
+    }
+}
+
+impl<'h, 'ss, H> SolveIter<'h, 'ss, H> {
+    /// Uses the given handler when no heuristic applies, instead of the one
+    /// chosen by [SolveOptions::on_stall].
+    pub fn with_stall_handler(mut self, stall_handler: &'h dyn StallHandler) -> Self {
+        self.stall_handler = stall_handler;
+        self
+    }
+
+    /// Whether the iteration ended because the solver stalled: that is,
+    /// because the board was not complete, no heuristic applied, and the
+    /// [StallHandler] stopped solving.
+    pub fn stalled(&self) -> bool {
+        self.stalled
+    }
+
+    /// Takes the error that ended the iteration, if a heuristic made changes
+    /// that aren't allowed while solving with [SolveOptions::checked].
+    pub fn take_error(&mut self) -> Option<anyhow::Error> {
+        self.error.take()
+    }
+
+    /// The work the solver has done so far, if solving with
+    /// [SolveOptions::metrics].
+    pub fn metrics(&self) -> Option<&SolveMetrics> {
+        self.metrics.as_ref()
+    }
+
+    /// Runs the given function, adding its work and the time it takes to the
+    /// given phase of the metrics, if they are being collected.
+    fn measure<T>(
+        &mut self,
+        phase: fn(&mut SolveMetrics) -> &mut Duration,
+        f: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        let Some(mut metrics) = self.metrics.take() else {
+            return f(self);
+        };
+        let result = collect(&mut metrics, phase, || f(self));
+        self.metrics = Some(metrics);
+        result
+    }
+}
+impl<'h, 'ss, H: AsHeuristic> Iterator for SolveIter<'h, 'ss, H> {
     type Item = SolveIterItem<'h, 'ss>;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -38,30 +154,105 @@ impl<'h, 'ss> Iterator for SolveIter<'h, 'ss> {
             return Some(SolveIterItem {
                 solve_state: self.solve_state.clone(),
                 next_heuristic: None,
+                changes: None,
             });
         }
-        let h = next_heuristic(&self.solve_state, self.solve_strategy, self.heuristics)?;
-        let changes = h.changes(&self.solve_state)?;
+        let step = match self.measure(|m| &mut m.choose_time, Self::next_step) {
+            Some(step) => Some(step),
+            None => self.measure(
+                |m| &mut m.stall_time,
+                |it| match it.stall_handler.on_stall(&it.solve_state) {
+                    StallResolution::Continue(h) => Some((h, it.stall_changes(h)?)),
+                    _ => None,
+                },
+            ),
+        };
+        let Some((h, changes)) = step else {
+            debug!("Solver stalled with {:?}", self.stall_handler);
+            self.stalled = true;
+            self.done = true;
+            return None;
+        };
         let old_solve_state = self.solve_state.clone();
-        self.solve_state.apply_changes(&changes);
+        let applied = self.measure(
+            |m| &mut m.apply_time,
+            |it| {
+                if it.solve_options.checked {
+                    it.solve_state.try_apply_changes(&changes)
+                } else {
+                    it.solve_state.apply_changes(&changes);
+                    Ok(())
+                }
+            },
+        );
+        if let Err(err) = applied {
+            debug!("Solver stopped: {err:#}");
+            self.error = Some(err.context(format!(
+                "Invalid step: {}",
+                h.description().replace('\n', " ")
+            )));
+            self.done = true;
+            return None;
+        }
         Some(SolveIterItem {
             solve_state: old_solve_state,
             next_heuristic: Some(h),
+            changes: Some(changes),
         })
     }
 }
 
 /// Returns an Iterator that represents the solving of the provided
 /// Queens solve state, using the given strategy and set of heuristics.
-pub fn solve_iter<'h, 'b>(
+pub fn solve_iter<'h, 'b, H: AsHeuristic>(
     solve_state: SolveState<'b>,
     solve_strategy: SolveStrategy,
-    heuristics: &'h [Box<dyn Heuristic>],
-) -> SolveIter<'h, 'b> {
+    heuristics: &'h [H],
+) -> SolveIter<'h, 'b, H> {
+    solve_iter_with_options(solve_state, SolveOptions::from(solve_strategy), heuristics)
+}
+
+/// Returns an Iterator like [solve_iter], but using the given [SolveOptions],
+/// so that strategies that make random choices can be seeded.
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Board;
+/// # use qsolve::heuristic::all_heuristics;
+/// # use qsolve::solveiter::solve_iter_with_options;
+/// # use qsolve::solvestate::{SolveOptions, SolveState, SolveStrategy};
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+/// let heuristics = all_heuristics(&board);
+/// let solve_options = SolveOptions {
+///     strategy: SolveStrategy::Human,
+///     seed: 7,
+///     ..SolveOptions::default()
+/// };
+/// let solved = solve_iter_with_options(SolveState::from(&board), solve_options, &heuristics)
+///     .last()
+///     .unwrap()
+///     .solve_state;
+/// assert!(solved.complete());
+/// # Ok(())
+/// # }
+/// ```
+pub fn solve_iter_with_options<'h, 'b, H: AsHeuristic>(
+    solve_state: SolveState<'b>,
+    solve_options: SolveOptions,
+    heuristics: &'h [H],
+) -> SolveIter<'h, 'b, H> {
     SolveIter {
         solve_state,
-        solve_strategy,
+        solve_options,
+        rng: solve_options.rng(),
         heuristics,
+        stall_handler: solve_options.on_stall.as_handler(),
+        stalled: false,
+        error: None,
+        metrics: solve_options.metrics.then(SolveMetrics::default),
         done: false,
     }
 }
@@ -72,7 +263,7 @@ mod tests {
 
     use anyhow::Result;
 
-    use crate::{board::Board, heuristic::all_heuristics};
+    use crate::{board::Board, filter::ChangeFilter, heuristic::all_heuristics, stall::OnStall};
 
     use super::*;
 
@@ -99,4 +290,178 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn solve_iter_consults_stall_handler() -> Result<()> {
+        // This board has two solutions, so no heuristic applies.
+        let board = Board::from_str("wwkk\nwwkk\nrrbb\nrrbb")?;
+        let heuristics = all_heuristics(&board);
+
+        let mut stopped = solve_iter(SolveState::from(&board), SolveStrategy::Fast, &heuristics);
+        assert!(stopped.next().is_none());
+        assert!(stopped.stalled());
+
+        let solve_options = SolveOptions {
+            on_stall: OnStall::Search,
+            ..SolveOptions::default()
+        };
+        let mut searched =
+            solve_iter_with_options(SolveState::from(&board), solve_options, &heuristics);
+        let last = searched.by_ref().last().unwrap();
+        assert!(last.solve_state.complete());
+        assert!(!searched.stalled());
+
+        #[derive(Debug)]
+        struct GiveUp;
+        impl StallHandler for GiveUp {
+            fn on_stall(&self, _solve_state: &SolveState) -> StallResolution<'_> {
+                StallResolution::Stop
+            }
+        }
+        let mut custom =
+            solve_iter_with_options(SolveState::from(&board), solve_options, &heuristics)
+                .with_stall_handler(&GiveUp);
+        assert!(custom.next().is_none());
+        assert!(custom.stalled());
+
+        Ok(())
+    }
+
+    #[test]
+    fn human_solve_is_seeded() -> Result<()> {
+        let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        let heuristics = all_heuristics(&board);
+        let descriptions = |seed, miss_percent| {
+            let solve_options = SolveOptions {
+                strategy: SolveStrategy::Human,
+                seed,
+                miss_percent,
+                ..SolveOptions::default()
+            };
+            solve_iter_with_options(SolveState::from(&board), solve_options, &heuristics)
+                .map(|item| item.next_heuristic.map(|h| h.description()))
+                .collect::<Vec<_>>()
+        };
+        for seed in 0..10 {
+            let steps = descriptions(seed, 50);
+            assert_eq!(steps, descriptions(seed, 50));
+            assert_eq!(steps.last(), Some(&None));
+        }
+        // Without any misses, the seed doesn't matter.
+        assert_eq!(descriptions(1, 0), descriptions(2, 0));
+        // With certain misses, the Human strategy still finishes.
+        assert_eq!(descriptions(3, 100).last(), Some(&None));
+
+        Ok(())
+    }
+
+    #[test]
+    fn checked_solve_stops_on_invalid_changes() -> Result<()> {
+        use crate::{
+            datastructure::CoordSet,
+            error::QSolveError,
+            heuristic::{Changes, Heuristic},
+        };
+
+        /// Places a Queen in the top left, then keeps x'ing it out.
+        #[derive(Debug)]
+        struct Buggy;
+        impl Heuristic for Buggy {
+            fn changes(&self, solve_state: &SolveState) -> Option<Changes> {
+                let x = CoordSet::from_iter([(0, 0)]);
+                Some(match solve_state.square(&(0, 0)) {
+                    None => Changes::AddQueen {
+                        queen: (0, 0),
+                        x: CoordSet::default(),
+                    },
+                    Some(_) => Changes::AddX { x },
+                })
+            }
+            fn seen_coords(&self, _solve_state: &SolveState) -> CoordSet {
+                CoordSet::from_iter([(0, 0)])
+            }
+            fn description(&self) -> String {
+                "Buggy\nheuristic".to_string()
+            }
+        }
+
+        let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        let heuristics = [Buggy];
+        let solve_options = SolveOptions {
+            checked: true,
+            ..SolveOptions::default()
+        };
+        let mut checked =
+            solve_iter_with_options(SolveState::from(&board), solve_options, &heuristics);
+        assert_eq!(checked.by_ref().count(), 1);
+        let err = checked.take_error().unwrap();
+        assert_eq!(
+            QSolveError::from_error(&err),
+            Some(QSolveError::InvalidChanges)
+        );
+        assert!(checked.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn short_lookahead_finds_shorter_solves() -> Result<()> {
+        let board = Board::from_str(
+            "wwwgggggg\nkkwgggggg\nyyyyrrggg\nyyyyyygbb\nyyyybbbbb\nyyybbbbbb\nyyyymmbbc\nyymmmmmmc\nWWWmmmmmm",
+        )?;
+        let heuristics = all_heuristics(&board);
+        let steps = |lookahead| {
+            let solve_options = SolveOptions {
+                strategy: SolveStrategy::Short,
+                lookahead,
+                ..SolveOptions::default()
+            };
+            let items =
+                solve_iter_with_options(SolveState::from(&board), solve_options, &heuristics)
+                    .collect::<Vec<_>>();
+            assert!(items.last().unwrap().solve_state.complete());
+            items.len()
+        };
+        assert!(steps(1) < steps(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn vetoed_changes_are_requeried() -> Result<()> {
+        let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        let heuristics = all_heuristics(&board);
+        let unfiltered = solve_iter(SolveState::from(&board), SolveStrategy::Fast, &heuristics)
+            .next()
+            .unwrap();
+        assert!(
+            unfiltered
+                .changes
+                .unwrap()
+                .changed_coords()
+                .contains(&(1, 0))
+        );
+        // Veto the first step the solver would have taken, so that it has to
+        // find another.
+        let solve_options = SolveOptions {
+            change_filter: Some(ChangeFilter(|solve_state, changes| {
+                (solve_state.changes_applied() > 0 || !changes.changed_coords().contains(&(1, 0)))
+                    .then_some(changes)
+            })),
+            ..SolveOptions::default()
+        };
+        let items = solve_iter_with_options(SolveState::from(&board), solve_options, &heuristics)
+            .collect::<Vec<_>>();
+        assert!(
+            !items[0]
+                .changes
+                .as_ref()
+                .unwrap()
+                .changed_coords()
+                .contains(&(1, 0))
+        );
+        assert!(items.last().unwrap().solve_state.complete());
+
+        Ok(())
+    }
 }
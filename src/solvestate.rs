@@ -1,16 +1,24 @@
 use std::fmt::{Display, Formatter, Write};
 
-use anyhow::{Result, bail};
+use anyhow::{Result, anyhow, bail};
+#[cfg(feature = "cli")]
 use clap::ValueEnum;
 use itertools::{Itertools, Position};
-use log::trace;
-use owo_colors::{AnsiColors, OwoColorize};
+use owo_colors::{AnsiColors, OwoColorize, Style};
+use tracing::{field::Empty, trace_span};
 
 use crate::{
+    binary,
     board::Board,
-    datastructure::{Coord, CoordSet},
+    datastructure::{Coord, CoordSet, LineSet, SquareColorSet},
+    error::QSolveError,
     file::QueensFile,
+    filter::ChangeFilter,
     heuristic::Changes,
+    palette::Palette,
+    rng::SeededRng,
+    squarecolor::SquareColor,
+    stall::OnStall,
 };
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -83,7 +91,8 @@ impl SquareVal {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
 /// What strategy to use for solving the puzzle
 pub enum SolveStrategy {
     /// Optimize for generating a solution quickly
@@ -93,6 +102,9 @@ pub enum SolveStrategy {
     Short,
     /// Optimize for generating a solution using the simplest moves
     Simple,
+    /// Mimic a typical human solver, who looks for small deductions within a
+    /// single row, column or color first and occasionally overlooks one
+    Human,
 }
 
 impl Display for SolveStrategy {
@@ -101,7 +113,87 @@ impl Display for SolveStrategy {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// Options that control how the puzzle is solved.
+pub struct SolveOptions {
+    /// What strategy to use for solving the puzzle.
+    pub strategy: SolveStrategy,
+
+    /// The seed for any choice the solver makes at random, such as breaking
+    /// ties between equally good heuristics. Solving with the same seed always
+    /// takes the same steps.
+    pub seed: u64,
+
+    /// How likely, in percent, the [SolveStrategy::Human] strategy is to
+    /// overlook each deduction available to it. The other strategies never
+    /// overlook anything.
+    pub miss_percent: u8,
+
+    /// How many steps beyond the next one the [SolveStrategy::Short] strategy
+    /// considers, choosing the step that leads to the most squares decided
+    /// over all of them. With 0, it greedily takes the step that decides the
+    /// most squares by itself. The other strategies ignore this.
+    pub lookahead: usize,
+
+    /// Whether to check every step's changes with
+    /// [SolveState::try_apply_changes], stopping the solve with an error
+    /// instead of continuing from a corrupted state if a heuristic makes
+    /// changes that aren't allowed.
+    pub checked: bool,
+
+    /// What to do when no heuristic applies to an unsolved board.
+    pub on_stall: OnStall,
+
+    /// A callback that can veto or trim each heuristic's changes before they
+    /// are applied. When it vetoes a heuristic, the solver looks for another.
+    pub change_filter: Option<ChangeFilter>,
+
+    /// Whether to count the solver's work as [SolveMetrics][crate::metrics::SolveMetrics]
+    /// while solving. This costs a little time, so it's off by default, and
+    /// it times each phase of the solve, so it needs a clock that targets such
+    /// as `wasm32-unknown-unknown` don't have.
+    pub metrics: bool,
+}
+
+impl Default for SolveOptions {
+    fn default() -> Self {
+        SolveOptions {
+            strategy: SolveStrategy::default(),
+            seed: 0,
+            miss_percent: 10,
+            lookahead: 0,
+            checked: false,
+            on_stall: OnStall::default(),
+            change_filter: None,
+            metrics: false,
+        }
+    }
+}
+
+impl SolveOptions {
+    /// Returns the probability that the [SolveStrategy::Human] strategy
+    /// overlooks a deduction, between 0.0 and 1.0.
+    pub fn miss_chance(&self) -> f64 {
+        f64::from(self.miss_percent.min(100)) / 100.0
+    }
+
+    /// Returns a random number generator seeded from these options.
+    pub fn rng(&self) -> SeededRng {
+        SeededRng::new(self.seed)
+    }
+}
+
+impl From<SolveStrategy> for SolveOptions {
+    fn from(strategy: SolveStrategy) -> Self {
+        SolveOptions {
+            strategy,
+            ..SolveOptions::default()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
 /// What characters to use in the animation
 pub enum Charset {
     /// Uses ASCII characters; Q for queens, x for impossible
@@ -112,6 +204,128 @@ pub enum Charset {
     Unicode,
 }
 
+impl Charset {
+    /// The arrow drawn between a board and the board it leads to.
+    pub fn arrow(&self) -> &'static str {
+        match self {
+            Charset::Ascii => "->",
+            Charset::Unicode => "\u{2192}",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+/// Extra information to draw on the blank squares of the board
+pub enum Overlay {
+    /// Draw blank squares as blanks
+    #[default]
+    None,
+    /// Draw how many candidates are left for each blank square's color,
+    /// and mark with a `!` squares that are the last candidate in their row
+    /// or column
+    Candidates,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// How highlighted squares are drawn by [SolveState::ansi_string_with_highlights].
+///
+/// The default style bolds and underlines the square, and draws blank squares
+/// with the [Charset]'s highlight character.
+pub struct HighlightStyle {
+    /// Whether the square is bolded.
+    pub bold: bool,
+
+    /// Whether the square is underlined.
+    pub underline: bool,
+
+    /// Whether the square blinks, on terminals that support it.
+    pub blink: bool,
+
+    /// Whether the square's foreground and background colors are swapped.
+    pub reverse: bool,
+
+    /// The foreground color to use instead of the one that contrasts with the
+    /// square's region.
+    pub color: Option<AnsiColors>,
+
+    /// The character to draw blank squares with, instead of the one from the
+    /// [Charset]. Squares containing a Queen or an X always show that value.
+    pub marker: Option<char>,
+}
+
+impl Default for HighlightStyle {
+    fn default() -> Self {
+        HighlightStyle::SEEN
+    }
+}
+
+impl HighlightStyle {
+    /// The style for squares a heuristic considered, when shown together
+    /// with the squares it changed.
+    pub const SEEN: HighlightStyle = HighlightStyle {
+        bold: true,
+        underline: true,
+        blink: false,
+        reverse: false,
+        color: None,
+        marker: None,
+    };
+
+    /// The style for squares a heuristic changed, when shown together with
+    /// the squares it considered.
+    pub const CHANGED: HighlightStyle = HighlightStyle {
+        bold: true,
+        underline: false,
+        blink: false,
+        reverse: true,
+        color: None,
+        marker: None,
+    };
+
+    /// The style for squares marked wrongly during play, drawn in blinking
+    /// red.
+    pub const MISTAKE: HighlightStyle = HighlightStyle {
+        bold: true,
+        underline: false,
+        blink: true,
+        reverse: true,
+        color: Some(AnsiColors::Red),
+        marker: None,
+    };
+
+    /// A style that doesn't change how the square is drawn, for use with a
+    /// marker.
+    pub const PLAIN: HighlightStyle = HighlightStyle {
+        bold: false,
+        underline: false,
+        blink: false,
+        reverse: false,
+        color: None,
+        marker: None,
+    };
+
+    /// Applies this highlight on top of the given style.
+    fn apply(&self, mut style: Style) -> Style {
+        if let Some(color) = self.color {
+            style = style.color(color);
+        }
+        if self.bold {
+            style = style.bold();
+        }
+        if self.underline {
+            style = style.underline();
+        }
+        if self.blink {
+            style = style.blink();
+        }
+        if self.reverse {
+            style = style.reversed();
+        }
+        style
+    }
+}
+
 #[derive(Clone, Debug)]
 /// A representation of a board in the process of being solved. This contains
 /// a board (which is constant across a given solving process) and a (possibly
@@ -123,8 +337,8 @@ pub enum Charset {
 /// We always assume that whenever a Queen has been placed, all squares that the
 /// Queen eliminates (be that by row, column, color or adjacency) are x'd out.
 /// Especially when taking input from the user, it's important to ensure that
-/// invariant is followed. The easiest way to do so is by calling
-/// [SolveState::apply_changes] once for each Queen.
+/// invariant is followed. The easiest way to do so is by building the state
+/// with [SolveState::with_changes] or [SolveState::with_queens].
 pub struct SolveState<'a> {
     /// The board that this solve state is solving
     pub board: &'a Board,
@@ -140,18 +354,30 @@ pub struct SolveState<'a> {
     ///  * Some(Queen), meaning we know a queen is there
     ///  * Some(X), meaning we know no queen can be there
     squares: Vec<Option<SquareVal>>,
+
+    /// The squares that are still blank, kept in step with `squares` so
+    /// that candidates can be found without scanning every square.
+    blank: CoordSet,
+
+    /// How many changes have been applied to this state, not counting the
+    /// markings it started with.
+    changes_applied: usize,
+
+    /// For each square, which change marked it (counting from 0), or None if
+    /// it is blank or was marked from the start; see [SolveState::provenance].
+    marked_by: Vec<Option<usize>>,
 }
 
 impl<'a> From<&'a QueensFile> for SolveState<'a> {
     fn from(queens_file: &'a QueensFile) -> Self {
-        let mut solve_state = SolveState {
-            board: &queens_file.board,
-            squares: queens_file
-                .squares
-                .clone()
-                .map(|x| x.into())
-                .unwrap_or_else(|| vec![None; queens_file.board.square_count()]),
-        };
+        let board = &queens_file.board;
+        let span = trace_span!(
+            "solve_state_from_file",
+            size = board.size(),
+            queens = Empty,
+            blank = Empty
+        );
+        let mut solve_state = SolveState::as_marked(queens_file);
 
         // So a Queens File might have Queens listed and not have the x's that those
         // Queens imply. This library assumes a SolveState always has those x's in place,
@@ -165,19 +391,119 @@ impl<'a> From<&'a QueensFile> for SolveState<'a> {
             .filter(|&(_, &sv)| sv == Some(SquareVal::Queen))
         {
             let queen = solve_state.board.idx_to_coord(&idx);
-            let x = solve_state
-                .board
-                .queen_borders(&queen)
-                .iter()
-                .filter(|&coord| solve_state.square(&coord).is_none())
-                .collect::<CoordSet>();
-            solve_state.apply_changes(&Changes::AddQueen { queen, x });
+            solve_state.add_queen(queen, CoordSet::default());
+        }
+
+        // The x's filled in above are part of the starting markings too.
+        solve_state.changes_applied = 0;
+        solve_state.marked_by.fill(None);
+
+        if !span.is_disabled() {
+            span.record(
+                "queens",
+                solve_state
+                    .squares
+                    .iter()
+                    .filter(|&&sv| sv == Some(SquareVal::Queen))
+                    .count(),
+            );
+            span.record("blank", solve_state.blank.len());
         }
 
-        trace!("From<QueensFile> for SolveState done:\n{solve_state}");
+        solve_state
+    }
+}
+
+impl<'a> SolveState<'a> {
+    /// Creates a solve state for the given board by applying each of the
+    /// given changes in order, starting from an empty board.
+    ///
+    /// Unlike [SolveState::apply_changes], this maintains the invariant that
+    /// every square a Queen eliminates is x'd out: each [Changes::AddQueen]
+    /// x's out the blank squares bordering its queen, in addition to its own
+    /// list of x's.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::datastructure::CoordSet;
+    /// # use qsolve::heuristic::Changes;
+    /// # use qsolve::solvestate::{SolveState, SquareVal};
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+    /// let solve_state = SolveState::with_changes(
+    ///     &board,
+    ///     &[
+    ///         Changes::AddX { x: CoordSet::from_iter([(0, 0)]) },
+    ///         Changes::AddQueen { queen: (0, 1), x: CoordSet::default() },
+    ///     ],
+    /// );
+    /// assert_eq!(solve_state.square(&(0, 0)), Some(SquareVal::X));
+    /// assert_eq!(solve_state.square(&(0, 1)), Some(SquareVal::Queen));
+    /// assert_eq!(solve_state.square(&(3, 1)), Some(SquareVal::X));
+    /// assert_eq!(solve_state.square(&(2, 0)), None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_changes(board: &'a Board, changes: &[Changes]) -> Self {
+        let mut solve_state = SolveState::from(board);
+        solve_state.apply_changes_maintaining_invariant(changes);
+        solve_state
+    }
 
+    /// Creates a solve state for the given board with queens at each of the
+    /// given coordinates, and every square those queens eliminate x'd out.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::solvestate::SolveState;
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+    /// let solve_state = SolveState::with_queens(&board, &[(0, 1), (1, 3), (2, 0), (3, 2)]);
+    /// assert!(solve_state.complete());
+    /// assert!(solve_state.is_valid());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_queens(board: &'a Board, queens: &[Coord]) -> Self {
+        let mut solve_state = SolveState::from(board);
+        for queen in queens {
+            solve_state.add_queen(*queen, CoordSet::default());
+        }
         solve_state
     }
+
+    /// Applies each of the given changes in order, x'ing out the squares that
+    /// any added Queens eliminate.
+    ///
+    /// The changes are not checked, so callers should check that the result
+    /// [is valid][SolveState::is_valid].
+    pub(crate) fn apply_changes_maintaining_invariant(&mut self, changes: &[Changes]) {
+        for change in changes {
+            match change {
+                Changes::AddQueen { queen, x } => self.add_queen(*queen, *x),
+                Changes::AddX { .. } => self.apply_changes_unchecked(change),
+            }
+        }
+    }
+
+    /// Places a queen at the given coordinate, x'ing out the given squares as
+    /// well as any blank squares the queen eliminates.
+    fn add_queen(&mut self, queen: Coord, x: CoordSet) {
+        let x = self
+            .board
+            .queen_borders(&queen)
+            .iter()
+            .filter(|&coord| self.square(&coord).is_none())
+            .chain(x.iter())
+            .collect::<CoordSet>();
+        self.apply_changes_unchecked(&Changes::AddQueen { queen, x });
+    }
 }
 
 impl SolveState<'_> {
@@ -212,6 +538,8 @@ impl SolveState<'_> {
     /// * No row contains multiple queens.
     /// * No color contains multiple queens.
     /// * No queens border each other.
+    /// * Every queen agrees with the [Clues][crate::clue::Clues] on its row
+    ///   and column, if the board has any.
     ///
     /// # Examples
     /// ```
@@ -228,24 +556,16 @@ impl SolveState<'_> {
     /// ```
     pub fn is_valid(&self) -> bool {
         let size = self.board.size();
-        let rows_valid = (0..size).all(|r| {
-            self.board
-                .row_coords(r)
-                .iter()
-                .map(|c| self.square(&c))
-                .filter(|&sv| sv == Some(SquareVal::Queen))
-                .count()
-                <= 1
-        });
-        let cols_valid = (0..size).all(|c| {
-            self.board
-                .col_coords(c)
-                .iter()
-                .map(|c| self.square(&c))
-                .filter(|&sv| sv == Some(SquareVal::Queen))
-                .count()
-                <= 1
-        });
+        let queen_coords = self
+            .squares
+            .iter()
+            .enumerate()
+            .filter(|&(_, &square)| square == Some(SquareVal::Queen))
+            .map(|(idx, _)| self.board.idx_to_coord(&idx))
+            .collect::<CoordSet>();
+        let rows_valid = queen_coords.iter_rows().all(|(_, cols)| cols.len() <= 1);
+        let cols_valid =
+            LineSet::from_iter(queen_coords.iter().map(|(_, c)| c)).len() == queen_coords.len();
         let colors_valid = self.board.all_colors().iter().all(|&&color| {
             self.board
                 .all_coords()
@@ -256,20 +576,16 @@ impl SolveState<'_> {
                 .count()
                 <= 1
         });
-        let queen_coords = self
-            .squares
-            .iter()
-            .enumerate()
-            .filter(|&(_, &square)| square == Some(SquareVal::Queen))
-            .map(|(idx, _)| self.board.idx_to_coord(&idx))
-            .collect::<CoordSet>();
         let queens_valid = queen_coords.clone().iter().all(|c| {
             self.board
                 .queen_borders(&c)
                 .intersection(&queen_coords)
                 .is_empty()
         });
-        rows_valid && cols_valid && colors_valid && queens_valid
+        let clues_valid = queen_coords
+            .iter()
+            .all(|c| self.board.clues().allows(c, size));
+        rows_valid && cols_valid && colors_valid && queens_valid && clues_valid
     }
 
     /// Returns the value in the given square.
@@ -277,58 +593,559 @@ impl SolveState<'_> {
         self.squares[self.board.coord_to_idx(coord)]
     }
 
+    /// Returns the squares that are still blank.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::file::QueensFile;
+    /// # use qsolve::solvestate::SolveState;
+    /// # use std::str::FromStr;
+    /// let queens_file = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\nxQxx\nxxxQ\nQxxx\nxx.x").unwrap();
+    /// let solve_state = SolveState::from(&queens_file);
+    /// assert_eq!(solve_state.blank_coords().iter().collect::<Vec<_>>(), vec![(3, 2)]);
+    /// ```
+    pub fn blank_coords(&self) -> CoordSet {
+        self.blank
+    }
+
+    /// Returns the coordinates of every square with a Queen. Once the board
+    /// is [complete][SolveState::complete], this is its solution, as found
+    /// by [all_solutions][crate::search::all_solutions].
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::datastructure::CoordSet;
+    /// # use qsolve::search::all_solutions;
+    /// # use qsolve::solvestate::SolveState;
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+    /// let solved = SolveState::with_queens(&board, &[(0, 1), (1, 3), (2, 0), (3, 2)]);
+    /// assert_eq!(solved.queens(), all_solutions(&board, 2)[0]);
+    /// assert_eq!(SolveState::from(&board).queens(), CoordSet::default());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn queens(&self) -> CoordSet {
+        self.board
+            .all_coords()
+            .iter()
+            .filter(|coord| self.square(coord) == Some(SquareVal::Queen))
+            .collect()
+    }
+
+    /// Returns the markings in the given file exactly as they are, without
+    /// x'ing out the squares that its Queens rule out.
+    ///
+    /// Unlike [SolveState::from], the result may break the invariant that
+    /// every square a Queen rules out is x'd out, so it is only fit for
+    /// comparing markings, as a [MarkingMode::Strict][crate::session::MarkingMode::Strict]
+    /// player would have made them, and not for solving.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::file::QueensFile;
+    /// # use qsolve::solvestate::{SolveState, SquareVal};
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let queens_file = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\n.Q..\n....\n....\n....")?;
+    /// assert_eq!(SolveState::as_marked(&queens_file).square(&(1, 1)), None);
+    /// assert_eq!(SolveState::from(&queens_file).square(&(1, 1)), Some(SquareVal::X));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn as_marked(queens_file: &QueensFile) -> SolveState<'_> {
+        let board = &queens_file.board;
+        let squares: Vec<Option<SquareVal>> = queens_file
+            .squares
+            .clone()
+            .map(|x| x.into())
+            .unwrap_or_else(|| vec![None; board.square_count()]);
+        let blank = board
+            .all_coords()
+            .iter()
+            .filter(|coord| squares[board.coord_to_idx(coord)].is_none())
+            .collect();
+        SolveState {
+            board,
+            squares,
+            blank,
+            changes_applied: 0,
+            marked_by: vec![None; board.square_count()],
+        }
+    }
+
+    /// Returns how far along solving is, from 0.0 for a blank board to 1.0
+    /// for a board where every square has a Queen or an X.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::solvestate::SolveState;
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+    /// assert_eq!(SolveState::from(&board).progress(), 0.0);
+    /// let partial = SolveState::with_queens(&board, &[(0, 1)]);
+    /// assert!(partial.progress() > 0.0 && partial.progress() < 1.0);
+    /// let solved = SolveState::with_queens(&board, &[(0, 1), (1, 3), (2, 0), (3, 2)]);
+    /// assert_eq!(solved.progress(), 1.0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn progress(&self) -> f32 {
+        let total = self.board.square_count();
+        if total == 0 {
+            return 1.0;
+        }
+        (total - self.blank.len()) as f32 / total as f32
+    }
+
+    /// Returns the squares of the given color that could still hold its
+    /// Queen: that is, the ones that are still blank. This is empty once the
+    /// color has a Queen.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::datastructure::CoordSet;
+    /// # use qsolve::solvestate::SolveState;
+    /// # use qsolve::squarecolor::SquareColor;
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+    /// let solve_state = SolveState::with_queens(&board, &[(0, 1)]);
+    /// assert_eq!(
+    ///     solve_state.candidates_for_color(SquareColor::Black),
+    ///     CoordSet::from_iter([(1, 3)])
+    /// );
+    /// assert!(solve_state.candidates_for_color(SquareColor::White).is_empty());
+    /// assert_eq!(solve_state.candidates_for_row(2).len(), 3);
+    /// assert_eq!(solve_state.candidates_for_col(0).len(), 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn candidates_for_color(&self, color: SquareColor) -> CoordSet {
+        self.board
+            .coords_for_color(&color)
+            .intersection(&self.blank)
+    }
+
+    /// Returns the highlights that draw the given overlay, for use with
+    /// [SolveState::ansi_string_with_highlights].
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::palette::Palette;
+    /// # use qsolve::solvestate::{Charset, Overlay, SolveState};
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+    /// let solve_state = SolveState::with_queens(&board, &[(0, 1)]);
+    /// let highlights = solve_state.overlay_highlights(Overlay::Candidates);
+    /// let ansi_string = solve_state.ansi_string_with_highlights(&highlights, Charset::Ascii, Palette::default())?;
+    /// // Black's last candidate is also the last in its row, and red and
+    /// // blue have three candidates each.
+    /// assert!(ansi_string.contains('!') && ansi_string.contains('3'));
+    /// assert!(solve_state.overlay_highlights(Overlay::None).is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn overlay_highlights(&self, overlay: Overlay) -> Vec<(CoordSet, HighlightStyle)> {
+        match overlay {
+            Overlay::None => vec![],
+            Overlay::Candidates => {
+                let size = self.board.size();
+                let last_in_line = (0..size)
+                    .map(|r| self.candidates_for_row(r))
+                    .chain((0..size).map(|c| self.candidates_for_col(c)))
+                    .filter(|candidates| candidates.len() == 1)
+                    .flat_map(|candidates| candidates.iter().collect::<Vec<_>>())
+                    .collect::<CoordSet>();
+                let mut highlights = vec![(
+                    last_in_line,
+                    HighlightStyle {
+                        bold: true,
+                        marker: Some('!'),
+                        ..HighlightStyle::PLAIN
+                    },
+                )];
+                for color in self.board.all_colors() {
+                    let candidates = self.candidates_for_color(*color);
+                    let marker = char::from_digit(candidates.len() as u32, 10).unwrap_or('+');
+                    highlights.push((
+                        candidates,
+                        HighlightStyle {
+                            marker: Some(marker),
+                            ..HighlightStyle::PLAIN
+                        },
+                    ));
+                }
+                highlights
+            }
+        }
+    }
+
+    /// Returns the squares of the given row that could still hold its Queen.
+    pub fn candidates_for_row(&self, r: usize) -> CoordSet {
+        self.board.row_coords(r).intersection(&self.blank)
+    }
+
+    /// Returns the squares of the given column that could still hold its Queen.
+    pub fn candidates_for_col(&self, c: usize) -> CoordSet {
+        self.board.col_coords(c).intersection(&self.blank)
+    }
+
+    /// Returns the colors that don't have a queen yet, in the order of
+    /// [Board::all_colors].
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::file::QueensFile;
+    /// # use qsolve::solvestate::SolveState;
+    /// # use qsolve::squarecolor::SquareColor;
+    /// # use std::str::FromStr;
+    /// let queens_file = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\nxQxx\nxxxQ\nQxxx\nxx.x").unwrap();
+    /// let solve_state = SolveState::from(&queens_file);
+    /// assert_eq!(solve_state.unplaced_colors(), vec![SquareColor::Blue]);
+    /// ```
+    pub fn unplaced_colors(&self) -> Vec<SquareColor> {
+        self.board
+            .all_colors()
+            .into_iter()
+            .filter(|color| {
+                self.board
+                    .coords_for_color(color)
+                    .iter()
+                    .all(|coord| self.square(&coord) != Some(SquareVal::Queen))
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Returns the rows that contain a blank square among the given coords.
+    pub fn blank_rows(&self, coords: &CoordSet) -> LineSet {
+        coords
+            .iter()
+            .filter(|coord| self.square(coord).is_none())
+            .map(|(r, _)| r)
+            .collect()
+    }
+
+    /// Returns the columns that contain a blank square among the given coords.
+    pub fn blank_cols(&self, coords: &CoordSet) -> LineSet {
+        coords
+            .iter()
+            .filter(|coord| self.square(coord).is_none())
+            .map(|(_, c)| c)
+            .collect()
+    }
+
+    /// Returns the colors of the blank squares among the given coords.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::solvestate::SolveState;
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+    /// let solve_state = SolveState::with_queens(&board, &[(0, 0)]);
+    /// let row = board.row_coords(1);
+    /// assert_eq!(solve_state.blank_colors(&row).len(), 1);
+    /// assert_eq!(solve_state.blank_cols(&row).len(), 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn blank_colors(&self, coords: &CoordSet) -> SquareColorSet {
+        coords
+            .iter()
+            .filter(|coord| self.square(coord).is_none())
+            .map(|coord| self.board.color(&coord))
+            .collect()
+    }
+
     /// Applies all of the provided changes, mutating the underlying
     /// SolveState accordingly.
+    ///
+    /// In debug builds, this panics if the changes are not allowed (see
+    /// [SolveState::try_apply_changes]).
     pub fn apply_changes(&mut self, changes: &Changes) {
+        #[cfg(debug_assertions)]
+        if let Err(err) = self.check_changes(changes) {
+            panic!("{err:#}");
+        }
+        self.apply_changes_unchecked(changes);
+    }
+
+    /// Applies all of the provided changes like [SolveState::apply_changes],
+    /// but first checks that they are allowed, failing with
+    /// [QSolveError::InvalidChanges] and leaving this state as it was if not.
+    ///
+    /// Changes are not allowed if they would put a Queen on a square that
+    /// was x'd out, or x out a square that has a Queen, or if they would
+    /// make a valid state [invalid][SolveState::is_valid].
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::datastructure::CoordSet;
+    /// # use qsolve::error::QSolveError;
+    /// # use qsolve::file::QueensFile;
+    /// # use qsolve::heuristic::Changes;
+    /// # use qsolve::solvestate::SolveState;
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let queens_file = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\nxQxx\n....\n....\n....")?;
+    /// let mut solve_state = SolveState::from(&queens_file);
+    /// let err = solve_state
+    ///     .try_apply_changes(&Changes::AddX { x: CoordSet::from_iter([(0, 1)]) })
+    ///     .unwrap_err();
+    /// assert_eq!(QSolveError::from_error(&err), Some(QSolveError::InvalidChanges));
+    /// solve_state.try_apply_changes(&Changes::AddX { x: CoordSet::from_iter([(1, 1)]) })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn try_apply_changes(&mut self, changes: &Changes) -> Result<()> {
+        self.check_changes(changes)?;
+        self.apply_changes_unchecked(changes);
+        Ok(())
+    }
+
+    /// Checks that the given changes are allowed by
+    /// [SolveState::try_apply_changes].
+    fn check_changes(&self, changes: &Changes) -> Result<()> {
+        let (queen, x) = match changes {
+            Changes::AddQueen { queen, x } => (Some(*queen), x),
+            Changes::AddX { x } => (None, x),
+        };
+        if let Some(queen) = queen
+            && self.square(&queen) == Some(SquareVal::X)
+        {
+            return Err(
+                anyhow!("Cannot place a Queen on {queen:?}, which is x'd out")
+                    .context(QSolveError::InvalidChanges),
+            );
+        }
+        if let Some(coord) = x
+            .iter()
+            .find(|coord| self.square(coord) == Some(SquareVal::Queen) || Some(*coord) == queen)
+        {
+            return Err(anyhow!("Cannot x out {coord:?}, which has a Queen")
+                .context(QSolveError::InvalidChanges));
+        }
+        if self.is_valid() {
+            let mut next_state = self.clone();
+            next_state.apply_changes_unchecked(changes);
+            if !next_state.is_valid() {
+                return Err(anyhow!("Changes {changes:?} break the rules of the puzzle")
+                    .context(QSolveError::InvalidChanges));
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the given changes without checking them.
+    pub(crate) fn apply_changes_unchecked(&mut self, changes: &Changes) {
+        let (queens, x) = match changes {
+            Changes::AddQueen { x, .. } => (1, x),
+            Changes::AddX { x } => (0, x),
+        };
+        let _span = trace_span!(
+            "apply_changes",
+            queens,
+            xs = x.len(),
+            blank = self.blank.len()
+        );
         match changes {
             Changes::AddQueen { queen, x } => {
-                self.squares[self.board.coord_to_idx(queen)] = Some(SquareVal::Queen);
+                self.set_square(*queen, SquareVal::Queen);
                 for coord in x {
-                    self.squares[self.board.coord_to_idx(&coord)] = Some(SquareVal::X)
+                    self.set_square(coord, SquareVal::X);
                 }
             }
             Changes::AddX { x } => {
                 for coord in x {
-                    self.squares[self.board.coord_to_idx(&coord)] = Some(SquareVal::X)
+                    self.set_square(coord, SquareVal::X);
                 }
             }
         }
+        self.changes_applied += 1;
+    }
+
+    /// Sets the value in the given square, keeping the blank squares in step,
+    /// and records that the change being applied marked it.
+    fn set_square(&mut self, coord: Coord, square_val: SquareVal) {
+        let idx = self.board.coord_to_idx(&coord);
+        self.squares[idx] = Some(square_val);
+        self.marked_by[idx] = Some(self.changes_applied);
+        self.blank.remove(coord);
+    }
+
+    /// Returns how many changes have been applied to this state, not counting
+    /// the markings it started with.
+    pub fn changes_applied(&self) -> usize {
+        self.changes_applied
+    }
+
+    /// Returns which change marked the given square, counting from 0 for the
+    /// first change applied to this state, or None if the square is blank or
+    /// was marked from the start, as in the [QueensFile] it came from.
+    ///
+    /// A solve from a fresh state applies one change per step, so this is
+    /// the index of the step in the solve; see
+    /// [Solution::marked_by][crate::solution::Solution::marked_by].
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::datastructure::CoordSet;
+    /// # use qsolve::file::QueensFile;
+    /// # use qsolve::heuristic::Changes;
+    /// # use qsolve::solvestate::SolveState;
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let queens_file = QueensFile::from_str("wwww\nwkkk\nrrrr\nbbbb\n\nx...\n....\n....\n....")?;
+    /// let mut solve_state = SolveState::from(&queens_file);
+    /// solve_state.apply_changes(&Changes::AddX { x: CoordSet::from_iter([(0, 2)]) });
+    /// solve_state.apply_changes(&Changes::AddX { x: CoordSet::from_iter([(0, 3)]) });
+    /// assert_eq!(solve_state.provenance(&(0, 0)), None);
+    /// assert_eq!(solve_state.provenance(&(0, 2)), Some(0));
+    /// assert_eq!(solve_state.provenance(&(0, 3)), Some(1));
+    /// assert_eq!(solve_state.provenance(&(1, 1)), None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn provenance(&self, coord: &Coord) -> Option<usize> {
+        self.marked_by[self.board.coord_to_idx(coord)]
+    }
+
+    /// Encodes the state and its board as compact bytes, as described in
+    /// [binary][crate::binary::to_bytes], which
+    /// [QueensFile::from_bytes][crate::file::QueensFile::from_bytes] reads
+    /// back. This is much smaller than the text file, so it suits caches and
+    /// links.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        binary::to_bytes(self)
     }
 
     /// Returns a string colored by OwoColorize that represents the
     /// SolveState, highlighting the given Coordinates.
-    pub fn ansi_string(&self, highlight: CoordSet, charset: Charset) -> Result<String> {
-        let mut f = String::new();
+    pub fn ansi_string(
+        &self,
+        highlight: CoordSet,
+        charset: Charset,
+        palette: Palette,
+    ) -> Result<String> {
+        self.ansi_string_with_highlights(
+            &[(highlight, HighlightStyle::default())],
+            charset,
+            palette,
+        )
+    }
+
+    /// Returns a string colored by OwoColorize that represents the
+    /// SolveState, highlighting each group of Coordinates with its own style.
+    ///
+    /// If a square is in more than one group, the first group's style is used.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::board::Board;
+    /// # use qsolve::datastructure::CoordSet;
+    /// # use qsolve::palette::Palette;
+    /// # use qsolve::solvestate::{Charset, HighlightStyle, SolveState};
+    /// # use std::str::FromStr;
+    /// # use anyhow::Result;
+    /// # fn main() -> Result<()> {
+    /// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+    /// let solve_state = SolveState::from(&board);
+    /// let seen = HighlightStyle { marker: Some('?'), ..HighlightStyle::default() };
+    /// let changed = HighlightStyle { marker: Some('!'), reverse: true, ..HighlightStyle::default() };
+    /// let ansi_string = solve_state.ansi_string_with_highlights(
+    ///     &[
+    ///         (CoordSet::from_iter([(0, 0)]), changed),
+    ///         (CoordSet::from_iter([(0, 0), (0, 1)]), seen),
+    ///     ],
+    ///     Charset::Ascii,
+    ///     Palette::default(),
+    /// )?;
+    /// assert!(ansi_string.contains('!') && ansi_string.contains('?'));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn ansi_string_with_highlights(
+        &self,
+        highlights: &[(CoordSet, HighlightStyle)],
+        charset: Charset,
+        palette: Palette,
+    ) -> Result<String> {
+        Ok(self
+            .ansi_squares_with_highlights(highlights, charset, palette)?
+            .iter()
+            .map(|row| row.concat())
+            .join("\n"))
+    }
+
+    /// Returns each square of the SolveState as its own string colored by
+    /// OwoColorize, one row at a time, drawn as by
+    /// [SolveState::ansi_string_with_highlights]. Each square takes up one
+    /// column on a terminal, so single squares can be redrawn in place.
+    pub fn ansi_squares_with_highlights(
+        &self,
+        highlights: &[(CoordSet, HighlightStyle)],
+        charset: Charset,
+        palette: Palette,
+    ) -> Result<Vec<Vec<String>>> {
+        let mut rows = Vec::with_capacity(self.board.size());
         for row_num in 0..self.board.size() {
+            let mut row = Vec::with_capacity(self.board.size());
             for col_num in 0..self.board.size() {
                 let coord = (row_num, col_num);
-                let highlight = highlight.contains(&coord);
+                let highlight = highlights
+                    .iter()
+                    .find(|(coords, _)| coords.contains(&coord))
+                    .map(|(_, style)| style);
                 let square = self.square(&coord);
-                let ansi_color = AnsiColors::from(self.board.color(&coord));
-                let fg_color = self.board.color(&coord).fg_color();
-                let c = SquareVal::as_char(square, highlight, &charset);
-                if highlight {
-                    write!(
-                        f,
-                        "{}",
-                        c.color(fg_color).on_color(ansi_color).bold().underline()
-                    )?
-                } else {
-                    write!(f, "{}", c.color(fg_color).on_color(ansi_color))?
+                let color = self.board.color(&coord);
+                let style = Style::new()
+                    .color(palette.fg(color))
+                    .on_color(palette.bg(color));
+                let c = match (square, highlight.and_then(|h| h.marker)) {
+                    (None, Some(marker)) => marker,
+                    _ => SquareVal::as_char(square, highlight.is_some(), &charset),
+                };
+                let mut f = String::new();
+                match highlight {
+                    Some(highlight) => write!(f, "{}", c.style(highlight.apply(style)))?,
+                    None => write!(f, "{}", c.style(style))?,
                 }
+                row.push(f);
             }
-            if row_num != self.board.size() - 1 {
-                writeln!(f)?
-            };
+            rows.push(row);
         }
-        Ok(f)
+        Ok(rows)
     }
 }
 
 impl<'a> From<&'a Board> for SolveState<'a> {
     fn from(board: &'a Board) -> Self {
         let squares = vec![None; board.square_count()];
-        SolveState { board, squares }
+        SolveState {
+            board,
+            squares,
+            blank: *board.all_coords(),
+            changes_applied: 0,
+            marked_by: vec![None; board.square_count()],
+        }
     }
 }
 
@@ -395,6 +1212,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn solvestate_with_changes() {
+        let board = Board::from_str("wwww\nkkkk\nrrrr\nbbbb").unwrap();
+        let ss = SolveState::with_changes(
+            &board,
+            &[
+                Changes::AddX {
+                    x: CoordSet::from_iter([(3, 3)]),
+                },
+                Changes::AddQueen {
+                    queen: (0, 0),
+                    x: CoordSet::from_iter([(2, 2)]),
+                },
+            ],
+        );
+        assert!(ss.is_valid());
+        assert_eq!(
+            format!("{ss}"),
+            "wwww\nkkkk\nrrrr\nbbbb\n\nQxxx\nxx  \nx x \nx  x"
+        );
+    }
+
+    #[test]
+    fn solvestate_with_queens() {
+        let board = Board::from_str("wwww\nkkkk\nrrrr\nbbbb").unwrap();
+        let qf = QueensFile::from_str("wwww\nkkkk\nrrrr\nbbbb\n\nQ...\n....\n....\n....").unwrap();
+        let ss = SolveState::with_queens(&board, &[(0, 0)]);
+        assert_eq!(format!("{ss}"), format!("{}", SolveState::from(&qf)));
+
+        let empty = SolveState::with_queens(&board, &[]);
+        assert!(empty.squares.iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn solvestate_candidates_stay_in_step() {
+        let queens_file =
+            QueensFile::try_from_text_file(&"games/linkedin-1-partial.txt".into()).unwrap();
+        let heuristics = crate::heuristic::all_heuristics(&queens_file.board);
+        let board = &queens_file.board;
+        for item in crate::solveiter::solve_iter(
+            SolveState::from(&queens_file),
+            SolveStrategy::Fast,
+            &heuristics,
+        ) {
+            let ss = item.solve_state;
+            let blank = |coords: CoordSet| {
+                coords
+                    .iter()
+                    .filter(|coord| ss.square(coord).is_none())
+                    .collect::<CoordSet>()
+            };
+            assert_eq!(ss.blank_coords(), blank(*board.all_coords()));
+            for color in board.all_colors() {
+                assert_eq!(
+                    ss.candidates_for_color(*color),
+                    blank(board.coords_for_color(color))
+                );
+            }
+            for line in 0..board.size() {
+                assert_eq!(ss.candidates_for_row(line), blank(board.row_coords(line)));
+                assert_eq!(ss.candidates_for_col(line), blank(board.col_coords(line)));
+            }
+        }
+    }
+
     #[test]
     fn solvestate_ansi_string() {
         let board_str = "wwww\nkkkk\nrrrr\nbbbb";
@@ -404,7 +1286,9 @@ mod tests {
         let ss = SolveState::from(&qf);
         assert!(ss.is_valid());
 
-        let ansi_string = ss.ansi_string(CoordSet::default(), Charset::Ascii).unwrap();
+        let ansi_string = ss
+            .ansi_string(CoordSet::default(), Charset::Ascii, Palette::default())
+            .unwrap();
         let ansi_re = Regex::new(r"\u{1b}\[[0-9;]*m").unwrap();
         let ansi_removed = ansi_re.replace_all(&ansi_string, "");
         assert_eq!(
@@ -423,7 +1307,11 @@ mod tests {
         assert!(ss.is_valid());
 
         let ansi_string = ss
-            .ansi_string(CoordSet::from_iter(vec![(0, 0)]), Charset::Ascii)
+            .ansi_string(
+                CoordSet::from_iter(vec![(0, 0)]),
+                Charset::Ascii,
+                Palette::default(),
+            )
             .unwrap();
         let ansi_re = Regex::new(r"\u{1b}\[[0-9;]*m").unwrap();
         let ansi_removed = ansi_re.replace_all(&ansi_string, "");
@@ -433,6 +1321,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn solvestate_ansi_string_with_highlights() {
+        let board_str = "wwww\nkkkk\nrrrr\nbbbb";
+        let squares_str = "Qxxx\nxx..\nx...\nx. _";
+        let qf_str = format!("{board_str}\n\n{squares_str}");
+        let qf = QueensFile::from_str(&qf_str).unwrap();
+        let ss = SolveState::from(&qf);
+
+        let first = HighlightStyle {
+            marker: Some('1'),
+            ..HighlightStyle::default()
+        };
+        let second = HighlightStyle {
+            marker: Some('2'),
+            bold: false,
+            underline: false,
+            reverse: true,
+            color: Some(AnsiColors::Red),
+            ..HighlightStyle::default()
+        };
+        let ansi_string = ss
+            .ansi_string_with_highlights(
+                &[
+                    (CoordSet::from_iter([(0, 0), (1, 2)]), first),
+                    (CoordSet::from_iter([(1, 2), (1, 3), (2, 1)]), second),
+                ],
+                Charset::Ascii,
+                Palette::default(),
+            )
+            .unwrap();
+        let ansi_re = Regex::new(r"\u{1b}\[[0-9;]*m").unwrap();
+        let ansi_removed = ansi_re.replace_all(&ansi_string, "");
+        assert_eq!(ansi_removed, "Qxxx\nxx12\nx2  \nx   ");
+        assert!(ansi_string.contains("\u{1b}[7m") || ansi_string.contains(";7"));
+    }
+
     #[test]
     fn solvestrategy_display() {
         assert_eq!(format!("{}", SolveStrategy::Fast), "Fast");
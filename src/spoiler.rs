@@ -0,0 +1,94 @@
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+use itertools::Itertools;
+
+use crate::{datastructure::CoordSet, notation::CoordStyle};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+/// How to hide a hint or answer, so that passing it on to a friend doesn't
+/// spoil it at a glance.
+pub enum SpoilerStyle {
+    /// Rotates letters by 13 and digits by 5, which [rot13] undoes.
+    #[default]
+    Rot13,
+    /// Wraps the text in an HTML `<details>` block, which Markdown chats and
+    /// issue trackers show collapsed until it is clicked.
+    Details,
+}
+
+impl SpoilerStyle {
+    /// Hides the given text in this style, under the given label.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::spoiler::SpoilerStyle;
+    /// assert_eq!(SpoilerStyle::Rot13.wrap("Hint", "Row 3"), "Hint (rot13): Ebj 8");
+    /// assert_eq!(
+    ///     SpoilerStyle::Details.wrap("Hint", "Row 3"),
+    ///     "<details><summary>Hint</summary>\n\nRow 3\n\n</details>"
+    /// );
+    /// ```
+    pub fn wrap(&self, label: &str, text: &str) -> String {
+        match self {
+            SpoilerStyle::Rot13 => {
+                let text = text.lines().map(rot13).join(" / ");
+                format!("{label} (rot13): {text}")
+            }
+            SpoilerStyle::Details => {
+                format!("<details><summary>{label}</summary>\n\n{text}\n\n</details>")
+            }
+        }
+    }
+}
+
+/// Rotates each ASCII letter 13 places through the alphabet and each digit
+/// 5 places, leaving everything else alone. Applying it twice gives back the
+/// original text.
+///
+/// # Examples
+/// ```
+/// # use qsolve::spoiler::rot13;
+/// assert_eq!(rot13("Queen at r1c8"), "Dhrra ng e6p3");
+/// assert_eq!(rot13(&rot13("Queen at r1c8")), "Queen at r1c8");
+/// ```
+pub fn rot13(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            let rotate = |base: u8, len: u8, by: u8| (base + (c as u8 - base + by) % len) as char;
+            match c {
+                'a'..='z' => rotate(b'a', 26, 13),
+                'A'..='Z' => rotate(b'A', 26, 13),
+                '0'..='9' => rotate(b'0', 10, 5),
+                c => c,
+            }
+        })
+        .collect()
+}
+
+/// Lists the given squares as `r1c2`, numbered from 1, in reading order.
+///
+/// This is [CoordStyle::squares_text] with the default style.
+pub fn squares_text(coords: &CoordSet) -> String {
+    CoordStyle::default().squares_text(coords)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rot13_hides_squares_and_undoes_itself() {
+        let squares = squares_text(&CoordSet::from_iter([(7, 0), (0, 9), (2, 3)]));
+        assert_eq!(squares, "r1c10 r3c4 r8c1");
+        let hidden = rot13(&squares);
+        assert_eq!(hidden, "e6p65 e8p9 e3p6");
+        assert_eq!(rot13(&hidden), squares);
+        let all = (' '..='~').collect::<String>();
+        assert_eq!(rot13(&rot13(&all)), all);
+        assert_eq!(
+            SpoilerStyle::Rot13.wrap("Hint", "Line one\nLine two"),
+            "Hint (rot13): Yvar bar / Yvar gjb"
+        );
+    }
+}
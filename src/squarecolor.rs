@@ -12,7 +12,7 @@ use owo_colors::AnsiColors;
 ///
 /// * `TryFrom<char>` for [SquareColor] allows for conversion from a [char] representation to a [SquareColor]. This is most notably used in parsing a [Board][crate::board::Board].
 /// * `From<SquareColor>` for [AnsiColors] allows for conversion from a [SquareColor] to an [AnsiColors] for printing squares to the terminal.
-/// * [SquareColor::to_unicode_block] returns the unicode block character for the color. This is useful for generating the share text for a solution.
+/// * [SquareColor::to_unicode_block] returns the unicode emoji for the color. This is useful for generating the share text for a solution.
 ///
 /// Each color is represented by a [char]. The [char] is the first character of the color
 /// for all colors other than black; for black, we use 'k' (since 'b' is already used for blue,
@@ -147,26 +147,26 @@ impl Display for SquareColor {
 }
 
 impl SquareColor {
-    /// Returns an appropriate Unicode block for the given color
+    /// Returns an appropriate Unicode emoji for the given color.
+    ///
+    /// This is the emoji from the classic [Palette][crate::palette::Palette];
+    /// see [Palette::emoji][crate::palette::Palette::emoji].
     pub fn to_unicode_block(&self) -> char {
-        match self {
-            SquareColor::Black => '\u{2B1B}',
-            SquareColor::Red => '\u{1F7E5}',
-            SquareColor::Green => '\u{1F7E9}',
-            SquareColor::Yellow => '\u{1F7E8}',
-            SquareColor::Blue => '\u{1F7E6}',
-            SquareColor::Magenta => '\u{1F7EA}',
-            SquareColor::Cyan => '\u{1F7E6}',
-            SquareColor::White => '\u{2B1C}',
-            SquareColor::BrightBlack => '\u{2B1B}',
-            SquareColor::BrightRed => '\u{1F7E5}',
-            SquareColor::BrightGreen => '\u{1F7E9}',
-            SquareColor::BrightYellow => '\u{1F7E8}',
-            SquareColor::BrightBlue => '\u{1F7E6}',
-            SquareColor::BrightMagenta => '\u{1F7EA}',
-            SquareColor::BrightCyan => '\u{1F7E6}',
-            SquareColor::BrightWhite => '\u{2B1C}',
-        }
+        crate::palette::Palette::Classic.emoji(*self)
+    }
+
+    /// Returns the circled letter for the given color.
+    ///
+    /// This is the [char] representation of the color (see [Display]), circled;
+    /// so [SquareColor::Yellow] becomes 'ⓨ' and [SquareColor::BrightYellow] becomes 'Ⓨ'.
+    pub fn to_circled_letter(&self) -> char {
+        let c = self.to_string().chars().next().unwrap();
+        let offset = if c.is_ascii_uppercase() {
+            0x24B6 + (c as u32 - 'A' as u32)
+        } else {
+            0x24D0 + (c as u32 - 'a' as u32)
+        };
+        char::from_u32(offset).unwrap()
     }
 
     /// This returns the ideal foreground color for the given square color.
@@ -183,6 +183,8 @@ impl SquareColor {
 
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
+
     use super::*;
 
     #[test]
@@ -218,4 +220,12 @@ mod tests {
             assert!(!u.is_ascii())
         }
     }
+
+    #[test]
+    fn squarecolor_circled_letter() {
+        assert_eq!(SquareColor::Yellow.to_circled_letter(), '\u{24E8}');
+        assert_eq!(SquareColor::BrightYellow.to_circled_letter(), '\u{24CE}');
+        let letters = ALL_SQUARE_COLORS.map(|sc| sc.to_circled_letter());
+        assert_eq!(letters.iter().unique().count(), ALL_SQUARE_COLORS.len());
+    }
 }
@@ -0,0 +1,143 @@
+#[cfg(feature = "cli")]
+use clap::ValueEnum;
+
+use crate::{
+    datastructure::CoordSet,
+    heuristic::{Changes, Heuristic},
+    search::solutions,
+    solvestate::{SolveState, SquareVal},
+};
+
+/// What a [StallHandler] decides to do when no heuristic applies.
+#[derive(Clone, Copy, Debug)]
+pub enum StallResolution<'h> {
+    /// Stop solving, leaving the board unsolved.
+    Stop,
+    /// Continue solving with the given heuristic, which must have changes
+    /// for the stalled state; if it doesn't, solving stops.
+    Continue(&'h dyn Heuristic),
+}
+
+/// Decides what to do when the solver stalls: that is, when the board is not
+/// complete but no heuristic applies to it.
+///
+/// The built-in handlers are the variants of [OnStall], which is chosen with
+/// [SolveOptions::on_stall][crate::solvestate::SolveOptions::on_stall]; a
+/// custom handler, such as one that asks the user, can be given to
+/// [SolveIter::with_stall_handler][crate::solveiter::SolveIter::with_stall_handler].
+pub trait StallHandler: std::fmt::Debug {
+    /// Returns what to do about the given stalled solve state.
+    fn on_stall(&self, solve_state: &SolveState) -> StallResolution<'_>;
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+/// The built-in ways to handle the solver stalling.
+pub enum OnStall {
+    /// Stop solving, leaving the board unsolved.
+    #[default]
+    Stop,
+    /// Search for a solution consistent with the board, and place one of
+    /// its Queens.
+    Search,
+}
+
+impl OnStall {
+    /// Returns this as a [StallHandler].
+    pub fn as_handler(self) -> &'static dyn StallHandler {
+        match self {
+            OnStall::Stop => &OnStall::Stop,
+            OnStall::Search => &OnStall::Search,
+        }
+    }
+}
+
+impl StallHandler for OnStall {
+    fn on_stall(&self, _solve_state: &SolveState) -> StallResolution<'_> {
+        match self {
+            OnStall::Stop => StallResolution::Stop,
+            OnStall::Search => StallResolution::Continue(&SearchFallback),
+        }
+    }
+}
+
+/// A heuristic of last resort, which searches for a solution consistent with
+/// the board and places the first of its Queens that is not yet placed.
+#[derive(Debug)]
+struct SearchFallback;
+
+impl Heuristic for SearchFallback {
+    fn changes(&self, solve_state: &SolveState) -> Option<Changes> {
+        let board = solve_state.board;
+        let solution = solutions(board).find(|queens| {
+            board
+                .all_coords()
+                .iter()
+                .all(|coord| match solve_state.square(&coord) {
+                    Some(SquareVal::Queen) => queens.contains(&coord),
+                    Some(SquareVal::X) => !queens.contains(&coord),
+                    None => true,
+                })
+        })?;
+        let queen = solution
+            .iter()
+            .find(|coord| solve_state.square(coord).is_none())?;
+        let (row, col) = queen;
+        let color = board.color(&queen);
+        let x = solve_state
+            .blank_coords()
+            .iter()
+            .filter(|&coord| coord != queen)
+            .filter(|coord| {
+                coord.0 == row
+                    || coord.1 == col
+                    || board.color(coord) == color
+                    || board.queen_borders(&queen).contains(coord)
+            })
+            .collect::<CoordSet>();
+        Some(Changes::AddQueen { queen, x })
+    }
+
+    fn seen_coords(&self, solve_state: &SolveState) -> CoordSet {
+        solve_state.blank_coords()
+    }
+
+    fn description(&self) -> String {
+        "No heuristic applies, so search for a solution.\nPlace one of its Queens.".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use anyhow::Result;
+
+    use crate::board::Board;
+
+    use super::*;
+
+    #[test]
+    fn search_fallback_places_a_queen() -> Result<()> {
+        let board = Board::from_str("wwkk\nwwkk\nrrbb\nrrbb")?;
+        let solve_state = SolveState::from(&board);
+        assert!(matches!(
+            OnStall::Stop.on_stall(&solve_state),
+            StallResolution::Stop
+        ));
+        let StallResolution::Continue(h) = OnStall::Search.on_stall(&solve_state) else {
+            panic!("Search should continue");
+        };
+        let Some(Changes::AddQueen { queen, x }) = h.changes(&solve_state) else {
+            panic!("Search should place a queen");
+        };
+        let mut next_state = solve_state.clone();
+        next_state.apply_changes(&Changes::AddQueen { queen, x });
+        assert!(next_state.is_valid());
+        assert_eq!(
+            next_state.blank_coords().len(),
+            board.square_count() - 1 - x.len()
+        );
+        Ok(())
+    }
+}
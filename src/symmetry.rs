@@ -0,0 +1,236 @@
+use std::fmt::Display;
+
+use itertools::Itertools;
+
+use crate::{
+    board::Board,
+    datastructure::{Coord, CoordSet},
+    solvestate::{SolveState, SquareVal},
+    squarecolor::SquareColor,
+};
+
+/// A symmetry of a square grid, other than the identity.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Symmetry {
+    /// Rotation by a quarter turn (symmetry under this implies symmetry under three quarters).
+    Rotate90,
+    /// Rotation by a half turn.
+    Rotate180,
+    /// Reflection across the vertical axis, swapping left and right.
+    FlipHorizontal,
+    /// Reflection across the horizontal axis, swapping top and bottom.
+    FlipVertical,
+    /// Reflection across the diagonal from the top left to the bottom right.
+    FlipDiagonal,
+    /// Reflection across the diagonal from the top right to the bottom left.
+    FlipAntiDiagonal,
+}
+
+/// A convenience const that contains all symmetries once.
+pub const ALL_SYMMETRIES: [Symmetry; 6] = [
+    Symmetry::Rotate90,
+    Symmetry::Rotate180,
+    Symmetry::FlipHorizontal,
+    Symmetry::FlipVertical,
+    Symmetry::FlipDiagonal,
+    Symmetry::FlipAntiDiagonal,
+];
+
+impl Symmetry {
+    /// Returns where the given coord moves to under this symmetry, on a grid
+    /// of the given size.
+    ///
+    /// # Examples
+    /// ```
+    /// # use qsolve::symmetry::Symmetry;
+    /// assert_eq!(Symmetry::Rotate90.apply(&(0, 0), 4), (0, 3));
+    /// assert_eq!(Symmetry::Rotate180.apply(&(0, 1), 4), (3, 2));
+    /// assert_eq!(Symmetry::FlipDiagonal.apply(&(0, 1), 4), (1, 0));
+    /// ```
+    pub fn apply(&self, &(r, c): &Coord, size: usize) -> Coord {
+        let last = size - 1;
+        match self {
+            Symmetry::Rotate90 => (c, last - r),
+            Symmetry::Rotate180 => (last - r, last - c),
+            Symmetry::FlipHorizontal => (r, last - c),
+            Symmetry::FlipVertical => (last - r, c),
+            Symmetry::FlipDiagonal => (c, r),
+            Symmetry::FlipAntiDiagonal => (last - c, last - r),
+        }
+    }
+
+    /// A short, stable name for the symmetry, such as `rotate-180`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Symmetry::Rotate90 => "rotate-90",
+            Symmetry::Rotate180 => "rotate-180",
+            Symmetry::FlipHorizontal => "flip-horizontal",
+            Symmetry::FlipVertical => "flip-vertical",
+            Symmetry::FlipDiagonal => "flip-diagonal",
+            Symmetry::FlipAntiDiagonal => "flip-antidiagonal",
+        }
+    }
+}
+
+/// Returns the symmetries under which the given queens map onto themselves.
+///
+/// # Examples
+/// ```
+/// # use qsolve::datastructure::CoordSet;
+/// # use qsolve::symmetry::{Symmetry, queen_symmetries};
+/// let queens = CoordSet::from_iter([(0, 1), (1, 3), (2, 0), (3, 2)]);
+/// assert_eq!(queen_symmetries(&queens, 4), vec![Symmetry::Rotate90, Symmetry::Rotate180]);
+/// ```
+pub fn queen_symmetries(queens: &CoordSet, size: usize) -> Vec<Symmetry> {
+    ALL_SYMMETRIES
+        .into_iter()
+        .filter(|symmetry| {
+            queens
+                .iter()
+                .all(|queen| queens.contains(&symmetry.apply(&queen, size)))
+        })
+        .collect()
+}
+
+/// Returns the symmetries under which the regions of the given board map
+/// onto themselves.
+///
+/// Only the shape of the regions matters, not their colors: a region may map
+/// onto a region of a different color, as long as every region does so.
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Board;
+/// # use qsolve::symmetry::{Symmetry, region_symmetries};
+/// # use std::str::FromStr;
+/// let board = Board::from_str("wwkk\nwwkk\nrrbb\nrrbb").unwrap();
+/// assert_eq!(region_symmetries(&board).len(), 6);
+///
+/// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb").unwrap();
+/// assert!(region_symmetries(&board).is_empty());
+/// ```
+pub fn region_symmetries(board: &Board) -> Vec<Symmetry> {
+    ALL_SYMMETRIES
+        .into_iter()
+        .filter(|symmetry| {
+            // The symmetry preserves the regions exactly when it induces a
+            // one-to-one mapping between colors.
+            let mapping = board
+                .all_coords()
+                .iter()
+                .map(|coord| {
+                    (
+                        board.color(&coord),
+                        board.color(&symmetry.apply(&coord, board.size())),
+                    )
+                })
+                .unique()
+                .collect::<Vec<(SquareColor, SquareColor)>>();
+            mapping.iter().map(|(from, _)| from).all_unique()
+                && mapping.iter().map(|(_, to)| to).all_unique()
+        })
+        .collect()
+}
+
+/// A report of the symmetries of a solved puzzle.
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Board;
+/// # use qsolve::solvestate::SolveState;
+/// # use qsolve::symmetry::SymmetryReport;
+/// # use std::str::FromStr;
+/// let board = Board::from_str("wwww\nwkkk\nrrrr\nbbbb").unwrap();
+/// let solved = SolveState::with_queens(&board, &[(0, 1), (1, 3), (2, 0), (3, 2)]);
+/// let report = SymmetryReport::new(&solved);
+/// assert_eq!(report.to_string(), "queens rotate-90, rotate-180; regions none");
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SymmetryReport {
+    /// The symmetries of the queens' placement.
+    pub queens: Vec<Symmetry>,
+
+    /// The symmetries of the board's regions.
+    pub regions: Vec<Symmetry>,
+}
+
+impl SymmetryReport {
+    /// Analyzes the queens placed in the given state, and the regions of its board.
+    pub fn new(solve_state: &SolveState) -> Self {
+        let board = solve_state.board;
+        let queens = board
+            .all_coords()
+            .iter()
+            .filter(|coord| solve_state.square(coord) == Some(SquareVal::Queen))
+            .collect::<CoordSet>();
+        SymmetryReport {
+            queens: queen_symmetries(&queens, board.size()),
+            regions: region_symmetries(board),
+        }
+    }
+}
+
+impl Display for SymmetryReport {
+    /// Displays the report on one line.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = |symmetries: &[Symmetry]| {
+            if symmetries.is_empty() {
+                "none".to_string()
+            } else {
+                symmetries.iter().map(Symmetry::name).join(", ")
+            }
+        };
+        write!(
+            f,
+            "queens {}; regions {}",
+            names(&self.queens),
+            names(&self.regions)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn symmetries_are_involutions_or_rotations() {
+        let size = 5;
+        let coords = (0..size).flat_map(|r| (0..size).map(move |c| (r, c)));
+        for coord in coords {
+            for symmetry in ALL_SYMMETRIES {
+                let once = symmetry.apply(&coord, size);
+                let twice = symmetry.apply(&once, size);
+                match symmetry {
+                    Symmetry::Rotate90 => {
+                        assert_eq!(twice, Symmetry::Rotate180.apply(&coord, size))
+                    }
+                    _ => assert_eq!(twice, coord),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn queen_symmetries_of_queens() {
+        let queens = CoordSet::from_iter([(0, 1), (1, 4), (2, 2), (3, 0), (4, 3)]);
+        assert_eq!(
+            queen_symmetries(&queens, 5),
+            vec![Symmetry::Rotate90, Symmetry::Rotate180]
+        );
+        let queens = CoordSet::from_iter([(0, 1), (1, 3), (2, 0), (3, 2), (4, 4)]);
+        assert_eq!(queen_symmetries(&queens, 5), vec![]);
+    }
+
+    #[test]
+    fn region_symmetries_ignore_colors() -> anyhow::Result<()> {
+        let board = Board::from_str("wkkk\nwwrk\nbwrr\nbbbr")?;
+        assert_eq!(region_symmetries(&board), vec![Symmetry::Rotate180]);
+        // Every region maps onto a region, but not one-to-one.
+        let board = Board::from_str("wwwk\nrrrk\nbbbk\nbbbk")?;
+        assert_eq!(region_symmetries(&board), vec![]);
+        Ok(())
+    }
+}
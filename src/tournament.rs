@@ -0,0 +1,224 @@
+use std::{
+    fmt::{Display, Formatter},
+    time::Duration,
+};
+
+use crate::estimate::format_minutes;
+
+/// The time added to a tournament score for each kind of help.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Penalties {
+    /// The time added for each hint or solver step asked for.
+    pub hint: Duration,
+
+    /// The time added for each move that disagreed with the solution.
+    pub mistake: Duration,
+}
+
+impl Default for Penalties {
+    fn default() -> Self {
+        Penalties {
+            hint: Duration::from_secs(30),
+            mistake: Duration::from_secs(10),
+        }
+    }
+}
+
+/// How one puzzle of a tournament went.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PuzzleScore {
+    /// The name of the puzzle.
+    pub name: String,
+
+    /// Whether the player solved the puzzle, rather than giving up on it.
+    pub solved: bool,
+
+    /// How long the player spent on the puzzle.
+    pub elapsed: Duration,
+
+    /// How many hints or solver steps the player asked for.
+    pub hints: usize,
+
+    /// How many moves disagreed with the solution.
+    pub mistakes: usize,
+}
+
+impl PuzzleScore {
+    /// Returns the time added to this puzzle for the help the player had.
+    pub fn penalty(&self, penalties: &Penalties) -> Duration {
+        penalties.hint * self.hints as u32 + penalties.mistake * self.mistakes as u32
+    }
+
+    /// Returns the time taken on this puzzle with its penalty added.
+    pub fn total(&self, penalties: &Penalties) -> Duration {
+        self.elapsed + self.penalty(penalties)
+    }
+}
+
+/// The scores for every puzzle of a tournament, which displays as a table
+/// with a row for each puzzle.
+///
+/// # Examples
+/// ```
+/// # use qsolve::tournament::{Penalties, PuzzleScore, Scoreboard};
+/// # use std::time::Duration;
+/// let scoreboard = Scoreboard {
+///     name: "Warm-up".to_string(),
+///     penalties: Penalties::default(),
+///     scores: vec![
+///         PuzzleScore {
+///             name: "One".to_string(),
+///             solved: true,
+///             elapsed: Duration::from_secs(75),
+///             ..PuzzleScore::default()
+///         },
+///         PuzzleScore {
+///             name: "Two".to_string(),
+///             solved: true,
+///             elapsed: Duration::from_secs(100),
+///             hints: 1,
+///             mistakes: 2,
+///         },
+///     ],
+/// };
+/// assert_eq!(scoreboard.total(), Duration::from_secs(225));
+/// assert_eq!(
+///     scoreboard.share_content(),
+///     "QSolve tournament Warm-up | 2 of 2 solved in 3:45\n👑✅"
+/// );
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Scoreboard {
+    /// The name of the tournament's pack.
+    pub name: String,
+
+    /// The penalties the scores were given.
+    pub penalties: Penalties,
+
+    /// The score for each puzzle, in the order they were played.
+    pub scores: Vec<PuzzleScore>,
+}
+
+impl Scoreboard {
+    /// Returns how many of the puzzles were solved.
+    pub fn solved(&self) -> usize {
+        self.scores.iter().filter(|score| score.solved).count()
+    }
+
+    /// Returns the total time over every puzzle, penalties included.
+    pub fn total(&self) -> Duration {
+        self.scores
+            .iter()
+            .map(|score| score.total(&self.penalties))
+            .sum()
+    }
+
+    /// Generates the share text for the tournament: a line with the overall
+    /// result, and a line with a crown for each puzzle solved without help,
+    /// a check mark for each solved with it, and a blank square for each
+    /// left unsolved.
+    pub fn share_content(&self) -> String {
+        let marks = self
+            .scores
+            .iter()
+            .map(
+                |score| match (score.solved, score.penalty(&self.penalties)) {
+                    (false, _) => '⬜',
+                    (true, Duration::ZERO) => '👑',
+                    (true, _) => '✅',
+                },
+            )
+            .collect::<String>();
+        format!(
+            "QSolve tournament {} | {} of {} solved in {}\n{marks}",
+            self.name,
+            self.solved(),
+            self.scores.len(),
+            format_minutes(self.total())
+        )
+    }
+}
+
+impl Display for Scoreboard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let width = self
+            .scores
+            .iter()
+            .map(|score| score.name.chars().count())
+            .chain(["Puzzle".len()])
+            .max()
+            .unwrap_or_default();
+        writeln!(
+            f,
+            "{:width$}  {:>6}  {:>5}  {:>8}  {:>7}  {:>6}",
+            "Puzzle", "Time", "Hints", "Mistakes", "Penalty", "Total"
+        )?;
+        for score in &self.scores {
+            let total = match score.solved {
+                true => format_minutes(score.total(&self.penalties)),
+                false => "DNF".to_string(),
+            };
+            writeln!(
+                f,
+                "{:width$}  {:>6}  {:>5}  {:>8}  {:>7}  {:>6}",
+                score.name,
+                format_minutes(score.elapsed),
+                score.hints,
+                score.mistakes,
+                format_minutes(score.penalty(&self.penalties)),
+                total
+            )?;
+        }
+        write!(
+            f,
+            "Solved {} of {} in {}",
+            self.solved(),
+            self.scores.len(),
+            format_minutes(self.total())
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scoreboard_adds_penalties_and_marks_unsolved() {
+        let scoreboard = Scoreboard {
+            name: "Pack".to_string(),
+            penalties: Penalties {
+                hint: Duration::from_secs(20),
+                mistake: Duration::from_secs(5),
+            },
+            scores: vec![
+                PuzzleScore {
+                    name: "A long name".to_string(),
+                    solved: true,
+                    elapsed: Duration::from_secs(61),
+                    hints: 2,
+                    mistakes: 1,
+                },
+                PuzzleScore {
+                    name: "B".to_string(),
+                    solved: false,
+                    elapsed: Duration::from_secs(30),
+                    ..PuzzleScore::default()
+                },
+            ],
+        };
+        assert_eq!(scoreboard.solved(), 1);
+        assert_eq!(scoreboard.total(), Duration::from_secs(136));
+        assert_eq!(
+            scoreboard.to_string(),
+            "Puzzle         Time  Hints  Mistakes  Penalty   Total\n\
+             A long name    1:01      2         1     0:45    1:46\n\
+             B              0:30      0         0     0:00     DNF\n\
+             Solved 1 of 2 in 2:16"
+        );
+        assert_eq!(
+            scoreboard.share_content(),
+            "QSolve tournament Pack | 1 of 2 solved in 2:16\n✅⬜"
+        );
+    }
+}
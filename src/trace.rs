@@ -0,0 +1,231 @@
+use std::{
+    fmt::Debug,
+    io::Write,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use anyhow::{Context, Result, anyhow};
+use log::trace;
+use serde_json::{Map, Value, json};
+use tracing::{
+    Event, Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+};
+use tracing_subscriber::{layer::Context as LayerContext, registry::LookupSpan};
+
+/// A [tracing_subscriber::Layer] for the solver's tracing spans, such as one
+/// heuristic checking one solve state, and events.
+///
+/// Each span is logged at the trace level once it closes, along with its
+/// fields and how long it was open. If the layer has a JSON writer, the span
+/// is also written to it as one line of JSON, with the span's name, its start
+/// time and duration in microseconds (counted from when the layer was made),
+/// and its fields. Events are treated as spans that take no time.
+///
+/// # Examples
+/// ```
+/// # use qsolve::trace::TraceLayer;
+/// # use tracing_subscriber::{Registry, layer::SubscriberExt};
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let layer = TraceLayer::with_json(std::io::sink());
+/// tracing::subscriber::with_default(Registry::default().with(layer.clone()), || {
+///     let span = tracing::trace_span!("count_squares", size = 8, squares = tracing::field::Empty);
+///     span.record("squares", 64);
+/// });
+/// layer.flush()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct TraceLayer {
+    json: Option<Arc<Mutex<Box<dyn Write + Send>>>>,
+    started: Instant,
+}
+
+/// What the layer keeps on each open span.
+struct SpanData {
+    fields: Map<String, Value>,
+    start: Instant,
+}
+
+/// Collects the fields of a span or event as JSON values.
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0
+            .insert(field.name().to_string(), json!(format!("{value:?}")));
+    }
+}
+
+impl TraceLayer {
+    /// Returns a layer that only logs spans.
+    pub fn new() -> Self {
+        TraceLayer {
+            json: None,
+            started: Instant::now(),
+        }
+    }
+
+    /// Returns a layer that logs spans and writes them to the given writer
+    /// as JSON lines.
+    ///
+    /// Call [TraceLayer::flush] once done, to flush the writer.
+    pub fn with_json(writer: impl Write + Send + 'static) -> Self {
+        TraceLayer {
+            json: Some(Arc::new(Mutex::new(Box::new(writer)))),
+            started: Instant::now(),
+        }
+    }
+
+    /// Flushes everything written to the JSON writer so far.
+    pub fn flush(&self) -> Result<()> {
+        let Some(json) = &self.json else {
+            return Ok(());
+        };
+        json.lock()
+            .map_err(|_| anyhow!("The trace writer was poisoned"))?
+            .flush()
+            .context("Could not write trace")
+    }
+
+    /// Logs a finished span and writes it as JSON, if there is a writer.
+    fn finish(&self, name: &str, target: &str, fields: Map<String, Value>, start: Instant) {
+        let duration = start.elapsed();
+        let fields = Value::Object(fields);
+        trace!(target: target, "{name} {fields} in {duration:?}");
+        let Some(json) = &self.json else {
+            return;
+        };
+        let Ok(mut writer) = json.lock() else {
+            return;
+        };
+        let line = json!({
+            "span": name,
+            "start_us": start.saturating_duration_since(self.started).as_micros() as u64,
+            "duration_us": duration.as_micros() as u64,
+            "fields": fields,
+        });
+        // A trace that can't be written shouldn't stop the solver.
+        let _ = writeln!(writer, "{line}");
+    }
+}
+
+impl Default for TraceLayer {
+    fn default() -> Self {
+        TraceLayer::new()
+    }
+}
+
+impl<S> tracing_subscriber::Layer<S> for TraceLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: LayerContext<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = Map::new();
+        attrs.record(&mut JsonVisitor(&mut fields));
+        span.extensions_mut().insert(SpanData {
+            fields,
+            start: Instant::now(),
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: LayerContext<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        if let Some(data) = span.extensions_mut().get_mut::<SpanData>() {
+            values.record(&mut JsonVisitor(&mut data.fields));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: LayerContext<'_, S>) {
+        let mut fields = Map::new();
+        event.record(&mut JsonVisitor(&mut fields));
+        let metadata = event.metadata();
+        self.finish(metadata.name(), metadata.target(), fields, Instant::now());
+    }
+
+    fn on_close(&self, id: Id, ctx: LayerContext<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(data) = span.extensions_mut().remove::<SpanData>() else {
+            return;
+        };
+        let metadata = span.metadata();
+        self.finish(metadata.name(), metadata.target(), data.fields, data.start);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing::field::Empty;
+    use tracing_subscriber::{Registry, layer::SubscriberExt};
+
+    use super::*;
+
+    /// A writer that can be read back once the spans are written.
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn spans_are_written_as_json_lines() -> Result<()> {
+        let shared = Shared::default();
+        let layer = TraceLayer::with_json(shared.clone());
+        tracing::subscriber::with_default(Registry::default().with(layer.clone()), || {
+            let span = tracing::trace_span!("outer", size = 4, outcome = Empty, ignored = Empty);
+            tracing::trace!(name: "point", offset = ?(1, 2));
+            span.record("outcome", "done");
+        });
+        layer.flush()?;
+        tracing::trace_span!("unrecorded").record("outcome", "none");
+
+        let lines = String::from_utf8(shared.0.lock().unwrap().clone())?
+            .lines()
+            .map(serde_json::from_str::<Value>)
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["span"], "point");
+        assert_eq!(lines[0]["fields"], json!({"offset": "(1, 2)"}));
+        assert_eq!(lines[1]["span"], "outer");
+        assert_eq!(lines[1]["fields"], json!({"size": 4, "outcome": "done"}));
+        assert!(lines[1]["duration_us"].is_u64());
+        Ok(())
+    }
+}
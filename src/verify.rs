@@ -0,0 +1,134 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{
+    board::Board,
+    datastructure::CoordSet,
+    search::{all_solutions, solutions},
+};
+
+/// Whether a board has no solution, exactly one, or more than one, as found
+/// by the exhaustive search in [search][crate::search].
+///
+/// The heuristic solver assumes a well-formed puzzle, so on a board without
+/// exactly one solution it just stalls; this says why.
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Board;
+/// # use qsolve::verify::Uniqueness;
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// assert!(Uniqueness::of(&Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?).is_unique());
+///
+/// let ambiguous = Uniqueness::of(&Board::from_str("wwww\nkkkk\nrrrr\nbbbb")?);
+/// assert!(matches!(ambiguous, Uniqueness::Multiple(_, _)));
+/// assert_eq!(ambiguous.to_string(), "more than one solution");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Uniqueness {
+    /// No placement of queens follows the rules.
+    NoSolution,
+
+    /// The board has exactly this solution.
+    Unique(CoordSet),
+
+    /// The board has at least these two solutions.
+    Multiple(CoordSet, CoordSet),
+}
+
+impl Uniqueness {
+    /// Searches the board for up to two solutions.
+    pub fn of(board: &Board) -> Uniqueness {
+        match &all_solutions(board, 2)[..] {
+            [] => Uniqueness::NoSolution,
+            [solution] => Uniqueness::Unique(*solution),
+            [first, second, ..] => Uniqueness::Multiple(*first, *second),
+        }
+    }
+
+    /// Returns whether the board has exactly one solution.
+    pub fn is_unique(&self) -> bool {
+        matches!(self, Uniqueness::Unique(_))
+    }
+
+    /// Returns the queens of either of two solutions that aren't in the
+    /// other, which are the squares a puzzle maker would need to rule out to
+    /// tell the solutions apart; empty unless there are multiple solutions.
+    pub fn differences(&self) -> CoordSet {
+        match self {
+            Uniqueness::Multiple(first, second) => CoordSet::from_iter(
+                first
+                    .union(second)
+                    .iter()
+                    .filter(|coord| !first.contains(coord) || !second.contains(coord)),
+            ),
+            _ => CoordSet::default(),
+        }
+    }
+}
+
+impl Display for Uniqueness {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Uniqueness::NoSolution => write!(f, "no solution"),
+            Uniqueness::Unique(_) => write!(f, "exactly one solution"),
+            Uniqueness::Multiple(_, _) => write!(f, "more than one solution"),
+        }
+    }
+}
+
+/// Counts the solutions to the board, stopping once `limit` have been found
+/// if one is given.
+///
+/// Without a limit, this searches the whole board, which can take a long time
+/// on boards with few distinct regions; see [Solutions][crate::search::Solutions].
+///
+/// # Examples
+/// ```
+/// # use qsolve::board::Board;
+/// # use qsolve::verify::count_solutions;
+/// # use std::str::FromStr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let board = Board::from_str("wwww\nkkkk\nrrrr\nbbbb")?;
+/// assert_eq!(count_solutions(&board, None), 2);
+/// assert_eq!(count_solutions(&board, Some(1)), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub fn count_solutions(board: &Board, limit: Option<usize>) -> usize {
+    solutions(board).take(limit.unwrap_or(usize::MAX)).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use anyhow::Result;
+
+    use super::*;
+
+    #[test]
+    fn uniqueness_finds_how_many_solutions() -> Result<()> {
+        // White and black both need their queen in the top row.
+        let impossible = Board::from_str("wwkk\nrrrr\nrrrr\nbbbb")?;
+        assert_eq!(Uniqueness::of(&impossible), Uniqueness::NoSolution);
+        assert_eq!(count_solutions(&impossible, None), 0);
+
+        let unique = Board::from_str("wwww\nwkkk\nrrrr\nbbbb")?;
+        let Uniqueness::Unique(solution) = Uniqueness::of(&unique) else {
+            panic!("Expected a unique solution");
+        };
+        assert_eq!(solution.len(), 4);
+        assert!(Uniqueness::of(&unique).differences().is_empty());
+
+        // The two solutions are mirror images, so share no queens.
+        let ambiguous = Board::from_str("wwww\nkkkk\nrrrr\nbbbb")?;
+        assert_eq!(Uniqueness::of(&ambiguous).differences().len(), 8);
+        assert_eq!(count_solutions(&ambiguous, None), 2);
+        Ok(())
+    }
+}
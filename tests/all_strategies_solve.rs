@@ -16,6 +16,7 @@ fn all_strategies_solve() -> Result<()> {
         SolveStrategy::Fast,
         SolveStrategy::Short,
         SolveStrategy::Simple,
+        SolveStrategy::Human,
     ] {
         let solve_state = SolveState::from(&queens_file);
         let heuristics = all_heuristics(solve_state.board);
@@ -1,9 +1,21 @@
 use assert_cmd::prelude::*;
 use std::process::Command;
 
+/// Returns a command running qsolve with its cache directory in a temporary
+/// directory, so that tests never read or write the real cache, or the
+/// practice history kept beside it.
+fn qsolve() -> Result<Command, Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("qsolve")?;
+    cmd.env(
+        "QSOLVE_CACHE_DIR",
+        std::env::temp_dir().join(format!("qsolve-test-cache-{}", std::process::id())),
+    );
+    Ok(cmd)
+}
+
 #[test]
 fn print_succeeds_on_text() -> Result<(), Box<dyn std::error::Error>> {
-    let mut cmd = Command::cargo_bin("qsolve")?;
+    let mut cmd = qsolve()?;
 
     cmd.arg("print").arg("games/linkedin-1-empty.txt");
     cmd.assert().success();
@@ -11,9 +23,23 @@ fn print_succeeds_on_text() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn print_succeeds_with_palettes() -> Result<(), Box<dyn std::error::Error>> {
+    for palette in ["classic", "pastel", "high-contrast"] {
+        let mut cmd = qsolve()?;
+        cmd.arg("print")
+            .arg("games/linkedin-1-empty.txt")
+            .arg("--palette")
+            .arg(palette);
+        cmd.assert().success();
+    }
+
+    Ok(())
+}
+
 #[test]
 fn print_succeeds_when_clearing() -> Result<(), Box<dyn std::error::Error>> {
-    let mut cmd = Command::cargo_bin("qsolve")?;
+    let mut cmd = qsolve()?;
 
     cmd.arg("print")
         .arg("games/linkedin-1-partial.txt")
@@ -23,9 +49,35 @@ fn print_succeeds_when_clearing() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn print_succeeds_with_progress() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("print")
+        .arg("games/linkedin-1.png")
+        .arg("--progress")
+        .arg("games/linkedin-1-partial.txt");
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn print_fails_with_mismatched_progress() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("print")
+        .arg("games/linkedin-1-empty.txt")
+        .arg("--progress")
+        .arg("games/gameofcrowns-1.txt");
+    cmd.assert().failure();
+
+    Ok(())
+}
+
 #[test]
 fn print_succeeds_on_image() -> Result<(), Box<dyn std::error::Error>> {
-    let mut cmd = Command::cargo_bin("qsolve")?;
+    let mut cmd = qsolve()?;
 
     cmd.arg("print").arg("games/linkedin-1.png");
     cmd.assert().success();
@@ -35,7 +87,7 @@ fn print_succeeds_on_image() -> Result<(), Box<dyn std::error::Error>> {
 
 #[test]
 fn print_fails_on_bad_file() -> Result<(), Box<dyn std::error::Error>> {
-    let mut cmd = Command::cargo_bin("qsolve")?;
+    let mut cmd = qsolve()?;
 
     cmd.arg("print").arg("games/bad-file-does-not-exist.txt");
     cmd.assert().failure();
@@ -45,7 +97,7 @@ fn print_fails_on_bad_file() -> Result<(), Box<dyn std::error::Error>> {
 
 #[test]
 fn print_accepts_image_file_type() -> Result<(), Box<dyn std::error::Error>> {
-    let mut cmd = Command::cargo_bin("qsolve")?;
+    let mut cmd = qsolve()?;
 
     cmd.arg("print")
         .arg("games/linkedin-1.png")
@@ -55,9 +107,55 @@ fn print_accepts_image_file_type() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn solve_reports_rotated_screenshots() -> Result<(), Box<dyn std::error::Error>> {
+    let rotated = std::env::temp_dir().join(format!("qsolve-rotated-{}.png", std::process::id()));
+    image::imageops::rotate90(&image::open("games/linkedin-1.png")?.to_rgb8()).save(&rotated)?;
+
+    let mut cmd = qsolve()?;
+    cmd.arg("solve").arg(&rotated).arg("--no-cache");
+    cmd.assert().success().stdout(predicates::str::contains(
+        "Found the grid after rotating the image 270\u{b0}",
+    ));
+
+    let mut cmd = qsolve()?;
+    cmd.arg("solve")
+        .arg(&rotated)
+        .args(["--no-cache", "--format", "json"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let json: serde_json::Value = serde_json::from_slice(&output)?;
+    assert_eq!(json["image_rotation_degrees"], 270);
+
+    std::fs::remove_file(&rotated)?;
+    Ok(())
+}
+
+#[test]
+fn solve_reads_image_from_stdin() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+
+    cmd.arg("solve")
+        .arg("--file-type=image")
+        .arg("-")
+        .write_stdin(std::fs::read("games/linkedin-1.png")?);
+    cmd.assert().success();
+
+    // Text isn't an image, whatever the file type says.
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+    cmd.arg("solve")
+        .arg("--file-type=image")
+        .arg("-")
+        .write_stdin(std::fs::read("games/linkedin-1-empty.txt")?);
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("format from its first bytes"));
+
+    Ok(())
+}
+
 #[test]
 fn print_accepts_text_file_type() -> Result<(), Box<dyn std::error::Error>> {
-    let mut cmd = Command::cargo_bin("qsolve")?;
+    let mut cmd = qsolve()?;
 
     cmd.arg("print")
         .arg("games/linkedin-1-empty.txt")
@@ -69,7 +167,7 @@ fn print_accepts_text_file_type() -> Result<(), Box<dyn std::error::Error>> {
 
 #[test]
 fn print_fails_on_bad_file_type() -> Result<(), Box<dyn std::error::Error>> {
-    let mut cmd = Command::cargo_bin("qsolve")?;
+    let mut cmd = qsolve()?;
 
     cmd.arg("print")
         .arg("games/linkedin-1-empty.txt")
@@ -81,19 +179,31 @@ fn print_fails_on_bad_file_type() -> Result<(), Box<dyn std::error::Error>> {
 
 #[test]
 fn animate_succeeds_on_text() -> Result<(), Box<dyn std::error::Error>> {
-    let mut cmd = Command::cargo_bin("qsolve")?;
+    let mut cmd = qsolve()?;
 
     cmd.arg("animate")
         .arg("games/linkedin-1-empty.txt")
         .arg("--delay=1");
     cmd.assert().success();
 
+    let mut cmd = qsolve()?;
+    cmd.args([
+        "animate",
+        "games/linkedin-1-empty.txt",
+        "--delay=1",
+        "--from-hints=6",
+        "--width=5",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("Done!"));
+
     Ok(())
 }
 
 #[test]
 fn solve_succeeds_on_text() -> Result<(), Box<dyn std::error::Error>> {
-    let mut cmd = Command::cargo_bin("qsolve")?;
+    let mut cmd = qsolve()?;
 
     cmd.arg("solve").arg("games/linkedin-1-empty.txt");
     cmd.assert().success();
@@ -103,7 +213,7 @@ fn solve_succeeds_on_text() -> Result<(), Box<dyn std::error::Error>> {
 
 #[test]
 fn solve_succeeds_with_share() -> Result<(), Box<dyn std::error::Error>> {
-    let mut cmd = Command::cargo_bin("qsolve")?;
+    let mut cmd = qsolve()?;
 
     cmd.arg("solve")
         .arg("games/linkedin-1-empty.txt")
@@ -113,9 +223,31 @@ fn solve_succeeds_with_share() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn solve_summary_uses_time_model() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("solve")
+        .arg("games/linkedin-1-empty.txt")
+        .arg("--no-cache")
+        .arg("--summary")
+        .arg("--time-model=tests/fixtures/time_model.toml");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("Estimated human time: 15:00"));
+
+    let mut cmd = qsolve()?;
+    cmd.arg("solve")
+        .arg("games/linkedin-1-empty.txt")
+        .arg("--time-model=tests/fixtures/missing.toml");
+    cmd.assert().failure();
+
+    Ok(())
+}
+
 #[test]
 fn solve_succeeds_with_share_text() -> Result<(), Box<dyn std::error::Error>> {
-    let mut cmd = Command::cargo_bin("qsolve")?;
+    let mut cmd = qsolve()?;
 
     cmd.arg("solve")
         .arg("games/linkedin-1-empty.txt")
@@ -125,9 +257,180 @@ fn solve_succeeds_with_share_text() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn solve_succeeds_from_share() -> Result<(), Box<dyn std::error::Error>> {
+    let grid = std::fs::read_to_string("games/linkedin-1-empty.txt")?
+        .chars()
+        .map(|c| match c {
+            'C' => '\u{1F7E7}',
+            'B' => '\u{1F7E6}',
+            'W' => '\u{2B1C}',
+            'R' => '\u{1F7E5}',
+            'Y' => '\u{1F7E8}',
+            'w' => '\u{1F7EB}',
+            'M' => '\u{1F7EA}',
+            'G' => '\u{1F7E9}',
+            c => c,
+        })
+        .collect::<String>();
+    let mut cmd = qsolve()?;
+
+    cmd.arg("solve")
+        .arg("--from-share")
+        .arg(format!("Queens #1 | 0:42\n{grid}\nlnkd.in/queens"));
+    cmd.assert().success();
+
+    let mut cmd = qsolve()?;
+    cmd.args(["solve", "--from-share", "Queens #1 | 0:42"]);
+    cmd.assert().failure();
+
+    Ok(())
+}
+
+#[test]
+fn solve_succeeds_with_share_options() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("solve")
+        .arg("games/linkedin-1-empty.txt")
+        .arg("--share")
+        .arg("--share-queens=5")
+        .arg("--share-style=letters");
+    cmd.assert().success();
+
+    let mut cmd = qsolve()?;
+    cmd.args([
+        "solve",
+        "games/linkedin-1-empty.txt",
+        "--share",
+        "--share-path=4",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::is_match(r"Path: \S{4} \+\d+\n")?);
+
+    Ok(())
+}
+
+#[test]
+fn compare_succeeds_on_text() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("compare")
+        .arg("games/linkedin-1-partial.txt")
+        .arg("games/linkedin-1-empty.txt");
+    cmd.assert().success();
+
+    Ok(())
+}
+
+#[test]
+fn compare_accepts_marking_mode() -> Result<(), Box<dyn std::error::Error>> {
+    for marking_mode in ["assisted", "strict"] {
+        let mut cmd = qsolve()?;
+
+        cmd.arg("compare")
+            .arg("games/linkedin-1-partial.txt")
+            .arg("games/linkedin-1-empty.txt")
+            .arg("--marking-mode")
+            .arg(marking_mode);
+        cmd.assert().success();
+    }
+
+    Ok(())
+}
+
+#[test]
+fn compare_fails_on_different_boards() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("compare")
+        .arg("games/linkedin-1-partial.txt")
+        .arg("games/gameofcrowns-1.txt");
+    cmd.assert().failure().code(4);
+
+    Ok(())
+}
+
+#[test]
+fn verify_share_roundtrips() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("solve")
+        .arg("games/linkedin-1-empty.txt")
+        .arg("--share")
+        .arg("--share-payload");
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let stdout = String::from_utf8(output)?;
+    let payload = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("Verify: "))
+        .ok_or("No payload found")?;
+
+    let mut verify_cmd = qsolve()?;
+    verify_cmd
+        .arg("verify-share")
+        .arg("games/linkedin-1-empty.txt")
+        .arg(payload);
+    verify_cmd.assert().success();
+
+    let mut wrong_board_cmd = qsolve()?;
+    wrong_board_cmd
+        .arg("verify-share")
+        .arg("games/gameofcrowns-1.txt")
+        .arg(payload);
+    wrong_board_cmd.assert().failure();
+
+    Ok(())
+}
+
+#[test]
+fn check_proof_accepts_proofs_from_prove() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("prove").arg("games/linkedin-1-empty.txt");
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let proof = std::env::temp_dir().join(format!("qsolve-proof-{}.json", std::process::id()));
+    std::fs::write(&proof, &output)?;
+
+    let mut check_cmd = qsolve()?;
+    check_cmd
+        .arg("check-proof")
+        .arg("games/linkedin-1-empty.txt")
+        .arg(&proof);
+    check_cmd
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Checked:"));
+
+    let mut wrong_board_cmd = qsolve()?;
+    wrong_board_cmd
+        .arg("check-proof")
+        .arg("games/gameofcrowns-1.txt")
+        .arg(&proof);
+    wrong_board_cmd.assert().failure().code(4);
+
+    let mut tampered: serde_json::Value = serde_json::from_slice(&output)?;
+    tampered["steps"][0]["premises"] = serde_json::json!(["row=1"]);
+    std::fs::write(&proof, tampered.to_string())?;
+    let mut tampered_cmd = qsolve()?;
+    tampered_cmd
+        .arg("check-proof")
+        .arg("games/linkedin-1-empty.txt")
+        .arg(&proof);
+    tampered_cmd
+        .assert()
+        .failure()
+        .code(7)
+        .stderr(predicates::str::contains("Step 1"));
+
+    std::fs::remove_file(&proof)?;
+    Ok(())
+}
+
 #[test]
 fn profile_succeeds_on_text() -> Result<(), Box<dyn std::error::Error>> {
-    let mut cmd = Command::cargo_bin("qsolve")?;
+    let mut cmd = qsolve()?;
 
     cmd.arg("profile").arg("games/linkedin-1-empty.txt");
     cmd.assert().success();
@@ -135,12 +438,1647 @@ fn profile_succeeds_on_text() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn profile_prints_metrics() -> Result<(), Box<dyn std::error::Error>> {
+    use predicates::prelude::PredicateBooleanExt;
+
+    let mut cmd = qsolve()?;
+
+    cmd.arg("profile")
+        .arg("games/linkedin-1-empty.txt")
+        .arg("--iterations")
+        .arg("2")
+        .arg("--metrics");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("2 iterations completed"))
+        .stdout(predicates::str::is_match(
+            r"Heuristic evaluations: [1-9]\d*",
+        )?)
+        .stdout(predicates::str::contains("Applying steps: "));
+
+    let mut cmd = qsolve()?;
+    cmd.arg("profile").arg("games/linkedin-1-empty.txt");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("Heuristic evaluations").not());
+
+    Ok(())
+}
+
 #[test]
 fn hint_succeeds_on_text() -> Result<(), Box<dyn std::error::Error>> {
-    let mut cmd = Command::cargo_bin("qsolve")?;
+    let mut cmd = qsolve()?;
 
     cmd.arg("hint").arg("games/linkedin-1-empty.txt");
     cmd.assert().success();
 
     Ok(())
 }
+
+#[test]
+fn hint_draws_boards_side_by_side() -> Result<(), Box<dyn std::error::Error>> {
+    let hint = |args: &[&str]| -> Result<String, Box<dyn std::error::Error>> {
+        let mut cmd = qsolve()?;
+        cmd.args(["hint", "games/linkedin-1-empty.txt", "--charset", "ascii"])
+            .args(args);
+        Ok(String::from_utf8(
+            cmd.assert().success().get_output().stdout.clone(),
+        )?)
+    };
+
+    let side_by_side = hint(&["--hint-type", "both"])?;
+    assert!(regex::Regex::new(r"(?m)^(.* -> .*\n){8}There is")?.is_match(&side_by_side));
+    assert!(!hint(&[])?.contains("->"));
+
+    // Too narrow for both boards, so they are drawn as one combined frame.
+    let narrow = hint(&["--hint-type", "both", "--width", "12"])?;
+    assert_eq!(narrow, hint(&["--width", "12"])?);
+    assert!(!narrow.contains("->"));
+
+    Ok(())
+}
+
+#[test]
+fn hint_focus_restricts_region() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+    cmd.arg("hint")
+        .arg("games/linkedin-1-empty.txt")
+        .arg("--focus")
+        .arg("row=3");
+    cmd.assert().success();
+
+    let mut cmd = qsolve()?;
+    cmd.arg("hint")
+        .arg("games/linkedin-1-empty.txt")
+        .arg("--focus")
+        .arg("row=0");
+    cmd.assert().failure();
+
+    for focus in ["row=9", "col=17", "color=k"] {
+        let mut cmd = qsolve()?;
+        cmd.arg("hint")
+            .arg("games/linkedin-1-empty.txt")
+            .arg("--focus")
+            .arg(focus);
+        cmd.assert()
+            .code(3)
+            .stderr(predicates::str::contains("is not on the board"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn hint_lists_alternatives() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+    cmd.arg("hint")
+        .arg("games/linkedin-1-empty.txt")
+        .arg("--alternatives")
+        .arg("3");
+    cmd.assert().success().stdout(predicates::str::is_match(
+        r"^(\d\. .+ \(decides \d+ squares?\)\n){3}$",
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn solve_with_lookahead() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+    cmd.arg("solve")
+        .arg("games/linkedin-1-empty.txt")
+        .arg("--strategy=short")
+        .arg("--lookahead=1")
+        .arg("-q");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::is_match(r"^solved in \d+ steps\n$")?);
+
+    let mut cmd = qsolve()?;
+    cmd.arg("solve")
+        .arg("games/linkedin-1-empty.txt")
+        .arg("--strategy=short")
+        .arg("--lookahead=1")
+        .arg("--share")
+        .arg("--share-payload");
+    cmd.assert().failure();
+
+    Ok(())
+}
+
+#[test]
+fn solve_checked_succeeds() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+    cmd.arg("solve")
+        .arg("games/linkedin-1-empty.txt")
+        .arg("--checked")
+        .arg("-q");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::is_match(r"^solved in \d+ steps\n$")?);
+
+    Ok(())
+}
+
+#[test]
+fn solve_explains_conflicting_marks() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+    cmd.arg("solve").arg("tests/fixtures/conflict.txt");
+    cmd.assert().code(6).stderr(predicates::str::contains(
+        "Revisit these marks: Queen at row 7 column 2",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn seed_is_accepted_anywhere() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+    cmd.arg("--seed")
+        .arg("7")
+        .arg("hint")
+        .arg("games/linkedin-1-empty.txt");
+    let before = cmd.assert().success().get_output().stdout.clone();
+
+    let mut cmd = qsolve()?;
+    cmd.arg("hint")
+        .arg("games/linkedin-1-empty.txt")
+        .arg("--seed=7");
+    cmd.assert().success().stdout(before);
+
+    Ok(())
+}
+
+#[test]
+fn seed_changes_human_solves_in_every_command() -> Result<(), Box<dyn std::error::Error>> {
+    for command in [&["report"][..], &["animate", "--delay", "0"]] {
+        let output = |seed: &str| -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+            let mut cmd = qsolve()?;
+            cmd.args(command)
+                .arg("games/linkedin-1-empty.txt")
+                .arg("--strategy")
+                .arg("human")
+                .arg("--seed")
+                .arg(seed);
+            Ok(cmd.assert().success().get_output().stdout.clone())
+        };
+        assert_eq!(output("1")?, output("1")?);
+        assert_ne!(output("0")?, output("1")?, "{command:?}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn miss_percent_changes_human_solves() -> Result<(), Box<dyn std::error::Error>> {
+    let summary =
+        |strategy: &str, miss_percent: &str| -> Result<String, Box<dyn std::error::Error>> {
+            let mut cmd = qsolve()?;
+            cmd.arg("solve")
+                .arg("games/linkedin-1-empty.txt")
+                .arg("--summary")
+                .arg("--no-cache")
+                .arg("--strategy")
+                .arg(strategy)
+                .arg("--miss-percent")
+                .arg(miss_percent);
+            let output = cmd.assert().success().get_output().stdout.clone();
+            Ok(String::from_utf8(output)?
+                .lines()
+                .filter(|line| line.starts_with("Estimated human time"))
+                .collect())
+        };
+    // The estimate always comes from a human solve, whichever strategy found
+    // the solution shown.
+    assert_eq!(summary("fast", "60")?, summary("human", "60")?);
+    assert_ne!(summary("human", "0")?, summary("human", "60")?);
+
+    let mut cmd = qsolve()?;
+    cmd.arg("solve")
+        .arg("games/linkedin-1-empty.txt")
+        .arg("--miss-percent")
+        .arg("101");
+    cmd.assert().failure().code(2);
+
+    Ok(())
+}
+
+#[test]
+fn solve_exits_with_parse_error_code() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("solve").arg("tests/fixtures/unparseable.txt");
+    cmd.assert().failure().code(3);
+
+    Ok(())
+}
+
+#[test]
+fn solve_reports_both_file_type_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("solve").arg("tests/fixtures/unparseable.txt");
+    cmd.assert()
+        .failure()
+        .code(3)
+        .stderr(predicates::str::contains("as text, or as an image"))
+        .stderr(predicates::str::contains(
+            "Failed to create board from text file",
+        ));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "net"))]
+#[test]
+fn print_from_url_needs_net_feature() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("print").arg("https://example.com/board.png");
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("the `net` feature"));
+
+    Ok(())
+}
+
+/// Serves each of the given responses, as a status line and body, to one
+/// HTTP request in turn on a local port, and returns the port.
+#[cfg(feature = "net")]
+fn serve(responses: Vec<(&'static str, Vec<u8>)>) -> Result<u16, Box<dyn std::error::Error>> {
+    use std::io::{BufRead, BufReader, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+    std::thread::spawn(move || {
+        for (status, body) in responses {
+            let Ok((stream, _)) = listener.accept() else {
+                return;
+            };
+            let mut reader = BufReader::new(stream);
+            let mut line = String::new();
+            while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+                line.clear();
+            }
+            let mut stream = reader.into_inner();
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream.write_all(&body);
+        }
+    });
+    Ok(port)
+}
+
+#[cfg(feature = "net")]
+#[test]
+fn print_downloads_board_from_url() -> Result<(), Box<dyn std::error::Error>> {
+    let board = std::fs::read("games/linkedin-1-empty.txt")?;
+    let port = serve(vec![("200 OK", board), ("404 Not Found", vec![])])?;
+
+    let mut cmd = qsolve()?;
+    cmd.arg("print")
+        .arg(format!("http://127.0.0.1:{port}/board.txt"));
+    cmd.assert().success();
+
+    let mut cmd = qsolve()?;
+    cmd.arg("print")
+        .arg(format!("http://127.0.0.1:{port}/missing.txt"));
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("Could not download"))
+        .stderr(predicates::str::contains("404"));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "preview"))]
+#[test]
+fn solve_preview_needs_preview_feature() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("solve")
+        .arg("games/linkedin-1.png")
+        .arg("--preview");
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("the `preview` feature"));
+
+    Ok(())
+}
+
+#[cfg(feature = "preview")]
+#[test]
+fn solve_previews_screenshots() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("solve")
+        .arg("games/linkedin-1.png")
+        .arg("--preview");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains('\u{2580}'));
+
+    let mut cmd = qsolve()?;
+    cmd.arg("solve")
+        .arg("games/linkedin-1.png")
+        .arg("--preview=kitty")
+        .arg("--format=json");
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("only works with text output"));
+
+    Ok(())
+}
+
+#[test]
+fn strict_rejects_split_regions_unless_repaired() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+    cmd.arg("solve").arg("tests/fixtures/split-region.txt");
+    cmd.assert().success();
+
+    let mut cmd = qsolve()?;
+    cmd.arg("solve")
+        .arg("tests/fixtures/split-region.txt")
+        .arg("--strict");
+    cmd.assert().code(4).stderr(predicates::str::contains(
+        "the likely repair is r3c4 from 'Red' to 'BrightRed'",
+    ));
+
+    let mut cmd = qsolve()?;
+    cmd.arg("solve")
+        .arg("tests/fixtures/split-region.txt")
+        .arg("--strict")
+        .arg("--auto-repair")
+        .arg("--quiet");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("solved in"));
+
+    Ok(())
+}
+
+#[test]
+fn solve_exits_with_invalid_board_code() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("solve").arg("tests/fixtures/invalid-colors.txt");
+    cmd.assert().failure().code(4);
+
+    Ok(())
+}
+
+#[test]
+fn print_exits_with_image_detection_code() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::temp_dir().join(format!("qsolve-blank-{}.png", std::process::id()));
+    image::RgbImage::from_pixel(64, 64, image::Rgb([255, 255, 255])).save(&path)?;
+    let mut cmd = qsolve()?;
+
+    cmd.arg("print").arg(&path);
+    let assert = cmd.assert();
+    std::fs::remove_file(&path)?;
+    assert.failure().code(5);
+
+    Ok(())
+}
+
+#[test]
+fn solve_exits_with_unsolvable_code() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("solve").arg("tests/fixtures/unsolvable-2.txt");
+    cmd.assert().failure().code(6);
+
+    Ok(())
+}
+
+#[test]
+fn solve_quiet_prints_only_step_count() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("solve").arg("games/linkedin-1-empty.txt").arg("-q");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::is_match(r"^solved in \d+ steps\n$")?);
+
+    Ok(())
+}
+
+#[test]
+fn solve_follows_rules() -> Result<(), Box<dyn std::error::Error>> {
+    // LinkedIn's puzzles rely on Queens not touching diagonally.
+    let mut cmd = qsolve()?;
+    cmd.args([
+        "solve",
+        "games/linkedin-1-empty.txt",
+        "--rules",
+        "none",
+        "--no-cache",
+        "-q",
+    ]);
+    cmd.assert()
+        .failure()
+        .code(6)
+        .stdout(predicates::str::contains("unsolved after"));
+
+    let mut cmd = qsolve()?;
+    cmd.args([
+        "solve",
+        "games/linkedin-1-empty.txt",
+        "--rules",
+        "adjacency-only",
+        "-q",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("solved in"));
+    Ok(())
+}
+
+#[test]
+fn solve_uses_sandwich_clues() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+    cmd.args(["solve", "tests/fixtures/sandwich.txt", "--no-cache", "-q"]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("solved in"));
+    Ok(())
+}
+
+#[test]
+fn solve_reports_already_solved() -> Result<(), Box<dyn std::error::Error>> {
+    use predicates::prelude::PredicateBooleanExt;
+
+    let mut cmd = qsolve()?;
+    cmd.args(["solve", "tests/fixtures/linkedin-1-solved.txt", "-q"]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::diff("already solved\n"));
+
+    let mut cmd = qsolve()?;
+    cmd.args(["solve", "tests/fixtures/linkedin-1-solved.txt", "--share"]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("The puzzle is already solved."))
+        .stdout(predicates::str::contains("First").not());
+
+    let mut cmd = qsolve()?;
+    cmd.args(["hint", "tests/fixtures/linkedin-1-solved.txt"]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::diff("The puzzle is already solved.\n"));
+    Ok(())
+}
+
+#[test]
+fn solve_quiet_reports_unsolved() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("solve")
+        .arg("tests/fixtures/unsolvable-2.txt")
+        .arg("--quiet");
+    cmd.assert()
+        .failure()
+        .code(6)
+        .stdout(predicates::str::starts_with("unsolved after"));
+
+    Ok(())
+}
+
+#[test]
+fn solve_summary_includes_histogram() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("solve")
+        .arg("games/linkedin-1-empty.txt")
+        .arg("--summary");
+    cmd.assert().success().stdout(predicates::str::is_match(
+        r"Solved in \d+ steps \(.*\)\n(  +\d+ \w+\n)+Starting entropy: \d+\.\d bits, branching factor \d+ \(.*\)\nSymmetry: queens .*; regions .*\nEstimated human time: \d+:\d\d\n$",
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn solve_heuristic_table_counts_squares() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("solve")
+        .arg("games/linkedin-1-empty.txt")
+        .arg("--no-cache")
+        .arg("--heuristic-table");
+    cmd.assert().success().stdout(predicates::str::is_match(
+        r"\nHeuristic +Uses +Squares\n(\w+ +\d+ +\d+\n)+Total +\d+ +64\n$",
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn solve_quiet_conflicts_with_summary() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("solve")
+        .arg("games/linkedin-1-empty.txt")
+        .arg("-q")
+        .arg("--summary");
+    cmd.assert().failure().code(2);
+
+    Ok(())
+}
+
+#[test]
+fn repl_runs_commands() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+
+    cmd.arg("repl")
+        .arg("games/linkedin-1-empty.txt")
+        .write_stdin("show\nhint\napply r1c1 Q\nundo\nbogus\nsolve\nquit\n");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("Error: Unknown command `bogus`"))
+        .stdout(predicates::str::contains("Solved in"));
+
+    Ok(())
+}
+
+#[test]
+fn repl_shares_played_time() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+
+    cmd.arg("repl")
+        .arg("--pause-on-hint")
+        .arg("games/linkedin-1-empty.txt")
+        .write_stdin("share\nhint\nsolve\ntime\nshare\n");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "Error: The puzzle isn't solved yet",
+        ))
+        .stdout(predicates::str::contains("Solved in 0:00"))
+        .stdout(predicates::str::contains("QSolve linkedin-1-empty | "));
+
+    Ok(())
+}
+
+#[test]
+fn repl_strict_marking_leaves_xs_to_the_player() -> Result<(), Box<dyn std::error::Error>> {
+    use predicates::prelude::PredicateBooleanExt;
+
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+
+    cmd.arg("repl")
+        .arg("--marking-mode")
+        .arg("strict")
+        .arg("games/linkedin-1-empty.txt")
+        .write_stdin("apply r1c4 Q\napply r1c5 x\nsolve\nquit\n");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("already marked").not())
+        .stdout(predicates::str::contains("Solved in"));
+
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+
+    cmd.arg("repl")
+        .arg("games/linkedin-1-empty.txt")
+        .write_stdin("apply r1c4 Q\napply r1c5 x\nquit\n");
+    cmd.assert().success().stdout(predicates::str::contains(
+        "Error: Row 1 column 5 is already marked",
+    ));
+
+    // Squares off the board are refused, rather than wrapping onto others.
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+
+    cmd.arg("repl")
+        .arg("--hard-mode")
+        .arg("games/linkedin-1-empty.txt")
+        .write_stdin(
+            "apply r99c1 x
+apply r1c20 x
+quit
+",
+        );
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "Error: Row 99 column 1 is not on the board",
+        ))
+        .stdout(predicates::str::contains(
+            "Error: Row 1 column 20 is not on the board",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn repl_points_out_mistakes_unless_hard_mode() -> Result<(), Box<dyn std::error::Error>> {
+    use predicates::prelude::PredicateBooleanExt;
+
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+
+    cmd.arg("repl")
+        .arg("games/linkedin-1-empty.txt")
+        .write_stdin("apply r99c1 Q\napply r1c1 Q\nundo\napply r1c4 Q\nquit\n");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "Error: Row 99 column 1 is not on the board",
+        ))
+        .stdout(predicates::str::contains(
+            "Mistake: r1c1 disagrees with the solution.",
+        ))
+        .stdout(predicates::str::contains("r1c4").not());
+
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+
+    cmd.arg("repl")
+        .arg("--hard-mode")
+        .arg("games/linkedin-1-empty.txt")
+        .write_stdin("apply r1c1 Q\nquit\n");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("Mistake").not());
+
+    Ok(())
+}
+
+#[test]
+fn tournament_scores_a_pack() -> Result<(), Box<dyn std::error::Error>> {
+    use predicates::prelude::PredicateBooleanExt;
+
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+
+    cmd.args(["tournament", "games/sample.qpk", "--hint-penalty", "60"])
+        .write_stdin("apply r1c1 Q\nundo\nsolve\n\nsolve\nquit\n");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("Puzzle 1 of 3: LinkedIn #1"))
+        .stdout(predicates::str::contains("Puzzle 2 of 3: QueensGame #1"))
+        .stdout(predicates::str::is_match(
+            r"LinkedIn #1 +0:00 +1 +1 +1:10 +1:10",
+        )?)
+        .stdout(predicates::str::contains("Puzzle 3 of 3").not())
+        .stdout(predicates::str::is_match(r"Game of Crowns #1 .* DNF")?)
+        .stdout(predicates::str::contains(
+            "QSolve tournament Sample | 2 of 3 solved in 2:",
+        ))
+        .stdout(predicates::str::contains("✅✅⬜"));
+
+    Ok(())
+}
+
+#[test]
+fn practice_records_history() -> Result<(), Box<dyn std::error::Error>> {
+    let cache_dir =
+        std::env::temp_dir().join(format!("qsolve-cli-practice-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&cache_dir);
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+
+    cmd.env("QSOLVE_CACHE_DIR", &cache_dir)
+        .args([
+            "practice",
+            "--size",
+            "6",
+            "--difficulty",
+            "easy",
+            "--seed",
+            "3",
+        ])
+        .write_stdin("load games/linkedin-1-empty.txt\nsolve\n\nquit\n");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("Practice puzzle 6x6 easy #3"))
+        .stdout(predicates::str::contains("Error: `load` isn't available"))
+        .stdout(predicates::str::contains(
+            "6x6 easy: Solved 1 of 2 (0 without hints)",
+        ));
+    let history = std::fs::read_to_string(cache_dir.join("practice-history.jsonl"))?;
+    assert_eq!(history.lines().count(), 2);
+
+    std::fs::remove_dir_all(&cache_dir)?;
+    Ok(())
+}
+
+#[test]
+fn practice_without_seed_starts_from_the_clock() -> Result<(), Box<dyn std::error::Error>> {
+    use predicates::prelude::PredicateBooleanExt;
+    let cache_dir = std::env::temp_dir().join(format!(
+        "qsolve-cli-practice-unseeded-{}",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&cache_dir);
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+    cmd.env("QSOLVE_CACHE_DIR", &cache_dir)
+        .args(["practice", "--size", "6", "--difficulty", "easy"])
+        .write_stdin("quit\n");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("Practice puzzle 6x6 easy #"))
+        .stdout(predicates::str::contains("Practice puzzle 6x6 easy #0\n").not());
+
+    let _ = std::fs::remove_dir_all(&cache_dir);
+    Ok(())
+}
+
+#[test]
+fn debug_steps_and_explains_heuristics() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+    cmd.args(["debug", "games/linkedin-1-empty.txt"])
+        .write_stdin("eval\nwhy LastSquareAvailable\ncandidates\nstep\nwhy Nope\nquit\n");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("LastSquareAvailable"))
+        .stdout(predicates::str::contains("Not applicable:"))
+        .stdout(predicates::str::contains("blank squares"))
+        .stdout(predicates::str::contains("No heuristic is called `Nope`"));
+    Ok(())
+}
+
+#[test]
+fn duel_alternates_and_checks_moves() -> Result<(), Box<dyn std::error::Error>> {
+    let duel = std::env::temp_dir().join(format!("qsolve-duel-{}.json", std::process::id()));
+    let mut cmd = qsolve()?;
+    cmd.args([
+        "duel",
+        "new",
+        "games/linkedin-1-empty.txt",
+        "--players",
+        "ann",
+        "bo",
+    ])
+    .arg("--output")
+    .arg(&duel);
+    cmd.assert().success();
+
+    let mut cmd = qsolve()?;
+    cmd.args(["duel", "move"])
+        .arg(&duel)
+        .args(["bo", "r1c4", "Q"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("It's ann's turn"));
+
+    let mut cmd = qsolve()?;
+    cmd.args(["duel", "move"])
+        .arg(&duel)
+        .args(["ann", "r1c1", "Q"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("disagrees with the solution"));
+
+    // Squares off the board are refused, rather than wrapping onto others.
+    for square in ["r99c1", "r1c20"] {
+        let mut cmd = qsolve()?;
+        cmd.args(["duel", "move"])
+            .arg(&duel)
+            .args(["ann", square, "x"]);
+        cmd.assert()
+            .failure()
+            .stderr(predicates::str::contains(format!(
+                "{square} is not on the board"
+            )));
+    }
+
+    let mut cmd = qsolve()?;
+    cmd.args(["duel", "move"])
+        .arg(&duel)
+        .args(["ann", "r1c4", "Q"]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("ann resolved 23 squares."))
+        .stdout(predicates::str::contains("bo to move."));
+
+    let mut cmd = qsolve()?;
+    cmd.args(["duel", "status"]).arg(&duel);
+    cmd.assert().success().stdout(predicates::str::contains(
+        "ann: 23 squares\nbo: 0 squares\nbo to move.",
+    ));
+
+    std::fs::remove_file(&duel)?;
+    Ok(())
+}
+
+#[test]
+fn generate_writes_solvable_boards() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+    cmd.args([
+        "generate",
+        "--size",
+        "6",
+        "--difficulty",
+        "easy",
+        "--seed",
+        "3",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::is_match(r"^([A-Za-z]{6}\n){6}$")?);
+
+    let board = std::env::temp_dir().join(format!("qsolve-generate-{}.txt", std::process::id()));
+    let mut cmd = qsolve()?;
+    cmd.args([
+        "generate",
+        "--size",
+        "6",
+        "--difficulty",
+        "easy",
+        "--seed",
+        "3",
+    ])
+    .arg("--output")
+    .arg(&board);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("--seed 3"));
+
+    let mut cmd = qsolve()?;
+    cmd.arg("solve").arg(&board).args(["--no-cache", "-q"]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("solved in"));
+
+    std::fs::remove_file(&board)?;
+    Ok(())
+}
+
+#[test]
+fn generate_without_seed_varies() -> Result<(), Box<dyn std::error::Error>> {
+    // Two boards from different seeds can match by chance, but not five.
+    let mut boards = Vec::new();
+    for _ in 0..5 {
+        let mut cmd = qsolve()?;
+        cmd.args(["generate", "--size", "6", "--difficulty", "easy"]);
+        boards.push(cmd.assert().success().get_output().stdout.clone());
+    }
+    assert!(boards.iter().any(|board| *board != boards[0]));
+    Ok(())
+}
+
+#[test]
+fn history_exports_and_merges() -> Result<(), Box<dyn std::error::Error>> {
+    let cache_dir = std::env::temp_dir().join(format!("qsolve-cli-history-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&cache_dir);
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+    cmd.env("QSOLVE_CACHE_DIR", &cache_dir)
+        .args([
+            "practice",
+            "--size",
+            "6",
+            "--difficulty",
+            "easy",
+            "--seed",
+            "3",
+        ])
+        .write_stdin("solve\n\nquit\n");
+    cmd.assert().success();
+
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+    cmd.env("QSOLVE_CACHE_DIR", &cache_dir)
+        .args(["history", "export", "--anonymized"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let exported = String::from_utf8(output)?;
+    assert!(exported.contains("\"tier_steps\""));
+    assert!(!exported.contains("\"seed\""));
+    assert!(!exported.contains("\"state\""));
+
+    let stats = cache_dir.join("stats.json");
+    std::fs::write(&stats, &exported)?;
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+    cmd.args(["history", "merge"]).arg(&stats).arg(&stats);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let merged: serde_json::Value = serde_json::from_slice(&output)?;
+    assert_eq!(merged["results"].as_array().map(Vec::len), Some(4));
+
+    std::fs::remove_dir_all(&cache_dir)?;
+    Ok(())
+}
+
+#[test]
+fn report_writes_montages() -> Result<(), Box<dyn std::error::Error>> {
+    let svg = std::env::temp_dir().join(format!("qsolve-montage-{}.svg", std::process::id()));
+    let mut cmd = qsolve()?;
+    cmd.args(["report", "games/linkedin-1-empty.txt", "--montage"])
+        .arg(&svg);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "games/linkedin-1-empty.txt: solved in 9 steps; starting entropy",
+        ))
+        .stdout(predicates::str::contains("Wrote 9 boards to"));
+    assert!(std::fs::read_to_string(&svg)?.contains(">Start<"));
+    std::fs::remove_file(&svg)?;
+
+    let png = svg.with_extension("png");
+    let mut cmd = qsolve()?;
+    cmd.args([
+        "report",
+        "games/linkedin-1-empty.txt",
+        "games/queensgame-1.txt",
+    ])
+    .arg("--montage")
+    .arg(&png);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("Wrote 2 boards to"));
+    assert!(std::fs::read(&png)?.starts_with(b"\x89PNG"));
+    std::fs::remove_file(&png)?;
+
+    let mut cmd = qsolve()?;
+    cmd.args([
+        "report",
+        "games/linkedin-1-empty.txt",
+        "--montage",
+        "montage.txt",
+    ]);
+    cmd.assert().failure().stderr(predicates::str::contains(
+        "Montages can only be written to .svg or .png files",
+    ));
+    Ok(())
+}
+
+#[test]
+fn why_filled_explains_squares() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+    cmd.args(["why-filled", "games/linkedin-1-empty.txt", "r5c5"]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("put an x in row 5 column 5"))
+        .stdout(predicates::str::contains(
+            "It relied on squares marked in steps",
+        ));
+
+    let mut cmd = qsolve()?;
+    cmd.args(["why-filled", "tests/fixtures/conflict.txt", "r1c1"]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("has an x from the start"));
+
+    let mut cmd = qsolve()?;
+    cmd.args(["why-filled", "games/linkedin-1-empty.txt", "r5c0"]);
+    cmd.assert().failure();
+
+    Ok(())
+}
+
+#[test]
+fn coords_style_applies_to_input_and_output() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+    cmd.args([
+        "why-filled",
+        "games/linkedin-1-empty.txt",
+        "c4r4",
+        "--coords",
+        "c0r0",
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("put an x in column 4 row 4"));
+
+    let json = |coords: Option<&str>| -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let mut cmd = qsolve()?;
+        cmd.args([
+            "solve",
+            "games/linkedin-1-empty.txt",
+            "--format",
+            "json",
+            "--no-cache",
+        ]);
+        if let Some(coords) = coords {
+            cmd.args(["--coords", coords]);
+        }
+        let output = cmd.assert().success().get_output().stdout.clone();
+        Ok(serde_json::from_slice(&output)?)
+    };
+    let (r, c) = {
+        let default = json(None)?;
+        let queen = &default["steps"][0]["queen"];
+        (queen[0].as_u64().unwrap(), queen[1].as_u64().unwrap())
+    };
+    assert_eq!(
+        json(Some("c1r1"))?["steps"][0]["queen"],
+        serde_json::json!([c + 1, r + 1])
+    );
+
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+    cmd.args(["repl", "games/linkedin-1-empty.txt", "--coords", "c1r1"])
+        .write_stdin("apply c1r1 Q\nquit\n");
+    cmd.assert().success().stdout(predicates::str::contains(
+        "Mistake: c1r1 disagrees with the solution.",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn heuristics_lists_the_catalog() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+    cmd.arg("heuristics");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("LastSquareAvailable (tier 0)"));
+
+    let mut cmd = qsolve()?;
+    cmd.args(["heuristics", "--format", "json"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let catalog = serde_json::from_slice::<serde_json::Value>(&output)?;
+    assert_eq!(catalog.as_array().map(Vec::len), Some(7));
+    assert_eq!(catalog[4]["anchor"], "n-colors-only-appear-in-n-lines");
+    assert_eq!(catalog[4]["parameters"][1]["name"], "n");
+
+    Ok(())
+}
+
+#[test]
+fn bench_server_solves_each_request() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+
+    cmd.arg("bench-server").write_stdin(
+        r#"{"id": "a", "board": ["wwww", "wkkk", "rrrr", "bbbb"]}
+4
+wwww
+wkkk
+rrrr
+bbbb
+
+{"id": 3, "board": "wwkk\nwwkk"}
+not a request
+"#,
+    );
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let responses = String::from_utf8(output)?
+        .lines()
+        .map(serde_json::from_str::<serde_json::Value>)
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(responses.len(), 4);
+    assert_eq!(responses[0]["id"], "a");
+    assert_eq!(responses[0]["solved"], true);
+    assert_eq!(responses[1]["id"], serde_json::Value::Null);
+    assert_eq!(responses[1]["step_count"], responses[0]["step_count"]);
+    assert_eq!(responses[2]["id"], 3);
+    assert!(responses[2]["error"].is_string());
+    assert!(responses[3]["error"].is_string());
+
+    Ok(())
+}
+
+#[test]
+fn bench_server_survives_oversized_boards() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+
+    let oversized = vec!["w".repeat(20); 20];
+    cmd.arg("bench-server").write_stdin(format!(
+        "{}\n{}\n",
+        serde_json::json!({"id": "big", "board": oversized}),
+        serde_json::json!({"id": "ok", "board": ["wwww", "wkkk", "rrrr", "bbbb"]}),
+    ));
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let responses = String::from_utf8(output)?
+        .lines()
+        .map(serde_json::from_str::<serde_json::Value>)
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0]["id"], "big");
+    assert!(responses[0]["error"].is_string());
+    assert_eq!(responses[1]["id"], "ok");
+    assert_eq!(responses[1]["solved"], true);
+
+    Ok(())
+}
+
+#[test]
+fn repl_requires_a_loaded_puzzle() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+
+    cmd.arg("repl").write_stdin("show\n");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("No puzzle loaded"));
+
+    Ok(())
+}
+
+#[test]
+fn solve_uses_cache() -> Result<(), Box<dyn std::error::Error>> {
+    let cache_dir = std::env::temp_dir().join(format!("qsolve-cli-cache-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&cache_dir);
+    let solve = |extra_args: &[&str]| -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut cmd = qsolve()?;
+        cmd.env("QSOLVE_CACHE_DIR", &cache_dir)
+            .arg("solve")
+            .arg("games/linkedin-1-empty.txt")
+            .args(extra_args);
+        Ok(cmd.assert().success().get_output().stdout.clone())
+    };
+
+    let uncached = solve(&["--no-cache"])?;
+    assert!(!cache_dir.exists());
+    assert_eq!(solve(&[])?, uncached);
+    assert!(cache_dir.read_dir()?.count() == 1);
+    assert_eq!(solve(&[])?, uncached);
+    assert_eq!(solve(&["-q"])?, solve(&["-q", "--no-cache"])?);
+
+    // Entries from another version of qsolve are ignored.
+    let entry_path = cache_dir.read_dir()?.next().unwrap()?.path();
+    let mut entry: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&entry_path)?)?;
+    entry["version"] = "0.0.1".into();
+    for steps in entry["steps"].as_object_mut().unwrap().values_mut() {
+        *steps = 999.into();
+    }
+    std::fs::write(&entry_path, entry.to_string())?;
+    assert_eq!(solve(&["-q"])?, solve(&["-q", "--no-cache"])?);
+
+    // The human strategy's steps depend on the seed, so it isn't shared
+    // between seeds.
+    let human = |seed: &str| solve(&["-q", "--strategy", "human", "--seed", seed]);
+    assert_eq!(human("1")?, human("1")?);
+    assert_eq!(
+        human("2")?,
+        solve(&["-q", "--strategy", "human", "--seed", "2", "--no-cache"])?
+    );
+
+    std::fs::remove_dir_all(&cache_dir)?;
+    Ok(())
+}
+
+#[test]
+fn heatmap_succeeds_on_text() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("heatmap").arg("games/linkedin-1-empty.txt");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("squares were never considered"));
+
+    Ok(())
+}
+
+#[test]
+fn heatmap_succeeds_as_svg() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("heatmap")
+        .arg("games/linkedin-1-empty.txt")
+        .arg("--heatmap-format")
+        .arg("svg");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::starts_with("<svg"));
+
+    Ok(())
+}
+
+#[test]
+fn discover_reports_stalled_boards() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+    cmd.arg("discover").arg("games");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::is_match(r"^Solved \d+ of \d+ boards\n$")?);
+
+    let mut cmd = qsolve()?;
+    cmd.arg("discover").arg("tests/fixtures");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("tests/fixtures/unsolvable-2.txt"))
+        .stdout(predicates::str::contains("Suggestion: "));
+
+    Ok(())
+}
+
+#[test]
+fn corpus_sync_keeps_licensed_boards() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = std::env::temp_dir().join(format!("qsolve-corpus-cli-{}", std::process::id()));
+    let mut cmd = qsolve()?;
+
+    cmd.args([
+        "corpus",
+        "sync",
+        "tests/fixtures/corpus/index.toml",
+        "--dir",
+    ])
+    .arg(&dir);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("Skipped unsolvable"))
+        .stdout(predicates::str::contains("Skipped unlicensed"))
+        .stdout(predicates::str::contains("Synced 1 of 3 boards"));
+    let manifest = std::fs::read_to_string(dir.join("manifest.toml"))?;
+    assert!(manifest.contains(r#"license = "CC0-1.0""#));
+    assert!(dir.join("sandwich.txt").exists());
+    std::fs::remove_dir_all(&dir)?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "net"))]
+#[test]
+fn corpus_sync_from_url_needs_net_feature() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.args(["corpus", "sync", "https://example.com/index.toml"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("the `net` feature"));
+
+    Ok(())
+}
+
+#[test]
+fn print_draws_candidate_overlay() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.arg("print")
+        .arg("games/linkedin-1-partial.txt")
+        .arg("--overlay=candidates");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("!"));
+
+    Ok(())
+}
+
+#[test]
+fn print_splits_boards_wider_than_width() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.args(["print", "games/linkedin-1-empty.txt", "--width=5"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let stdout = regex::Regex::new("\x1b\\[[0-9;]*m")?
+        .replace_all(std::str::from_utf8(&output)?, "")
+        .to_string();
+    // The 8 columns are split into bands of 5 and 3, with a blank line between.
+    assert_eq!(stdout.lines().count(), 17);
+    assert!(stdout.lines().all(|line| line.chars().count() <= 5));
+    assert_eq!(stdout.lines().nth(8), Some(""));
+
+    Ok(())
+}
+
+#[test]
+fn link_opens_the_same_board() -> Result<(), Box<dyn std::error::Error>> {
+    let print = |board: &str| -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut cmd = qsolve()?;
+        cmd.args(["print", board]);
+        Ok(cmd.assert().success().get_output().stdout.clone())
+    };
+    let expected = print("games/linkedin-1-partial.txt")?;
+
+    let mut cmd = qsolve()?;
+    cmd.args(["link", "games/linkedin-1-partial.txt"]);
+    let link = String::from_utf8(cmd.assert().success().get_output().stdout.clone())?;
+    assert!(link.starts_with("qsolve://"));
+    assert_eq!(print(link.trim())?, expected);
+
+    let mut cmd = qsolve()?;
+    cmd.args(["link", "--plain", "games/linkedin-1-partial.txt"]);
+    let fragment = String::from_utf8(cmd.assert().success().get_output().stdout.clone())?;
+    assert_eq!(print(&format!("#{}", fragment.trim()))?, expected);
+
+    // A link keeps its rules unless --rules is given again.
+    let mut cmd = qsolve()?;
+    cmd.args([
+        "link",
+        "--rules",
+        "full-diagonals",
+        "games/linkedin-1-empty.txt",
+    ]);
+    let link = String::from_utf8(cmd.assert().success().get_output().stdout.clone())?;
+    let mut cmd = qsolve()?;
+    cmd.args(["link", link.trim()]);
+    cmd.assert().success().stdout(link.clone());
+    let mut cmd = qsolve()?;
+    cmd.args(["link", "--rules", "adjacency-only", link.trim()]);
+    let relinked = String::from_utf8(cmd.assert().success().get_output().stdout.clone())?;
+    assert_ne!(relinked, link);
+
+    let mut cmd = qsolve()?;
+    cmd.args(["print", "qsolve://AAAA"]);
+    cmd.assert().failure().code(3);
+
+    Ok(())
+}
+
+#[test]
+fn link_draws_qr_codes() -> Result<(), Box<dyn std::error::Error>> {
+    let png = std::env::temp_dir().join(format!("qsolve-qr-{}.png", std::process::id()));
+    let mut cmd = qsolve()?;
+    cmd.args(["link", "games/linkedin-1-empty.txt", "--qr", "--qr-png"])
+        .arg(&png);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let stdout = std::str::from_utf8(&output)?;
+    // The link, then a version 5 code of 37 modules with its quiet zone,
+    // at two modules to a line.
+    assert!(stdout.starts_with("qsolve://"));
+    assert_eq!(stdout.lines().count(), 1 + 23);
+    assert!(stdout.contains('\u{2580}'));
+
+    let image = image::open(&png)?;
+    assert_eq!((image.width(), image.height()), (45 * 8, 45 * 8));
+    std::fs::remove_file(&png)?;
+
+    Ok(())
+}
+
+#[test]
+fn hint_spoiler_safe_waits_to_reveal() -> Result<(), Box<dyn std::error::Error>> {
+    use predicates::prelude::PredicateBooleanExt;
+
+    let hint = "There is only one possiblity left for 'BrightWhite' Color.";
+
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+    cmd.args(["hint", "games/linkedin-1-partial.txt", "--spoiler-safe"])
+        .write_stdin("");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("Hint (rot13): Gurer vf bayl bar"))
+        .stdout(predicates::str::contains(hint).not())
+        .stdout(predicates::str::contains("\u{265b}").not());
+
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+    cmd.args([
+        "hint",
+        "games/linkedin-1-partial.txt",
+        "--spoiler-safe=details",
+    ])
+    .write_stdin("y\n");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "<details><summary>Hint</summary>",
+        ))
+        .stdout(predicates::str::contains("\u{265b}"));
+
+    let mut cmd = assert_cmd::Command::from_std(qsolve()?);
+    cmd.args([
+        "solve",
+        "games/linkedin-1-empty.txt",
+        "--no-cache",
+        "--spoiler-safe",
+    ])
+    .write_stdin("n\n");
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("Queens (rot13): e6p9"))
+        .stdout(predicates::str::contains("\u{265b}").not());
+
+    Ok(())
+}
+
+#[test]
+fn trace_json_records_spans() -> Result<(), Box<dyn std::error::Error>> {
+    let trace = std::env::temp_dir().join(format!("qsolve-trace-{}.jsonl", std::process::id()));
+    let mut cmd = qsolve()?;
+    cmd.arg("--trace-json")
+        .arg(&trace)
+        .args(["solve", "games/linkedin-1-empty.txt", "--no-cache"]);
+    cmd.assert().success();
+    let written = std::fs::read_to_string(&trace)?;
+    assert!(written.lines().count() > 1);
+    assert!(written.contains(r#""span":"heuristic""#));
+    assert!(written.contains(r#""kind":"LastSquareAvailable""#));
+    assert!(written.contains(r#""span":"apply_changes""#));
+    std::fs::remove_file(&trace)?;
+    Ok(())
+}
+
+#[test]
+fn overlay_draws_onto_screenshots() -> Result<(), Box<dyn std::error::Error>> {
+    let output = std::env::temp_dir().join(format!("qsolve-overlay-{}.png", std::process::id()));
+    let mut cmd = qsolve()?;
+    cmd.args(["overlay", "games/linkedin-1.png", "-o"])
+        .arg(&output);
+    cmd.assert().success();
+    let overlaid = image::open(&output)?;
+    let original = image::open("games/linkedin-1.png")?;
+    assert_eq!(overlaid.width(), original.width());
+    assert_ne!(overlaid.to_rgb8(), original.to_rgb8());
+    std::fs::remove_file(&output)?;
+
+    let mut cmd = qsolve()?;
+    cmd.args(["overlay", "games/linkedin-1-empty.txt", "-o"])
+        .arg(&output);
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("not a text board"));
+    Ok(())
+}
+
+#[test]
+fn fix_colors_merges_and_splits() -> Result<(), Box<dyn std::error::Error>> {
+    let board = std::env::temp_dir().join(format!("qsolve-fix-colors-{}.txt", std::process::id()));
+    std::fs::write(&board, "wwww\nwkkk\nrrRr\nbbbb\n\n....\nQ...\n....\n....\n")?;
+
+    let mut cmd = qsolve()?;
+    cmd.arg("fix-colors").arg(&board).args(["--merge", "R=r"]);
+    cmd.assert()
+        .success()
+        .stdout("wwww\nwkkk\nrrrr\nbbbb\n\n....\nQ...\n....\n....\n");
+
+    // Splitting off r1c3 cuts r1c4 off from the rest of the white region.
+    let mut cmd = qsolve()?;
+    cmd.arg("fix-colors")
+        .arg(&board)
+        .args(["--merge", "R=r", "--split", "r1c3=k"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("'White' Color is split"));
+
+    let mut cmd = qsolve()?;
+    cmd.arg("fix-colors")
+        .arg(&board)
+        .args(["--split", "c3r3=r", "--in-place"]);
+    cmd.assert().success();
+    assert!(std::fs::read_to_string(&board)?.starts_with("wwww\nwkkk\nrrrr\nbbbb\n"));
+
+    std::fs::remove_file(&board)?;
+    Ok(())
+}
+
+#[test]
+fn edit_needs_a_terminal() -> Result<(), Box<dyn std::error::Error>> {
+    let board = std::env::temp_dir().join(format!("qsolve-edit-{}.txt", std::process::id()));
+
+    let mut cmd = qsolve()?;
+    cmd.arg("edit").arg(&board).args(["--size", "17"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("between 1 and 16 squares wide"));
+
+    // Tests don't run in a terminal, so the board can't be drawn.
+    let mut cmd = qsolve()?;
+    cmd.arg("edit").arg(&board);
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("needs an interactive terminal"));
+    assert!(!board.exists());
+
+    let mut cmd = qsolve()?;
+    cmd.args(["edit", "games/linkedin-1-empty.txt", "--size", "9"]);
+    cmd.assert().failure().stderr(predicates::str::contains(
+        "already has a board 8 squares wide",
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn grade_scores_puzzles() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+    cmd.args(["grade", "games/linkedin-1-empty.txt"]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::is_match(r"^Score: [1-9]\d*\n")?)
+        .stdout(predicates::str::contains(
+            "Hardest step: AllPossibilitiesEliminateSquare (tier 1)",
+        ));
+
+    let mut cmd = qsolve()?;
+    cmd.args(["grade", "games/linkedin-1-partial.txt", "--format", "json"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let json: serde_json::Value = serde_json::from_slice(&output)?;
+    assert_eq!(json["hardest_tier"], 0);
+    assert_eq!(json["solved"], true);
+    assert_eq!(json["qsolve_version"], env!("CARGO_PKG_VERSION"));
+
+    let board = std::env::temp_dir().join(format!("qsolve-grade-{}.txt", std::process::id()));
+    std::fs::write(&board, "wwww\nkkkk\nrrrr\nbbbb\n")?;
+    let mut cmd = qsolve()?;
+    cmd.arg("grade").arg(&board);
+    cmd.assert()
+        .code(6)
+        .stderr(predicates::str::contains("can't be graded"));
+    std::fs::remove_file(&board)?;
+
+    Ok(())
+}
+
+#[test]
+fn verify_reports_how_many_solutions() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+    cmd.args(["verify", "games/linkedin-1-empty.txt", "--count"]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("The puzzle has 1 solution\n"))
+        .stdout(predicates::str::contains("exactly one solution"));
+
+    let board = std::env::temp_dir().join(format!("qsolve-verify-{}.txt", std::process::id()));
+    std::fs::write(&board, "wwww\nkkkk\nrrrr\nbbbb\n")?;
+    let mut cmd = qsolve()?;
+    cmd.arg("verify").arg(&board).arg("--count");
+    cmd.assert()
+        .code(4)
+        .stdout(predicates::str::contains("The puzzle has 2 solutions"))
+        .stderr(predicates::str::contains("differ at r1c2 r1c3"));
+
+    std::fs::write(&board, "wwkk\nrrrr\nrrrr\nbbbb\n")?;
+    let mut cmd = qsolve()?;
+    cmd.arg("verify").arg(&board);
+    cmd.assert()
+        .code(4)
+        .stderr(predicates::str::contains("no solution"));
+
+    std::fs::remove_file(&board)?;
+    Ok(())
+}
+
+#[test]
+fn solve_uses_symmetry_with_advanced() -> Result<(), Box<dyn std::error::Error>> {
+    // The regions look the same after a half turn.
+    let board = std::env::temp_dir().join(format!("qsolve-symmetric-{}.txt", std::process::id()));
+    std::fs::write(&board, "rrgkk\nrggky\nrgggy\nrbggy\nbbgyy\n")?;
+
+    let mut cmd = qsolve()?;
+    cmd.arg("solve").arg(&board).args(["--no-cache", "-q"]);
+    cmd.assert().code(6);
+
+    let mut cmd = qsolve()?;
+    cmd.arg("solve")
+        .arg(&board)
+        .args(["--advanced", "--heuristic-table"]);
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("SymmetryFixesQueen"));
+
+    let mut cmd = qsolve()?;
+    cmd.arg("solve")
+        .arg(&board)
+        .args(["--advanced", "--share", "--share-payload"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("cannot be used with --advanced"));
+
+    std::fs::remove_file(&board)?;
+    Ok(())
+}
+
+#[test]
+fn self_check_update_reads_releases() -> Result<(), Box<dyn std::error::Error>> {
+    let releases =
+        std::env::temp_dir().join(format!("qsolve-releases-{}.json", std::process::id()));
+    std::fs::write(
+        &releases,
+        r#"[
+            {"tag_name": "v99.0.0-beta.1", "prerelease": true, "html_url": "https://example.com/beta"},
+            {"tag_name": "v1.0.0", "prerelease": false, "html_url": "https://example.com/1.0.0"}
+        ]"#,
+    )?;
+
+    let mut cmd = qsolve()?;
+    cmd.args(["self", "check-update", "--releases"])
+        .arg(&releases);
+    cmd.assert().success().stdout(predicates::str::contains(
+        "is up to date (the latest stable release is 1.0.0)",
+    ));
+
+    let mut cmd = qsolve()?;
+    cmd.args(["self", "check-update", "--channel", "prerelease"])
+        .args(["--format", "json", "--releases"])
+        .arg(&releases);
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let json: serde_json::Value = serde_json::from_slice(&output)?;
+    assert_eq!(json["qsolve_version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(json["latest_url"], "https://example.com/beta");
+    assert_eq!(json["update_available"], true);
+
+    std::fs::write(&releases, "not json")?;
+    let mut cmd = qsolve()?;
+    cmd.args(["self", "check-update", "--releases"])
+        .arg(&releases);
+    cmd.assert().code(3);
+
+    std::fs::remove_file(&releases)?;
+    Ok(())
+}
+
+#[cfg(feature = "net")]
+#[test]
+fn self_check_update_downloads_releases() -> Result<(), Box<dyn std::error::Error>> {
+    let releases = br#"[{"tag_name": "v99.0.0", "html_url": "https://example.com/99.0.0"}]"#;
+    let port = serve(vec![("200 OK", releases.to_vec())])?;
+
+    let mut cmd = qsolve()?;
+    cmd.args(["self", "check-update", "--releases"])
+        .arg(format!("http://127.0.0.1:{port}/releases"));
+    cmd.assert()
+        .success()
+        .stdout(predicates::str::contains("qsolve 99.0.0 is available"));
+
+    Ok(())
+}
+
+#[cfg(not(feature = "net"))]
+#[test]
+fn self_check_update_needs_net_feature() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = qsolve()?;
+
+    cmd.args(["self", "check-update"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicates::str::contains("the `net` feature"));
+
+    Ok(())
+}
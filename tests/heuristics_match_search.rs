@@ -0,0 +1,157 @@
+//! Checks the heuristic solver against the brute-force search in
+//! [qsolve::search], which knows nothing about heuristics.
+//!
+//! Every game in `games/` and a batch of randomly generated boards with a
+//! unique solution are solved both ways. The heuristics are allowed to get
+//! stuck, but every Queen they place must be in the solution the search
+//! finds, and no X may cover one of its squares. Any board where they
+//! disagree is listed in the failure, with the board and where it diverged.
+//!
+//! Set `QSOLVE_GENERATED_BOARDS` to check more generated boards than the
+//! default, which is kept small so that `cargo test` stays fast:
+//!
+//! ```text
+//! QSOLVE_GENERATED_BOARDS=5000 cargo test --release --test heuristics_match_search -- --nocapture
+//! ```
+
+use std::{ffi::OsStr, fs};
+
+use anyhow::Result;
+
+use qsolve::{
+    board::Board,
+    datastructure::CoordSet,
+    file::QueensFile,
+    generate::random_unique_board,
+    heuristic::all_heuristics,
+    rng::SeededRng,
+    search::all_solutions,
+    solution::Solution,
+    solvestate::{SolveState, SolveStrategy, SquareVal},
+};
+
+/// How many generated boards to check when `QSOLVE_GENERATED_BOARDS` isn't set.
+const DEFAULT_GENERATED_BOARDS: usize = 100;
+
+/// The sizes of the generated boards, which are used in turn.
+const GENERATED_SIZES: [usize; 5] = [5, 6, 7, 8, 9];
+
+/// What happened when the heuristics solved one board.
+enum Outcome {
+    /// The heuristics found the solution the search found.
+    Solved,
+    /// The heuristics got stuck without making a mistake.
+    Stuck,
+    /// The heuristics disagreed with the search, as described.
+    Diverged(String),
+}
+
+/// Solves the board with every strategy and compares the result with the
+/// solution from the search.
+fn check(board: &Board) -> Outcome {
+    let solutions = all_solutions(board, 2);
+    let [solution] = solutions.as_slice() else {
+        return Outcome::Diverged(format!(
+            "search found {} solutions, not one",
+            solutions.len()
+        ));
+    };
+    let heuristics = all_heuristics(board);
+    let mut stuck = false;
+    for strategy in [
+        SolveStrategy::Fast,
+        SolveStrategy::Short,
+        SolveStrategy::Simple,
+    ] {
+        let final_state =
+            Solution::solve(SolveState::from(board), strategy, &heuristics).final_state;
+        let wrong_queens = final_state
+            .queens()
+            .iter()
+            .filter(|coord| !solution.contains(coord))
+            .collect::<CoordSet>();
+        let wrong_xs = solution
+            .iter()
+            .filter(|coord| final_state.square(coord) == Some(SquareVal::X))
+            .collect::<CoordSet>();
+        if !wrong_queens.is_empty() || !wrong_xs.is_empty() {
+            return Outcome::Diverged(format!(
+                "with {strategy}, Queens at {:?} and Xs at {:?} disagree with the solution {:?}\n{final_state}",
+                wrong_queens.iter().collect::<Vec<_>>(),
+                wrong_xs.iter().collect::<Vec<_>>(),
+                solution.iter().collect::<Vec<_>>(),
+            ));
+        }
+        stuck |= !final_state.complete();
+    }
+    if stuck {
+        Outcome::Stuck
+    } else {
+        Outcome::Solved
+    }
+}
+
+/// Checks every board, printing a summary and returning a report of the
+/// boards that diverged.
+fn report<'a>(label: &str, boards: impl IntoIterator<Item = (String, &'a Board)>) -> Vec<String> {
+    let (mut solved, mut stuck) = (0, 0);
+    let mut diverged = vec![];
+    for (name, board) in boards {
+        match check(board) {
+            Outcome::Solved => solved += 1,
+            Outcome::Stuck => stuck += 1,
+            Outcome::Diverged(why) => diverged.push(format!("{name}: {why}\n{board}")),
+        }
+    }
+    println!(
+        "{label}: {solved} solved, {stuck} stuck, {} diverged",
+        diverged.len()
+    );
+    diverged
+}
+
+#[test]
+fn heuristics_match_search_on_games() -> Result<()> {
+    let mut games = vec![];
+    for dir_entry in fs::read_dir("games/")? {
+        let path = dir_entry?.path();
+        if path.extension().and_then(OsStr::to_str) == Some("txt") {
+            games.push((
+                path.display().to_string(),
+                QueensFile::try_from_text_file(&path)?,
+            ));
+        }
+    }
+    let diverged = report(
+        "games",
+        games
+            .iter()
+            .map(|(name, queens_file)| (name.clone(), &queens_file.board)),
+    );
+    assert!(diverged.is_empty(), "{}", diverged.join("\n\n"));
+    Ok(())
+}
+
+#[test]
+fn heuristics_match_search_on_generated_boards() -> Result<()> {
+    let count = match std::env::var("QSOLVE_GENERATED_BOARDS") {
+        Ok(count) => count.parse()?,
+        Err(_) => DEFAULT_GENERATED_BOARDS,
+    };
+    let mut boards = vec![];
+    for seed in 0.. {
+        if boards.len() == count {
+            break;
+        }
+        let size = GENERATED_SIZES[seed as usize % GENERATED_SIZES.len()];
+        if let Some(board) = random_unique_board(size, &mut SeededRng::new(seed)) {
+            boards.push((format!("seed {seed}"), board));
+        }
+    }
+    let diverged = report(
+        "generated",
+        boards.iter().map(|(name, board)| (name.clone(), board)),
+    );
+    assert!(diverged.is_empty(), "{}", diverged.join("\n\n"));
+    Ok(())
+}
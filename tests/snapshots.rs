@@ -0,0 +1,61 @@
+use assert_cmd::prelude::*;
+use regex::Regex;
+use std::process::Command;
+
+/// The bundled text games, which every snapshot is taken across.
+const GAMES: [&str; 4] = [
+    "linkedin-1-empty",
+    "linkedin-1-partial",
+    "gameofcrowns-1",
+    "queensgame-1",
+];
+
+/// Runs qsolve with the given arguments, and returns its stdout normalized for
+/// snapshotting: ANSI escape sequences are stripped, and timings and the
+/// version of qsolve are replaced with placeholders.
+fn normalized_output(args: &[&str]) -> Result<String, Box<dyn std::error::Error>> {
+    // Keep the cache in a temporary directory, so the real one is untouched.
+    let output = Command::cargo_bin("qsolve")?
+        .args(args)
+        .env(
+            "QSOLVE_CACHE_DIR",
+            std::env::temp_dir().join(format!("qsolve-snapshot-cache-{}", std::process::id())),
+        )
+        .output()?;
+    assert!(output.status.success(), "qsolve {args:?} failed");
+    let stdout = String::from_utf8(output.stdout)?;
+    let stdout = Regex::new(r"\x1b\[[0-9;]*[A-Za-z]")?.replace_all(&stdout, "");
+    let stdout = Regex::new(r#""elapsed_micros": \d+"#)?
+        .replace_all(&stdout, r#""elapsed_micros": "[elapsed]""#);
+    let stdout = Regex::new(r#""qsolve_version": "[^"]*""#)?
+        .replace_all(&stdout, r#""qsolve_version": "[version]""#);
+    Ok(stdout.into_owned())
+}
+
+#[test]
+fn print_snapshots() -> Result<(), Box<dyn std::error::Error>> {
+    for game in GAMES {
+        let output = normalized_output(&["print", &format!("games/{game}.txt")])?;
+        insta::assert_snapshot!(format!("print_{game}"), output);
+    }
+    Ok(())
+}
+
+#[test]
+fn solve_json_snapshots() -> Result<(), Box<dyn std::error::Error>> {
+    for game in GAMES {
+        let output =
+            normalized_output(&["solve", &format!("games/{game}.txt"), "--format", "json"])?;
+        insta::assert_snapshot!(format!("solve_json_{game}"), output);
+    }
+    Ok(())
+}
+
+#[test]
+fn hint_snapshots() -> Result<(), Box<dyn std::error::Error>> {
+    for game in GAMES {
+        let output = normalized_output(&["hint", &format!("games/{game}.txt")])?;
+        insta::assert_snapshot!(format!("hint_{game}"), output);
+    }
+    Ok(())
+}
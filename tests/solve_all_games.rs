@@ -1,30 +1,106 @@
-use std::{ffi::OsStr, fs};
+use std::{ffi::OsStr, fs, path::Path};
 
 use anyhow::Result;
 
 use qsolve::{
     file::QueensFile,
     heuristic::all_heuristics,
-    solveiter::solve_iter,
+    manifest::Manifest,
+    search::all_solutions,
+    solution::Solution,
     solvestate::{SolveState, SolveStrategy},
 };
 
 #[test]
 fn solves_all_folder() -> Result<()> {
+    let manifest = Manifest::try_from_file(Path::new("games/manifest.toml"))?;
     for dir_entry in fs::read_dir("games/")? {
         let dir_entry = dir_entry?;
         if dir_entry.path().extension().and_then(OsStr::to_str) != Some("txt") {
             continue;
         }
-        let queens_file = QueensFile::try_from_text_file(&dir_entry.path())?;
-        let solve_state = SolveState::from(&queens_file);
-        let heuristics = all_heuristics(solve_state.board);
-        let state_iter_items =
-            solve_iter(solve_state, SolveStrategy::Fast, &heuristics).collect::<Vec<_>>();
-        let final_state = &state_iter_items.iter().last().unwrap().solve_state;
+        let name = dir_entry
+            .path()
+            .file_stem()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
         assert!(
-            final_state.complete(),
-            "Testing final state completion for {:?}",
+            manifest.game(&name).is_some(),
+            "{name} is missing from games/manifest.toml"
+        );
+    }
+
+    for game in &manifest.games {
+        let queens_file = QueensFile::try_from_text_file(&game.path(Path::new("games")))?;
+        let heuristics = all_heuristics(&queens_file.board);
+        for strategy in [
+            SolveStrategy::Fast,
+            SolveStrategy::Short,
+            SolveStrategy::Simple,
+            SolveStrategy::Human,
+        ] {
+            let solution = Solution::solve(SolveState::from(&queens_file), strategy, &heuristics);
+            assert!(
+                solution.final_state.complete(),
+                "Testing final state completion for {} with {strategy}",
+                game.name
+            );
+            if let Some(expected) = game.expected_steps(strategy) {
+                assert_eq!(
+                    solution.step_count(),
+                    expected,
+                    "Testing step count for {} with {strategy}",
+                    game.name
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn solves_community_folder_if_synced() -> Result<()> {
+    // `qsolve corpus sync` fills this directory; it isn't checked in.
+    let dir = Path::new("games/community");
+    if !dir.join("manifest.toml").exists() {
+        return Ok(());
+    }
+    let manifest = Manifest::try_from_file(&dir.join("manifest.toml"))?;
+    for game in &manifest.games {
+        let queens_file = QueensFile::try_from_text_file(&game.path(dir))?;
+        let heuristics = all_heuristics(&queens_file.board);
+        for &(strategy, expected) in &game.steps {
+            let solution = Solution::solve(SolveState::from(&queens_file), strategy, &heuristics);
+            assert!(
+                solution.is_solved(),
+                "Testing completion for community game {} with {strategy}",
+                game.name
+            );
+            assert_eq!(
+                solution.step_count(),
+                expected,
+                "Testing step count for community game {} with {strategy}",
+                game.name
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn all_folder_games_have_unique_solutions() -> Result<()> {
+    for dir_entry in fs::read_dir("games/")? {
+        let dir_entry = dir_entry?;
+        if dir_entry.path().extension().and_then(OsStr::to_str) != Some("txt") {
+            continue;
+        }
+        let queens_file = QueensFile::try_from_text_file(&dir_entry.path())?;
+        let solutions = all_solutions(&queens_file.board, 2);
+        assert_eq!(
+            solutions.len(),
+            1,
+            "Testing solution uniqueness for {:?}",
             dir_entry.path()
         );
     }
@@ -0,0 +1,159 @@
+//! An opt-in guard against the solver getting slower.
+//!
+//! This solves every game in `games/manifest.toml` with every strategy, and
+//! fails if the solve got more than [MAX_REGRESSION_PERCENT] slower than the
+//! baseline in `tests/fixtures/solve_time_baseline.toml`, or if any strategy
+//! takes more steps, or checks heuristics more often, in total.
+//!
+//! Wall-clock times can't be compared across machines, so the time is stored
+//! relative to a fixed calibration workload that doesn't use the solver. Even
+//! so, timings are noisy; the heuristic evaluations counted by [SolveMetrics]
+//! are not, and catch smaller slowdowns. This only runs with the `perf-guard` feature, and
+//! should be run in release mode:
+//!
+//! ```text
+//! cargo test --release --features perf-guard --test solve_time_regression
+//! ```
+//!
+//! After an intended change, set `QSOLVE_UPDATE_BASELINE=1` to record a new baseline.
+#![cfg(feature = "perf-guard")]
+
+use std::{
+    hint::black_box,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use toml::Table;
+
+use qsolve::{
+    file::QueensFile,
+    heuristic::all_heuristics,
+    manifest::Manifest,
+    metrics::SolveMetrics,
+    solution::Solution,
+    solvestate::{SolveOptions, SolveState, SolveStrategy},
+};
+
+/// How much slower than the baseline the solve may get before the test fails.
+const MAX_REGRESSION_PERCENT: f64 = 20.0;
+
+/// How many times each measurement is repeated; the fastest is used.
+const ROUNDS: usize = 5;
+
+const BASELINE_PATH: &str = "tests/fixtures/solve_time_baseline.toml";
+
+const STRATEGIES: [(SolveStrategy, &str); 3] = [
+    (SolveStrategy::Fast, "fast"),
+    (SolveStrategy::Short, "short"),
+    (SolveStrategy::Simple, "simple"),
+];
+
+/// Returns the fastest time of several runs of the given function.
+fn fastest(f: impl Fn()) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+/// A fixed amount of integer work, to measure how fast this machine is.
+fn calibration_workload() {
+    let mut x: u64 = 1;
+    for i in 0..20_000_000u64 {
+        x = black_box(x.wrapping_mul(6364136223846793005).wrapping_add(i));
+    }
+    black_box(x);
+}
+
+/// Solves every game with the given strategy, returning the total step count
+/// and, if `metrics` is set, the solver's total work.
+fn solve_corpus(
+    games: &[QueensFile],
+    strategy: SolveStrategy,
+    metrics: bool,
+) -> (usize, SolveMetrics) {
+    let solve_options = SolveOptions {
+        strategy,
+        metrics,
+        ..SolveOptions::default()
+    };
+    let mut steps = 0;
+    let mut total_metrics = SolveMetrics::default();
+    for queens_file in games {
+        let heuristics = all_heuristics(&queens_file.board);
+        let solution =
+            Solution::solve_with_options(SolveState::from(queens_file), solve_options, &heuristics);
+        steps += solution.step_count();
+        total_metrics += solution.metrics.unwrap_or_default();
+    }
+    (steps, total_metrics)
+}
+
+#[test]
+fn solve_time_has_not_regressed() -> Result<()> {
+    let manifest = Manifest::try_from_file(Path::new("games/manifest.toml"))?;
+    let games = manifest
+        .games
+        .iter()
+        .map(|game| QueensFile::try_from_text_file(&game.path(Path::new("games"))))
+        .collect::<Result<Vec<_>>>()?;
+
+    let calibration = fastest(calibration_workload);
+    let solve_time = fastest(|| {
+        for (strategy, _) in STRATEGIES {
+            black_box(solve_corpus(&games, strategy, false));
+        }
+    });
+    let normalized_time = solve_time.as_secs_f64() / calibration.as_secs_f64();
+    let mut steps = Table::new();
+    let mut evaluations = Table::new();
+    for (strategy, key) in STRATEGIES {
+        let (step_count, metrics) = solve_corpus(&games, strategy, true);
+        steps.insert(key.into(), (step_count as i64).into());
+        evaluations.insert(key.into(), (metrics.heuristic_evaluations as i64).into());
+    }
+
+    if std::env::var_os("QSOLVE_UPDATE_BASELINE").is_some() {
+        let mut baseline = Table::new();
+        baseline.insert("normalized_time".into(), normalized_time.into());
+        baseline.insert("steps".into(), steps.into());
+        baseline.insert("heuristic_evaluations".into(), evaluations.into());
+        std::fs::write(BASELINE_PATH, baseline.to_string())?;
+        return Ok(());
+    }
+
+    let baseline = std::fs::read_to_string(BASELINE_PATH)?.parse::<Table>()?;
+    let baseline_time = baseline["normalized_time"]
+        .as_float()
+        .context("Baseline has no normalized_time")?;
+    let regression_percent = (normalized_time / baseline_time - 1.0) * 100.0;
+    assert!(
+        regression_percent <= MAX_REGRESSION_PERCENT,
+        "Solving got {regression_percent:.1}% slower ({normalized_time:.3} vs {baseline_time:.3} calibration units)"
+    );
+    for (_, key) in STRATEGIES {
+        let baseline_steps = baseline["steps"][key]
+            .as_integer()
+            .with_context(|| format!("Baseline has no step count for {key}"))?;
+        assert!(
+            steps[key].as_integer().unwrap() <= baseline_steps,
+            "The {key} strategy takes {} steps in total, up from {baseline_steps}",
+            steps[key]
+        );
+        let baseline_evaluations = baseline["heuristic_evaluations"][key]
+            .as_integer()
+            .with_context(|| format!("Baseline has no heuristic evaluations for {key}"))?;
+        assert!(
+            evaluations[key].as_integer().unwrap() <= baseline_evaluations,
+            "The {key} strategy checks heuristics {} times in total, up from {baseline_evaluations}",
+            evaluations[key]
+        );
+    }
+    Ok(())
+}